@@ -272,6 +272,49 @@ fn bench_hash(c: &mut Criterion) {
     group.finish();
 }
 
+/// CID paths: materialize-then-hash (the old `hash_value`) against the
+/// streaming `HashSink`, plus `encode_into` against `encode`. `xlarge` is a
+/// ~4 MiB env where the avoided buffer is the whole encoding.
+fn bench_cid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cid");
+
+    let mut xlarge = BTreeMap::new();
+    for i in 0..64 {
+        xlarge.insert(
+            format!("blob_{i:02}"),
+            Value::Bytes(vec![i as u8; 64 * 1024]),
+        );
+    }
+    let fixtures = [
+        ("small", make_small()),
+        ("medium", make_medium()),
+        ("large", make_large()),
+        ("xlarge", Value::Map(xlarge)),
+    ];
+
+    for (name, v) in &fixtures {
+        group.bench_with_input(BenchmarkId::new("encode_then_hash", name), v, |b, v| {
+            b.iter(|| *blake3::hash(&nrf_core::encode(black_box(v))).as_bytes())
+        });
+        group.bench_with_input(BenchmarkId::new("hash_sink", name), v, |b, v| {
+            b.iter(|| nrf_core::hash_value(black_box(v)))
+        });
+        group.bench_with_input(BenchmarkId::new("encoded_len", name), v, |b, v| {
+            b.iter(|| nrf_core::encoded_len(black_box(v)))
+        });
+        group.bench_with_input(BenchmarkId::new("encode_into_reused", name), v, |b, v| {
+            let mut buf = Vec::with_capacity(nrf_core::encoded_len(v));
+            b.iter(|| {
+                buf.clear();
+                nrf_core::encode_into(&mut buf, black_box(v)).unwrap();
+                buf.len()
+            })
+        });
+    }
+
+    group.finish();
+}
+
 fn bench_size(c: &mut Criterion) {
     // Not a timing benchmark — just prints encoded sizes for comparison
     let small_nrf = nrf_core::encode(&make_small());
@@ -311,5 +354,12 @@ fn bench_size(c: &mut Criterion) {
     c.bench_function("size_report", |b| b.iter(|| 1 + 1));
 }
 
-criterion_group!(benches, bench_encode, bench_decode, bench_hash, bench_size);
+criterion_group!(
+    benches,
+    bench_encode,
+    bench_decode,
+    bench_hash,
+    bench_cid,
+    bench_size
+);
 criterion_main!(benches);
@@ -84,6 +84,24 @@ pub fn js_normalize(val: JsValue) -> Result<JsValue, JsError> {
         .map_err(|e| JsError::new(&format!("SerializeError: {e}")))
 }
 
+/// Render NRF bytes in diagnostic notation, e.g. `{"a": 1, "b": h'00ff'}`.
+/// Set `pretty` for indented multi-line output.
+#[wasm_bindgen(js_name = "toDiag")]
+pub fn js_to_diag(bytes: &[u8], pretty: bool) -> Result<String, JsError> {
+    let val = nrf_core::decode_located(bytes).map_err(|e| JsError::new(&format!("{e}")))?;
+    Ok(if pretty {
+        nrf_core::to_diag_pretty(&val)
+    } else {
+        nrf_core::to_diag(&val)
+    })
+}
+
+/// Parse diagnostic notation (strict) into canonical NRF bytes.
+#[wasm_bindgen(js_name = "fromDiag")]
+pub fn js_from_diag(text: &str) -> Result<Vec<u8>, JsError> {
+    nrf_core::diag_to_bytes(text).map_err(|e| JsError::new(&format!("{e}")))
+}
+
 /// Encode hex bytes as lowercase hex string.
 #[wasm_bindgen(js_name = "encodeHex")]
 pub fn js_encode_hex(bytes: &[u8]) -> String {
@@ -191,7 +209,7 @@ mod tests {
 
     #[test]
     fn test_json_to_value_float_rejected() {
-        let j = serde_json::json!(3.14);
+        let j = serde_json::json!(2.5);
         let result = json_to_value(&j);
         // JsError can't be constructed in non-wasm tests, so we check
         // that the function does not return Ok.
@@ -231,4 +249,13 @@ mod tests {
         let back = value_to_json(&v);
         assert_eq!(back, j);
     }
+
+    #[test]
+    fn test_diag_roundtrip() {
+        let bytes = b"nrf1\x06\x02\x02\x03\x00\x00\x00\x00\x00\x00\x00\x2a";
+        let text = js_to_diag(bytes, false).unwrap();
+        assert_eq!(text, "[true, 42]");
+        assert_eq!(js_from_diag(&text).unwrap(), bytes.to_vec());
+        assert_eq!(js_from_diag(&js_to_diag(bytes, true).unwrap()).unwrap(), bytes.to_vec());
+    }
 }
@@ -74,6 +74,7 @@ pub fn js_verify_seal(
     let opts = ubl_capsule::seal::VerifyOpts {
         allowed_skew_ns,
         now_ns: Some(js_now_nanos_i64()),
+        ..Default::default()
     };
     ubl_capsule::seal::verify_with_opts(&capsule, &pk, &opts)
         .map_err(|e| JsError::new(&e.to_string()))
@@ -96,21 +97,81 @@ pub fn js_verify_seal_bytes(
     let opts = ubl_capsule::seal::VerifyOpts {
         allowed_skew_ns,
         now_ns: Some(js_now_nanos_i64()),
+        ..Default::default()
     };
     ubl_capsule::seal::verify_with_opts(&capsule, &pk, &opts)
         .map_err(|e| JsError::new(&e.to_string()))
 }
 
+fn verify_seal_threshold_capsule(
+    capsule: &ubl_capsule::Capsule,
+    pk_bytes: Uint8Array,
+    keyring_hex: HashMap<String, String>,
+    threshold: usize,
+    allowed_skew_ns: i64,
+) -> Result<(), JsError> {
+    let pk = pk_from_uint8array(pk_bytes)?;
+    let opts = ubl_capsule::seal::VerifyOpts {
+        allowed_skew_ns,
+        now_ns: Some(js_now_nanos_i64()),
+        threshold: Some(ubl_capsule::seal::ThresholdPolicy {
+            k: threshold,
+            keys: keyring_from_hex(keyring_hex)?,
+            ..Default::default()
+        }),
+        key_time_ms: None,
+    };
+    ubl_capsule::seal::verify_with_opts(capsule, &pk, &opts)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Verify a multi-seal capsule against a K-of-N threshold.
+///
+/// Inputs:
+/// - `capsule`: JS object compatible with `ubl_capsule::Capsule`.
+/// - `pkBytes`: Uint8Array(32) Ed25519 public key of the author (`seal.kid`).
+/// - `keyringHex`: JS object mapping co-signer `did#key` -> hex-encoded public key (32 bytes).
+/// - `threshold`: valid seals required among author + co-signers.
+/// - `allowedSkewNs`: clock skew tolerance, nanoseconds.
+#[wasm_bindgen(js_name = "verifySealThreshold")]
+pub fn js_verify_seal_threshold(
+    capsule: JsValue,
+    pk_bytes: Uint8Array,
+    keyring_hex: JsValue,
+    threshold: usize,
+    allowed_skew_ns: i64,
+) -> Result<(), JsError> {
+    let capsule: ubl_capsule::Capsule = serde_wasm_bindgen::from_value(capsule)
+        .map_err(|e| JsError::new(&format!("InvalidCapsule: {e}")))?;
+    let keyring: HashMap<String, String> = serde_wasm_bindgen::from_value(keyring_hex)
+        .map_err(|e| JsError::new(&format!("InvalidKeyring: {e}")))?;
+    verify_seal_threshold_capsule(&capsule, pk_bytes, keyring, threshold, allowed_skew_ns)
+}
+
+/// Verify a multi-seal capsule from canonical capsule bytes (ai-nrf1 stream).
+#[wasm_bindgen(js_name = "verifySealThresholdBytes")]
+pub fn js_verify_seal_threshold_bytes(
+    capsule_bytes: &[u8],
+    pk_bytes: Uint8Array,
+    keyring_hex: JsValue,
+    threshold: usize,
+    allowed_skew_ns: i64,
+) -> Result<(), JsError> {
+    let capsule = bytes_to_capsule(capsule_bytes)?;
+    let keyring: HashMap<String, String> = serde_wasm_bindgen::from_value(keyring_hex)
+        .map_err(|e| JsError::new(&format!("InvalidKeyring: {e}")))?;
+    verify_seal_threshold_capsule(&capsule, pk_bytes, keyring, threshold, allowed_skew_ns)
+}
+
 #[derive(Serialize)]
 struct ChainReport {
     ok: bool,
     hops: usize,
 }
 
-fn verify_receipts_chain_capsule(
-    capsule: &ubl_capsule::Capsule,
+fn keyring_from_hex(
     keyring_hex: HashMap<String, String>,
-) -> Result<ChainReport, JsError> {
+) -> Result<HashMap<String, ed25519_dalek::VerifyingKey>, JsError> {
     let mut pks: HashMap<String, ed25519_dalek::VerifyingKey> = HashMap::new();
     for (node, hex_pk) in keyring_hex {
         let bytes =
@@ -122,7 +183,14 @@ fn verify_receipts_chain_capsule(
             .map_err(|_| JsError::new("InvalidKeyring: pk decode failed"))?;
         pks.insert(node, pk);
     }
+    Ok(pks)
+}
 
+fn verify_receipts_chain_capsule(
+    capsule: &ubl_capsule::Capsule,
+    keyring_hex: HashMap<String, String>,
+) -> Result<ChainReport, JsError> {
+    let pks = keyring_from_hex(keyring_hex)?;
     let resolve = |node: &str| -> Option<ed25519_dalek::VerifyingKey> { pks.get(node).copied() };
     ubl_capsule::receipt::verify_chain(&capsule.id, &capsule.receipts, &resolve)
         .map_err(|e| JsError::new(&e.to_string()))?;
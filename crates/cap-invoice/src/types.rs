@@ -1,5 +1,5 @@
 use serde::{Serialize, Deserialize};
-use rust_decimal::Decimal;
+use nrf1::NrfDecimal;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,29 +26,25 @@ pub struct CustomerRef {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InvoiceLine {
     pub sku: String,
-    pub qty: Decimal,
-    pub unit_total: Decimal,
-    pub line_total: Decimal,
+    pub qty: NrfDecimal,
+    pub unit_total: NrfDecimal,
+    pub line_total: NrfDecimal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Totals {
-    pub subtotal: Decimal,
-    pub tax: Decimal,
-    pub total: Decimal,
+    pub subtotal: NrfDecimal,
+    pub tax: NrfDecimal,
+    pub total: NrfDecimal,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum InvoiceStatus {
+    #[default]
     Draft,
     PendingAsk,
     Approved,
     Rejected,
 }
 
-impl Default for InvoiceStatus {
-    fn default() -> Self {
-        Self::Draft
-    }
-}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use rust_decimal::Decimal;
+use nrf1::NrfDecimal;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Deserialize)]
@@ -14,7 +14,7 @@ pub struct QuoteCreateReq {
 #[derive(Debug, Clone, Deserialize)]
 pub struct QuoteItemReq {
     pub sku: String,
-    pub qty: Option<Decimal>,
+    pub qty: Option<NrfDecimal>,
     pub region: Option<String>,
     pub category: Option<String>,
     pub coupons: Option<Vec<String>>,
@@ -30,18 +30,18 @@ pub struct QuoteResp {
 #[derive(Debug, Clone, Serialize)]
 pub struct QuoteLine {
     pub sku: String,
-    pub qty: Decimal,
-    pub unit_net: Decimal,
-    pub unit_tax: Decimal,
-    pub unit_total: Decimal,
-    pub line_total: Decimal,
+    pub qty: NrfDecimal,
+    pub unit_net: NrfDecimal,
+    pub unit_tax: NrfDecimal,
+    pub unit_total: NrfDecimal,
+    pub line_total: NrfDecimal,
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct QuoteTotals {
-    pub subtotal: Decimal,
-    pub tax: Decimal,
-    pub total: Decimal,
+    pub subtotal: NrfDecimal,
+    pub tax: NrfDecimal,
+    pub total: NrfDecimal,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -3,7 +3,7 @@ pub mod api;
 use api::*;
 use anyhow::{anyhow, Result};
 use dashmap::DashMap;
-use rust_decimal::Decimal;
+use nrf1::NrfDecimal;
 use std::sync::LazyLock;
 use uuid::Uuid;
 
@@ -16,14 +16,14 @@ pub fn create_quote(req: &QuoteCreateReq) -> Result<QuoteResp> {
 
     let id = Uuid::new_v4();
     let mut lines = vec![];
-    let mut subtotal = Decimal::ZERO;
-    let mut tax = Decimal::ZERO;
-    let mut total = Decimal::ZERO;
+    let mut subtotal = NrfDecimal::ZERO;
+    let mut tax = NrfDecimal::ZERO;
+    let mut total = NrfDecimal::ZERO;
 
     for it in &req.items {
         let pr = cap_pricing::api::PriceReq {
             sku: it.sku.clone(),
-            qty: it.qty.or(Some(Decimal::ONE)),
+            qty: it.qty.or(Some(NrfDecimal::ONE)),
             region: it.region.clone().or(req.region.clone()),
             category: it.category.clone(),
             customer_tier: req.customer_tier.clone(),
@@ -31,13 +31,13 @@ pub fn create_quote(req: &QuoteCreateReq) -> Result<QuoteResp> {
             explain: false,
         };
         let priced = cap_pricing::engine::price_one(cfg, &pr)?;
-        subtotal += priced.total_net;
-        tax += priced.total_tax;
-        total += priced.total;
+        subtotal = cap_pricing::engine::add(subtotal, priced.total_net)?;
+        tax = cap_pricing::engine::add(tax, priced.total_tax)?;
+        total = cap_pricing::engine::add(total, priced.total)?;
 
         lines.push(QuoteLine {
             sku: pr.sku,
-            qty: pr.qty.unwrap_or(Decimal::ONE),
+            qty: pr.qty.unwrap_or(NrfDecimal::ONE),
             unit_net: priced.unit_net,
             unit_tax: priced.unit_tax,
             unit_total: priced.unit_total,
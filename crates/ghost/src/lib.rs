@@ -1,7 +1,6 @@
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use nrf1::Value;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 
 // ---------------------------------------------------------------------------
 // Ghost — Write-Before-Execute state machine (BASE terrain)
@@ -54,6 +53,7 @@ pub struct Ghost {
     pub wbe: Wbe,            // write-before-execute record
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cause: Option<ExpireCause>, // only set when expired
+    #[serde(with = "nrf1::serde::bytes")]
     pub nonce: Vec<u8>,      // 16 bytes
     pub url: String,         // rich URL
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -111,9 +111,24 @@ impl Ghost {
 
     /// Canonical NRF map without sig and ghost_cid (the hash preimage).
     pub fn nrf_without_sig(&self) -> Value {
-        use Value::*;
-        let mut m = BTreeMap::new();
-
+        let status = match self.status {
+            GhostStatus::Pending => "pending",
+            GhostStatus::Expired => "expired",
+        };
+        // ghost_cid and sig deliberately omitted
+        let mut v = nrf1::nrf!({
+            "nonce": bytes(self.nonce.clone()),
+            "status": status,
+            "t": self.t,
+            "url": &self.url,
+            "v": &self.v,
+            "wbe": {
+                "intent": &self.wbe.intent,
+                "what": &self.wbe.what,
+                "when": self.wbe.when,
+                "who": &self.wbe.who,
+            },
+        });
         if let Some(c) = &self.cause {
             let s = match c {
                 ExpireCause::Timeout => "timeout",
@@ -121,31 +136,9 @@ impl Ghost {
                 ExpireCause::Drift => "drift",
                 ExpireCause::None => "none",
             };
-            m.insert("cause".into(), String(s.into()));
+            v["cause"] = s.into();
         }
-
-        m.insert("nonce".into(), Bytes(self.nonce.clone()));
-
-        let status_str = match self.status {
-            GhostStatus::Pending => "pending",
-            GhostStatus::Expired => "expired",
-        };
-        m.insert("status".into(), String(status_str.into()));
-
-        m.insert("t".into(), Int(self.t));
-        m.insert("url".into(), String(self.url.clone()));
-        m.insert("v".into(), String(self.v.clone()));
-
-        // wbe as sub-map
-        let mut wm = BTreeMap::new();
-        wm.insert("intent".into(), String(self.wbe.intent.clone()));
-        wm.insert("what".into(), String(self.wbe.what.clone()));
-        wm.insert("when".into(), Int(self.wbe.when));
-        wm.insert("who".into(), String(self.wbe.who.clone()));
-        m.insert("wbe".into(), Map(wm));
-
-        // ghost_cid and sig deliberately omitted
-        Map(m)
+        v
     }
 
     pub fn compute_cid(&self) -> String {
@@ -111,3 +111,17 @@ fn test_ghost_as_ref() {
     assert_eq!(r.ghost_id, "storage-id-123");
     assert_eq!(r.ghost_cid, g.ghost_cid);
 }
+
+#[test]
+fn test_ghost_serde_preimage_matches_hand_built() {
+    let mut g = make_test_ghost();
+    for _ in 0..2 {
+        let nrf1::Value::Map(mut m) = nrf1::serde::to_value(&g).unwrap() else {
+            panic!("ghost must serialize to a map");
+        };
+        m.remove("ghost_cid");
+        m.remove("sig");
+        assert_eq!(nrf1::Value::Map(m), g.nrf_without_sig());
+        g.expire(ExpireCause::Timeout);
+    }
+}
@@ -53,6 +53,7 @@ impl LlmOutput {
 // ---------------------------------------------------------------------------
 
 /// Trait for LLM providers — allows test doubles.
+#[allow(clippy::double_must_use)]
 #[async_trait::async_trait]
 pub trait LlmProvider: Send + Sync {
     async fn invoke(
@@ -86,7 +86,7 @@ pub fn append_and_verify(
     kind: &str,
     node: &str,
     ts: i64,
-    resolve_pk: &dyn Fn(&str) -> Option<ed25519_dalek::VerifyingKey>,
+    resolve_pk: &dyn ubl_capsule::KeyResolver,
 ) -> anyhow::Result<()> {
     let prev = capsule
         .receipts
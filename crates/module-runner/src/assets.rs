@@ -1,6 +1,8 @@
-//! In-memory AssetResolver for tests and development.
+//! AssetResolvers: in-memory for tests and development, and a directory of
+//! content-addressed files for deployments.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use modules_core::{Asset, AssetResolver, Cid};
 
@@ -42,3 +44,65 @@ impl AssetResolver for MemoryResolver {
         Box::new(self.clone())
     }
 }
+
+/// Assets stored as `<dir>/<hex cid>`, where the CID is the BLAKE3 of the
+/// file. Contents are re-hashed on every read, so a swapped file fails
+/// instead of being served under the wrong CID.
+#[derive(Clone)]
+pub struct DirResolver {
+    dir: PathBuf,
+}
+
+impl DirResolver {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl AssetResolver for DirResolver {
+    fn get(&self, cid: &Cid) -> anyhow::Result<Asset> {
+        let name = hex::encode(cid);
+        let bytes = std::fs::read(self.dir.join(&name)).map_err(|e| {
+            anyhow::anyhow!("asset b3:{name} not found in {}: {e}", self.dir.display())
+        })?;
+        anyhow::ensure!(
+            blake3::hash(&bytes).as_bytes() == cid,
+            "asset b3:{name} does not match its CID"
+        );
+        Ok(Asset {
+            cid: *cid,
+            bytes,
+            mime: "application/octet-stream".into(),
+        })
+    }
+
+    fn box_clone(&self) -> Box<dyn AssetResolver> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dir_resolver_checks_content_against_cid() {
+        let dir = std::env::temp_dir().join(format!(
+            "ai-nrf1-assets-test-{:?}",
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let r = DirResolver::new(&dir);
+
+        let cid = *blake3::hash(b"salt").as_bytes();
+        assert!(r.get(&cid).is_err());
+        std::fs::write(dir.join(hex::encode(cid)), b"salt").unwrap();
+        assert_eq!(r.get(&cid).unwrap().bytes, b"salt");
+
+        std::fs::write(dir.join(hex::encode(cid)), b"other").unwrap();
+        let err = r.get(&cid).unwrap_err().to_string();
+        assert!(err.contains("does not match"), "{err}");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
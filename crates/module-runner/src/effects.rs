@@ -28,6 +28,7 @@ pub struct ExecCtx {
 // Trait
 // ---------------------------------------------------------------------------
 
+#[allow(clippy::double_must_use)]
 #[async_trait]
 pub trait EffectExecutor: Send + Sync {
     async fn execute(&self, effect: &Effect, ctx: &ExecCtx) -> anyhow::Result<()>;
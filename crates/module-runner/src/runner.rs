@@ -102,7 +102,11 @@ impl<'a, E: EffectExecutor> Runner<'a, E> {
                 },
             };
 
-            let out: CapOutput = cap.execute(input)?;
+            let mut out: CapOutput = cap.execute(input)?;
+            if let (Some(profile), Some(new_env)) = (cap.rho_profile(), out.new_env.as_mut()) {
+                *new_env = nrf1::rho::normalize_with_profile(new_env, profile)
+                    .map_err(|e| anyhow::anyhow!("{}: ρ profile: {e}", step.kind))?;
+            }
             let elapsed_ms = t0.elapsed().as_millis() as i64;
 
             // Collect env update
@@ -194,4 +194,10 @@ pub trait Capability: Send + Sync {
 
     /// Pure, deterministic execution. No IO.
     fn execute(&self, input: CapInput) -> anyhow::Result<CapOutput>;
+
+    /// ρ profile the runtime applies to `new_env` (decimals, timestamps,
+    /// sets this capability writes). `None` = plain ρ is enough.
+    fn rho_profile(&self) -> Option<&nrf1::rho::Profile> {
+        None
+    }
 }
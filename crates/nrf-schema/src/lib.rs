@@ -0,0 +1,1054 @@
+//! `nrf-schema` — validate NRF values against the repo's JSON schemas.
+//!
+//! Schemas are ordinary JSON Schema documents (the files under `schemas/`
+//! and `configs/schema/`), compiled once and checked against an
+//! `nrf_core::Value` — not against JSON — so the NRF type system is visible:
+//!
+//!   type "bytes"             Value::Bytes (JSON has no such type)
+//!   type "integer"/"number"  Value::Int; bounds are exact i64 comparisons
+//!   minLength/maxLength      chars for strings, bytes for Bytes
+//!   byteLength               exact Bytes length (e.g. 32 for a hash)
+//!   format                   ascii | did | decimal | timestamp | uri | cid
+//!
+//! Validation never stops at the first problem: `validate` returns every
+//! violation with its path (`env.items[3].sku`), in document order.
+//!
+//! Supported keywords: type, const, enum, pattern, format, minLength,
+//! maxLength, byteLength, minimum, maximum, exclusiveMinimum,
+//! exclusiveMaximum, items, minItems, maxItems, uniqueItems, properties,
+//! patternProperties, additionalProperties, required, minProperties,
+//! maxProperties, allOf, anyOf, oneOf, not, if/then/else and local `$ref`
+//! (`#`, `#/$defs/<name>`, `#/definitions/<name>`). Annotations (`title`,
+//! `description`, `$id`, ...) are ignored; an unknown `format` is an error.
+
+use nrf_core::{push_path_index, push_path_key, Value};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// A schema document that cannot be compiled.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SchemaError {
+    #[error("unknown builtin schema: {0}")]
+    UnknownBuiltin(String),
+    #[error("schema is not valid JSON: {0}")]
+    Json(String),
+    #[error("bad {keyword} at {at}: {message}")]
+    BadKeyword {
+        at: String,
+        keyword: String,
+        message: String,
+    },
+    #[error("unresolved $ref: {0}")]
+    UnresolvedRef(String),
+}
+
+/// One failed check. `path` is empty for the root value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    pub path: String,
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() {
+            "<root>"
+        } else {
+            &self.path
+        };
+        write!(f, "{path}: {}", self.message)
+    }
+}
+
+/// All violations of one `check` call (never empty).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} schema violation(s): ", self.violations.len())?;
+        for (i, v) in self.violations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{v}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+// ---------------------------------------------------------------------------
+// Builtin schemas (shipped in the repo)
+// ---------------------------------------------------------------------------
+
+const BUILTINS: &[(&str, &str)] = &[
+    (
+        "receipt.v1",
+        include_str!("../../../schemas/receipt.v1.json"),
+    ),
+    ("ghost.v1", include_str!("../../../schemas/ghost.v1.json")),
+    (
+        "product.v1",
+        include_str!("../../../schemas/product.v1.json"),
+    ),
+    (
+        "manifest.v1",
+        include_str!("../../../configs/schema/manifest.v1.json"),
+    ),
+    (
+        "ubl-json.v1",
+        include_str!("../../../schemas/ubl-json.v1.json"),
+    ),
+    (
+        "model-card.v1",
+        include_str!("../../../schemas/model-card.v1.json"),
+    ),
+    (
+        "reasoning.bit.v1",
+        include_str!("../../../schemas/reasoning.bit.v1.json"),
+    ),
+    (
+        "reasoning.bit.v1.1",
+        include_str!("../../../schemas/reasoning.bit.v1.1.json"),
+    ),
+];
+
+/// Names accepted by `Schema::builtin`.
+pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+    BUILTINS.iter().map(|(name, _)| *name)
+}
+
+// ---------------------------------------------------------------------------
+// Compiled form
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Null,
+    Boolean,
+    Integer,
+    String,
+    Bytes,
+    Array,
+    Object,
+}
+
+impl Type {
+    fn parse(s: &str) -> Option<Type> {
+        Some(match s {
+            "null" => Type::Null,
+            "boolean" => Type::Boolean,
+            // NRF has no floats: every number is an Int.
+            "integer" | "number" => Type::Integer,
+            "string" => Type::String,
+            "bytes" => Type::Bytes,
+            "array" => Type::Array,
+            "object" => Type::Object,
+            _ => return None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Integer => "integer",
+            Type::String => "string",
+            Type::Bytes => "bytes",
+            Type::Array => "array",
+            Type::Object => "object",
+        }
+    }
+
+    fn matches(&self, v: &Value) -> bool {
+        matches!(
+            (self, v),
+            (Type::Null, Value::Null)
+                | (Type::Boolean, Value::Bool(_))
+                | (Type::Integer, Value::Int(_))
+                | (Type::String, Value::String(_))
+                | (Type::Bytes, Value::Bytes(_))
+                | (Type::Array, Value::Array(_))
+                | (Type::Object, Value::Map(_))
+        )
+    }
+}
+
+fn value_type(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "Null",
+        Value::Bool(_) => "Bool",
+        Value::Int(_) => "Int",
+        Value::String(_) => "String",
+        Value::Bytes(_) => "Bytes",
+        Value::Array(_) => "Array",
+        Value::Map(_) => "Map",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    Did,
+    Decimal,
+    Timestamp,
+    Uri,
+    Cid,
+}
+
+impl Format {
+    fn parse(s: &str) -> Option<Format> {
+        Some(match s {
+            "ascii" => Format::Ascii,
+            "did" => Format::Did,
+            "decimal" => Format::Decimal,
+            "timestamp" | "date-time" => Format::Timestamp,
+            "uri" => Format::Uri,
+            "cid" => Format::Cid,
+            _ => return None,
+        })
+    }
+
+    fn check(&self, s: &str) -> Result<(), &'static str> {
+        let ok = match self {
+            Format::Ascii => s.is_ascii(),
+            Format::Did => is_did(s),
+            Format::Decimal => nrf_core::rho::normalize_decimal(s).is_ok(),
+            Format::Timestamp => nrf_core::rho::normalize_timestamp(s).is_ok(),
+            Format::Uri => is_uri(s),
+            Format::Cid => is_cid(s),
+        };
+        if ok {
+            return Ok(());
+        }
+        Err(match self {
+            Format::Ascii => "not ASCII",
+            Format::Did => "not an ASCII DID (did:<method>:<id>)",
+            Format::Decimal => "not a decimal string (no exponent, no leading zeros)",
+            Format::Timestamp => "not an RFC 3339 UTC timestamp (…Z)",
+            Format::Uri => "not an absolute URI",
+            Format::Cid => "not a CID (b3:<64 lowercase hex>)",
+        })
+    }
+}
+
+/// `did:<method>:<id>` — method is lowercase alphanumeric, all ASCII.
+fn is_did(s: &str) -> bool {
+    let Some(rest) = s.strip_prefix("did:") else {
+        return false;
+    };
+    let Some((method, id)) = rest.split_once(':') else {
+        return false;
+    };
+    s.is_ascii()
+        && !method.is_empty()
+        && method
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+        && !id.is_empty()
+        && !id
+            .bytes()
+            .any(|b| b.is_ascii_whitespace() || b.is_ascii_control())
+}
+
+/// `scheme:rest` with an RFC 3986 scheme and no whitespace.
+fn is_uri(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once(':') else {
+        return false;
+    };
+    let mut bytes = scheme.bytes();
+    bytes.next().is_some_and(|b| b.is_ascii_alphabetic())
+        && bytes.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+        && !rest.is_empty()
+        && !rest.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+fn is_cid(s: &str) -> bool {
+    s.strip_prefix("b3:").is_some_and(|hex| {
+        hex.len() == 64
+            && hex
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// `true` / `false` schema.
+    always: Option<bool>,
+    types: Option<Vec<Type>>,
+    const_: Option<Value>,
+    enum_: Option<Vec<Value>>,
+    pattern: Option<Regex>,
+    format: Option<Format>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    byte_length: Option<usize>,
+    minimum: Option<i64>,
+    maximum: Option<i64>,
+    exclusive_minimum: Option<i64>,
+    exclusive_maximum: Option<i64>,
+    items: Option<Box<Node>>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    unique_items: bool,
+    properties: BTreeMap<String, Node>,
+    pattern_properties: Vec<(Regex, Node)>,
+    additional: Option<Box<Node>>,
+    required: Vec<String>,
+    min_properties: Option<usize>,
+    max_properties: Option<usize>,
+    all_of: Vec<Node>,
+    any_of: Vec<Node>,
+    one_of: Vec<Node>,
+    not: Option<Box<Node>>,
+    if_: Option<Box<Node>>,
+    then: Option<Box<Node>>,
+    else_: Option<Box<Node>>,
+    ref_: Option<String>,
+}
+
+/// A compiled schema. Cheap to reuse; compile once, validate many values.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    id: Option<String>,
+    root: Node,
+    defs: BTreeMap<String, Node>,
+}
+
+// ---------------------------------------------------------------------------
+// Compilation
+// ---------------------------------------------------------------------------
+
+struct Compiler {
+    refs: Vec<String>,
+}
+
+fn bad(at: &str, keyword: &str, message: impl Into<String>) -> SchemaError {
+    SchemaError::BadKeyword {
+        at: if at.is_empty() {
+            "#".into()
+        } else {
+            format!("#{at}")
+        },
+        keyword: keyword.into(),
+        message: message.into(),
+    }
+}
+
+fn pointer_escape(s: &str) -> String {
+    s.replace('~', "~0").replace('/', "~1")
+}
+
+impl Compiler {
+    fn node(&mut self, j: &serde_json::Value, at: &str) -> Result<Node, SchemaError> {
+        let obj = match j {
+            serde_json::Value::Bool(b) => {
+                return Ok(Node {
+                    always: Some(*b),
+                    ..Node::default()
+                })
+            }
+            serde_json::Value::Object(obj) => obj,
+            _ => return Err(bad(at, "schema", "expected an object or a boolean")),
+        };
+        let mut n = Node::default();
+        for (k, v) in obj {
+            let here = format!("{at}/{}", pointer_escape(k));
+            match k.as_str() {
+                "type" => {
+                    let names: Vec<&serde_json::Value> = match v {
+                        serde_json::Value::Array(a) => a.iter().collect(),
+                        other => vec![other],
+                    };
+                    let mut types = Vec::new();
+                    for name in names {
+                        let t = name
+                            .as_str()
+                            .and_then(Type::parse)
+                            .ok_or_else(|| bad(&here, k, format!("unknown type {name}")))?;
+                        types.push(t);
+                    }
+                    n.types = Some(types);
+                }
+                "const" => n.const_ = Some(to_value(v, &here, k)?),
+                "enum" => {
+                    let items = v
+                        .as_array()
+                        .ok_or_else(|| bad(&here, k, "expected an array"))?;
+                    n.enum_ = Some(
+                        items
+                            .iter()
+                            .map(|item| to_value(item, &here, k))
+                            .collect::<Result<_, _>>()?,
+                    );
+                }
+                "pattern" => n.pattern = Some(regex(v, &here, k)?),
+                "format" => {
+                    let name = v
+                        .as_str()
+                        .ok_or_else(|| bad(&here, k, "expected a string"))?;
+                    n.format = Some(
+                        Format::parse(name)
+                            .ok_or_else(|| bad(&here, k, format!("unknown format {name:?}")))?,
+                    );
+                }
+                "minLength" => n.min_length = Some(count(v, &here, k)?),
+                "maxLength" => n.max_length = Some(count(v, &here, k)?),
+                "byteLength" => n.byte_length = Some(count(v, &here, k)?),
+                "minimum" => n.minimum = Some(int(v, &here, k)?),
+                "maximum" => n.maximum = Some(int(v, &here, k)?),
+                "exclusiveMinimum" => n.exclusive_minimum = Some(int(v, &here, k)?),
+                "exclusiveMaximum" => n.exclusive_maximum = Some(int(v, &here, k)?),
+                "items" => n.items = Some(Box::new(self.node(v, &here)?)),
+                "minItems" => n.min_items = Some(count(v, &here, k)?),
+                "maxItems" => n.max_items = Some(count(v, &here, k)?),
+                "uniqueItems" => {
+                    n.unique_items = v
+                        .as_bool()
+                        .ok_or_else(|| bad(&here, k, "expected a boolean"))?
+                }
+                "properties" => {
+                    let props = v
+                        .as_object()
+                        .ok_or_else(|| bad(&here, k, "expected an object"))?;
+                    for (name, sub) in props {
+                        let at = format!("{here}/{}", pointer_escape(name));
+                        n.properties.insert(name.clone(), self.node(sub, &at)?);
+                    }
+                }
+                "patternProperties" => {
+                    let props = v
+                        .as_object()
+                        .ok_or_else(|| bad(&here, k, "expected an object"))?;
+                    for (re, sub) in props {
+                        let at = format!("{here}/{}", pointer_escape(re));
+                        let compiled = Regex::new(re).map_err(|e| bad(&at, k, e.to_string()))?;
+                        n.pattern_properties.push((compiled, self.node(sub, &at)?));
+                    }
+                }
+                "additionalProperties" => n.additional = Some(Box::new(self.node(v, &here)?)),
+                "required" => {
+                    let names = v
+                        .as_array()
+                        .ok_or_else(|| bad(&here, k, "expected an array"))?;
+                    for name in names {
+                        let name = name
+                            .as_str()
+                            .ok_or_else(|| bad(&here, k, "expected property names"))?;
+                        n.required.push(name.to_string());
+                    }
+                }
+                "minProperties" => n.min_properties = Some(count(v, &here, k)?),
+                "maxProperties" => n.max_properties = Some(count(v, &here, k)?),
+                "allOf" => n.all_of = self.list(v, &here, k)?,
+                "anyOf" => n.any_of = self.list(v, &here, k)?,
+                "oneOf" => n.one_of = self.list(v, &here, k)?,
+                "not" => n.not = Some(Box::new(self.node(v, &here)?)),
+                "if" => n.if_ = Some(Box::new(self.node(v, &here)?)),
+                "then" => n.then = Some(Box::new(self.node(v, &here)?)),
+                "else" => n.else_ = Some(Box::new(self.node(v, &here)?)),
+                "$ref" => {
+                    let target = v
+                        .as_str()
+                        .ok_or_else(|| bad(&here, k, "expected a string"))?;
+                    let local = target == "#"
+                        || target
+                            .strip_prefix("#/$defs/")
+                            .is_some_and(|s| !s.contains('/'))
+                        || target
+                            .strip_prefix("#/definitions/")
+                            .is_some_and(|s| !s.contains('/'));
+                    if !local {
+                        return Err(bad(
+                            &here,
+                            k,
+                            format!(
+                                "only local refs (#, #/$defs/<name>) are supported, got {target:?}"
+                            ),
+                        ));
+                    }
+                    self.refs.push(target.to_string());
+                    n.ref_ = Some(target.to_string());
+                }
+                // Annotations, ids and definition containers (compiled by `Schema::from_json`).
+                _ => {}
+            }
+        }
+        Ok(n)
+    }
+
+    fn list(&mut self, v: &serde_json::Value, at: &str, k: &str) -> Result<Vec<Node>, SchemaError> {
+        let items = v
+            .as_array()
+            .ok_or_else(|| bad(at, k, "expected an array"))?;
+        if items.is_empty() {
+            return Err(bad(at, k, "expected at least one schema"));
+        }
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| self.node(item, &format!("{at}/{i}")))
+            .collect()
+    }
+}
+
+fn to_value(v: &serde_json::Value, at: &str, k: &str) -> Result<Value, SchemaError> {
+    ubl_json_view::from_json(v).map_err(|e| bad(at, k, format!("not an NRF value: {e}")))
+}
+
+fn regex(v: &serde_json::Value, at: &str, k: &str) -> Result<Regex, SchemaError> {
+    let s = v.as_str().ok_or_else(|| bad(at, k, "expected a string"))?;
+    Regex::new(s).map_err(|e| bad(at, k, e.to_string()))
+}
+
+fn count(v: &serde_json::Value, at: &str, k: &str) -> Result<usize, SchemaError> {
+    v.as_u64()
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or_else(|| bad(at, k, "expected a non-negative integer"))
+}
+
+fn int(v: &serde_json::Value, at: &str, k: &str) -> Result<i64, SchemaError> {
+    v.as_i64()
+        .ok_or_else(|| bad(at, k, "expected an Int64 bound (NRF has no floats)"))
+}
+
+impl Schema {
+    /// Compile a schema document.
+    pub fn from_json(j: &serde_json::Value) -> Result<Schema, SchemaError> {
+        let mut c = Compiler { refs: Vec::new() };
+        let root = c.node(j, "")?;
+        let mut defs = BTreeMap::new();
+        for container in ["$defs", "definitions"] {
+            let Some(entries) = j.get(container) else {
+                continue;
+            };
+            let entries = entries
+                .as_object()
+                .ok_or_else(|| bad(&format!("/{container}"), container, "expected an object"))?;
+            for (name, sub) in entries {
+                let key = format!("#/{container}/{name}");
+                let node = c.node(sub, &key[1..])?;
+                defs.insert(key, node);
+            }
+        }
+        if let Some(missing) = c.refs.iter().find(|r| *r != "#" && !defs.contains_key(*r)) {
+            return Err(SchemaError::UnresolvedRef(missing.clone()));
+        }
+        let id = j.get("$id").and_then(|v| v.as_str()).map(str::to_string);
+        Ok(Schema { id, root, defs })
+    }
+
+    /// Compile a schema from its JSON text.
+    pub fn parse(text: &str) -> Result<Schema, SchemaError> {
+        let j: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| SchemaError::Json(e.to_string()))?;
+        Schema::from_json(&j)
+    }
+
+    /// One of the schemas shipped in the repo (see `builtin_names`).
+    pub fn builtin(name: &str) -> Result<Schema, SchemaError> {
+        let (_, text) = BUILTINS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .ok_or_else(|| SchemaError::UnknownBuiltin(name.to_string()))?;
+        Schema::parse(text)
+    }
+
+    /// The document's `$id`, if any.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Every violation, in document order. Empty ⇔ valid.
+    pub fn validate(&self, v: &Value) -> Vec<Violation> {
+        let mut out = Vec::new();
+        let mut path = String::new();
+        self.walk(&self.root, v, &mut path, &mut out);
+        out
+    }
+
+    pub fn is_valid(&self, v: &Value) -> bool {
+        self.validate(v).is_empty()
+    }
+
+    /// `Ok(())` or all violations as one error.
+    pub fn check(&self, v: &Value) -> Result<(), ValidationError> {
+        let violations = self.validate(v);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { violations })
+        }
+    }
+
+    fn passes(&self, node: &Node, v: &Value, path: &mut String) -> bool {
+        let mut scratch = Vec::new();
+        self.walk(node, v, path, &mut scratch);
+        scratch.is_empty()
+    }
+
+    fn walk(&self, node: &Node, v: &Value, path: &mut String, out: &mut Vec<Violation>) {
+        if node.always == Some(false) {
+            fail(out, path, "false", "no value is allowed here".into());
+            return;
+        }
+        if let Some(target) = &node.ref_ {
+            let resolved = if target == "#" {
+                &self.root
+            } else {
+                &self.defs[target]
+            };
+            // Sibling keywords of `$ref` still apply (2019-09 and later).
+            self.walk(resolved, v, path, out);
+        }
+
+        if let Some(types) = &node.types {
+            if !types.iter().any(|t| t.matches(v)) {
+                let names: Vec<&str> = types.iter().map(Type::name).collect();
+                fail(
+                    out,
+                    path,
+                    "type",
+                    format!("expected {}, got {}", names.join(" or "), value_type(v)),
+                );
+                // Type-specific keywords would only repeat the same complaint.
+                return;
+            }
+        }
+        if let Some(c) = &node.const_ {
+            if c != v {
+                fail(
+                    out,
+                    path,
+                    "const",
+                    format!("expected {}", nrf_core::to_diag(c)),
+                );
+            }
+        }
+        if let Some(options) = &node.enum_ {
+            if !options.contains(v) {
+                let shown: Vec<String> = options.iter().map(nrf_core::to_diag).collect();
+                fail(
+                    out,
+                    path,
+                    "enum",
+                    format!("expected one of {}", shown.join(", ")),
+                );
+            }
+        }
+
+        match v {
+            Value::String(s) => {
+                let chars = s.chars().count();
+                check_len(node, chars, "characters", path, out);
+                if let Some(re) = &node.pattern {
+                    if !re.is_match(s) {
+                        fail(
+                            out,
+                            path,
+                            "pattern",
+                            format!("does not match /{}/", re.as_str()),
+                        );
+                    }
+                }
+                if let Some(format) = &node.format {
+                    if let Err(why) = format.check(s) {
+                        fail(out, path, "format", why.into());
+                    }
+                }
+            }
+            Value::Bytes(b) => {
+                check_len(node, b.len(), "bytes", path, out);
+                if let Some(n) = node.byte_length {
+                    if b.len() != n {
+                        fail(
+                            out,
+                            path,
+                            "byteLength",
+                            format!("expected {n} bytes, got {}", b.len()),
+                        );
+                    }
+                }
+            }
+            Value::Int(i) => {
+                let i = *i;
+                if let Some(m) = node.minimum.filter(|m| i < *m) {
+                    fail(out, path, "minimum", format!("{i} is less than {m}"));
+                }
+                if let Some(m) = node.maximum.filter(|m| i > *m) {
+                    fail(out, path, "maximum", format!("{i} is greater than {m}"));
+                }
+                if let Some(m) = node.exclusive_minimum.filter(|m| i <= *m) {
+                    fail(
+                        out,
+                        path,
+                        "exclusiveMinimum",
+                        format!("{i} is not greater than {m}"),
+                    );
+                }
+                if let Some(m) = node.exclusive_maximum.filter(|m| i >= *m) {
+                    fail(
+                        out,
+                        path,
+                        "exclusiveMaximum",
+                        format!("{i} is not less than {m}"),
+                    );
+                }
+            }
+            Value::Array(items) => {
+                if let Some(m) = node.min_items.filter(|m| items.len() < *m) {
+                    fail(
+                        out,
+                        path,
+                        "minItems",
+                        format!("expected at least {m} items, got {}", items.len()),
+                    );
+                }
+                if let Some(m) = node.max_items.filter(|m| items.len() > *m) {
+                    fail(
+                        out,
+                        path,
+                        "maxItems",
+                        format!("expected at most {m} items, got {}", items.len()),
+                    );
+                }
+                if node.unique_items {
+                    for (i, item) in items.iter().enumerate() {
+                        if let Some(j) = items[..i].iter().position(|x| x == item) {
+                            fail(
+                                out,
+                                path,
+                                "uniqueItems",
+                                format!("items [{j}] and [{i}] are equal"),
+                            );
+                        }
+                    }
+                }
+                if let Some(schema) = &node.items {
+                    for (i, item) in items.iter().enumerate() {
+                        let len = path.len();
+                        push_path_index(path, i);
+                        self.walk(schema, item, path, out);
+                        path.truncate(len);
+                    }
+                }
+            }
+            Value::Map(m) => {
+                for name in &node.required {
+                    if !m.contains_key(name) {
+                        fail(
+                            out,
+                            path,
+                            "required",
+                            format!("missing required property {name:?}"),
+                        );
+                    }
+                }
+                if let Some(n) = node.min_properties.filter(|n| m.len() < *n) {
+                    fail(
+                        out,
+                        path,
+                        "minProperties",
+                        format!("expected at least {n} properties, got {}", m.len()),
+                    );
+                }
+                if let Some(n) = node.max_properties.filter(|n| m.len() > *n) {
+                    fail(
+                        out,
+                        path,
+                        "maxProperties",
+                        format!("expected at most {n} properties, got {}", m.len()),
+                    );
+                }
+                for (k, child) in m {
+                    let len = path.len();
+                    push_path_key(path, k);
+                    let mut matched = false;
+                    if let Some(schema) = node.properties.get(k) {
+                        matched = true;
+                        self.walk(schema, child, path, out);
+                    }
+                    for (re, schema) in &node.pattern_properties {
+                        if re.is_match(k) {
+                            matched = true;
+                            self.walk(schema, child, path, out);
+                        }
+                    }
+                    if !matched {
+                        match node.additional.as_deref() {
+                            Some(Node {
+                                always: Some(false),
+                                ..
+                            }) => fail(
+                                out,
+                                path,
+                                "additionalProperties",
+                                "property is not allowed".into(),
+                            ),
+                            Some(schema) => self.walk(schema, child, path, out),
+                            None => {}
+                        }
+                    }
+                    path.truncate(len);
+                }
+            }
+            Value::Null | Value::Bool(_) => {}
+        }
+
+        // Combinators report their own verdict; branch details would be noise.
+        for schema in &node.all_of {
+            self.walk(schema, v, path, out);
+        }
+        if !node.any_of.is_empty() && !node.any_of.iter().any(|s| self.passes(s, v, path)) {
+            out.push(Violation {
+                path: path.clone(),
+                keyword: "anyOf",
+                message: format!("matches none of {} alternatives", node.any_of.len()),
+            });
+        }
+        if !node.one_of.is_empty() {
+            let hits = node
+                .one_of
+                .iter()
+                .filter(|s| self.passes(s, v, path))
+                .count();
+            if hits != 1 {
+                out.push(Violation {
+                    path: path.clone(),
+                    keyword: "oneOf",
+                    message: format!(
+                        "matches {hits} of {} alternatives, expected exactly one",
+                        node.one_of.len()
+                    ),
+                });
+            }
+        }
+        if let Some(schema) = &node.not {
+            if self.passes(schema, v, path) {
+                out.push(Violation {
+                    path: path.clone(),
+                    keyword: "not",
+                    message: "matches a schema it must not match".into(),
+                });
+            }
+        }
+        if let Some(cond) = &node.if_ {
+            let branch = if self.passes(cond, v, path) {
+                &node.then
+            } else {
+                &node.else_
+            };
+            if let Some(schema) = branch {
+                self.walk(schema, v, path, out);
+            }
+        }
+    }
+}
+
+fn fail(out: &mut Vec<Violation>, path: &str, keyword: &'static str, message: String) {
+    out.push(Violation {
+        path: path.to_string(),
+        keyword,
+        message,
+    });
+}
+
+fn check_len(node: &Node, len: usize, unit: &str, path: &str, out: &mut Vec<Violation>) {
+    if let Some(m) = node.min_length.filter(|m| len < *m) {
+        fail(
+            out,
+            path,
+            "minLength",
+            format!("expected at least {m} {unit}, got {len}"),
+        );
+    }
+    if let Some(m) = node.max_length.filter(|m| len > *m) {
+        fail(
+            out,
+            path,
+            "maxLength",
+            format!("expected at most {m} {unit}, got {len}"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nrf_core::from_diag;
+    use serde_json::json;
+
+    fn schema(j: serde_json::Value) -> Schema {
+        Schema::from_json(&j).unwrap()
+    }
+
+    fn paths(s: &Schema, diag: &str) -> Vec<(String, &'static str)> {
+        s.validate(&from_diag(diag).unwrap())
+            .into_iter()
+            .map(|v| (v.path, v.keyword))
+            .collect()
+    }
+
+    #[test]
+    fn every_builtin_compiles() {
+        for name in builtin_names() {
+            Schema::builtin(name).unwrap_or_else(|e| panic!("{name}: {e}"));
+        }
+        assert_eq!(
+            Schema::builtin("nope").unwrap_err(),
+            SchemaError::UnknownBuiltin("nope".into())
+        );
+    }
+
+    #[test]
+    fn reports_all_violations_with_paths() {
+        let s = Schema::builtin("receipt.v1").unwrap();
+        let got = paths(
+            &s,
+            r#"{"body_cid": "b3:00", "cid": 7, "claims": {}, "extra": 1,
+                "ghost": {"budget": -1, "cost_ms": 0, "counter": 0, "window_day": 9},
+                "signer": "did:ubl:a"}"#,
+        );
+        assert_eq!(
+            got,
+            vec![
+                ("".into(), "required"),
+                ("body_cid".into(), "pattern"),
+                ("cid".into(), "type"),
+                ("extra".into(), "additionalProperties"),
+                ("ghost.budget".into(), "minimum"),
+                ("ghost.window_day".into(), "maximum"),
+            ]
+        );
+    }
+
+    #[test]
+    fn distinguishes_nrf_types() {
+        let s = schema(json!({
+            "type": "object",
+            "properties": {
+                "hash": {"type": "bytes", "byteLength": 32},
+                "nonce": {"type": "bytes", "minLength": 16},
+                "n": {"type": "integer", "minimum": -9223372036854775808_i64, "maximum": 9223372036854775806_i64}
+            }
+        }));
+        assert!(paths(
+            &s,
+            &format!(r#"{{"hash": h'{}', "n": 1}}"#, "00".repeat(32))
+        )
+        .is_empty());
+        assert_eq!(
+            paths(
+                &s,
+                r#"{"hash": "00", "n": 9223372036854775807, "nonce": h'0102'}"#
+            ),
+            vec![
+                ("hash".into(), "type"),
+                ("n".into(), "maximum"),
+                ("nonce".into(), "minLength"),
+            ]
+        );
+        let v = s.validate(&from_diag(r#"{"hash": h'00'}"#).unwrap());
+        assert_eq!(v[0].to_string(), "hash: expected 32 bytes, got 1");
+    }
+
+    #[test]
+    fn nrf_formats() {
+        let s = schema(json!({
+            "type": "object",
+            "properties": {
+                "did": {"type": "string", "format": "did"},
+                "amount": {"type": "string", "format": "decimal"},
+                "at": {"type": "string", "format": "timestamp"},
+                "cid": {"type": "string", "format": "cid"},
+                "home": {"type": "string", "format": "uri"}
+            }
+        }));
+        let ok = format!(
+            r#"{{"amount": "-12.50", "at": "2024-01-15T10:30:00Z", "cid": "b3:{}", "did": "did:ubl:alice", "home": "https://ubl.agency"}}"#,
+            "ab".repeat(32)
+        );
+        assert!(paths(&s, &ok).is_empty());
+        assert_eq!(
+            paths(
+                &s,
+                r#"{"amount": "1e3", "at": "2024-01-15 10:30", "cid": "b3:AB", "did": "did:ubl:alïce", "home": "not a uri"}"#
+            ),
+            vec![
+                ("amount".into(), "format"),
+                ("at".into(), "format"),
+                ("cid".into(), "format"),
+                ("did".into(), "format"),
+                ("home".into(), "format"),
+            ]
+        );
+    }
+
+    #[test]
+    fn refs_and_combinators() {
+        let s = schema(json!({
+            "$defs": {"cid": {"type": "string", "format": "cid"}},
+            "type": "array",
+            "items": {"anyOf": [{"type": "null"}, {"$ref": "#/$defs/cid"}]},
+            "uniqueItems": true
+        }));
+        assert!(paths(&s, "[null]").is_empty());
+        assert_eq!(
+            paths(&s, r#"[null, null, "x"]"#),
+            vec![("".into(), "uniqueItems"), ("[2]".into(), "anyOf")]
+        );
+
+        let s = schema(json!({
+            "type": "object",
+            "if": {"properties": {"kind": {"const": "pay"}}, "required": ["kind"]},
+            "then": {"required": ["amount"]},
+            "else": {"not": {"required": ["amount"]}}
+        }));
+        assert!(paths(&s, r#"{"amount": "1", "kind": "pay"}"#).is_empty());
+        assert_eq!(
+            paths(&s, r#"{"kind": "pay"}"#),
+            vec![("".into(), "required")]
+        );
+        assert_eq!(paths(&s, r#"{"amount": "1"}"#), vec![("".into(), "not")]);
+    }
+
+    #[test]
+    fn rejects_bad_schemas() {
+        let err = |j: serde_json::Value| Schema::from_json(&j).unwrap_err();
+        assert!(matches!(
+            err(json!({"type": "float"})),
+            SchemaError::BadKeyword { .. }
+        ));
+        assert!(matches!(
+            err(json!({"maximum": 1.5})),
+            SchemaError::BadKeyword { .. }
+        ));
+        assert!(matches!(
+            err(json!({"format": "email"})),
+            SchemaError::BadKeyword { .. }
+        ));
+        assert!(matches!(
+            err(json!({"pattern": "("})),
+            SchemaError::BadKeyword { .. }
+        ));
+        assert_eq!(
+            err(json!({"$ref": "#/$defs/missing"})),
+            SchemaError::UnresolvedRef("#/$defs/missing".into())
+        );
+        assert!(matches!(
+            err(json!({"$ref": "https://example.com/s.json"})),
+            SchemaError::BadKeyword { .. }
+        ));
+    }
+}
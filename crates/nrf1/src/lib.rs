@@ -4,12 +4,28 @@
 pub use nrf_core::Error as NrfError;
 pub use nrf_core::Result;
 pub use nrf_core::Value;
+pub use nrf_core::nrf;
 pub use nrf_core::MAGIC;
 
 pub use nrf_core::decode;
+pub use nrf_core::{decode_located, DecodeError};
+pub use nrf_core::{from_diag, to_diag, to_diag_pretty};
+pub use nrf_core::{diff, diff_bytes, Change, ChangeKind};
+pub use nrf_core::merkle;
+pub use nrf_core::query;
+pub use nrf_core::rho;
 pub use nrf_core::encode;
+pub use nrf_core::{encode_into, encoded_len, HashSink};
+pub use nrf_core::{encode_with_opts, try_encode, DecodeOpts};
 pub use nrf_core::hash_bytes;
 pub use nrf_core::hash_value;
+pub use nrf_core::{NrfDecimal, RoundingMode};
+
+/// Framed record log: canonical NRF records with CIDs and an optional index.
+pub mod records;
+
+/// serde data format: `#[derive(Serialize)]` types straight to canonical NRF.
+pub use nrf_core::serde;
 
 // Compat aliases used by receipt, reasoning-bit, sdk-rs
 pub use nrf_core::blake3_cid;
@@ -0,0 +1,621 @@
+//! Framed record log — many canonical NRF values in one binary file.
+//!
+//! ```text
+//! file    = header record* [index trailer]
+//! header  = "NRFLOG" 0x00 0x01                      (magic + version)
+//! record  = 0x01 len:u32be cid:[32] nrf:[len]       nrf includes "nrf1"
+//! index   = 0x02 count:u32be (cid:[32] offset:u64be)*count
+//! trailer = index_offset:u64be "NRFIDX01"           (last 16 bytes)
+//! ```
+//!
+//! `cid` is BLAKE3 over the record's NRF bytes — the same hash as
+//! `hash_value` — so every record is checked as it is read. Records are only
+//! written if they are canonical under the default decode limits, and the
+//! bytes are kept as-is: no re-derivation from JSON on the way out.
+//!
+//! The index is optional. `IndexedLog` uses it when the trailer is present
+//! and consistent, and otherwise builds the same map by scanning.
+
+use crate::{NrfError, Value};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+pub const LOG_MAGIC: [u8; 8] = *b"NRFLOG\x00\x01";
+pub const INDEX_MAGIC: [u8; 8] = *b"NRFIDX01";
+
+const TAG_RECORD: u8 = 0x01;
+const TAG_INDEX: u8 = 0x02;
+const RECORD_HEAD: u64 = 1 + 4 + 32;
+const TRAILER_LEN: u64 = 16;
+
+/// BLAKE3 of a record's NRF bytes.
+pub type Cid = [u8; 32];
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LogError {
+    Io(String),
+    /// The file does not start with `LOG_MAGIC`.
+    BadHeader,
+    /// The file ends inside the frame starting at `offset`.
+    Truncated {
+        offset: u64,
+    },
+    /// Stored CID does not match the record bytes.
+    CidMismatch {
+        offset: u64,
+    },
+    /// Unknown frame tag, absurd length, or malformed index.
+    Corrupt {
+        offset: u64,
+        reason: String,
+    },
+    /// Record bytes are not canonical NRF (on append, or when decoded).
+    Canon {
+        offset: u64,
+        error: NrfError,
+    },
+}
+
+impl std::fmt::Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogError::Io(e) => write!(f, "record log I/O: {e}"),
+            LogError::BadHeader => write!(f, "record log: bad header (expected NRFLOG v1)"),
+            LogError::Truncated { offset } => {
+                write!(f, "record log: truncated frame at byte {offset}")
+            }
+            LogError::CidMismatch { offset } => {
+                write!(f, "record log: CID mismatch for record at byte {offset}")
+            }
+            LogError::Corrupt { offset, reason } => {
+                write!(f, "record log: corrupt at byte {offset}: {reason}")
+            }
+            LogError::Canon { offset, error } => {
+                write!(
+                    f,
+                    "record log: non-canonical record at byte {offset}: {error}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
+
+impl From<io::Error> for LogError {
+    fn from(e: io::Error) -> Self {
+        LogError::Io(e.to_string())
+    }
+}
+
+fn corrupt(offset: u64, reason: impl Into<String>) -> LogError {
+    LogError::Corrupt {
+        offset,
+        reason: reason.into(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Record
+// ---------------------------------------------------------------------------
+
+/// One stored value: its canonical bytes, their CID and where the frame starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub offset: u64,
+    pub cid: Cid,
+    pub nrf: Vec<u8>,
+}
+
+impl Record {
+    /// Decode the record (default limits).
+    pub fn value(&self) -> Result<Value, LogError> {
+        crate::decode(&self.nrf).map_err(|error| LogError::Canon {
+            offset: self.offset,
+            error,
+        })
+    }
+
+    /// `b3:<hex>`, the form used in receipts and capsules.
+    pub fn cid_string(&self) -> String {
+        format!("b3:{}", nrf_core::encode_hex_lower(&self.cid))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Writer
+// ---------------------------------------------------------------------------
+
+/// Appends records; `finish` writes the index and trailer.
+pub struct LogWriter<W: Write> {
+    w: W,
+    offset: u64,
+    index: Vec<(Cid, u64)>,
+}
+
+impl<W: Write> LogWriter<W> {
+    /// Start a new log (writes the header).
+    pub fn new(mut w: W) -> Result<Self, LogError> {
+        w.write_all(&LOG_MAGIC)?;
+        Ok(Self {
+            w,
+            offset: LOG_MAGIC.len() as u64,
+            index: Vec::new(),
+        })
+    }
+
+    /// Encode (checked, default limits) and append a value.
+    pub fn append(&mut self, v: &Value) -> Result<Cid, LogError> {
+        let nrf = crate::try_encode(v).map_err(|error| LogError::Canon {
+            offset: self.offset,
+            error,
+        })?;
+        self.write_record(&nrf)
+    }
+
+    /// Append already-encoded bytes; rejected unless they decode canonically.
+    pub fn append_nrf(&mut self, nrf: &[u8]) -> Result<Cid, LogError> {
+        crate::decode(nrf).map_err(|error| LogError::Canon {
+            offset: self.offset,
+            error,
+        })?;
+        self.write_record(nrf)
+    }
+
+    /// Records written (including those found by `open_append`).
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Push buffered records through to the underlying writer. The log
+    /// stays open and unindexed; readers scan up to the last record.
+    pub fn flush(&mut self) -> Result<(), LogError> {
+        self.w.flush()?;
+        Ok(())
+    }
+
+    /// Write the index and trailer, flush, and hand back the writer.
+    pub fn finish(mut self) -> Result<W, LogError> {
+        let count = u32::try_from(self.index.len())
+            .map_err(|_| corrupt(self.offset, "too many records for one index"))?;
+        self.w.write_all(&[TAG_INDEX])?;
+        self.w.write_all(&count.to_be_bytes())?;
+        for (cid, offset) in &self.index {
+            self.w.write_all(cid)?;
+            self.w.write_all(&offset.to_be_bytes())?;
+        }
+        self.w.write_all(&self.offset.to_be_bytes())?;
+        self.w.write_all(&INDEX_MAGIC)?;
+        self.w.flush()?;
+        Ok(self.w)
+    }
+
+    /// Flush without an index (readers will scan).
+    pub fn finish_without_index(mut self) -> Result<W, LogError> {
+        self.w.flush()?;
+        Ok(self.w)
+    }
+
+    fn write_record(&mut self, nrf: &[u8]) -> Result<Cid, LogError> {
+        let len = u32::try_from(nrf.len()).map_err(|_| LogError::Canon {
+            offset: self.offset,
+            error: NrfError::SizeExceeded,
+        })?;
+        let cid = nrf_core::hash_bytes(nrf);
+        self.w.write_all(&[TAG_RECORD])?;
+        self.w.write_all(&len.to_be_bytes())?;
+        self.w.write_all(&cid)?;
+        self.w.write_all(nrf)?;
+        self.index.push((cid, self.offset));
+        self.offset += RECORD_HEAD + nrf.len() as u64;
+        Ok(cid)
+    }
+}
+
+impl LogWriter<BufWriter<File>> {
+    /// Create `path` if missing, otherwise verify every existing record,
+    /// drop the old index and continue after the last record. A torn tail
+    /// is an error, never silently cut.
+    pub fn open_append(path: impl AsRef<Path>) -> Result<Self, LogError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        if file.metadata()?.len() == 0 {
+            return LogWriter::new(BufWriter::new(file));
+        }
+        let mut index = Vec::new();
+        let end = {
+            let mut reader = LogReader::new(BufReader::new(&mut file))?;
+            for rec in reader.by_ref() {
+                let rec = rec?;
+                index.push((rec.cid, rec.offset));
+            }
+            reader.offset
+        };
+        file.set_len(end)?;
+        file.seek(SeekFrom::Start(end))?;
+        Ok(Self {
+            w: BufWriter::new(file),
+            offset: end,
+            index,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sequential reader
+// ---------------------------------------------------------------------------
+
+/// Iterates records in file order, verifying each CID. Stops at the index
+/// (which it checks against the records seen) or at end of file.
+pub struct LogReader<R: Read> {
+    r: R,
+    offset: u64,
+    seen: Vec<(Cid, u64)>,
+    done: bool,
+}
+
+impl<R: Read> LogReader<R> {
+    pub fn new(mut r: R) -> Result<Self, LogError> {
+        let mut magic = [0u8; 8];
+        read_exact_or(&mut r, &mut magic, LogError::BadHeader)?;
+        if magic != LOG_MAGIC {
+            return Err(LogError::BadHeader);
+        }
+        Ok(Self {
+            r,
+            offset: LOG_MAGIC.len() as u64,
+            seen: Vec::new(),
+            done: false,
+        })
+    }
+
+    fn next_record(&mut self) -> Result<Option<Record>, LogError> {
+        let start = self.offset;
+        let mut tag = [0u8; 1];
+        if self.r.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+        match tag[0] {
+            TAG_RECORD => {
+                let rec = read_record_body(&mut self.r, start)?;
+                self.offset += RECORD_HEAD + rec.nrf.len() as u64;
+                self.seen.push((rec.cid, start));
+                Ok(Some(rec))
+            }
+            TAG_INDEX => {
+                let index = read_index_body(&mut self.r, start)?;
+                if index != self.seen {
+                    return Err(corrupt(start, "index does not match records"));
+                }
+                let mut trailer = [0u8; TRAILER_LEN as usize];
+                read_exact_or(
+                    &mut self.r,
+                    &mut trailer,
+                    LogError::Truncated { offset: start },
+                )?;
+                if trailer[8..] != INDEX_MAGIC || trailer[..8] != start.to_be_bytes() {
+                    return Err(corrupt(start, "bad trailer"));
+                }
+                if self.r.read(&mut tag)? != 0 {
+                    return Err(corrupt(start, "data after trailer"));
+                }
+                Ok(None)
+            }
+            other => Err(corrupt(start, format!("unknown frame tag {other:#04x}"))),
+        }
+    }
+}
+
+impl<R: Read> Iterator for LogReader<R> {
+    type Item = Result<Record, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.next_record().transpose();
+        if !matches!(item, Some(Ok(_))) {
+            self.done = true;
+        }
+        item
+    }
+}
+
+fn read_exact_or<R: Read>(r: &mut R, buf: &mut [u8], eof: LogError) -> Result<(), LogError> {
+    r.read_exact(buf).map_err(|e| {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            eof
+        } else {
+            e.into()
+        }
+    })
+}
+
+/// After the tag byte: length, CID, bytes; the CID is checked here.
+fn read_record_body<R: Read>(r: &mut R, offset: u64) -> Result<Record, LogError> {
+    let mut head = [0u8; 36];
+    read_exact_or(r, &mut head, LogError::Truncated { offset })?;
+    let len = u32::from_be_bytes(head[..4].try_into().expect("4 bytes")) as usize;
+    if len > nrf_core::DecodeOpts::default().max_total_bytes {
+        return Err(corrupt(offset, format!("record length {len} over limit")));
+    }
+    let cid: Cid = head[4..].try_into().expect("32 bytes");
+    let mut nrf = vec![0u8; len];
+    read_exact_or(r, &mut nrf, LogError::Truncated { offset })?;
+    if nrf_core::hash_bytes(&nrf) != cid {
+        return Err(LogError::CidMismatch { offset });
+    }
+    Ok(Record { offset, cid, nrf })
+}
+
+fn read_index_body<R: Read>(r: &mut R, offset: u64) -> Result<Vec<(Cid, u64)>, LogError> {
+    let mut count = [0u8; 4];
+    read_exact_or(r, &mut count, LogError::Truncated { offset })?;
+    let count = u32::from_be_bytes(count) as usize;
+    let mut entries = Vec::with_capacity(count.min(1 << 16));
+    for _ in 0..count {
+        let mut entry = [0u8; 40];
+        read_exact_or(r, &mut entry, LogError::Truncated { offset })?;
+        let cid: Cid = entry[..32].try_into().expect("32 bytes");
+        let at = u64::from_be_bytes(entry[32..].try_into().expect("8 bytes"));
+        entries.push((cid, at));
+    }
+    Ok(entries)
+}
+
+// ---------------------------------------------------------------------------
+// Random access
+// ---------------------------------------------------------------------------
+
+/// CID → record lookups over a seekable log.
+pub struct IndexedLog<R: Read + Seek> {
+    r: R,
+    offsets: HashMap<Cid, u64>,
+    order: Vec<Cid>,
+    from_trailer: bool,
+}
+
+impl<R: Read + Seek> IndexedLog<R> {
+    /// Load the trailing index, or scan (verifying every record) if the log
+    /// has none.
+    pub fn open(mut r: R) -> Result<Self, LogError> {
+        r.seek(SeekFrom::Start(0))?;
+        let mut magic = [0u8; 8];
+        read_exact_or(&mut r, &mut magic, LogError::BadHeader)?;
+        if magic != LOG_MAGIC {
+            return Err(LogError::BadHeader);
+        }
+        let (entries, from_trailer) = match Self::read_trailer_index(&mut r)? {
+            Some(entries) => (entries, true),
+            None => {
+                r.seek(SeekFrom::Start(0))?;
+                let mut entries = Vec::new();
+                for rec in LogReader::new(&mut r)? {
+                    let rec = rec?;
+                    entries.push((rec.cid, rec.offset));
+                }
+                (entries, false)
+            }
+        };
+        let mut offsets = HashMap::with_capacity(entries.len());
+        let mut order = Vec::with_capacity(entries.len());
+        for (cid, at) in entries {
+            order.push(cid);
+            // Duplicate values share a CID; the first copy wins.
+            offsets.entry(cid).or_insert(at);
+        }
+        Ok(Self {
+            r,
+            offsets,
+            order,
+            from_trailer,
+        })
+    }
+
+    fn read_trailer_index(r: &mut R) -> Result<Option<Vec<(Cid, u64)>>, LogError> {
+        let len = r.seek(SeekFrom::End(0))?;
+        if len < LOG_MAGIC.len() as u64 + 5 + TRAILER_LEN {
+            return Ok(None);
+        }
+        r.seek(SeekFrom::Start(len - TRAILER_LEN))?;
+        let mut trailer = [0u8; TRAILER_LEN as usize];
+        r.read_exact(&mut trailer)?;
+        if trailer[8..] != INDEX_MAGIC {
+            return Ok(None);
+        }
+        let at = u64::from_be_bytes(trailer[..8].try_into().expect("8 bytes"));
+        if at < LOG_MAGIC.len() as u64 || at >= len - TRAILER_LEN {
+            return Ok(None);
+        }
+        r.seek(SeekFrom::Start(at))?;
+        let mut tag = [0u8; 1];
+        r.read_exact(&mut tag)?;
+        if tag[0] != TAG_INDEX {
+            return Ok(None);
+        }
+        let Ok(entries) = read_index_body(r, at) else {
+            return Ok(None);
+        };
+        // The index must end exactly where the trailer begins.
+        if r.stream_position()? != len - TRAILER_LEN {
+            return Ok(None);
+        }
+        Ok(Some(entries))
+    }
+
+    /// Whether lookups came from the stored index (vs. a scan).
+    pub fn has_index(&self) -> bool {
+        self.from_trailer
+    }
+
+    /// Number of records (duplicates included).
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// CIDs in file order.
+    pub fn cids(&self) -> &[Cid] {
+        &self.order
+    }
+
+    pub fn contains(&self, cid: &Cid) -> bool {
+        self.offsets.contains_key(cid)
+    }
+
+    /// Read and verify the record with this CID.
+    pub fn get(&mut self, cid: &Cid) -> Result<Option<Record>, LogError> {
+        let Some(&at) = self.offsets.get(cid) else {
+            return Ok(None);
+        };
+        self.r.seek(SeekFrom::Start(at))?;
+        let mut tag = [0u8; 1];
+        read_exact_or(&mut self.r, &mut tag, LogError::Truncated { offset: at })?;
+        if tag[0] != TAG_RECORD {
+            return Err(corrupt(at, "index points at a non-record frame"));
+        }
+        let rec = read_record_body(&mut self.r, at)?;
+        if rec.cid != *cid {
+            return Err(corrupt(at, "index points at a different record"));
+        }
+        Ok(Some(rec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn values() -> Vec<Value> {
+        vec![
+            crate::from_diag(r#"{"act": "ATTEST", "n": 1}"#).unwrap(),
+            crate::from_diag(r#"["x", h'00ff', null]"#).unwrap(),
+            Value::Int(-7),
+        ]
+    }
+
+    fn build(index: bool) -> (Vec<u8>, Vec<Cid>) {
+        let mut w = LogWriter::new(Vec::new()).unwrap();
+        let cids: Vec<Cid> = values().iter().map(|v| w.append(v).unwrap()).collect();
+        let bytes = if index {
+            w.finish().unwrap()
+        } else {
+            w.finish_without_index().unwrap()
+        };
+        (bytes, cids)
+    }
+
+    #[test]
+    fn roundtrip_keeps_canonical_bytes() {
+        for index in [true, false] {
+            let (bytes, cids) = build(index);
+            let recs: Vec<Record> = LogReader::new(&bytes[..])
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(recs.len(), 3);
+            for ((rec, v), cid) in recs.iter().zip(values()).zip(&cids) {
+                assert_eq!(rec.nrf, crate::encode(&v));
+                assert_eq!(rec.value().unwrap(), v);
+                assert_eq!(&rec.cid, cid);
+                assert_eq!(rec.cid_string(), crate::blake3_cid(&v));
+            }
+        }
+    }
+
+    #[test]
+    fn random_access_with_and_without_index() {
+        for index in [true, false] {
+            let (bytes, cids) = build(index);
+            let mut log = IndexedLog::open(Cursor::new(bytes)).unwrap();
+            assert_eq!(log.has_index(), index);
+            assert_eq!(log.cids(), &cids[..]);
+            let rec = log.get(&cids[1]).unwrap().unwrap();
+            assert_eq!(rec.value().unwrap(), values()[1]);
+            assert!(log.get(&[0; 32]).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn detects_corruption() {
+        let (bytes, _) = build(true);
+        let read_all = |b: &[u8]| -> Result<Vec<Record>, LogError> { LogReader::new(b)?.collect() };
+
+        let mut flipped = bytes.clone();
+        flipped[LOG_MAGIC.len() + RECORD_HEAD as usize + 6] ^= 0x01;
+        assert_eq!(
+            read_all(&flipped).unwrap_err(),
+            LogError::CidMismatch {
+                offset: LOG_MAGIC.len() as u64
+            }
+        );
+
+        let (plain, _) = build(false);
+        let torn = &plain[..plain.len() - 2];
+        assert!(matches!(
+            read_all(torn).unwrap_err(),
+            LogError::Truncated { .. }
+        ));
+
+        let mut bad_tag = bytes.clone();
+        bad_tag[LOG_MAGIC.len()] = 0x09;
+        assert!(matches!(
+            read_all(&bad_tag).unwrap_err(),
+            LogError::Corrupt { .. }
+        ));
+
+        assert_eq!(
+            read_all(b"NRFLOG\x00\x02").unwrap_err(),
+            LogError::BadHeader
+        );
+
+        let mut w = LogWriter::new(Vec::new()).unwrap();
+        assert!(matches!(
+            w.append_nrf(b"nrf1\x04\x01\xff").unwrap_err(),
+            LogError::Canon { offset: 8, .. }
+        ));
+    }
+
+    #[test]
+    fn open_append_replaces_index() {
+        let path = std::env::temp_dir().join(format!("nrf1-records-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut w = LogWriter::open_append(&path).unwrap();
+        w.append(&values()[0]).unwrap();
+        w.finish().unwrap();
+
+        let mut w = LogWriter::open_append(&path).unwrap();
+        assert_eq!(w.len(), 1);
+        w.append(&values()[2]).unwrap();
+        w.finish().unwrap();
+
+        let mut log = IndexedLog::open(File::open(&path).unwrap()).unwrap();
+        assert!(log.has_index());
+        assert_eq!(log.len(), 2);
+        let cid = log.cids()[1];
+        assert_eq!(
+            log.get(&cid).unwrap().unwrap().value().unwrap(),
+            Value::Int(-7)
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,7 +1,6 @@
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use nrf1::Value;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 
 // ---------------------------------------------------------------------------
 // Permit — the accountability closer (BASE terrain)
@@ -45,20 +44,20 @@ pub struct Permit {
 impl Permit {
     /// Canonical NRF map without sig and permit_cid (the hash preimage).
     pub fn nrf_without_sig(&self) -> Value {
-        use Value::*;
-        let mut m = BTreeMap::new();
-        m.insert("act".into(), String(self.act.clone()));
-        m.insert("decision".into(), String(self.decision.clone()));
-        m.insert("expires_at".into(), Int(self.expires_at));
-        m.insert("input_hash".into(), String(self.input_hash.clone()));
-        m.insert("issued_at".into(), Int(self.issued_at));
-        m.insert("issuer_did".into(), String(self.issuer_did.clone()));
+        let mut v = nrf1::nrf!({
+            "act": &self.act,
+            "decision": &self.decision,
+            "expires_at": self.expires_at,
+            "input_hash": &self.input_hash,
+            "issued_at": self.issued_at,
+            "issuer_did": &self.issuer_did,
+            "request_cid": &self.request_cid,
+            "v": &self.v,
+        });
         if let Some(p) = &self.policy {
-            m.insert("policy".into(), String(p.clone()));
+            v["policy"] = p.into();
         }
-        m.insert("request_cid".into(), String(self.request_cid.clone()));
-        m.insert("v".into(), String(self.v.clone()));
-        Map(m)
+        v
     }
 
     pub fn compute_cid(&self) -> String {
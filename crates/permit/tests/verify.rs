@@ -85,3 +85,15 @@ fn test_permit_wrong_key() {
     let err = verify_permit(&p, "b3:bbbb", now, &wrong_vk).unwrap_err();
     assert!(matches!(err, PermitError::BadSignature));
 }
+
+#[test]
+fn test_permit_serde_preimage_matches_hand_built() {
+    let mut p = make_test_permit();
+    p.permit_cid = p.compute_cid();
+    let nrf1::Value::Map(mut m) = nrf1::serde::to_value(&p).unwrap() else {
+        panic!("permit must serialize to a map");
+    };
+    m.remove("permit_cid");
+    m.remove("sig");
+    assert_eq!(nrf1::Value::Map(m), p.nrf_without_sig());
+}
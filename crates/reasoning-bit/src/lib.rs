@@ -39,58 +39,89 @@ pub struct ReasoningBit {
     pub sig: Option<Vec<u8>>, // wrapper signature (optional here)
 }
 
+// ---------------------------------------------------------------------------
+// Signed preimage — derived, not hand-built
+//
+// Everything except `determinism` and `sig`. Floats are not NRF: scores go
+// in as fixed-point millionths (µ), truncated toward zero.
+// ---------------------------------------------------------------------------
+
+fn micros(x: f32) -> i64 {
+    (x * 1_000_000.0) as i64
+}
+
+#[derive(Serialize)]
+struct JudgmentPreimage<'a> {
+    verdict: &'a str,
+    confidence: i64,
+    reasoning: &'a str,
+}
+
+#[derive(Serialize)]
+struct UsagePreimage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hrd_score: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct Preimage<'a> {
+    v: &'a str,
+    context_cid: &'a str,
+    prompt_hash: &'a str,
+    model: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model_sha256: Option<&'a str>,
+    policy: &'a str,
+    judgment: JudgmentPreimage<'a>,
+    usage: UsagePreimage,
+}
+
 impl ReasoningBit {
-    pub fn sign_bytes(&self) -> Vec<u8> {
-        // Canonical NRF bytes of the ReasoningBit (without sig)
-        nrf1::encode_stream(&self.to_nrf())
+    fn preimage(&self) -> Preimage<'_> {
+        Preimage {
+            v: &self.v,
+            context_cid: &self.context_cid,
+            prompt_hash: &self.prompt_hash,
+            model: &self.model,
+            model_sha256: self.model_sha256.as_deref(),
+            policy: &self.policy,
+            judgment: JudgmentPreimage {
+                verdict: &self.judgment.verdict,
+                confidence: micros(self.judgment.confidence),
+                reasoning: &self.judgment.reasoning,
+            },
+            usage: UsagePreimage {
+                input_tokens: self.usage.input_tokens,
+                output_tokens: self.usage.output_tokens,
+                hrd_score: self.usage.hrd_score.map(micros),
+            },
+        }
+    }
+
+    /// Canonical NRF bytes of the ReasoningBit (without sig). Fails if a
+    /// string is not NFC.
+    pub fn sign_bytes(&self) -> nrf1::Result<Vec<u8>> {
+        nrf1::serde::to_bytes(&self.preimage())
     }
-    pub fn sign(&mut self, sk: &SigningKey) {
-        let sig = sk.sign(&self.sign_bytes());
+    pub fn sign(&mut self, sk: &SigningKey) -> nrf1::Result<()> {
+        let sig = sk.sign(&self.sign_bytes()?);
         self.sig = Some(sig.to_bytes().to_vec());
+        Ok(())
     }
     pub fn verify(&self, vk: &VerifyingKey) -> bool {
-        if let Some(ref s) = self.sig {
-            if let Ok(sig) = Signature::from_slice(s) {
-                return vk.verify_strict(&self.sign_bytes(), &sig).is_ok();
-            }
-        }
-        false
+        let (Some(s), Ok(bytes)) = (&self.sig, self.sign_bytes()) else {
+            return false;
+        };
+        Signature::from_slice(s).is_ok_and(|sig| vk.verify_strict(&bytes, &sig).is_ok())
     }
-    pub fn to_nrf(&self) -> Value {
-        use Value::*;
-        let mut m = std::collections::BTreeMap::new();
-        m.insert("v".into(), String(self.v.clone()));
-        m.insert("context_cid".into(), String(self.context_cid.clone()));
-        m.insert("prompt_hash".into(), String(self.prompt_hash.clone()));
-        m.insert("model".into(), String(self.model.clone()));
-        if let Some(ms) = &self.model_sha256 {
-            m.insert("model_sha256".into(), String(ms.clone()));
-        }
-        m.insert("policy".into(), String(self.policy.clone()));
-        // judgment
-        let mut jm = std::collections::BTreeMap::new();
-        jm.insert("verdict".into(), String(self.judgment.verdict.clone()));
-        jm.insert(
-            "confidence".into(),
-            Value::Int((self.judgment.confidence * 1_000_000.0) as i64),
-        ); // fixed-point µ
-        jm.insert("reasoning".into(), String(self.judgment.reasoning.clone()));
-        m.insert("judgment".into(), Map(jm));
-        // usage
-        let mut um = std::collections::BTreeMap::new();
-        if let Some(x) = self.usage.input_tokens {
-            um.insert("input_tokens".into(), Value::Int(x as i64));
-        }
-        if let Some(x) = self.usage.output_tokens {
-            um.insert("output_tokens".into(), Value::Int(x as i64));
-        }
-        if let Some(x) = self.usage.hrd_score {
-            um.insert("hrd_score".into(), Value::Int((x * 1_000_000.0) as i64));
-        }
-        m.insert("usage".into(), Map(um));
-        Map(m)
+    pub fn to_nrf(&self) -> nrf1::Result<Value> {
+        nrf1::serde::to_value(&self.preimage())
     }
-    pub fn cid(&self) -> String {
-        nrf1::blake3_cid(&self.to_nrf())
+    pub fn cid(&self) -> nrf1::Result<String> {
+        nrf1::serde::cid(&self.preimage())
     }
 }
@@ -0,0 +1,65 @@
+use reasoning_bit::*;
+
+fn make_test_bit() -> ReasoningBit {
+    ReasoningBit {
+        v: "reasoning.bit.v1".into(),
+        context_cid: "b3:00".into(),
+        prompt_hash: "sha256:ab".into(),
+        model: "model-x".into(),
+        model_sha256: Some("cd".into()),
+        policy: "eu-ai-act@1".into(),
+        judgment: Judgment {
+            verdict: "PASS".into(),
+            confidence: 0.75,
+            reasoning: "fine".into(),
+        },
+        usage: Usage {
+            input_tokens: Some(12),
+            output_tokens: None,
+            hrd_score: Some(0.5),
+        },
+        determinism: Determinism {
+            seed: 7,
+            temperature: 0.0,
+            top_p: 1.0,
+            model_sha256: "cd".into(),
+        },
+        sig: None,
+    }
+}
+
+// CIDs of the same bits under the hand-built preimage this crate used
+// before it was derived; signatures made then must still verify.
+#[test]
+fn preimage_bytes_are_unchanged() {
+    let b = make_test_bit();
+    assert_eq!(
+        b.cid().unwrap(),
+        "b3:7d939cbb6d413a93ba46e682c3c8eda84af069b7f2eaa9f779e3e711edabf01f"
+    );
+    let mut b = make_test_bit();
+    b.model_sha256 = None;
+    b.usage = Usage::default();
+    assert_eq!(
+        b.cid().unwrap(),
+        "b3:2d56ea84a586af37e9de4997f13e51af55631df534e676a1ea9ca3514c2a872e"
+    );
+    assert_eq!(nrf1::encode(&b.to_nrf().unwrap()), b.sign_bytes().unwrap());
+}
+
+#[test]
+fn sign_and_verify() {
+    let sk = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+    let mut b = make_test_bit();
+    b.sign(&sk).unwrap();
+    assert!(b.verify(&sk.verifying_key()));
+
+    // determinism is not signed; the judgment is.
+    b.determinism.seed = 8;
+    assert!(b.verify(&sk.verifying_key()));
+    b.judgment.confidence = 0.7;
+    assert!(!b.verify(&sk.verifying_key()));
+
+    b.judgment.reasoning = "e\u{0301}".into();
+    assert!(b.sign(&sk).is_err());
+}
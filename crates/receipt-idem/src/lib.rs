@@ -1,5 +1,4 @@
 //! Helper para construir idempotency-key para AppendReceipt e payloads associados.
-use anyhow::Result;
 
 pub fn idempotency_key(tenant: &str, trace_id: &str, plan_cid: &str) -> String {
     let s = format!("{}:{}:{}", tenant, trace_id, plan_cid);
@@ -1,7 +1,10 @@
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use nrf1::merkle::{PathSeg, Proof};
+use nrf1::rho::{Profile, Rule};
 use nrf1::Value;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::sync::LazyLock;
 
 // ---------------------------------------------------------------------------
 // Supporting types
@@ -16,7 +19,11 @@ pub struct RuntimeInfo {
     pub hal_ref: Option<String>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub env: BTreeMap<String, String>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "nrf1::serde::bytes_seq"
+    )]
     pub certs: Vec<Vec<u8>>,
 }
 
@@ -71,6 +78,8 @@ pub struct Receipt {
     pub body: Value,      // canonical NRF value
     pub body_cid: String, // b3:<hex> of encode(body)
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_root: Option<String>, // b3m:<hex> Merkle root of body (per-field proofs)
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub inputs_cid: Option<String>, // b3:<hex> of input context
 
     // --- policy ---
@@ -101,6 +110,7 @@ pub struct Receipt {
     pub ghost: Option<GhostInfo>,
 
     // --- entropy ---
+    #[serde(with = "nrf1::serde::bytes")]
     pub nonce: Vec<u8>, // 16 bytes
 
     // --- location ---
@@ -111,6 +121,28 @@ pub struct Receipt {
     pub sig: Option<Vec<u8>>, // Ed25519(BLAKE3(NRF(without sig)))
 }
 
+static RECEIPT_PROFILE: LazyLock<Profile> = LazyLock::new(|| {
+    Profile::new(&[
+        ("v", Rule::AsciiId),
+        ("issuer_did", Rule::AsciiId),
+        ("subject_did", Rule::AsciiId),
+        ("kid", Rule::AsciiId),
+        ("act", Rule::AsciiId),
+        ("subject", Rule::AsciiId),
+        ("decision", Rule::AsciiId),
+        ("body_cid", Rule::AsciiId),
+        ("body_root", Rule::AsciiId),
+        ("inputs_cid", Rule::AsciiId),
+        ("reasoning_cid", Rule::AsciiId),
+        ("permit_cid", Rule::AsciiId),
+        ("$.pipeline_prev[*]", Rule::AsciiId),
+        ("rt.binary_sha256", Rule::AsciiId),
+        ("rt.hal_ref", Rule::AsciiId),
+        ("prev", Rule::AsciiId),
+    ])
+    .expect("receipt ρ profile")
+});
+
 // ---------------------------------------------------------------------------
 // Rich URL builder
 // ---------------------------------------------------------------------------
@@ -198,6 +230,9 @@ impl Receipt {
         // payload
         m.insert("body".into(), self.body.clone());
         m.insert("body_cid".into(), String(self.body_cid.clone()));
+        if let Some(br) = &self.body_root {
+            m.insert("body_root".into(), String(br.clone()));
+        }
         if let Some(ic) = &self.inputs_cid {
             m.insert("inputs_cid".into(), String(ic.clone()));
         }
@@ -284,11 +319,34 @@ impl Receipt {
         nrf1::blake3_cid(&self.body)
     }
 
-    pub fn sign(&mut self, sk: &SigningKey) {
-        let bytes = nrf1::encode_stream(&self.nrf_without_sig());
+    /// Merkle root of the body; set `body_root` to this before signing to
+    /// allow proving single body fields with `prove_body_field`.
+    pub fn compute_body_root(&self) -> String {
+        nrf1::merkle::merkle_cid(&self.body)
+    }
+
+    /// Inclusion proof for one body field, checkable with `verify_body_field`.
+    pub fn prove_body_field(&self, path: &[PathSeg<'_>]) -> nrf1::Result<Proof> {
+        nrf1::merkle::prove(&self.body, path)
+    }
+
+    /// The ρ profile of the hash preimage: identifiers are ASCII.
+    pub fn rho_profile() -> &'static Profile {
+        &RECEIPT_PROFILE
+    }
+
+    /// Sign the receipt. Refuses (`SizeExceeded`, `StringTooLong`, ...)
+    /// when its NRF would not decode under the default limits, and
+    /// (`Rho`) when the preimage is not canonical under `rho_profile`.
+    pub fn sign(&mut self, sk: &SigningKey) -> nrf1::Result<()> {
+        let v = self.nrf_without_sig();
+        nrf1::rho::validate_with_profile(&v, &RECEIPT_PROFILE)
+            .map_err(|e| nrf1::NrfError::Rho(e.to_string()))?;
+        let bytes = nrf1::try_encode(&v)?;
         let digest = blake3::hash(&bytes);
         let sig = sk.sign(digest.as_bytes());
         self.sig = Some(sig.to_bytes().to_vec());
+        Ok(())
     }
 
     pub fn verify(&self, vk: &VerifyingKey) -> bool {
@@ -307,6 +365,11 @@ impl Receipt {
         if self.compute_body_cid() != self.body_cid {
             return Err("body_cid mismatch");
         }
+        if let Some(br) = &self.body_root {
+            if self.compute_body_root() != *br {
+                return Err("body_root mismatch");
+            }
+        }
         if self.compute_cid() != self.receipt_cid {
             return Err("receipt_cid mismatch");
         }
@@ -317,3 +380,9 @@ impl Receipt {
         Ok(())
     }
 }
+
+/// Check a body field against a receipt's `body_root` (`b3m:<hex>`) without
+/// the rest of the body. The receipt signature covers `body_root`.
+pub fn verify_body_field(body_root: &str, leaf: &Value, proof: &Proof) -> bool {
+    nrf1::merkle::verify_cid(body_root, leaf, proof)
+}
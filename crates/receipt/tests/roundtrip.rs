@@ -1,4 +1,5 @@
 use ed25519_dalek::SigningKey;
+use nrf1::merkle::PathSeg;
 use nrf1::Value;
 use receipt::*;
 use std::collections::BTreeMap;
@@ -24,6 +25,7 @@ fn make_test_receipt() -> Receipt {
         effects: None,
         body,
         body_cid,
+        body_root: None,
         inputs_cid: None,
         policy: None,
         reasoning_cid: None,
@@ -70,7 +72,7 @@ fn test_sign_and_verify() {
 
     let mut r = make_test_receipt();
     r.receipt_cid = r.compute_cid();
-    r.sign(&sk);
+    r.sign(&sk).unwrap();
 
     assert!(r.sig.is_some());
     assert!(r.verify(&vk));
@@ -85,11 +87,33 @@ fn test_verify_fails_with_wrong_key() {
 
     let mut r = make_test_receipt();
     r.receipt_cid = r.compute_cid();
-    r.sign(&sk);
+    r.sign(&sk).unwrap();
 
     assert!(!r.verify(&wrong_vk));
 }
 
+#[test]
+fn test_sign_refuses_undecodable_receipt() {
+    let sk = SigningKey::generate(&mut rand::thread_rng());
+    let mut r = make_test_receipt();
+    // Over the 1 MiB default string limit: decoders would reject it.
+    r.body = Value::String("x".repeat(2 * 1024 * 1024));
+    assert_eq!(r.sign(&sk), Err(nrf1::NrfError::StringTooLong));
+    assert!(r.sig.is_none());
+}
+
+#[test]
+fn test_sign_enforces_rho_profile() {
+    let sk = SigningKey::generate(&mut rand::thread_rng());
+    let mut r = make_test_receipt();
+    r.issuer_did = "did:ex:caf\u{e9}".into();
+    match r.sign(&sk) {
+        Err(nrf1::NrfError::Rho(msg)) => assert!(msg.contains("at issuer_did"), "{msg}"),
+        other => panic!("expected Rho error, got {other:?}"),
+    }
+    assert!(r.sig.is_none());
+}
+
 #[test]
 fn test_verify_integrity() {
     let mut r = make_test_receipt();
@@ -114,3 +138,58 @@ fn test_ghost_decision_null_effects_ok() {
     r.receipt_cid = r.compute_cid();
     assert!(r.verify_integrity().is_ok());
 }
+
+#[test]
+fn test_body_field_proof_against_signed_root() {
+    let mut r = make_test_receipt();
+    r.body_root = Some(r.compute_body_root());
+    r.receipt_cid = r.compute_cid();
+    assert!(r.verify_integrity().is_ok());
+
+    let proof = r.prove_body_field(&[PathSeg::Key("hello")]).unwrap();
+    let root = r.body_root.as_deref().unwrap();
+    assert!(verify_body_field(root, &Value::String("world".into()), &proof));
+    assert!(!verify_body_field(root, &Value::String("mars".into()), &proof));
+
+    r.body = Value::Null;
+    r.body_cid = r.compute_body_cid();
+    assert_eq!(r.verify_integrity(), Err("body_root mismatch"));
+}
+
+#[test]
+fn test_receipt_serde_preimage_matches_hand_built() {
+    let mut full = make_test_receipt();
+    full.subject_did = Some("did:ubl:subject".into());
+    full.kid = Some("did:ubl:test-issuer#k1".into());
+    full.effects = Some(Value::Map(BTreeMap::new()));
+    full.body_root = Some(full.compute_body_root());
+    full.pipeline_prev = vec!["b3:01".into(), "b3:02".into()];
+    full.rt.hal_ref = Some("hal:1".into());
+    full.rt.env = BTreeMap::from([("K".into(), "v".into())]);
+    full.rt.certs = vec![vec![1, 2], vec![3]];
+    full.prev = Some("b3:00".into());
+    full.chain = Some(ChainInfo {
+        prev_cid: "b3:00".into(),
+        skips: vec![None],
+        link_hash: "b3:ff".into(),
+    });
+    full.ghost = Some(GhostInfo {
+        budget: 10,
+        counter: 1,
+        cost_ms: 5,
+        window_day: 3,
+    });
+    full.sig = Some(vec![0; 64]);
+
+    for r in [make_test_receipt(), full] {
+        let Value::Map(mut m) = nrf1::serde::to_value(&r).unwrap() else {
+            panic!("receipt must serialize to a map");
+        };
+        // receipt_cid and sig are computed over the preimage; chain is a
+        // skip-list enrichment outside it.
+        m.remove("receipt_cid");
+        m.remove("sig");
+        m.remove("chain");
+        assert_eq!(Value::Map(m), r.nrf_without_sig());
+    }
+}
@@ -1,6 +1,8 @@
-use nrf_core::{rho, Value};
+use nrf_core::rho::{self, Profile, Rule};
+use nrf_core::Value;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::sync::LazyLock;
 use thiserror::Error;
 
 // ---------------------------------------------------------------------------
@@ -127,8 +129,14 @@ impl RuntimeInfo {
 
         let v = Value::Map(m);
 
-        // ρ-normalize: NFC strings, strip nulls, sort keys
-        rho::normalize(&v).map_err(|e| RuntimeError::RhoFailed(format!("{e}")))
+        // ρ-normalize: NFC strings, strip nulls, sort keys, ASCII identifiers
+        rho::normalize_with_profile(&v, &RUNTIME_PROFILE)
+            .map_err(|e| RuntimeError::RhoFailed(format!("{e}")))
+    }
+
+    /// The ρ profile every canonical RuntimeInfo satisfies.
+    pub fn rho_profile() -> &'static Profile {
+        &RUNTIME_PROFILE
     }
 
     /// Compute the CID of this RuntimeInfo (ρ-canonical).
@@ -145,11 +153,17 @@ impl RuntimeInfo {
         self.validate_structure()?;
         // Verify ρ-canonicality: normalize and check it doesn't change
         let v = self.to_canonical_value()?;
-        rho::validate(&v).map_err(|e| RuntimeError::NotCanonical(format!("{e}")))?;
+        rho::validate_with_profile(&v, &RUNTIME_PROFILE)
+            .map_err(|e| RuntimeError::NotCanonical(format!("{e}")))?;
         Ok(())
     }
 }
 
+static RUNTIME_PROFILE: LazyLock<Profile> = LazyLock::new(|| {
+    Profile::new(&[("binary_sha256", Rule::AsciiId), ("hal_ref", Rule::AsciiId)])
+        .expect("runtime ρ profile")
+});
+
 // =========================================================================
 // RuntimeAttestation trait — the contract for MODULE implementations
 //
@@ -276,6 +290,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn runtime_info_rejects_non_ascii_binary_hash() {
+        let mut info = test_info();
+        info.binary_sha256 = "abc def".into();
+        assert!(matches!(
+            info.to_canonical_value(),
+            Err(RuntimeError::RhoFailed(msg)) if msg.contains("at binary_sha256")
+        ));
+    }
+
     #[test]
     fn runtime_info_rejects_empty_name() {
         let mut info = test_info();
@@ -0,0 +1,531 @@
+//! Verification bundle — everything an auditor needs, in one NRF file.
+//!
+//! ```text
+//! bundle = nrf1 || {
+//!   "v":         "ubl-bundle/1",
+//!   "capsule":   Capsule,                 (same fields as the capsule JSON)
+//!   "receipts":  [receipt::Receipt],      referenced act receipts
+//!   "permits":   [permit::Permit],
+//!   "ghosts":    [ghost::Ghost],
+//!   "artifacts": [{cid: "b3:<hex>", data: Bytes}],
+//!   "keys":      [{kid, pk: Bytes(32), not_before?, not_after?}],
+//!   "did_docs":  [String],                static DID documents (JSON text)
+//! }
+//! ```
+//!
+//! [`verify`] checks everything offline: the capsule ID, seals and hop chain,
+//! every receipt / permit / ghost (CID + signature, keys resolved at signing
+//! time), every artifact against its CID, and that references (`permit_cid`,
+//! `b3:` evidence) point inside the bundle.
+//!
+//! Bundled keys and DID documents are self-asserted: they show the bundle is
+//! consistent, not who signed it. Pass trusted anchors to [`verify`] to
+//! resolve signers from pinned keys only (did:key signers included); the
+//! report lists every bundled key and every resolved signer with its
+//! fingerprint and provenance either way.
+
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use ubl_capsule::resolver::{DidKey, FirstMatch, KeyRecord, KeyResolver, Keyring, ResolveError};
+use ubl_capsule::Capsule;
+
+pub const BUNDLE_VERSION: &str = "ubl-bundle/1";
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum BundleError {
+    #[error("Err.Bundle.Decode: {0}")]
+    Decode(String),
+    #[error("Err.Bundle.Encode: {0}")]
+    Encode(String),
+    #[error("Err.Bundle.Version: unsupported {0}")]
+    Version(String),
+    #[error("Err.Bundle.DidDoc: {0}")]
+    DidDoc(String),
+}
+
+// ---------------------------------------------------------------------------
+// Format
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub v: String,
+    pub capsule: Capsule,
+    #[serde(default)]
+    pub receipts: Vec<receipt::Receipt>,
+    #[serde(default)]
+    pub permits: Vec<permit::Permit>,
+    #[serde(default)]
+    pub ghosts: Vec<ghost::Ghost>,
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    #[serde(default)]
+    pub keys: Vec<BundleKey>,
+    #[serde(default)]
+    pub did_docs: Vec<String>,
+}
+
+/// Opaque bytes addressed by `b3:<hex>` of the bytes themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub cid: String,
+    #[serde(with = "nrf1::serde::bytes")]
+    pub data: Vec<u8>,
+}
+
+impl Artifact {
+    pub fn new(data: Vec<u8>) -> Self {
+        Artifact {
+            cid: cid_of(&data),
+            data,
+        }
+    }
+}
+
+/// A signer key, valid on `[not_before, not_after)` (epoch-millis).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleKey {
+    pub kid: String,
+    #[serde(with = "nrf1::serde::bytes")]
+    pub pk: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<i64>,
+}
+
+impl From<&KeyRecord> for BundleKey {
+    fn from(r: &KeyRecord) -> Self {
+        BundleKey {
+            kid: r.kid.clone(),
+            pk: r.key.as_bytes().to_vec(),
+            not_before: r.not_before,
+            not_after: r.not_after,
+        }
+    }
+}
+
+impl Bundle {
+    pub fn new(capsule: Capsule) -> Self {
+        Bundle {
+            v: BUNDLE_VERSION.into(),
+            capsule,
+            receipts: vec![],
+            permits: vec![],
+            ghosts: vec![],
+            artifacts: vec![],
+            keys: vec![],
+            did_docs: vec![],
+        }
+    }
+
+    /// Add a signer key with no validity window.
+    pub fn add_key(&mut self, kid: impl Into<String>, pk: &VerifyingKey) {
+        self.keys.push(BundleKey {
+            kid: kid.into(),
+            pk: pk.as_bytes().to_vec(),
+            not_before: None,
+            not_after: None,
+        });
+    }
+
+    /// Add a DID document; rejected here if it would not parse at verify time.
+    pub fn add_did_document(&mut self, json: String) -> Result<(), BundleError> {
+        Keyring::from_did_document_json(&json).map_err(|e| BundleError::DidDoc(e.to_string()))?;
+        self.did_docs.push(json);
+        Ok(())
+    }
+
+    /// Canonical NRF bytes (`nrf1` magic + value).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BundleError> {
+        nrf1::serde::to_bytes(self).map_err(|e| BundleError::Encode(e.to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BundleError> {
+        let b: Bundle =
+            nrf1::serde::from_bytes(bytes).map_err(|e| BundleError::Decode(e.to_string()))?;
+        if b.v != BUNDLE_VERSION {
+            return Err(BundleError::Version(b.v));
+        }
+        Ok(b)
+    }
+
+    /// The trusted anchors alone if given (a did:key signer must be pinned
+    /// like any other); otherwise did:key, then the bundled keys and DID
+    /// documents. Every bundled key lands in `report.keys`.
+    fn resolver(&self, anchors: Option<&Keyring>, report: &mut Report) -> FirstMatch {
+        let mut ring = Keyring::new();
+        for k in &self.keys {
+            let key = <[u8; 32]>::try_from(k.pk.as_slice())
+                .ok()
+                .and_then(|b| VerifyingKey::from_bytes(&b).ok());
+            match key {
+                Some(key) => ring.insert(KeyRecord {
+                    kid: k.kid.clone(),
+                    key,
+                    not_before: k.not_before,
+                    not_after: k.not_after,
+                }),
+                None => report.fail(
+                    format!("key {}", k.kid),
+                    "Err.Key.Malformed: not an Ed25519 key",
+                ),
+            }
+        }
+        let mut docs = Vec::new();
+        for (i, doc) in self.did_docs.iter().enumerate() {
+            match Keyring::from_did_document_json(doc) {
+                Ok(ring) => docs.push(ring),
+                Err(e) => report.fail(format!("did_docs[{i}]"), e),
+            }
+        }
+        for r in std::iter::once(&ring)
+            .chain(&docs)
+            .flat_map(Keyring::records)
+        {
+            report.key(r, anchors);
+        }
+
+        let mut chain: Vec<Box<dyn KeyResolver>> = Vec::new();
+        match anchors {
+            Some(anchors) => chain.push(Box::new(anchors.clone())),
+            None => {
+                chain.push(Box::new(DidKey));
+                chain.push(Box::new(ring));
+                for d in docs {
+                    chain.push(Box::new(d));
+                }
+            }
+        }
+        FirstMatch(chain)
+    }
+}
+
+/// `b3:<hex>` of raw bytes.
+pub fn cid_of(data: &[u8]) -> String {
+    format!("b3:{}", blake3::hash(data).to_hex())
+}
+
+// ---------------------------------------------------------------------------
+// Report
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// What was checked, e.g. `capsule seal` or `permit b3:…`.
+    pub subject: String,
+    pub result: Result<(), String>,
+}
+
+/// Where a key's trust comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provenance {
+    /// Used as shipped: no anchors were given.
+    SelfAsserted,
+    /// Matches a trusted anchor (same kid and key).
+    Pinned,
+    /// Not used: anchors were given and do not hold this key.
+    Ignored,
+    /// Decoded from a did:key id (no anchors given): proves only that the
+    /// signer holds the key the id names.
+    DidKey,
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Provenance::SelfAsserted => "self-asserted",
+            Provenance::Pinned => "pinned",
+            Provenance::Ignored => "ignored, not pinned",
+            Provenance::DidKey => "did:key, self-certifying",
+        })
+    }
+}
+
+/// A key shipped in the bundle (as a key record or in a DID document), or
+/// one a signer was resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    pub kid: String,
+    /// `b3:<hex>` of the 32 public-key bytes.
+    pub fingerprint: String,
+    pub provenance: Provenance,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub keys: Vec<KeyInfo>,
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|c| c.result.is_ok())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|c| c.result.is_err())
+    }
+
+    fn record(&mut self, subject: impl Into<String>, result: Result<(), String>) {
+        self.checks.push(Check {
+            subject: subject.into(),
+            result,
+        });
+    }
+
+    fn fail(&mut self, subject: impl Into<String>, err: impl fmt::Display) {
+        self.record(subject, Err(err.to_string()));
+    }
+
+    /// Record a bundled key; one that contradicts a pinned kid is a failure.
+    fn key(&mut self, r: &KeyRecord, anchors: Option<&Keyring>) {
+        let provenance = match anchors {
+            None => Provenance::SelfAsserted,
+            Some(a) => {
+                let pinned: Vec<_> = a.records().filter(|p| p.kid == r.kid).collect();
+                if pinned.iter().any(|p| p.key == r.key) {
+                    Provenance::Pinned
+                } else {
+                    if !pinned.is_empty() {
+                        self.fail(
+                            format!("key {}", r.kid),
+                            "Err.Bundle.KeyMismatch: differs from the pinned key",
+                        );
+                    }
+                    Provenance::Ignored
+                }
+            }
+        };
+        self.keys.push(KeyInfo {
+            kid: r.kid.clone(),
+            fingerprint: cid_of(r.key.as_bytes()),
+            provenance,
+        });
+    }
+
+    /// Record the keys signers resolved to that are not listed already.
+    fn resolved(&mut self, used: BTreeMap<String, VerifyingKey>, anchored: bool) {
+        for (kid, key) in used {
+            let fingerprint = cid_of(key.as_bytes());
+            if self
+                .keys
+                .iter()
+                .any(|k| k.kid == kid && k.fingerprint == fingerprint)
+            {
+                continue;
+            }
+            // Without anchors, did:key comes first in the chain.
+            let provenance = if anchored {
+                Provenance::Pinned
+            } else if kid.starts_with("did:key:") {
+                Provenance::DidKey
+            } else {
+                Provenance::SelfAsserted
+            };
+            self.keys.push(KeyInfo {
+                kid,
+                fingerprint,
+                provenance,
+            });
+        }
+    }
+}
+
+/// Remembers every key it hands out, so the report can list them.
+struct Recorder {
+    inner: FirstMatch,
+    used: RefCell<BTreeMap<String, VerifyingKey>>,
+}
+
+impl KeyResolver for Recorder {
+    fn resolve(&self, kid: &str, at_ms: i64) -> Result<VerifyingKey, ResolveError> {
+        let key = self.inner.resolve(kid, at_ms)?;
+        self.used.borrow_mut().insert(kid.to_string(), key);
+        Ok(key)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for k in &self.keys {
+            writeln!(f, "KEY   {} {} ({})", k.kid, k.fingerprint, k.provenance)?;
+        }
+        for c in &self.checks {
+            match &c.result {
+                Ok(()) => writeln!(f, "OK    {}", c.subject)?,
+                Err(e) => writeln!(f, "FAIL  {}: {e}", c.subject)?,
+            }
+        }
+        let failed = self.failures().count();
+        if failed == 0 {
+            write!(f, "OK: {} checks passed", self.checks.len())
+        } else {
+            write!(f, "FAILED: {failed} of {} checks", self.checks.len())
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Verification
+// ---------------------------------------------------------------------------
+
+/// Verify a bundle offline. `now_ns` is the clock for `hdr.exp` (host time
+/// when `None`); every signature is checked with the key valid when it was
+/// made. Multi-seal capsules must carry all N seals.
+///
+/// With `anchors`, signers resolve only through the anchors; bundled keys
+/// are reported but never trusted. Without, the bundle vouches for its own
+/// keys and the report marks each one self-asserted. Either way every key
+/// a signer resolved to is listed with its provenance.
+pub fn verify(b: &Bundle, now_ns: Option<i64>, anchors: Option<&Keyring>) -> Report {
+    let mut report = Report::default();
+    let resolver = Recorder {
+        inner: b.resolver(anchors, &mut report),
+        used: RefCell::default(),
+    };
+    verify_capsule(&b.capsule, &resolver, now_ns, &mut report);
+
+    let mut known: BTreeSet<&str> = BTreeSet::new();
+    known.extend(b.receipts.iter().map(|r| r.receipt_cid.as_str()));
+    known.extend(b.permits.iter().map(|p| p.permit_cid.as_str()));
+    known.extend(b.ghosts.iter().map(|g| g.ghost_cid.as_str()));
+    known.extend(b.artifacts.iter().map(|a| a.cid.as_str()));
+
+    for e in b
+        .capsule
+        .env
+        .evidence
+        .iter()
+        .filter(|e| e.starts_with("b3:"))
+    {
+        let found = known.contains(e.as_str());
+        report.record(
+            format!("capsule evidence {e}"),
+            if found {
+                Ok(())
+            } else {
+                Err("Err.Bundle.Missing: not in bundle".into())
+            },
+        );
+    }
+    for r in &b.receipts {
+        let mut res = verify_receipt(r, &resolver);
+        if let (Ok(()), Some(pc)) = (&res, &r.permit_cid) {
+            if !b.permits.iter().any(|p| &p.permit_cid == pc) {
+                res = Err(format!("Err.Bundle.Missing: permit {pc} not in bundle"));
+            }
+        }
+        report.record(format!("receipt {}", r.receipt_cid), res);
+    }
+    for p in &b.permits {
+        report.record(
+            format!("permit {}", p.permit_cid),
+            verify_permit(p, &resolver),
+        );
+    }
+    for g in &b.ghosts {
+        report.record(format!("ghost {}", g.ghost_cid), verify_ghost(g, &resolver));
+    }
+    for a in &b.artifacts {
+        let got = cid_of(&a.data);
+        report.record(
+            format!("artifact {}", a.cid),
+            if got == a.cid {
+                Ok(())
+            } else {
+                Err(format!("Err.Bundle.CidMismatch: data hashes to {got}"))
+            },
+        );
+    }
+    report.resolved(resolver.used.into_inner(), anchors.is_some());
+    report
+}
+
+fn verify_capsule(
+    c: &Capsule,
+    resolver: &dyn KeyResolver,
+    now_ns: Option<i64>,
+    report: &mut Report,
+) {
+    report.record(
+        "capsule id",
+        match ubl_capsule::compute_id(c) {
+            Ok(id) if id == c.id => Ok(()),
+            Ok(id) => Err(format!(
+                "Err.Capsule.IDMismatch: computes to {}",
+                hex::encode(id)
+            )),
+            Err(e) => Err(e),
+        },
+    );
+    let n = 1 + c.seal.cosigs.len();
+    // N-of-N: every declared co-signer must sign, so all of them are
+    // allowed; trust in their keys comes from `resolver`.
+    let opts = ubl_capsule::seal::VerifyOpts {
+        now_ns,
+        threshold: (n > 1).then(|| ubl_capsule::seal::ThresholdPolicy {
+            k: n,
+            signers: c.seal.cosigs.iter().map(|s| s.kid.clone()).collect(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    report.record(
+        if n > 1 {
+            format!("capsule seal ({n}-of-{n})")
+        } else {
+            "capsule seal".into()
+        },
+        ubl_capsule::seal::verify_resolved(c, resolver, &opts).map_err(|e| e.to_string()),
+    );
+    if !c.receipts.is_empty() {
+        report.record(
+            format!("capsule hops ({})", c.receipts.len()),
+            ubl_capsule::receipt::verify_chain(&c.id, &c.receipts, resolver)
+                .map_err(|e| e.to_string()),
+        );
+    }
+}
+
+/// Artifact timestamps are epoch-nanos; key windows are epoch-millis.
+fn key_at(resolver: &dyn KeyResolver, kid: &str, t_ns: i64) -> Result<VerifyingKey, String> {
+    resolver
+        .resolve(kid, t_ns.div_euclid(1_000_000))
+        .map_err(|e| e.to_string())
+}
+
+fn verify_receipt(r: &receipt::Receipt, resolver: &dyn KeyResolver) -> Result<(), String> {
+    r.verify_integrity()
+        .map_err(|e| format!("Err.Receipt.Integrity: {e}"))?;
+    let vk = key_at(resolver, r.kid.as_deref().unwrap_or(&r.issuer_did), r.t)?;
+    if !r.verify(&vk) {
+        return Err("Err.Receipt.BadSignature".into());
+    }
+    Ok(())
+}
+
+fn verify_permit(p: &permit::Permit, resolver: &dyn KeyResolver) -> Result<(), String> {
+    let vk = key_at(resolver, &p.issuer_did, p.issued_at)?;
+    // Checked as of issuance: an auditor asks whether it was valid, not
+    // whether it still is.
+    permit::verify_permit(p, &p.input_hash, p.issued_at, &vk)
+        .map_err(|e| format!("Err.Permit: {e}"))
+}
+
+fn verify_ghost(g: &ghost::Ghost, resolver: &dyn KeyResolver) -> Result<(), String> {
+    g.verify_integrity()
+        .map_err(|e| format!("Err.Ghost.Integrity: {e}"))?;
+    let vk = key_at(resolver, &g.wbe.who, g.t)?;
+    if !g.verify(&vk) {
+        return Err("Err.Ghost.BadSignature".into());
+    }
+    Ok(())
+}
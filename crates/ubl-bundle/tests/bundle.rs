@@ -0,0 +1,339 @@
+use ed25519_dalek::SigningKey;
+use std::collections::BTreeMap;
+use ubl_bundle::*;
+use ubl_capsule::resolver::{did_key_for, KeyRecord, Keyring};
+use ubl_capsule::{seal, Capsule, Envelope, Header, Seal, DOMAIN};
+
+fn key() -> SigningKey {
+    SigningKey::generate(&mut rand::thread_rng())
+}
+
+struct Keys {
+    author: SigningKey,
+    relay: SigningKey,
+    issuer: SigningKey,
+    authority: SigningKey,
+    actor: SigningKey,
+}
+
+fn make_permit(authority: &SigningKey) -> permit::Permit {
+    let mut p = permit::Permit {
+        v: "permit-v1".into(),
+        permit_cid: String::new(),
+        request_cid: "b3:aaaa".into(),
+        decision: "ALLOW".into(),
+        input_hash: "b3:bbbb".into(),
+        issuer_did: "did:ubl:authority".into(),
+        issued_at: 1_700_000_000_000_000_000,
+        expires_at: 1_700_000_100_000_000_000,
+        act: "EVALUATE".into(),
+        policy: None,
+        sig: None,
+    };
+    p.permit_cid = p.compute_cid();
+    p.sign(authority);
+    p
+}
+
+fn make_receipt(issuer: &SigningKey, permit_cid: &str) -> receipt::Receipt {
+    let body = nrf1::nrf!({"verdict": "ok"});
+    let mut r = receipt::Receipt {
+        v: "receipt-v1".into(),
+        receipt_cid: String::new(),
+        t: 1_700_000_000_000_000_000,
+        issuer_did: "did:ubl:issuer".into(),
+        subject_did: None,
+        kid: Some("did:ubl:issuer#k1".into()),
+        act: "EVALUATE".into(),
+        subject: "b3:0000".into(),
+        decision: Some("ALLOW".into()),
+        effects: None,
+        body_cid: nrf1::blake3_cid(&body),
+        body,
+        body_root: None,
+        inputs_cid: None,
+        policy: None,
+        reasoning_cid: None,
+        permit_cid: Some(permit_cid.into()),
+        pipeline_prev: vec![],
+        rt: receipt::RuntimeInfo {
+            name: "test-runtime".into(),
+            version: "0.1.0".into(),
+            binary_sha256: "abcd1234".into(),
+            hal_ref: None,
+            env: BTreeMap::new(),
+            certs: vec![],
+        },
+        prev: None,
+        chain: None,
+        ghost: None,
+        nonce: vec![0u8; 16],
+        url: "https://example.com/r".into(),
+        sig: None,
+    };
+    r.receipt_cid = r.compute_cid();
+    r.sign(issuer).unwrap();
+    r
+}
+
+fn make_ghost(actor: &SigningKey) -> ghost::Ghost {
+    let wbe = ghost::Wbe {
+        who: "did:ubl:actor".into(),
+        what: "run job".into(),
+        when: 1_700_000_000_000_000_000,
+        intent: "EVALUATE".into(),
+    };
+    let mut g = ghost::Ghost::new_pending(wbe, vec![1u8; 16], "https://example.com/g".into());
+    g.sign(actor);
+    g
+}
+
+fn make_bundle() -> (Bundle, Keys) {
+    let k = Keys {
+        author: key(),
+        relay: key(),
+        issuer: key(),
+        authority: key(),
+        actor: key(),
+    };
+    let artifact = Artifact::new(b"model output".to_vec());
+    let author = did_key_for(&k.author.verifying_key());
+    let mut c = Capsule {
+        domain: DOMAIN.into(),
+        id: [0u8; 32],
+        hdr: Header {
+            src: author.clone(),
+            dst: None,
+            nonce: [3u8; 16],
+            ts: 1_700_000_000_000,
+            act: "EVALUATE".into(),
+            scope: None,
+            exp: None,
+        },
+        env: Envelope {
+            body: serde_json::json!({"score": 7}),
+            links: None,
+            evidence: vec![artifact.cid.clone()],
+            enc: None,
+            merkle: None,
+        },
+        seal: Seal {
+            kid: author,
+            sig: [0u8; 64],
+            scope: "capsule".into(),
+            aud: None,
+            cosigs: vec![],
+        },
+        receipts: vec![],
+    };
+    seal::sign(&mut c, &k.author).unwrap();
+    let hop = ubl_capsule::receipt::add_hop(
+        c.id,
+        [0u8; 32],
+        "relay",
+        "did:ubl:relay#k1",
+        1_700_000_000_500,
+        &k.relay,
+    )
+    .unwrap();
+    c.receipts.push(hop);
+
+    let permit = make_permit(&k.authority);
+    let mut b = Bundle::new(c);
+    b.receipts.push(make_receipt(&k.issuer, &permit.permit_cid));
+    b.permits.push(permit);
+    b.ghosts.push(make_ghost(&k.actor));
+    b.artifacts.push(artifact);
+    b.keys.push(BundleKey {
+        kid: "did:ubl:relay#k1".into(),
+        pk: k.relay.verifying_key().as_bytes().to_vec(),
+        not_before: Some(1_600_000_000_000),
+        not_after: Some(1_800_000_000_000),
+    });
+    b.add_key("did:ubl:issuer#k1", &k.issuer.verifying_key());
+    b.add_key("did:ubl:actor", &k.actor.verifying_key());
+    let doc = format!(
+        r##"{{"id": "did:ubl:authority", "verificationMethod": [
+            {{"id": "did:ubl:authority", "publicKeyMultibase": "{}"}}]}}"##,
+        ubl_capsule::resolver::encode_multibase_ed25519(&k.authority.verifying_key())
+    );
+    b.add_did_document(doc).unwrap();
+    (b, k)
+}
+
+fn failed(r: &Report) -> Vec<&str> {
+    r.failures().map(|c| c.subject.as_str()).collect()
+}
+
+#[test]
+fn bundle_roundtrips_and_verifies_offline() {
+    let (b, _) = make_bundle();
+    let bytes = b.to_bytes().unwrap();
+    assert_eq!(&bytes[..4], b"nrf1");
+    let back = Bundle::from_bytes(&bytes).unwrap();
+    assert_eq!(back.to_bytes().unwrap(), bytes);
+
+    let report = verify(&back, None, None);
+    assert!(report.is_ok(), "{report}");
+    let subjects: Vec<_> = report.checks.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects.len(), 8);
+    assert_eq!(
+        &subjects[..3],
+        ["capsule id", "capsule seal", "capsule hops (1)"]
+    );
+    assert!(report.to_string().ends_with("OK: 8 checks passed"));
+}
+
+#[test]
+fn tampering_is_reported_per_item() {
+    let (b, _) = make_bundle();
+
+    let mut t = b.clone();
+    t.artifacts[0].data.push(0);
+    let r = verify(&t, None, None);
+    assert_eq!(
+        failed(&r),
+        [format!("artifact {}", t.artifacts[0].cid).as_str()]
+    );
+
+    let mut t = b.clone();
+    t.capsule.env.body = serde_json::json!({"score": 8});
+    assert_eq!(
+        failed(&verify(&t, None, None)),
+        ["capsule id", "capsule seal"]
+    );
+
+    let mut t = b.clone();
+    t.permits.clear();
+    let r = verify(&t, None, None);
+    let err = r.failures().next().unwrap().result.clone().unwrap_err();
+    assert!(err.starts_with("Err.Bundle.Missing: permit"), "{err}");
+
+    let mut t = b.clone();
+    t.ghosts[0].wbe.what = "something else".into();
+    let r = verify(&t, None, None);
+    assert_eq!(
+        failed(&r),
+        [format!("ghost {}", t.ghosts[0].ghost_cid).as_str()]
+    );
+    assert!(r.to_string().ends_with("FAILED: 1 of 8 checks"));
+}
+
+#[test]
+fn keys_are_checked_at_signing_time() {
+    let (mut b, _) = make_bundle();
+    // The relay key was rotated out before the hop was signed.
+    b.keys[0].not_after = Some(1_700_000_000_000);
+    let r = verify(&b, None, None);
+    assert_eq!(failed(&r), ["capsule hops (1)"]);
+    let err = r.failures().next().unwrap().result.clone().unwrap_err();
+    assert!(err.contains("Err.Key.Expired"), "{err}");
+
+    // Without the authority's DID document the permit cannot be checked.
+    let (mut b, _) = make_bundle();
+    b.did_docs.clear();
+    let err = verify(&b, None, None)
+        .failures()
+        .next()
+        .unwrap()
+        .result
+        .clone()
+        .unwrap_err();
+    assert!(err.starts_with("Err.Key.NotFound"), "{err}");
+}
+
+fn anchors(pins: &[(&str, &SigningKey)]) -> Keyring {
+    let mut ring = Keyring::new();
+    for (kid, sk) in pins {
+        ring.insert(KeyRecord {
+            kid: kid.to_string(),
+            key: sk.verifying_key(),
+            not_before: None,
+            not_after: None,
+        });
+    }
+    ring
+}
+
+#[test]
+fn trusted_anchors_replace_bundled_keys() {
+    let (b, k) = make_bundle();
+    let author = did_key_for(&k.author.verifying_key());
+    let pins = anchors(&[
+        (author.as_str(), &k.author),
+        ("did:ubl:relay#k1", &k.relay),
+        ("did:ubl:issuer#k1", &k.issuer),
+        ("did:ubl:actor", &k.actor),
+        ("did:ubl:authority", &k.authority),
+    ]);
+
+    // The four bundled keys, then the did:key author the seal resolved to.
+    let r = verify(&b, None, None);
+    assert_eq!(r.keys.len(), 5);
+    assert!(r.keys[..4]
+        .iter()
+        .all(|k| k.provenance == Provenance::SelfAsserted));
+    assert_eq!(r.keys[4].kid, author);
+    assert_eq!(r.keys[4].provenance, Provenance::DidKey);
+    let issuer = cid_of(k.issuer.verifying_key().as_bytes());
+    let line = format!("KEY   did:ubl:issuer#k1 {issuer} (self-asserted)");
+    assert!(r.to_string().contains(&line), "{r}");
+
+    let r = verify(&b, None, Some(&pins));
+    assert!(r.is_ok(), "{r}");
+    assert_eq!(r.keys.len(), 5);
+    assert!(r.keys.iter().all(|k| k.provenance == Provenance::Pinned));
+
+    // Under anchors a did:key signer is trusted only if pinned.
+    let unpinned = anchors(&[
+        ("did:ubl:relay#k1", &k.relay),
+        ("did:ubl:issuer#k1", &k.issuer),
+        ("did:ubl:actor", &k.actor),
+        ("did:ubl:authority", &k.authority),
+    ]);
+    let r = verify(&b, None, Some(&unpinned));
+    assert_eq!(failed(&r), ["capsule seal"]);
+
+    // A receipt re-signed by a forger who also swapped the bundled key:
+    // the bundle vouches for itself, the anchors do not.
+    let forger = key();
+    let mut t = b.clone();
+    t.receipts[0] = make_receipt(&forger, &t.permits[0].permit_cid);
+    t.keys[1].pk = forger.verifying_key().as_bytes().to_vec();
+    assert!(verify(&t, None, None).is_ok());
+    let r = verify(&t, None, Some(&pins));
+    let receipt = format!("receipt {}", t.receipts[0].receipt_cid);
+    assert_eq!(failed(&r), ["key did:ubl:issuer#k1", receipt.as_str()]);
+
+    // A signer missing from the anchors is not found, whatever the bundle says.
+    let pins = anchors(&[
+        (author.as_str(), &k.author),
+        ("did:ubl:relay#k1", &k.relay),
+        ("did:ubl:issuer#k1", &k.issuer),
+        ("did:ubl:authority", &k.authority),
+    ]);
+    let r = verify(&b, None, Some(&pins));
+    let ghost = format!("ghost {}", b.ghosts[0].ghost_cid);
+    assert_eq!(failed(&r), [ghost.as_str()]);
+    let actor = r.keys.iter().find(|k| k.kid == "did:ubl:actor").unwrap();
+    assert_eq!(actor.provenance, Provenance::Ignored);
+}
+
+#[test]
+fn decode_rejects_other_versions_and_garbage() {
+    let (mut b, _) = make_bundle();
+    b.v = "ubl-bundle/9".into();
+    let bytes = b.to_bytes().unwrap();
+    assert_eq!(
+        Bundle::from_bytes(&bytes).unwrap_err(),
+        BundleError::Version("ubl-bundle/9".into())
+    );
+    assert!(matches!(
+        Bundle::from_bytes(b"nope"),
+        Err(BundleError::Decode(_))
+    ));
+    assert!(matches!(
+        b.add_did_document("{}".into()),
+        Err(BundleError::DidDoc(_))
+    ));
+}
@@ -9,11 +9,14 @@
 //!     "code": "Err.NRF.InvalidMagic",
 //!     "message": "expected 'nrf1' magic header, got [0x00, 0x00, 0x00, 0x00]",
 //!     "hint": "Ensure the buffer starts with the 4-byte NRF magic: 0x6e726631",
-//!     "status": 400
+//!     "status": 400,
+//!     "at": { "offset": 0, "path": "" }
 //!   }
 //! }
 //! ```
 //!
+//! `at` is present only when the failure has a location (decode errors).
+//!
 //! Existing crates keep their own error enums for type safety.
 //! This crate sits at the TOP of the dependency graph and depends on them.
 
@@ -29,6 +32,17 @@ pub struct UblError {
     pub message: String,
     pub hint: String,
     pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at: Option<ErrorAt>,
+}
+
+/// Where an error happened: byte offset into the input (when the input was
+/// binary) and logical path such as `env.body.items[3].sku`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorAt {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    pub path: String,
 }
 
 impl UblError {
@@ -38,20 +52,31 @@ impl UblError {
             message: message.into(),
             hint: hint.into(),
             status,
+            at: None,
         }
     }
 
+    /// Attach a location.
+    pub fn with_at(mut self, offset: Option<usize>, path: impl Into<String>) -> Self {
+        self.at = Some(ErrorAt {
+            offset,
+            path: path.into(),
+        });
+        self
+    }
+
     /// JSON body for HTTP error responses.
     pub fn to_json(&self) -> serde_json::Value {
-        serde_json::json!({
-            "ok": false,
-            "error": {
-                "code": self.code,
-                "message": self.message,
-                "hint": self.hint,
-                "status": self.status,
-            }
-        })
+        let mut error = serde_json::json!({
+            "code": self.code,
+            "message": self.message,
+            "hint": self.hint,
+            "status": self.status,
+        });
+        if let Some(at) = &self.at {
+            error["at"] = serde_json::json!(at);
+        }
+        serde_json::json!({ "ok": false, "error": error })
     }
 
     /// Single-line display for logs and CLI.
@@ -182,11 +207,56 @@ impl From<nrf_core::Error> for UblError {
                 "Err.NRF.Float",
                 "Floats are forbidden in NRF. Use Int64 instead. For decimals, multiply by the appropriate power of 10 (e.g., cents instead of dollars).",
             ),
+            IntegerOverflow => (
+                "Err.NRF.IntegerOverflow",
+                "Integer does not fit in Int64. NRF only carries signed 64-bit integers; use a string or bytes for wider values.",
+            ),
+            Serde(_) => (
+                "Err.NRF.Serde",
+                "The type could not be mapped to NRF by nrf_core::serde. Check the message for the offending field or variant.",
+            ),
+            Diag(_) => (
+                "Err.NRF.Diag",
+                "Diagnostic notation syntax error. Values are null, true, false, integers, \"strings\", h'hex' bytes, [arrays] and {\"maps\": ...} with sorted keys.",
+            ),
+            PathNotFound(_) => (
+                "Err.NRF.PathNotFound",
+                "No value at this path. Keys and array indices must exist in the value; check spelling and bounds.",
+            ),
+            Merkle(_) => (
+                "Err.NRF.Merkle",
+                "Malformed Merkle inclusion proof. Each step needs index, count and 32-byte siblings; map steps also carry key.",
+            ),
+            Query(_) => (
+                "Err.NRF.Query",
+                "Invalid path query. Use a.b.0, a JSON Pointer (/a/b/0) or JSONPath ($.a['b'][0], [*], [1:3], [?(@.x > 1)]); get/set/delete need a singular path.",
+            ),
+            Rho(_) => (
+                "Err.NRF.Rho",
+                "Value or ρ profile rejected. Profiles map paths to 'timestamp', 'decimal', 'set' or 'ascii-id'.",
+            ),
+            Stream(_) => (
+                "Err.NRF.Stream",
+                "StreamDecoder misuse. decode_value reads a whole value and must be called on a fresh decoder, before next_event.",
+            ),
         };
         UblError::new(code, format!("{e}"), hint, 400)
     }
 }
 
+// ---------------------------------------------------------------------------
+// nrf-core::DecodeError → UblError (same code/hint, plus location)
+// ---------------------------------------------------------------------------
+#[cfg(feature = "nrf")]
+impl From<nrf_core::DecodeError> for UblError {
+    fn from(e: nrf_core::DecodeError) -> Self {
+        let message = e.to_string();
+        let mut ue = UblError::from(e.kind).with_at(Some(e.offset), e.path);
+        ue.message = message;
+        ue
+    }
+}
+
 // ---------------------------------------------------------------------------
 // nrf-core::rho::RhoError → UblError
 // ---------------------------------------------------------------------------
@@ -194,6 +264,9 @@ impl From<nrf_core::Error> for UblError {
 impl From<nrf_core::rho::RhoError> for UblError {
     fn from(e: nrf_core::rho::RhoError) -> Self {
         use nrf_core::rho::RhoError::*;
+        if let AtPath { path, error } = e {
+            return UblError::from(*error).with_at(None, path);
+        }
         let (code, hint) = match &e {
             InvalidUTF8 => (
                 "Err.Rho.InvalidUTF8",
@@ -207,6 +280,19 @@ impl From<nrf_core::rho::RhoError> for UblError {
                 "Err.Rho.InvalidTimestamp",
                 "Timestamps must be RFC-3339 UTC with 'Z' suffix and minimal fractional seconds. Example: '2024-01-15T10:30:00Z'. Invalid: '2024-01-15 10:30:00', '2024-01-15T10:30:00.000Z' (use no fraction if .000).",
             ),
+            InvalidId(_) => (
+                "Err.Rho.InvalidId",
+                "Identifier fields (DIDs, CIDs, key ids) must be printable ASCII with no whitespace. Example: 'did:key:z6Mk...', 'b3:4f2a...'.",
+            ),
+            ProfileMismatch(_) => (
+                "Err.Rho.ProfileMismatch",
+                "The artifact's ρ profile expects a different kind of value here: timestamp/decimal/ascii-id rules need strings, set rules need arrays.",
+            ),
+            NotCanonical(_) => (
+                "Err.Rho.NotCanonical",
+                "The value changes under its ρ profile (e.g. an unsorted set or '.000' in a timestamp). Apply rho::normalize_with_profile before signing.",
+            ),
+            AtPath { .. } => unreachable!("handled above"),
         };
         UblError::new(code, format!("{e}"), hint, 400)
     }
@@ -268,11 +354,33 @@ impl From<ubl_json_view::JsonViewError> for UblError {
                 "Err.JsonView.NrfDecode",
                 "Failed to decode NRF bytes. The binary data may be corrupted or not valid NRF. Re-encode from the source value.",
             ),
+            PathNotFound => (
+                "Err.JsonView.PathNotFound",
+                "No value at this path. Check the key names and array indices against the document.",
+            ),
+            BadPath(_) => (
+                "Err.JsonView.BadPath",
+                "Invalid path query. Use a.b.0, a JSON Pointer (/a/b/0) or JSONPath ($.a['b'][0], [*], [1:3], [?(@.x > 1)]).",
+            ),
+            BadDisclosure(_) => (
+                "Err.JsonView.BadDisclosure",
+                "Not a valid disclosure. Expected {\"salts\", \"value\"} from disclose_json, with salts shaped like the value.",
+            ),
         };
         UblError::new(code, format!("{e}"), hint, 400)
     }
 }
 
+#[cfg(feature = "json_view")]
+impl From<ubl_json_view::JsonViewErrorAt> for UblError {
+    fn from(e: ubl_json_view::JsonViewErrorAt) -> Self {
+        let message = e.to_string();
+        let mut ue = UblError::from(e.error).with_at(None, e.path);
+        ue.message = message;
+        ue
+    }
+}
+
 // ---------------------------------------------------------------------------
 // ubl_capsule::receipt::HopError → UblError
 // ---------------------------------------------------------------------------
@@ -306,6 +414,11 @@ impl From<ubl_capsule::receipt::HopError> for UblError {
                 "Duplicate 'prev' value detected — two receipts claim the same predecessor. This indicates a fork in the receipt chain, which is forbidden.",
                 422,
             ),
+            Key(..) => (
+                "Err.Hop.Key",
+                "The hop node's key could not be resolved at the receipt's ts: unknown node, malformed key, or outside the key's validity window (e.g. signed after rotation). Check the keyring, did:key or DID document.",
+                403,
+            ),
         };
         UblError::new(code, format!("{e}"), hint, status)
     }
@@ -349,11 +462,93 @@ impl From<ubl_capsule::seal::SealError> for UblError {
                 "Capsule has expired. The expiration timestamp is in the past. Create a new capsule with a future expiration, or check clock synchronization.",
                 410,
             ),
+            BadCoSignature { .. } => (
+                "Err.Seal.BadCoSignature",
+                "A co-signature in seal.cosigs does not verify. The co-signer's key may not match the keyring, or the capsule was modified after co-signing.",
+                403,
+            ),
+            DuplicateSigner { .. } => (
+                "Err.Seal.DuplicateSigner",
+                "The same kid appears twice among seal.kid and seal.cosigs. Each signer may hold only one slot in a multi-seal.",
+                400,
+            ),
+            UnknownSigner { .. } => (
+                "Err.Seal.UnknownSigner",
+                "A co-signature is present but the verifier has no public key for its kid. Add the co-signer's key to the keyring.",
+                403,
+            ),
+            UntrustedSigner { .. } => (
+                "Err.Seal.UntrustedSigner",
+                "A co-signature is present but its kid is not on the verifier's allowlist. A capsule names its own co-signers, so only allowlisted kids count towards the threshold; pin the co-signer's key or pass --signer.",
+                403,
+            ),
+            ForeignSigner { .. } => (
+                "Err.Seal.ForeignSigner",
+                "seal.kid belongs to a different DID than hdr.src. A resolved key only proves who holds it; the author's kid must be a key of hdr.src (same DID before '#').",
+                403,
+            ),
+            BadThreshold { .. } => (
+                "Err.Seal.BadThreshold",
+                "The K-of-N threshold must be between 1 and the number of signers (author plus co-signers).",
+                400,
+            ),
+            ThresholdNotMet { .. } => (
+                "Err.Seal.ThresholdNotMet",
+                "Fewer valid seals than the policy requires. Collect the missing co-signatures with `ubl cap sign --kid`, or lower the threshold.",
+                403,
+            ),
+            Key(_) => (
+                "Err.Seal.Key",
+                "The signer's key could not be resolved at the lookup time (hdr.ts, or the trusted --key-time): unknown kid, malformed key, or outside the key's validity window. Check the keyring, did:key or DID document.",
+                403,
+            ),
         };
         UblError::new(code, format!("{e}"), hint, status)
     }
 }
 
+// ---------------------------------------------------------------------------
+// ubl_capsule::builder::BuildError → UblError
+// ---------------------------------------------------------------------------
+#[cfg(feature = "capsule")]
+impl From<ubl_capsule::builder::BuildError> for UblError {
+    fn from(e: ubl_capsule::builder::BuildError) -> Self {
+        use ubl_capsule::builder::BuildError::*;
+        let (code, hint) = match &e {
+            BadAct(_) => (
+                "Err.Hdr.BadAct",
+                "hdr.act must be one of ATTEST, EVALUATE or TRANSACT (uppercase).",
+            ),
+            NotASCII { .. } => (
+                "Err.Canon.NotASCII",
+                "DIDs, kids and CIDs must be non-empty printable ASCII, with no spaces or control characters. Percent-encode or transliterate anything else.",
+            ),
+            DuplicateSigner { .. } => (
+                "Err.Seal.DuplicateSigner",
+                "Each kid may hold only one slot in a multi-seal. Drop the repeated co-signer, and do not list the author as a co-signer.",
+            ),
+            BadAudience => (
+                "Err.Seal.BadAudience",
+                "seal.aud must equal hdr.dst. Set only the destination and let the builder derive the audience.",
+            ),
+            ExpBeforeTs { .. } => (
+                "Err.Hdr.Expired",
+                "hdr.exp (epoch-nanos) must be later than hdr.ts. Use a positive TTL, or check that exp is in nanoseconds, not milliseconds.",
+            ),
+            Sign(msg) => {
+                let code = msg.split(':').next().unwrap_or("Err.Canon.Invalid");
+                return UblError::new(
+                    code,
+                    msg.clone(),
+                    "The body could not be canonicalized for signing. Remove floats and keep numbers within Int64.",
+                    400,
+                );
+            }
+        };
+        UblError::new(code, format!("{e}"), hint, 400)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // runtime::RuntimeError → UblError
 // ---------------------------------------------------------------------------
@@ -477,6 +672,53 @@ impl From<ubl_auth::AuthError> for UblError {
     }
 }
 
+// ---------------------------------------------------------------------------
+// nrf_schema::SchemaError / ValidationError → UblError
+// ---------------------------------------------------------------------------
+#[cfg(feature = "schema")]
+impl From<nrf_schema::SchemaError> for UblError {
+    fn from(e: nrf_schema::SchemaError) -> Self {
+        use nrf_schema::SchemaError::*;
+        let (code, hint, status) = match &e {
+            UnknownBuiltin(_) => (
+                "Err.Schema.UnknownBuiltin",
+                "No builtin schema with that name. Builtins are receipt.v1, ghost.v1, product.v1, manifest.v1, ubl-json.v1, model-card.v1, reasoning.bit.v1 and reasoning.bit.v1.1.",
+                404,
+            ),
+            Json(_) => (
+                "Err.Schema.BadJSON",
+                "The schema document is not valid JSON. Check for trailing commas or unquoted keys.",
+                400,
+            ),
+            BadKeyword { .. } => (
+                "Err.Schema.BadKeyword",
+                "A schema keyword has an unsupported value. Bounds must be Int64 (no floats), patterns valid regexes, formats one of ascii, did, decimal, timestamp, uri, cid.",
+                400,
+            ),
+            UnresolvedRef(_) => (
+                "Err.Schema.UnresolvedRef",
+                "Only local refs are supported: '#', '#/$defs/<name>' or '#/definitions/<name>'. Define the target under $defs.",
+                400,
+            ),
+        };
+        UblError::new(code, format!("{e}"), hint, status)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl From<nrf_schema::ValidationError> for UblError {
+    fn from(e: nrf_schema::ValidationError) -> Self {
+        let path = e.violations.first().map(|v| v.path.clone()).unwrap_or_default();
+        UblError::new(
+            "Err.Schema.Invalid",
+            format!("{e}"),
+            "The value does not match its schema. Every violation is listed with its path; fix them all before resubmitting.",
+            400,
+        )
+        .with_at(None, path)
+    }
+}
+
 // =========================================================================
 // Convenience: middleware / API error constructors
 // =========================================================================
@@ -565,6 +807,18 @@ mod tests {
         assert_eq!(ubl.status, 400);
     }
 
+    #[cfg(feature = "nrf")]
+    #[test]
+    fn test_decode_error_carries_location() {
+        let err = nrf_core::decode_located(b"nrf1\x07\x02\x04\x01b\x00\x04\x01a\x00").unwrap_err();
+        let ubl: UblError = err.into();
+        assert_eq!(ubl.code, "Err.NRF.UnsortedKeys");
+        assert_eq!(ubl.message, "UnsortedKeys at byte 10 (a)");
+        let j = ubl.to_json();
+        assert_eq!(j["error"]["at"], serde_json::json!({"offset": 10, "path": "a"}));
+        assert!(UblError::internal("x").to_json()["error"].get("at").is_none());
+    }
+
     #[cfg(feature = "nrf")]
     #[test]
     fn test_rho_error_conversion() {
@@ -572,6 +826,14 @@ mod tests {
         let ubl: UblError = rho_err.into();
         assert_eq!(ubl.code, "Err.Rho.InvalidTimestamp");
         assert!(ubl.hint.contains("RFC-3339"));
+
+        let at = nrf_core::rho::RhoError::AtPath {
+            path: "items[0].price".into(),
+            error: Box::new(nrf_core::rho::RhoError::InvalidDecimal("1e2".into())),
+        };
+        let ubl: UblError = at.into();
+        assert_eq!(ubl.code, "Err.Rho.InvalidDecimal");
+        assert_eq!(ubl.at.unwrap().path, "items[0].price");
     }
 
     #[cfg(feature = "json_view")]
@@ -583,6 +845,45 @@ mod tests {
         assert!(ubl.hint.contains("Int64"));
     }
 
+    #[cfg(feature = "json_view")]
+    #[test]
+    fn test_json_view_error_at_conversion() {
+        let j = serde_json::json!({"env": {"items": [1, 2.5]}});
+        let ubl: UblError = ubl_json_view::from_json_at(&j).unwrap_err().into();
+        assert_eq!(ubl.code, "Err.JsonView.Float");
+        assert_eq!(ubl.at.unwrap().path, "env.items[1]");
+    }
+
+    #[cfg(all(feature = "schema", feature = "nrf"))]
+    #[test]
+    fn test_schema_validation_error_conversion() {
+        let schema = nrf_schema::Schema::builtin("ghost.v1").unwrap();
+        let v = nrf_core::from_diag(r#"{"nonce": 1, "t": 0}"#).unwrap();
+        let ubl: UblError = schema.check(&v).unwrap_err().into();
+        assert_eq!(ubl.code, "Err.Schema.Invalid");
+        assert_eq!(ubl.status, 400);
+        assert!(ubl.message.starts_with("4 schema violation(s): <root>: missing required property"));
+        assert!(ubl.message.contains("nonce: expected string, got Int"));
+        assert_eq!(ubl.at.unwrap().path, "");
+    }
+
+    #[cfg(feature = "capsule")]
+    #[test]
+    fn test_build_error_conversion() {
+        use ubl_capsule::CapsuleBuilder;
+        let ubl: UblError = CapsuleBuilder::new("PAY", "did:ubl:a").build().unwrap_err().into();
+        assert_eq!(ubl.code, "Err.Hdr.BadAct");
+        assert_eq!(ubl.status, 400);
+
+        let sk = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let ubl: UblError = CapsuleBuilder::new("ATTEST", "did:ubl:a")
+            .body(serde_json::json!({"x": 0.5}))
+            .sign(&sk)
+            .unwrap_err()
+            .into();
+        assert_eq!(ubl.code, "Err.Canon.Float");
+    }
+
     #[test]
     fn test_convenience_constructors() {
         let e = UblError::missing_header("X-Tenant", "Add X-Tenant header");
@@ -1,6 +1,8 @@
+use nrf_core::rho::{Profile, Rule};
 use nrf_core::Value;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::sync::LazyLock;
 use uuid::Uuid;
 
 // ---------------------------------------------------------------------------
@@ -48,6 +50,16 @@ pub struct LedgerEntry {
     pub payload: serde_json::Value, // full signed object
 }
 
+static LEDGER_PROFILE: LazyLock<Profile> = LazyLock::new(|| {
+    Profile::new(&[
+        ("ts", Rule::Timestamp),
+        ("roles", Rule::Set),
+        ("cid", Rule::AsciiId),
+        ("did", Rule::AsciiId),
+    ])
+    .expect("ledger ρ profile")
+});
+
 impl LedgerEntry {
     #[allow(clippy::too_many_arguments)]
     pub fn now(
@@ -92,7 +104,8 @@ impl LedgerEntry {
     ///
     /// This is the blessed path: struct → nrf_core::Value → ρ-normalize.
     /// The resulting Value has deterministic key order (BTreeMap),
-    /// NFC-normalized strings, and null values stripped.
+    /// NFC-normalized strings, null values stripped, and the rules of
+    /// `rho_profile` applied.
     pub fn to_canonical_value(&self) -> Result<Value, LedgerError> {
         let mut m = BTreeMap::new();
 
@@ -126,11 +139,17 @@ impl LedgerEntry {
             .map_err(|e| LedgerError::Serialization(format!("payload→NRF: {e}")))?;
         m.insert("payload".into(), nrf_payload);
 
-        // ρ-normalize: NFC strings, strip nulls, sort keys
-        nrf_core::rho::normalize(&Value::Map(m))
+        // ρ-normalize: NFC strings, strip nulls, sort keys, plus the ledger profile
+        nrf_core::rho::normalize_with_profile(&Value::Map(m), &LEDGER_PROFILE)
             .map_err(|e| LedgerError::Serialization(format!("ρ-normalize: {e}")))
     }
 
+    /// The ρ profile of a ledger line: `ts` is a timestamp, `roles` a set,
+    /// `cid` / `did` ASCII identifiers.
+    pub fn rho_profile() -> &'static Profile {
+        &LEDGER_PROFILE
+    }
+
     /// Serialize to canonical JSON string (one NDJSON line).
     ///
     /// Path: struct → nrf_core::Value → ρ-normalize → ubl_json_view::to_json → JSON string.
@@ -271,6 +290,7 @@ impl From<serde_json::Error> for LedgerError {
 ///
 /// Modules implement this trait to provide storage backends.
 /// The BASE ships NullLedger as fallback.
+#[allow(clippy::double_must_use)]
 #[async_trait::async_trait]
 pub trait LedgerWriter: Send + Sync {
     async fn append(&self, entry: &LedgerEntry) -> Result<(), LedgerError>;
@@ -1,5 +1,6 @@
 pub mod ledger;
 pub mod ndjson;
+pub mod nrflog;
 
 #[cfg(feature = "s3")]
 mod s3;
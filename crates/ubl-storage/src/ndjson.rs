@@ -204,7 +204,7 @@ impl NdjsonLedger {
             .map_err(|e| LedgerError::Io(e.to_string()))?
         {
             let app_entry = app_entry.map_err(|e| LedgerError::Io(e.to_string()))?;
-            if !app_entry.file_type().map_or(false, |t| t.is_dir()) {
+            if !app_entry.file_type().is_ok_and(|t| t.is_dir()) {
                 continue;
             }
             let app = app_entry.file_name().to_string_lossy().to_string();
@@ -213,7 +213,7 @@ impl NdjsonLedger {
                 .map_err(|e| LedgerError::Io(e.to_string()))?
             {
                 let tenant_entry = tenant_entry.map_err(|e| LedgerError::Io(e.to_string()))?;
-                if !tenant_entry.file_type().map_or(false, |t| t.is_dir()) {
+                if !tenant_entry.file_type().is_ok_and(|t| t.is_dir()) {
                     continue;
                 }
                 let tenant = tenant_entry.file_name().to_string_lossy().to_string();
@@ -239,7 +239,7 @@ impl NdjsonLedger {
             .map_err(|e| LedgerError::Io(e.to_string()))?
         {
             let app_entry = app_entry.map_err(|e| LedgerError::Io(e.to_string()))?;
-            if !app_entry.file_type().map_or(false, |t| t.is_dir()) {
+            if !app_entry.file_type().is_ok_and(|t| t.is_dir()) {
                 continue;
             }
             let app = app_entry.file_name().to_string_lossy().to_string();
@@ -247,7 +247,7 @@ impl NdjsonLedger {
                 .map_err(|e| LedgerError::Io(e.to_string()))?
             {
                 let tenant_entry = tenant_entry.map_err(|e| LedgerError::Io(e.to_string()))?;
-                if !tenant_entry.file_type().map_or(false, |t| t.is_dir()) {
+                if !tenant_entry.file_type().is_ok_and(|t| t.is_dir()) {
                     continue;
                 }
                 let tenant = tenant_entry.file_name().to_string_lossy().to_string();
@@ -272,14 +272,15 @@ impl LedgerWriter for NdjsonLedger {
         let mut line = entry.to_canonical_json()?;
         line.push('\n');
 
-        // Append to file (atomic per-line via O_APPEND)
-        tokio::fs::OpenOptions::new()
+        // Append to file (atomic per-line via O_APPEND). tokio's File hands
+        // writes to a blocking task; flush so the line is on disk on return.
+        let mut file = tokio::fs::OpenOptions::new()
             .create(true)
             .append(true)
             .open(&path)
-            .await?
-            .write_all(line.as_bytes())
             .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await?;
 
         Ok(())
     }
@@ -324,6 +325,20 @@ mod tests {
         assert_eq!(entries[0].tenant, "default");
     }
 
+    #[test]
+    fn test_canonical_value_applies_ledger_profile() {
+        let mut entry = test_entry("myapp", "default");
+        entry.ts = "2024-01-15T10:30:00.100Z".into();
+        entry.roles = vec!["viewer".into(), "admin".into(), "viewer".into()];
+        let v = entry.to_canonical_value().unwrap();
+        let json = ubl_json_view::to_json(&v);
+        assert_eq!(json["ts"], "2024-01-15T10:30:00.1Z");
+        assert_eq!(json["roles"], serde_json::json!(["admin", "viewer"]));
+
+        entry.did = "did test".into();
+        assert!(entry.to_canonical_value().is_err());
+    }
+
     #[tokio::test]
     async fn test_compress_weekly() {
         let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,235 @@
+use crate::ledger::{LedgerEntry, LedgerError, LedgerWriter};
+use nrf1::records::{LogError, LogReader, LogWriter};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+// ---------------------------------------------------------------------------
+// NrfLogLedger — file-based append-only ledger of canonical NRF records
+//
+// Layout:
+//   {base_dir}/{app}/{tenant}/{stream}.nrflog   (nrf1::records framing)
+//
+// Each record is the ρ-normalized LedgerEntry encoded as ai-nrf1 bytes and
+// stored with its CID, so the bytes that were hashed are the bytes on disk
+// (NdjsonLedger keeps only the JSON view). Serialization path:
+// LedgerEntry → to_canonical_value → NRF record. Deserialization path:
+// record (CID checked) → to_json → from_canonical_json.
+//
+// Writers stay open and are flushed after every append; a crash leaves an
+// unindexed log that `LogWriter::open_append` verifies and continues.
+// Appends do their file I/O on the blocking pool, never on the runtime.
+// `close_all` writes the CID index so archived logs support lookup by CID.
+// ---------------------------------------------------------------------------
+
+type Writers = HashMap<PathBuf, LogWriter<BufWriter<File>>>;
+
+pub struct NrfLogLedger {
+    base_dir: PathBuf,
+    writers: Arc<Mutex<Writers>>,
+}
+
+impl NrfLogLedger {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        let base_dir = base_dir.into();
+        tracing::info!(base_dir = %base_dir.display(), "nrflog ledger initialized");
+        Self {
+            base_dir,
+            writers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Path to the record log for a given (app, tenant, stream)
+    pub fn stream_path(&self, app: &str, tenant: &str, stream: &str) -> PathBuf {
+        self.base_dir
+            .join(app)
+            .join(tenant)
+            .join(format!("{stream}.nrflog"))
+    }
+
+    /// Read all entries from a stream, most recent last. Every record's CID
+    /// is verified; a corrupt or torn log is an error, not skipped.
+    pub fn read_stream(
+        &self,
+        app: &str,
+        tenant: &str,
+        stream: &str,
+    ) -> Result<Vec<LedgerEntry>, LedgerError> {
+        let path = self.stream_path(app, tenant, stream);
+        // Hold the lock so no append is half-written while we scan.
+        let _writers = lock(&self.writers)?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = File::open(&path)?;
+        let reader = LogReader::new(BufReader::new(file)).map_err(log_err)?;
+        let mut entries = Vec::new();
+        for rec in reader {
+            let v = rec.and_then(|r| r.value()).map_err(log_err)?;
+            let line = serde_json::to_string(&ubl_json_view::to_json(&v))?;
+            entries.push(LedgerEntry::from_canonical_json(&line)?);
+        }
+        Ok(entries)
+    }
+
+    /// Index and close every open log. Later appends reopen them.
+    pub fn close_all(&self) -> Result<(), LedgerError> {
+        let mut writers = lock(&self.writers)?;
+        for (_, w) in writers.drain() {
+            w.finish().map_err(log_err)?;
+        }
+        Ok(())
+    }
+}
+
+fn lock(writers: &Mutex<Writers>) -> Result<MutexGuard<'_, Writers>, LedgerError> {
+    writers
+        .lock()
+        .map_err(|_| LedgerError::Io("nrflog writer lock poisoned".into()))
+}
+
+fn log_err(e: LogError) -> LedgerError {
+    match e {
+        LogError::Io(e) => LedgerError::Io(e.to_string()),
+        other => LedgerError::Serialization(other.to_string()),
+    }
+}
+
+#[async_trait::async_trait]
+impl LedgerWriter for NrfLogLedger {
+    async fn append(&self, entry: &LedgerEntry) -> Result<(), LedgerError> {
+        let path = self.stream_path(&entry.app, &entry.tenant, entry.stream_name());
+        let value = entry.to_canonical_value()?;
+
+        let writers = Arc::clone(&self.writers);
+        tokio::task::spawn_blocking(move || {
+            let mut writers = lock(&writers)?;
+            let w = match writers.entry(path) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => {
+                    if let Some(parent) = e.key().parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    let w = LogWriter::open_append(e.key()).map_err(log_err)?;
+                    e.insert(w)
+                }
+            };
+            w.append(&value).map_err(log_err)?;
+            w.flush().map_err(log_err)
+        })
+        .await
+        .map_err(|e| LedgerError::Io(format!("nrflog append task: {e}")))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::LedgerEvent;
+    use nrf1::records::IndexedLog;
+    use uuid::Uuid;
+
+    fn test_entry(app: &str, tenant: &str, cid: &str) -> LedgerEntry {
+        LedgerEntry::now(
+            LedgerEvent::PipelineExecuted,
+            app,
+            tenant,
+            None,
+            vec![],
+            Uuid::nil(),
+            cid,
+            "did:test",
+            Some("ALLOW".into()),
+            serde_json::json!({"test": true}),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_append_read_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = NrfLogLedger::new(dir.path());
+        ledger
+            .append(&test_entry("myapp", "t1", "b3:a"))
+            .await
+            .unwrap();
+        ledger
+            .append(&test_entry("myapp", "t1", "b3:b"))
+            .await
+            .unwrap();
+
+        let entries = ledger.read_stream("myapp", "t1", "executions").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].cid, "b3:b");
+        assert!(ledger
+            .read_stream("myapp", "t2", "executions")
+            .unwrap()
+            .is_empty());
+
+        // A fresh ledger (e.g. after a restart) continues the same log.
+        let ledger = NrfLogLedger::new(dir.path());
+        ledger
+            .append(&test_entry("myapp", "t1", "b3:c"))
+            .await
+            .unwrap();
+        ledger.close_all().unwrap();
+        let entries = ledger.read_stream("myapp", "t1", "executions").unwrap();
+        let cids: Vec<_> = entries.iter().map(|e| e.cid.as_str()).collect();
+        assert_eq!(cids, ["b3:a", "b3:b", "b3:c"]);
+    }
+
+    #[tokio::test]
+    async fn test_records_hold_the_canonical_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = NrfLogLedger::new(dir.path());
+        let entry = test_entry("myapp", "t1", "b3:a");
+        ledger.append(&entry).await.unwrap();
+        ledger.close_all().unwrap();
+
+        let nrf = nrf1::encode(&entry.to_canonical_value().unwrap());
+        let file = File::open(ledger.stream_path("myapp", "t1", "executions")).unwrap();
+        let mut log = IndexedLog::open(BufReader::new(file)).unwrap();
+        assert!(log.has_index());
+        let cid = nrf_core::hash_bytes(&nrf);
+        assert_eq!(log.get(&cid).unwrap().unwrap().nrf, nrf);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_log_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = NrfLogLedger::new(dir.path());
+        ledger
+            .append(&test_entry("myapp", "t1", "b3:a"))
+            .await
+            .unwrap();
+        let path = ledger.stream_path("myapp", "t1", "executions");
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        std::fs::write(&path, bytes).unwrap();
+        assert!(ledger.read_stream("myapp", "t1", "executions").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_appends_all_land() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = Arc::new(NrfLogLedger::new(dir.path()));
+        let tasks: Vec<_> = (0..16)
+            .map(|i| {
+                let ledger = Arc::clone(&ledger);
+                tokio::spawn(async move {
+                    ledger
+                        .append(&test_entry("myapp", "t1", &format!("b3:{i}")))
+                        .await
+                })
+            })
+            .collect();
+        for t in tasks {
+            t.await.unwrap().unwrap();
+        }
+        let entries = ledger.read_stream("myapp", "t1", "executions").unwrap();
+        assert_eq!(entries.len(), 16);
+    }
+}
@@ -1,6 +1,12 @@
 use blake3::Hasher;
 use serde::{Deserialize, Serialize};
 
+pub mod sirp;
+pub mod store;
+
+pub use sirp::{SirpContext, SirpError, SirpFlow, SirpState};
+pub use store::{FlowStore, StoreError};
+
 // ---------------------------------------------------------------------------
 // UBL Capsule v1 — BASE terrain
 //
@@ -35,7 +41,7 @@ pub enum ProofLevel {
 // Capsule roles (SIRP positions)
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum CapsuleRole {
     Intent,    // "I want to do X" — sent before execution
     Result,    // "X happened" — sent after execution
@@ -43,6 +49,17 @@ pub enum CapsuleRole {
     Execution, // executor confirms completion
 }
 
+impl std::fmt::Display for CapsuleRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CapsuleRole::Intent => "INTENT",
+            CapsuleRole::Result => "RESULT",
+            CapsuleRole::Delivery => "DELIVERY",
+            CapsuleRole::Execution => "EXECUTION",
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Envelope type (what the capsule carries — from capsule spec env.t)
 // ---------------------------------------------------------------------------
@@ -142,6 +159,8 @@ pub struct Envelope {
     pub meta: Option<Meta>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub links: Option<Links>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<CapsuleRole>, // SIRP position; covered by the seal
 }
 
 // ---------------------------------------------------------------------------
@@ -186,6 +205,12 @@ impl HopReceipt {
         h.finalize().as_bytes().to_vec()
     }
 
+    /// Sign this hop receipt (sets `sig`).
+    pub fn sign(&mut self, sk: &ed25519_dalek::SigningKey) {
+        use ed25519_dalek::Signer;
+        self.sig = sk.sign(&self.sig_preimage()).to_bytes().to_vec();
+    }
+
     /// Verify this hop receipt's signature.
     pub fn verify(&self, vk: &ed25519_dalek::VerifyingKey) -> bool {
         use ed25519_dalek::Verifier;
@@ -200,7 +225,8 @@ impl HopReceipt {
 // ---------------------------------------------------------------------------
 // Capsule — the full wire artifact
 //
-// id = BLAKE3(NRF(capsule \ {id, seal.sig, receipts[*].sig}))
+// id = BLAKE3(NRF(ρ(capsule \ {id, seal.sig, receipts})))
+// (hops sign over the id, so receipts stay outside it)
 // seal.sig signs BLAKE3(NRF({domain, id, hdr, env}))
 // ---------------------------------------------------------------------------
 
@@ -221,6 +247,22 @@ impl Capsule {
         format!("b3:{}", hex::encode(&self.id))
     }
 
+    /// Recompute the content ID from the capsule's fields. Fails on
+    /// floats, non-NFC strings or anything else NRF cannot hold.
+    pub fn compute_id(&self) -> Result<Vec<u8>, String> {
+        let mut v = nrf1::serde::to_value(self).map_err(|e| format!("Err.Canon.{e}"))?;
+        if let nrf1::Value::Map(m) = &mut v {
+            m.remove("id");
+            m.remove("receipts");
+            if let Some(nrf1::Value::Map(seal)) = m.get_mut("seal") {
+                seal.remove("sig");
+            }
+        }
+        let v = nrf1::rho::normalize(&v).map_err(|e| format!("Err.Canon.Rho: {e}"))?;
+        let bytes = nrf1::try_encode(&v).map_err(|e| format!("Err.Canon.{e}"))?;
+        Ok(blake3::hash(&bytes).as_bytes().to_vec())
+    }
+
     /// Compute the seal signature preimage:
     /// BLAKE3(domain || id || hdr_json || env_json)
     ///
@@ -237,6 +279,12 @@ impl Capsule {
         h.finalize().as_bytes().to_vec()
     }
 
+    /// Seal the capsule with the sender's key (sets `seal.sig`).
+    pub fn sign_seal(&mut self, sk: &ed25519_dalek::SigningKey) {
+        use ed25519_dalek::Signer;
+        self.seal.sig = sk.sign(&self.seal_preimage()).to_bytes().to_vec();
+    }
+
     /// Verify the seal signature against the sender's public key.
     pub fn verify_seal(&self, vk: &ed25519_dalek::VerifyingKey) -> bool {
         use ed25519_dalek::Verifier;
@@ -302,34 +350,3 @@ impl Capsule {
         Ok(())
     }
 }
-
-// ---------------------------------------------------------------------------
-// SIRP flow: INTENT → DELIVERY → EXECUTION → RESULT
-// A complete SIRP exchange is 4 capsules. Each links to the previous via CID.
-// ---------------------------------------------------------------------------
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SirpFlow {
-    pub intent: Capsule,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub delivery: Option<Capsule>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub execution: Option<Capsule>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub result: Option<Capsule>,
-}
-
-impl SirpFlow {
-    pub fn new(intent: Capsule) -> Self {
-        Self {
-            intent,
-            delivery: None,
-            execution: None,
-            result: None,
-        }
-    }
-
-    pub fn is_complete(&self) -> bool {
-        self.delivery.is_some() && self.execution.is_some() && self.result.is_some()
-    }
-}
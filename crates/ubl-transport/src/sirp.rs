@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ubl_capsule::resolver::{KeyResolver, ResolveError};
+
+use crate::{Capsule, CapsuleRole};
+
+// ---------------------------------------------------------------------------
+// SIRP flow: INTENT → DELIVERY → EXECUTION → RESULT
+//
+// A complete SIRP exchange is 4 capsules. Each links to the previous via CID.
+// The sender S opens the flow with INTENT (S → R); the receiver R answers
+// with DELIVERY, EXECUTION and RESULT (R → S). Every transition checks:
+//
+//   role      env.role is the next expected stage
+//   id        the capsule ID is the content hash of its fields
+//   parties   hdr.src / hdr.dst (and seal.aud, if set) match the stage
+//   signer    seal.kid belongs to hdr.src, and the seal verifies
+//   link      env.links.prev is the previous stage's capsule ID
+//   hops      receipts form a chain and every hop signature verifies
+//   time      the capsule has not expired and the stage deadline holds
+//
+// A transition that fails leaves the flow unchanged, except a missed
+// deadline, which moves it to TimedOut for good.
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum SirpError {
+    #[error("Err.Sirp.OutOfOrder: expected {expected}, got {got}")]
+    OutOfOrder {
+        expected: CapsuleRole,
+        got: CapsuleRole,
+    },
+    #[error("Err.Sirp.MissingRole: env.role must be {expected}")]
+    MissingRole { expected: CapsuleRole },
+    #[error("Err.Sirp.Closed: flow is {state:?}, no further stages accepted")]
+    Closed { state: SirpState },
+    #[error("Err.Sirp.TimedOut: stage deadline {deadline} passed, now={now}")]
+    TimedOut { deadline: i64, now: i64 },
+    #[error("Err.Sirp.Expired: {role} capsule expired at {exp}, now={now}")]
+    Expired {
+        role: CapsuleRole,
+        exp: i64,
+        now: i64,
+    },
+    #[error("Err.Sirp.WrongParty: {role} must go from {src} to {dst}")]
+    WrongParty {
+        role: CapsuleRole,
+        src: String,
+        dst: String,
+    },
+    #[error("Err.Sirp.WrongSigner: {role} sealed by {kid}, not by {src}")]
+    WrongSigner {
+        role: CapsuleRole,
+        kid: String,
+        src: String,
+    },
+    #[error("Err.Sirp.BadSeal: {role} seal does not verify")]
+    BadSeal { role: CapsuleRole },
+    #[error("Err.Sirp.Key: {role}: {error}")]
+    Key {
+        role: CapsuleRole,
+        error: ResolveError,
+    },
+    #[error("Err.Sirp.BadLink: {role} links.prev is not the {prev} capsule ID")]
+    BadLink {
+        role: CapsuleRole,
+        prev: CapsuleRole,
+    },
+    #[error("Err.Sirp.BadHopChain: {role} receipts do not form a chain")]
+    BadHopChain { role: CapsuleRole },
+    #[error("Err.Sirp.BadHopSignature: {role} receipt[{index}] signature invalid")]
+    BadHopSignature { role: CapsuleRole, index: usize },
+    #[error("Err.Sirp.IdMismatch: {role} id is not the hash of its content")]
+    IdMismatch { role: CapsuleRole },
+    #[error("Err.Sirp.Canon: {role}: {error}")]
+    Canon { role: CapsuleRole, error: String },
+    #[error("Err.Sirp.Invariant: {role}: {reason}")]
+    Invariant {
+        role: CapsuleRole,
+        reason: &'static str,
+    },
+}
+
+/// Where a flow stands: the next stage it waits for, or how it ended.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SirpState {
+    AwaitingDelivery,
+    AwaitingExecution,
+    AwaitingResult,
+    Complete,
+    TimedOut,
+}
+
+impl SirpState {
+    /// The role the next capsule must carry, if the flow is still open.
+    pub fn expected(&self) -> Option<CapsuleRole> {
+        match self {
+            SirpState::AwaitingDelivery => Some(CapsuleRole::Delivery),
+            SirpState::AwaitingExecution => Some(CapsuleRole::Execution),
+            SirpState::AwaitingResult => Some(CapsuleRole::Result),
+            SirpState::Complete | SirpState::TimedOut => None,
+        }
+    }
+}
+
+/// What a transition checks against: keys, the current time and how long
+/// the counterparty has for the next stage.
+pub struct SirpContext<'a> {
+    /// Resolves `seal.kid` and hop `node`s (at the capsule's / hop's time).
+    pub keys: &'a dyn KeyResolver,
+    /// Current time, epoch-nanos.
+    pub now_ns: i64,
+    /// Deadline for the next stage after each accepted one (`None` = only
+    /// the capsules' own `hdr.exp` applies).
+    pub stage_timeout_ns: Option<i64>,
+}
+
+/// A SIRP exchange. Only [`SirpFlow::start`] and the transition methods
+/// create or advance one; a deserialized flow is trusted as stored.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SirpFlow {
+    intent: Capsule,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delivery: Option<Capsule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    execution: Option<Capsule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Capsule>,
+    state: SirpState,
+    /// When the last stage was accepted, epoch-nanos.
+    updated_at: i64,
+    /// Next stage must arrive by then, epoch-nanos.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deadline: Option<i64>,
+}
+
+impl SirpFlow {
+    /// Open a flow with a verified INTENT capsule (S → R).
+    pub fn start(intent: Capsule, ctx: &SirpContext<'_>) -> Result<Self, SirpError> {
+        let (src, dst) = (intent.hdr.src.clone(), intent.hdr.dst.clone());
+        check_stage(&intent, CapsuleRole::Intent, &src, &dst, None, ctx)?;
+        Ok(Self {
+            intent,
+            delivery: None,
+            execution: None,
+            result: None,
+            state: SirpState::AwaitingDelivery,
+            updated_at: ctx.now_ns,
+            deadline: deadline(ctx),
+        })
+    }
+
+    /// Flow ID: the INTENT capsule's CID.
+    pub fn id(&self) -> String {
+        self.intent.id_cid()
+    }
+
+    pub fn state(&self) -> SirpState {
+        self.state
+    }
+
+    pub fn deadline(&self) -> Option<i64> {
+        self.deadline
+    }
+
+    pub fn updated_at(&self) -> i64 {
+        self.updated_at
+    }
+
+    pub fn intent(&self) -> &Capsule {
+        &self.intent
+    }
+
+    pub fn delivery(&self) -> Option<&Capsule> {
+        self.delivery.as_ref()
+    }
+
+    pub fn execution(&self) -> Option<&Capsule> {
+        self.execution.as_ref()
+    }
+
+    pub fn result(&self) -> Option<&Capsule> {
+        self.result.as_ref()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.state == SirpState::Complete
+    }
+
+    /// R acknowledges the INTENT.
+    pub fn deliver(&mut self, c: Capsule, ctx: &SirpContext<'_>) -> Result<(), SirpError> {
+        self.transition(CapsuleRole::Delivery, c, ctx)
+    }
+
+    /// R confirms execution.
+    pub fn execute(&mut self, c: Capsule, ctx: &SirpContext<'_>) -> Result<(), SirpError> {
+        self.transition(CapsuleRole::Execution, c, ctx)
+    }
+
+    /// R reports the outcome; completes the flow.
+    pub fn finish(&mut self, c: Capsule, ctx: &SirpContext<'_>) -> Result<(), SirpError> {
+        self.transition(CapsuleRole::Result, c, ctx)
+    }
+
+    /// Accept whichever stage `c` claims to be (by `env.role`).
+    pub fn advance(&mut self, c: Capsule, ctx: &SirpContext<'_>) -> Result<(), SirpError> {
+        let expected = self
+            .state
+            .expected()
+            .ok_or(SirpError::Closed { state: self.state })?;
+        let role = c.env.role.ok_or(SirpError::MissingRole { expected })?;
+        self.transition(role, c, ctx)
+    }
+
+    /// Move to `TimedOut` if the stage deadline has passed. Returns whether
+    /// the flow timed out now.
+    pub fn check_deadline(&mut self, now_ns: i64) -> bool {
+        match (self.state.expected(), self.deadline) {
+            (Some(_), Some(deadline)) if now_ns > deadline => {
+                self.state = SirpState::TimedOut;
+                self.updated_at = now_ns;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn transition(
+        &mut self,
+        role: CapsuleRole,
+        c: Capsule,
+        ctx: &SirpContext<'_>,
+    ) -> Result<(), SirpError> {
+        let expected = self
+            .state
+            .expected()
+            .ok_or(SirpError::Closed { state: self.state })?;
+        if let (true, Some(deadline)) = (self.check_deadline(ctx.now_ns), self.deadline) {
+            return Err(SirpError::TimedOut {
+                deadline,
+                now: ctx.now_ns,
+            });
+        }
+        if role != expected {
+            return Err(SirpError::OutOfOrder {
+                expected,
+                got: role,
+            });
+        }
+        let (prev_role, prev) = match role {
+            CapsuleRole::Delivery => (CapsuleRole::Intent, &self.intent),
+            CapsuleRole::Execution => (
+                CapsuleRole::Delivery,
+                self.delivery.as_ref().ok_or(SirpError::Invariant {
+                    role,
+                    reason: "flow awaits EXECUTION but holds no DELIVERY",
+                })?,
+            ),
+            _ => (
+                CapsuleRole::Execution,
+                self.execution.as_ref().ok_or(SirpError::Invariant {
+                    role,
+                    reason: "flow awaits RESULT but holds no EXECUTION",
+                })?,
+            ),
+        };
+        // Every answer goes back from R to S.
+        let (src, dst) = (&self.intent.hdr.dst, &self.intent.hdr.src);
+        check_stage(&c, role, src, dst, Some((prev_role, &prev.id)), ctx)?;
+
+        let (slot, next) = match role {
+            CapsuleRole::Delivery => (&mut self.delivery, SirpState::AwaitingExecution),
+            CapsuleRole::Execution => (&mut self.execution, SirpState::AwaitingResult),
+            _ => (&mut self.result, SirpState::Complete),
+        };
+        *slot = Some(c);
+        self.state = next;
+        self.updated_at = ctx.now_ns;
+        self.deadline = match next {
+            SirpState::Complete => None,
+            _ => deadline(ctx),
+        };
+        Ok(())
+    }
+}
+
+fn deadline(ctx: &SirpContext<'_>) -> Option<i64> {
+    ctx.stage_timeout_ns.map(|t| ctx.now_ns.saturating_add(t))
+}
+
+/// Epoch-nanos → the epoch-millis key resolvers work in.
+fn ms(ns: i64) -> i64 {
+    ns / 1_000_000
+}
+
+fn check_stage(
+    c: &Capsule,
+    role: CapsuleRole,
+    src: &str,
+    dst: &str,
+    prev: Option<(CapsuleRole, &Vec<u8>)>,
+    ctx: &SirpContext<'_>,
+) -> Result<(), SirpError> {
+    match c.env.role {
+        Some(r) if r == role => {}
+        Some(got) => {
+            return Err(SirpError::OutOfOrder {
+                expected: role,
+                got,
+            })
+        }
+        None => return Err(SirpError::MissingRole { expected: role }),
+    }
+    c.check_invariants()
+        .map_err(|reason| SirpError::Invariant { role, reason })?;
+    if c.id.len() != 32 {
+        return Err(SirpError::Invariant {
+            role,
+            reason: "capsule id must be 32 bytes",
+        });
+    }
+    // The ID keys the flow and the next stage's link: it must be earned.
+    let id = c
+        .compute_id()
+        .map_err(|error| SirpError::Canon { role, error })?;
+    if id != c.id {
+        return Err(SirpError::IdMismatch { role });
+    }
+
+    // Parties: src → dst, with the audience (if bound) on dst.
+    let aud_ok = c.seal.aud.as_deref().is_none_or(|aud| aud == dst);
+    if c.hdr.src != src || c.hdr.dst != dst || !aud_ok {
+        return Err(SirpError::WrongParty {
+            role,
+            src: src.into(),
+            dst: dst.into(),
+        });
+    }
+    let kid = &c.seal.kid;
+    let own_key = kid == src || kid.strip_prefix(src).is_some_and(|f| f.starts_with('#'));
+    if !own_key {
+        return Err(SirpError::WrongSigner {
+            role,
+            kid: kid.clone(),
+            src: src.into(),
+        });
+    }
+
+    // Time: the capsule's own expiry.
+    if ctx.now_ns > c.hdr.exp {
+        return Err(SirpError::Expired {
+            role,
+            exp: c.hdr.exp,
+            now: ctx.now_ns,
+        });
+    }
+
+    // Link to the previous stage.
+    if let Some((prev_role, prev_id)) = prev {
+        let linked = c.env.links.as_ref().and_then(|l| l.prev.as_ref());
+        if linked != Some(prev_id) {
+            return Err(SirpError::BadLink {
+                role,
+                prev: prev_role,
+            });
+        }
+    }
+
+    // Seal, under the signer's key at the capsule's time.
+    let at = ms(c.hdr.ts.unwrap_or(ctx.now_ns));
+    let vk = ctx
+        .keys
+        .resolve(kid, at)
+        .map_err(|error| SirpError::Key { role, error })?;
+    if !c.verify_seal(&vk) {
+        return Err(SirpError::BadSeal { role });
+    }
+
+    // Hops.
+    if !c.verify_hop_chain() {
+        return Err(SirpError::BadHopChain { role });
+    }
+    for (index, hop) in c.receipts.iter().enumerate() {
+        let vk = ctx
+            .keys
+            .resolve(&hop.node, ms(hop.ts))
+            .map_err(|error| SirpError::Key { role, error })?;
+        if !hop.verify(&vk) {
+            return Err(SirpError::BadHopSignature { role, index });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use thiserror::Error;
+
+use crate::sirp::{SirpContext, SirpError, SirpFlow, SirpState};
+use crate::Capsule;
+
+// ---------------------------------------------------------------------------
+// Flow store — in-flight SIRP exchanges on disk
+//
+// One JSON file per flow: <state_dir>/sirp-flows/<intent id hex>.json.
+// Writes go through a temp file + rename, so a crash never leaves a
+// half-written flow. A transition that fails is not persisted, except a
+// missed deadline (the flow is saved as TimedOut).
+//
+// start / advance / expire read, change and rewrite a flow under a lock
+// striped by flow ID, so concurrent callers on one FlowStore cannot lose
+// an update. The lock is in-process: one process owns a state dir, and
+// several processes sharing it are not coordinated.
+// ---------------------------------------------------------------------------
+
+const LOCK_STRIPES: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error(transparent)]
+    Sirp(#[from] SirpError),
+    #[error("Err.Sirp.UnknownFlow: {0}")]
+    UnknownFlow(String),
+    #[error("Err.Sirp.DuplicateFlow: {0} already exists")]
+    DuplicateFlow(String),
+    #[error("Err.Sirp.Store: {0}")]
+    Io(String),
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(e: std::io::Error) -> Self {
+        StoreError::Io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        StoreError::Io(e.to_string())
+    }
+}
+
+pub struct FlowStore {
+    dir: PathBuf,
+    locks: [Mutex<()>; LOCK_STRIPES],
+}
+
+impl FlowStore {
+    pub fn new(state_dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: state_dir.as_ref().join("sirp-flows"),
+            locks: Default::default(),
+        }
+    }
+
+    /// Accepts the `b3:<hex>` flow ID or bare hex.
+    fn flow_path(&self, id: &str) -> Result<PathBuf, StoreError> {
+        let hex_id = id.strip_prefix("b3:").unwrap_or(id);
+        let valid = hex_id.len() == 64 && hex_id.bytes().all(|b| b.is_ascii_hexdigit());
+        if !valid {
+            return Err(StoreError::UnknownFlow(id.into()));
+        }
+        Ok(self
+            .dir
+            .join(format!("{}.json", hex_id.to_ascii_lowercase())))
+    }
+
+    /// The flow's path, locked for a read-modify-write. The guarded data is
+    /// `()`, so a poisoned stripe is still safe to take.
+    fn lock_flow(&self, id: &str) -> Result<(PathBuf, MutexGuard<'_, ()>), StoreError> {
+        let path = self.flow_path(id)?;
+        let stripe = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| usize::from_str_radix(&s[..1], 16).ok())
+            .unwrap_or(0);
+        let guard = self.locks[stripe % LOCK_STRIPES]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        Ok((path, guard))
+    }
+
+    fn save(&self, flow: &SirpFlow) -> Result<(), StoreError> {
+        let path = self.flow_path(&flow.id())?;
+        std::fs::create_dir_all(&self.dir)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(flow)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<SirpFlow>, StoreError> {
+        let path = self.flow_path(id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&std::fs::read(path)?)?))
+    }
+
+    /// All flows, optionally only those in `state`, oldest update first.
+    pub fn list(&self, state: Option<SirpState>) -> Result<Vec<SirpFlow>, StoreError> {
+        if !self.dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut flows = vec![];
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let flow: SirpFlow = serde_json::from_slice(&std::fs::read(&path)?)?;
+            if state.is_none_or(|s| s == flow.state()) {
+                flows.push(flow);
+            }
+        }
+        flows.sort_by_key(|f| f.updated_at());
+        Ok(flows)
+    }
+
+    /// Verify an INTENT and record a new flow.
+    pub fn start(&self, intent: Capsule, ctx: &SirpContext<'_>) -> Result<SirpFlow, StoreError> {
+        let flow = SirpFlow::start(intent, ctx)?;
+        let (path, _guard) = self.lock_flow(&flow.id())?;
+        if path.exists() {
+            return Err(StoreError::DuplicateFlow(flow.id()));
+        }
+        self.save(&flow)?;
+        Ok(flow)
+    }
+
+    /// Apply the next stage (by `env.role`) to a stored flow.
+    pub fn advance(
+        &self,
+        id: &str,
+        c: Capsule,
+        ctx: &SirpContext<'_>,
+    ) -> Result<SirpFlow, StoreError> {
+        let (_, _guard) = self.lock_flow(id)?;
+        let mut flow = self
+            .get(id)?
+            .ok_or_else(|| StoreError::UnknownFlow(id.into()))?;
+        match flow.advance(c, ctx) {
+            Ok(()) => {
+                self.save(&flow)?;
+                Ok(flow)
+            }
+            Err(e @ SirpError::TimedOut { .. }) => {
+                self.save(&flow)?;
+                Err(e.into())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Mark every open flow past its deadline as TimedOut. Returns their IDs.
+    pub fn expire(&self, now_ns: i64) -> Result<Vec<String>, StoreError> {
+        let mut expired = vec![];
+        for listed in self.list(None)? {
+            // Re-read under the lock: the flow may have moved on since.
+            let (_, _guard) = self.lock_flow(&listed.id())?;
+            let Some(mut flow) = self.get(&listed.id())? else {
+                continue;
+            };
+            if flow.check_deadline(now_ns) {
+                self.save(&flow)?;
+                expired.push(flow.id());
+            }
+        }
+        Ok(expired)
+    }
+}
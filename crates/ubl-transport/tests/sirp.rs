@@ -0,0 +1,404 @@
+use ed25519_dalek::SigningKey;
+use std::collections::HashMap;
+use ubl_transport::*;
+
+const T0: i64 = 1_700_000_000_000_000_000;
+const SEC: i64 = 1_000_000_000;
+const S: &str = "did:ubl:sender";
+const R: &str = "did:ubl:receiver";
+
+struct Parties {
+    sender: SigningKey,
+    receiver: SigningKey,
+    relay: SigningKey,
+    keys: HashMap<String, ed25519_dalek::VerifyingKey>,
+}
+
+fn parties() -> Parties {
+    let key = || SigningKey::generate(&mut rand::thread_rng());
+    let (sender, receiver, relay) = (key(), key(), key());
+    let keys = [
+        (format!("{S}#k1"), sender.verifying_key()),
+        (format!("{R}#k1"), receiver.verifying_key()),
+        ("did:ubl:relay#k1".to_string(), relay.verifying_key()),
+    ]
+    .into();
+    Parties {
+        sender,
+        receiver,
+        relay,
+        keys,
+    }
+}
+
+fn ctx(p: &Parties, now_ns: i64) -> SirpContext<'_> {
+    SirpContext {
+        keys: &p.keys,
+        now_ns,
+        stage_timeout_ns: Some(60 * SEC),
+    }
+}
+
+fn capsule(role: CapsuleRole, from: &str, to: &str, prev: Option<&Capsule>) -> Capsule {
+    let mut c = Capsule {
+        v: CAPSULE_VERSION.into(),
+        id: vec![],
+        hdr: Header {
+            src: from.into(),
+            dst: to.into(),
+            nonce: vec![7u8; 16],
+            exp: T0 + 3600 * SEC,
+            chan: None,
+            ts: Some(T0),
+        },
+        env: Envelope {
+            t: EnvelopeType::Record,
+            agent: None,
+            intent: Intent {
+                kind: "EVALUATE".into(),
+                name: "score".into(),
+                args: None,
+            },
+            ctx: None,
+            decision: Decision {
+                verdict: "REQUIRE".into(),
+                reason: None,
+                metrics: None,
+            },
+            evidence: None,
+            meta: None,
+            links: prev.map(|c| Links {
+                prev: Some(c.id.clone()),
+                trace: None,
+            }),
+            role: Some(role),
+        },
+        seal: Seal {
+            alg: SigAlg::Ed25519,
+            kid: format!("{from}#k1"),
+            domain: CAPSULE_DOMAIN.into(),
+            scope: "capsule".into(),
+            aud: Some(to.into()),
+            sig: vec![],
+        },
+        receipts: vec![],
+    };
+    c.id = c.compute_id().unwrap();
+    c
+}
+
+/// Re-derive the ID (after any edits) and seal.
+fn signed(mut c: Capsule, sk: &SigningKey) -> Capsule {
+    c.id = c.compute_id().unwrap();
+    c.sign_seal(sk);
+    c
+}
+
+fn relay_hop(c: &Capsule, sk: &SigningKey) -> HopReceipt {
+    let mut hop = HopReceipt {
+        of: c.id.clone(),
+        prev: vec![0u8; 32],
+        kind: "relay".into(),
+        node: "did:ubl:relay#k1".into(),
+        ts: T0 + SEC,
+        sig: vec![],
+    };
+    hop.sign(sk);
+    hop
+}
+
+#[test]
+fn full_flow_is_persisted_stage_by_stage() {
+    let p = parties();
+    let dir = tempfile::tempdir().unwrap();
+    let store = FlowStore::new(dir.path());
+
+    let intent = signed(capsule(CapsuleRole::Intent, S, R, None), &p.sender);
+    let flow = store.start(intent.clone(), &ctx(&p, T0)).unwrap();
+    let id = flow.id();
+    assert_eq!(flow.state(), SirpState::AwaitingDelivery);
+    assert_eq!(flow.deadline(), Some(T0 + 60 * SEC));
+    assert!(matches!(
+        store.start(intent.clone(), &ctx(&p, T0)),
+        Err(StoreError::DuplicateFlow(_))
+    ));
+
+    let mut delivery = capsule(CapsuleRole::Delivery, R, S, Some(&intent));
+    delivery.receipts.push(relay_hop(&delivery, &p.relay));
+    let delivery = signed(delivery, &p.receiver);
+    let execution = signed(
+        capsule(CapsuleRole::Execution, R, S, Some(&delivery)),
+        &p.receiver,
+    );
+    let result = signed(
+        capsule(CapsuleRole::Result, R, S, Some(&execution)),
+        &p.receiver,
+    );
+
+    let flow = store.advance(&id, delivery, &ctx(&p, T0 + SEC)).unwrap();
+    assert_eq!(flow.state(), SirpState::AwaitingExecution);
+    store
+        .advance(&id, execution, &ctx(&p, T0 + 2 * SEC))
+        .unwrap();
+    assert_eq!(
+        store.list(Some(SirpState::AwaitingResult)).unwrap().len(),
+        1
+    );
+    let flow = store
+        .advance(&id, result.clone(), &ctx(&p, T0 + 3 * SEC))
+        .unwrap();
+    assert!(flow.is_complete());
+    assert_eq!(flow.deadline(), None);
+
+    let stored = store.get(&id).unwrap().unwrap();
+    assert_eq!(stored.state(), SirpState::Complete);
+    assert_eq!(stored.result().unwrap().id, result.id);
+    assert_eq!(
+        store
+            .advance(&id, result, &ctx(&p, T0))
+            .unwrap_err()
+            .to_string(),
+        "Err.Sirp.Closed: flow is Complete, no further stages accepted"
+    );
+}
+
+#[test]
+fn transitions_reject_bad_stages() {
+    let p = parties();
+    let intent = signed(capsule(CapsuleRole::Intent, S, R, None), &p.sender);
+    let mut flow = SirpFlow::start(intent.clone(), &ctx(&p, T0)).unwrap();
+    let now = ctx(&p, T0 + SEC);
+    let delivery = || capsule(CapsuleRole::Delivery, R, S, Some(&intent));
+
+    // Out of order: RESULT before DELIVERY, and a role mismatch on deliver().
+    let result = signed(capsule(CapsuleRole::Result, R, S, None), &p.receiver);
+    assert_eq!(
+        flow.advance(result.clone(), &now).unwrap_err(),
+        SirpError::OutOfOrder {
+            expected: CapsuleRole::Delivery,
+            got: CapsuleRole::Result
+        }
+    );
+    assert!(matches!(
+        flow.deliver(result, &now),
+        Err(SirpError::OutOfOrder { .. })
+    ));
+    let mut c = delivery();
+    c.env.role = None;
+    assert_eq!(
+        flow.advance(c, &now).unwrap_err(),
+        SirpError::MissingRole {
+            expected: CapsuleRole::Delivery
+        }
+    );
+
+    // Wrong direction: the sender answers its own intent.
+    let c = signed(
+        capsule(CapsuleRole::Delivery, S, R, Some(&intent)),
+        &p.sender,
+    );
+    assert!(matches!(
+        flow.deliver(c, &now),
+        Err(SirpError::WrongParty { .. })
+    ));
+
+    // Sealed under someone else's kid.
+    let mut c = delivery();
+    c.seal.kid = "did:ubl:relay#k1".into();
+    let c = signed(c, &p.relay);
+    assert!(matches!(
+        flow.deliver(c, &now),
+        Err(SirpError::WrongSigner { .. })
+    ));
+
+    // Link to something other than the intent.
+    let c = signed(
+        capsule(CapsuleRole::Delivery, R, S, Some(&delivery())),
+        &p.receiver,
+    );
+    assert_eq!(
+        flow.deliver(c, &now).unwrap_err(),
+        SirpError::BadLink {
+            role: CapsuleRole::Delivery,
+            prev: CapsuleRole::Intent
+        }
+    );
+
+    // Signed by the wrong key, or changed after sealing (with or without
+    // re-deriving the ID).
+    let c = signed(delivery(), &p.sender);
+    assert!(matches!(
+        flow.deliver(c, &now),
+        Err(SirpError::BadSeal { .. })
+    ));
+    let mut c = signed(delivery(), &p.receiver);
+    c.env.decision.verdict = "GHOST".into();
+    c.env.links.as_mut().unwrap().trace = Some(vec![1; 32]);
+    assert_eq!(
+        flow.deliver(c.clone(), &now).unwrap_err(),
+        SirpError::IdMismatch {
+            role: CapsuleRole::Delivery
+        }
+    );
+    c.id = c.compute_id().unwrap();
+    assert!(matches!(
+        flow.deliver(c, &now),
+        Err(SirpError::BadSeal { .. })
+    ));
+
+    // An ID that is not the content hash, even when sealed over.
+    let mut c = delivery();
+    c.id = vec![9u8; 32];
+    c.sign_seal(&p.receiver);
+    assert!(matches!(
+        flow.deliver(c, &now),
+        Err(SirpError::IdMismatch { .. })
+    ));
+    let mut c = delivery();
+    c.env.intent.args = Some(serde_json::json!({"x": 1.5}));
+    c.sign_seal(&p.receiver);
+    assert!(matches!(
+        flow.deliver(c, &now),
+        Err(SirpError::Canon { .. })
+    ));
+
+    // Expired capsule.
+    let mut c = delivery();
+    c.hdr.exp = T0;
+    let c = signed(c, &p.receiver);
+    assert!(matches!(
+        flow.deliver(c, &now),
+        Err(SirpError::Expired { .. })
+    ));
+
+    // Unknown signer key.
+    let mut keys = p.keys.clone();
+    keys.remove(&format!("{R}#k1"));
+    let no_key = SirpContext { keys: &keys, ..now };
+    assert!(matches!(
+        flow.deliver(signed(delivery(), &p.receiver), &no_key),
+        Err(SirpError::Key { .. })
+    ));
+
+    // Hops: forged signature, then a broken chain.
+    let mut c = delivery();
+    let mut hop = relay_hop(&c, &p.receiver);
+    c.receipts.push(hop.clone());
+    let c = signed(c, &p.receiver);
+    assert_eq!(
+        flow.deliver(c, &ctx(&p, T0 + SEC)).unwrap_err(),
+        SirpError::BadHopSignature {
+            role: CapsuleRole::Delivery,
+            index: 0
+        }
+    );
+    let mut c = delivery();
+    hop.of = vec![0u8; 32];
+    c.receipts.push(hop);
+    let c = signed(c, &p.receiver);
+    assert!(matches!(
+        flow.deliver(c, &ctx(&p, T0 + SEC)),
+        Err(SirpError::BadHopChain { .. })
+    ));
+
+    // None of the rejections moved the flow.
+    assert_eq!(flow.state(), SirpState::AwaitingDelivery);
+    flow.deliver(signed(delivery(), &p.receiver), &ctx(&p, T0 + SEC))
+        .unwrap();
+    assert_eq!(flow.state(), SirpState::AwaitingExecution);
+}
+
+#[test]
+fn missed_deadlines_time_flows_out() {
+    let p = parties();
+    let dir = tempfile::tempdir().unwrap();
+    let store = FlowStore::new(dir.path());
+
+    let late = signed(capsule(CapsuleRole::Intent, S, R, None), &p.sender);
+    let id = store.start(late.clone(), &ctx(&p, T0)).unwrap().id();
+    let delivery = signed(
+        capsule(CapsuleRole::Delivery, R, S, Some(&late)),
+        &p.receiver,
+    );
+    let err = store
+        .advance(&id, delivery, &ctx(&p, T0 + 61 * SEC))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Err.Sirp.TimedOut: stage deadline {} passed, now={}",
+            T0 + 60 * SEC,
+            T0 + 61 * SEC
+        )
+    );
+    assert_eq!(
+        store.get(&id).unwrap().unwrap().state(),
+        SirpState::TimedOut
+    );
+
+    // The sweep only touches open flows past their deadline.
+    let mut other = capsule(CapsuleRole::Intent, S, R, None);
+    other.hdr.nonce = vec![9u8; 16];
+    let other = store
+        .start(signed(other, &p.sender), &ctx(&p, T0 + 30 * SEC))
+        .unwrap();
+    assert!(store.expire(T0 + 60 * SEC).unwrap().is_empty());
+    assert_eq!(store.expire(T0 + 91 * SEC).unwrap(), vec![other.id()]);
+    assert_eq!(store.list(Some(SirpState::TimedOut)).unwrap().len(), 2);
+    assert!(matches!(
+        store.get("b3:nope"),
+        Err(StoreError::UnknownFlow(_))
+    ));
+}
+
+#[test]
+fn stored_flow_missing_a_stage_is_an_invariant_error() {
+    let p = parties();
+    let intent = signed(capsule(CapsuleRole::Intent, S, R, None), &p.sender);
+    let flow = SirpFlow::start(intent.clone(), &ctx(&p, T0)).unwrap();
+    let delivery = signed(
+        capsule(CapsuleRole::Delivery, R, S, Some(&intent)),
+        &p.receiver,
+    );
+
+    // A stored flow claiming AwaitingExecution without its DELIVERY.
+    let mut j = serde_json::to_value(&flow).unwrap();
+    j["state"] = serde_json::json!("AwaitingExecution");
+    let mut flow: SirpFlow = serde_json::from_value(j).unwrap();
+    let execution = signed(
+        capsule(CapsuleRole::Execution, R, S, Some(&delivery)),
+        &p.receiver,
+    );
+    assert!(matches!(
+        flow.execute(execution, &ctx(&p, T0 + SEC)),
+        Err(SirpError::Invariant {
+            role: CapsuleRole::Execution,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn concurrent_advances_apply_a_stage_once() {
+    let p = parties();
+    let dir = tempfile::tempdir().unwrap();
+    let store = FlowStore::new(dir.path());
+    let intent = signed(capsule(CapsuleRole::Intent, S, R, None), &p.sender);
+    let id = store.start(intent.clone(), &ctx(&p, T0)).unwrap().id();
+    let delivery = signed(
+        capsule(CapsuleRole::Delivery, R, S, Some(&intent)),
+        &p.receiver,
+    );
+
+    let results: Vec<_> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..8)
+            .map(|_| s.spawn(|| store.advance(&id, delivery.clone(), &ctx(&p, T0 + SEC))))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+    assert!(results
+        .iter()
+        .filter_map(|r| r.as_ref().err())
+        .all(|e| matches!(e, StoreError::Sirp(SirpError::OutOfOrder { .. }))));
+}
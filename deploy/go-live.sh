@@ -48,7 +48,7 @@ fi
 # 2. Create state directories
 # ------------------------------------------------------------------
 echo "▸ Creating state directories..."
-mkdir -p "$STATE_DIR"/{idem,permit-tickets,llm-cache,resume}
+mkdir -p "$STATE_DIR"/{idem,permit-tickets,llm-cache,resume,assets}
 echo "  ✓ $STATE_DIR"
 
 # ------------------------------------------------------------------
@@ -28,17 +28,15 @@ def _dec_varint32(b: bytes, i: int) -> tuple[int,int]:
     for idx in range(5):
         if i >= len(b): raise NrfError("UnexpectedEOF")
         byte = b[i]; i += 1
-        if idx == 0 and byte == 0x80:
+        if idx > 0 and byte == 0x00:
+            raise NrfError("NonMinimalVarint")
+        if idx == 4 and byte > 0x0F:
             raise NrfError("NonMinimalVarint")
         res |= (byte & 0x7F) << shift
         cont = (byte & 0x80) != 0
         shift += 7
         if not cont:
             return res, i
-        if idx == 4:
-            if (byte & 0x80) or (byte & 0xF0):
-                raise NrfError("NonMinimalVarint")
-            return res, i
     raise NrfError("NonMinimalVarint")
 
 def _ensure_string_rules(s: str):
@@ -46,7 +46,7 @@ def _decode_varint32(b, off):
         payload = byte & 0x7F
         if i > 0 and byte == 0x00:
             raise NonMinimalVarint()
-        if i == 0 and byte == 0x80:
+        if i == 4 and byte > 0x0F:
             raise NonMinimalVarint()
         result |= (payload << shift)
         if (byte & 0x80) == 0:
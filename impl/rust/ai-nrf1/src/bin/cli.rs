@@ -1,8 +1,11 @@
-//! Simple CLI: canonicalize input to NRF or convert between NRF and CBOR (when feature enabled).
+//! Simple CLI: canonicalize input to NRF or convert between NRF, CBOR and
+//! MessagePack (features `compat_cbor` / `compat_msgpack`).
 //! Usage:
 //!   nrf1 canon --in nrf --out nrf < input.bin > output.bin
 //!   nrf1 canon --in cbor --out nrf < input.cbor > output.nrf
 //!   nrf1 canon --in nrf --out cbor < input.nrf > output.cbor  (requires feature compat_cbor)
+//!   nrf1 canon --in msgpack --out nrf < input.mp > output.nrf (requires feature compat_msgpack)
+//!   nrf1 canon --in nrf --out diag < input.nrf            (diagnostic notation)
 
 use std::env;
 use std::io::{self, Read, Write};
@@ -19,7 +22,7 @@ fn write_all_stdout(bytes: &[u8]) -> io::Result<()> {
 }
 
 fn usage() {
-    eprintln!("nrf1 canon --in {{nrf|cbor}} --out {{nrf|cbor}} <in >out");
+    eprintln!("nrf1 canon --in {{nrf|cbor|msgpack|diag}} --out {{nrf|cbor|msgpack|diag}} <in >out");
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -52,17 +55,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Parse to Value
     let value = match in_fmt {
-        "nrf" => ai_nrf1::decode(&data)?,
+        "nrf" => ai_nrf1::decode_located(&data)?,
+        "diag" => ai_nrf1::from_diag(std::str::from_utf8(&data)?)?,
         "cbor" => {
             #[cfg(feature = "compat_cbor")]
             {
-                ai_nrf1::compat_cbor::cbor::from_slice(&data)?
+                ai_nrf1::compat::cbor::from_slice(&data)?
             }
             #[cfg(not(feature = "compat_cbor"))]
             {
                 return Err("CBOR support not enabled (build with --features compat_cbor)".into());
             }
         }
+        "msgpack" => {
+            #[cfg(feature = "compat_msgpack")]
+            {
+                ai_nrf1::compat::msgpack::from_slice(&data)?
+            }
+            #[cfg(not(feature = "compat_msgpack"))]
+            {
+                return Err(
+                    "MessagePack support not enabled (build with --features compat_msgpack)".into(),
+                );
+            }
+        }
         _ => {
             return Err("unsupported --in format".into());
         }
@@ -71,16 +87,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Emit in desired format
     let out = match out_fmt {
         "nrf" => ai_nrf1::encode(&value),
+        "diag" => (ai_nrf1::to_diag_pretty(&value) + "\n").into_bytes(),
         "cbor" => {
             #[cfg(feature = "compat_cbor")]
             {
-                ai_nrf1::compat_cbor::cbor::to_vec(&value)?
+                ai_nrf1::compat::cbor::to_vec(&value)?
             }
             #[cfg(not(feature = "compat_cbor"))]
             {
                 return Err("CBOR support not enabled (build with --features compat_cbor)".into());
             }
         }
+        "msgpack" => {
+            #[cfg(feature = "compat_msgpack")]
+            {
+                ai_nrf1::compat::msgpack::to_vec(&value)?
+            }
+            #[cfg(not(feature = "compat_msgpack"))]
+            {
+                return Err(
+                    "MessagePack support not enabled (build with --features compat_msgpack)".into(),
+                );
+            }
+        }
         _ => {
             return Err("unsupported --out format".into());
         }
@@ -0,0 +1,227 @@
+//! Deterministic CBOR (RFC 8949 §4.2.1 core requirements) ↔ ai-nrf1.
+//!
+//! | NRF    | CBOR                                  |
+//! |--------|---------------------------------------|
+//! | Null   | simple 22 (`f6`)                      |
+//! | Bool   | simple 20 / 21 (`f4` / `f5`)          |
+//! | Int    | major 0 / 1, Int64 range              |
+//! | String | major 3 (text), NFC                   |
+//! | Bytes  | major 2                               |
+//! | Array  | major 4                               |
+//! | Map    | major 5, text keys                    |
+//!
+//! Arguments use the shortest form and lengths are definite. Map keys are
+//! ordered by their encoded bytes, which for text keys means shorter keys
+//! first — not NRF's order, so maps are re-sorted in both directions.
+
+use super::{check_canonical, CompatErrorKind, Reader, Result};
+use crate::{DecodeOpts, Value};
+use std::collections::BTreeMap;
+
+/// Strict decode with the default `DecodeOpts` limits.
+pub fn from_slice(bytes: &[u8]) -> Result<Value> {
+    from_slice_with_opts(bytes, &DecodeOpts::default())
+}
+
+pub fn from_slice_with_opts(bytes: &[u8], opts: &DecodeOpts) -> Result<Value> {
+    let mut r = Reader::new(bytes, opts)?;
+    let v = item(&mut r, 0)?;
+    r.finish(v)
+}
+
+/// Deterministic encoding of a canonical value (errors as `try_encode`).
+pub fn to_vec(value: &Value) -> crate::Result<Vec<u8>> {
+    check_canonical(value)?;
+    let mut out = Vec::new();
+    write_item(&mut out, value);
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Decode
+// ---------------------------------------------------------------------------
+
+const MAJOR_UINT: u8 = 0;
+const MAJOR_NINT: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+/// Initial byte plus argument, enforcing the shortest form. Major type 7 is
+/// returned unparsed (`ai` in the argument) since its `ai` is not a length.
+fn head(r: &mut Reader) -> Result<(u8, u64)> {
+    let at = r.pos();
+    let ib = r.byte()?;
+    let (major, ai) = (ib >> 5, ib & 0x1f);
+    if major == MAJOR_SIMPLE {
+        return Ok((major, u64::from(ai)));
+    }
+    let (arg, min) = match ai {
+        0..=23 => return Ok((major, u64::from(ai))),
+        24 => (r.be(1)?, 24),
+        25 => (r.be(2)?, 0x100),
+        26 => (r.be(4)?, 0x1_0000),
+        27 => (r.be(8)?, 0x1_0000_0000),
+        31 if major != MAJOR_UINT && major != MAJOR_NINT && major != MAJOR_TAG => {
+            return Err(r.fail_at(at, CompatErrorKind::NotDeterministic("indefinite length")));
+        }
+        _ => {
+            return Err(r.fail_at(
+                at,
+                CompatErrorKind::Malformed("reserved additional information"),
+            ))
+        }
+    };
+    if arg < min {
+        return Err(r.fail_at(
+            at,
+            CompatErrorKind::NotDeterministic("non-shortest argument"),
+        ));
+    }
+    Ok((major, arg))
+}
+
+fn item(r: &mut Reader, depth: usize) -> Result<Value> {
+    let at = r.pos();
+    r.check_depth(at, depth)?;
+    let (major, arg) = head(r)?;
+    Ok(match major {
+        MAJOR_UINT => match i64::try_from(arg) {
+            Ok(n) => Value::Int(n),
+            Err(_) => return Err(r.fail_at(at, CompatErrorKind::IntegerOverflow)),
+        },
+        // -1 - arg: fits exactly when arg <= i64::MAX (down to i64::MIN).
+        MAJOR_NINT => match i64::try_from(arg) {
+            Ok(n) => Value::Int(-1 - n),
+            Err(_) => return Err(r.fail_at(at, CompatErrorKind::IntegerOverflow)),
+        },
+        MAJOR_BYTES => Value::Bytes(r.bytes(at, arg)?),
+        MAJOR_TEXT => Value::String(r.text(at, arg)?),
+        MAJOR_ARRAY => {
+            let n = r.array_len(at, arg)?;
+            let mut items = Vec::with_capacity(n.min(r.remaining()));
+            for i in 0..n {
+                items.push(r.at_index(i, |r| item(r, depth + 1))?);
+            }
+            Value::Array(items)
+        }
+        MAJOR_MAP => {
+            let n = r.map_len(at, arg)?;
+            let mut map = BTreeMap::new();
+            let mut prev: Option<&[u8]> = None;
+            for _ in 0..n {
+                let key_at = r.pos();
+                let (key_major, key_len) = head(r)?;
+                if key_major != MAJOR_TEXT {
+                    return Err(r.fail_at(key_at, CompatErrorKind::NonStringKey));
+                }
+                let key = r.text(key_at, key_len)?;
+                let encoded = r.since(key_at);
+                if let Some(p) = prev {
+                    match p.cmp(encoded) {
+                        std::cmp::Ordering::Less => {}
+                        std::cmp::Ordering::Equal => {
+                            return Err(r.canon_at(key_at, crate::Error::DuplicateKey))
+                        }
+                        std::cmp::Ordering::Greater => {
+                            return Err(r.fail_at(
+                                key_at,
+                                CompatErrorKind::NotDeterministic("map keys out of order"),
+                            ))
+                        }
+                    }
+                }
+                prev = Some(encoded);
+                let val = r.at_key(&key, |r| item(r, depth + 1))?;
+                map.insert(key, val);
+            }
+            Value::Map(map)
+        }
+        MAJOR_TAG => return Err(r.fail_at(at, CompatErrorKind::Tag(arg))),
+        _ => match arg {
+            20 => Value::Bool(false),
+            21 => Value::Bool(true),
+            22 => Value::Null,
+            0..=23 => return Err(r.fail_at(at, CompatErrorKind::Simple(arg as u8))),
+            24 => match r.byte()? {
+                v @ 32.. => return Err(r.fail_at(at, CompatErrorKind::Simple(v))),
+                _ => {
+                    return Err(r.fail_at(
+                        at,
+                        CompatErrorKind::Malformed("two-byte simple value below 32"),
+                    ))
+                }
+            },
+            25..=27 => return Err(r.fail_at(at, CompatErrorKind::Float)),
+            31 => return Err(r.fail_at(at, CompatErrorKind::Malformed("unexpected break"))),
+            _ => {
+                return Err(r.fail_at(
+                    at,
+                    CompatErrorKind::Malformed("reserved additional information"),
+                ))
+            }
+        },
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Encode
+// ---------------------------------------------------------------------------
+
+fn write_head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let mt = major << 5;
+    if arg < 24 {
+        out.push(mt | arg as u8);
+    } else if arg <= 0xff {
+        out.extend_from_slice(&[mt | 24, arg as u8]);
+    } else if arg <= 0xffff {
+        out.push(mt | 25);
+        out.extend_from_slice(&(arg as u16).to_be_bytes());
+    } else if arg <= 0xffff_ffff {
+        out.push(mt | 26);
+        out.extend_from_slice(&(arg as u32).to_be_bytes());
+    } else {
+        out.push(mt | 27);
+        out.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+fn write_item(out: &mut Vec<u8>, v: &Value) {
+    match v {
+        Value::Null => out.push(0xf6),
+        Value::Bool(false) => out.push(0xf4),
+        Value::Bool(true) => out.push(0xf5),
+        Value::Int(n) if *n >= 0 => write_head(out, MAJOR_UINT, *n as u64),
+        // -1 - n == !n for negative n
+        Value::Int(n) => write_head(out, MAJOR_NINT, !*n as u64),
+        Value::String(s) => write_text(out, s),
+        Value::Bytes(b) => {
+            write_head(out, MAJOR_BYTES, b.len() as u64);
+            out.extend_from_slice(b);
+        }
+        Value::Array(items) => {
+            write_head(out, MAJOR_ARRAY, items.len() as u64);
+            for it in items {
+                write_item(out, it);
+            }
+        }
+        Value::Map(m) => {
+            write_head(out, MAJOR_MAP, m.len() as u64);
+            // Encoded text keys compare by length first, then bytes.
+            let mut entries: Vec<_> = m.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| (a.len(), a.as_bytes()).cmp(&(b.len(), b.as_bytes())));
+            for (k, val) in entries {
+                write_text(out, k);
+                write_item(out, val);
+            }
+        }
+    }
+}
+
+fn write_text(out: &mut Vec<u8>, s: &str) {
+    write_head(out, MAJOR_TEXT, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
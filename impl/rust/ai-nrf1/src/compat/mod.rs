@@ -0,0 +1,241 @@
+//! Strict bridges between ai-nrf1 and other binary formats.
+//!
+//! - `cbor` (feature `compat_cbor`): deterministic CBOR, RFC 8949 §4.2.
+//! - `msgpack` (feature `compat_msgpack`): MessagePack, shortest forms only.
+//!
+//! Both directions are exact. `to_vec` accepts any value `try_encode`
+//! accepts, and `from_slice(&to_vec(v)?)` returns `v`. `from_slice` accepts
+//! only the bytes `to_vec` would produce, so re-encoding an accepted input
+//! gives the same bytes. Anything else is rejected with a `CompatError`
+//! naming the byte offset and logical path — never silently rewritten:
+//! floats, tags, extension types, non-string keys, integers outside i64,
+//! non-shortest lengths, indefinite lengths, out-of-order keys, non-NFC text.
+
+use crate::{DecodeOpts, Error, Value};
+use std::fmt;
+
+#[cfg(feature = "compat_cbor")]
+pub mod cbor;
+#[cfg(feature = "compat_msgpack")]
+pub mod msgpack;
+
+/// Why a CBOR / MessagePack input was rejected.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CompatErrorKind {
+    /// CBOR half/single/double or MessagePack float32/float64.
+    Float,
+    /// CBOR tag (major type 6), with its number.
+    Tag(u64),
+    /// MessagePack extension type (ext 8/16/32, fixext), with its type byte.
+    Ext(i8),
+    /// CBOR simple value other than false/true/null (e.g. 23 = undefined).
+    Simple(u8),
+    /// A map key that is not a text string.
+    NonStringKey,
+    /// An integer outside the Int64 range.
+    IntegerOverflow,
+    /// Well-formed, but not the one encoding this bridge emits.
+    NotDeterministic(&'static str),
+    /// Not well-formed CBOR / MessagePack.
+    Malformed(&'static str),
+    UnexpectedEOF,
+    TrailingData,
+    /// Representable in the format but not canonical NRF (NotNFC,
+    /// BOMPresent, DuplicateKey, a `DecodeOpts` limit, ...).
+    Canon(Error),
+}
+
+impl fmt::Display for CompatErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Float => write!(f, "Float"),
+            Self::Tag(n) => write!(f, "Tag({n})"),
+            Self::Ext(t) => write!(f, "Ext({t})"),
+            Self::Simple(v) => write!(f, "Simple({v})"),
+            Self::NonStringKey => write!(f, "NonStringKey"),
+            Self::IntegerOverflow => write!(f, "IntegerOverflow"),
+            Self::NotDeterministic(why) => write!(f, "NotDeterministic({why})"),
+            Self::Malformed(why) => write!(f, "Malformed({why})"),
+            Self::UnexpectedEOF => write!(f, "UnexpectedEOF"),
+            Self::TrailingData => write!(f, "TrailingData"),
+            Self::Canon(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// A rejected input with its location, as in `DecodeError`: `offset` is the
+/// byte offset of the offending item (or key), `path` its logical path.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompatError {
+    pub kind: CompatErrorKind,
+    pub offset: usize,
+    pub path: String,
+}
+
+impl fmt::Display for CompatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{} at byte {}", self.kind, self.offset)
+        } else {
+            write!(f, "{} at byte {} ({})", self.kind, self.offset, self.path)
+        }
+    }
+}
+
+impl std::error::Error for CompatError {}
+
+pub type Result<T> = std::result::Result<T, CompatError>;
+
+// ---------------------------------------------------------------------------
+// Shared reader — position, path and limits for both decoders
+// ---------------------------------------------------------------------------
+
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    path: String,
+    opts: &'a DecodeOpts,
+}
+
+#[allow(dead_code)] // each format uses a subset
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8], opts: &'a DecodeOpts) -> Result<Self> {
+        let r = Reader {
+            data,
+            pos: 0,
+            path: String::new(),
+            opts,
+        };
+        if data.len() > opts.max_total_bytes {
+            return Err(r.fail_at(0, CompatErrorKind::Canon(Error::SizeExceeded)));
+        }
+        Ok(r)
+    }
+
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub(crate) fn fail_at(&self, offset: usize, kind: CompatErrorKind) -> CompatError {
+        CompatError {
+            kind,
+            offset,
+            path: self.path.clone(),
+        }
+    }
+
+    pub(crate) fn canon_at(&self, offset: usize, e: Error) -> CompatError {
+        self.fail_at(offset, CompatErrorKind::Canon(e))
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.data.len() - self.pos < n {
+            return Err(self.fail_at(self.pos, CompatErrorKind::UnexpectedEOF));
+        }
+        let out = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(out)
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn be(&mut self, n: usize) -> Result<u64> {
+        Ok(self
+            .take(n)?
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 8) | u64::from(b)))
+    }
+
+    /// The encoded bytes from `start` to the current position.
+    pub(crate) fn since(&self, start: usize) -> &'a [u8] {
+        &self.data[start..self.pos]
+    }
+
+    pub(crate) fn finish(&self, v: Value) -> Result<Value> {
+        if self.pos != self.data.len() {
+            return Err(self.fail_at(self.pos, CompatErrorKind::TrailingData));
+        }
+        // NRF ints are always 9 bytes, so a small input can still encode
+        // past the limit `decode` would apply to the result.
+        if crate::encoded_len(&v) > self.opts.max_total_bytes {
+            return Err(self.canon_at(0, Error::SizeExceeded));
+        }
+        Ok(v)
+    }
+
+    pub(crate) fn check_depth(&self, at: usize, depth: usize) -> Result<()> {
+        if depth > self.opts.max_depth {
+            return Err(self.canon_at(at, Error::DepthExceeded));
+        }
+        Ok(())
+    }
+
+    /// A declared length, within its `DecodeOpts` limit. Allocations are
+    /// also capped by `remaining`, so a hostile header costs nothing.
+    pub(crate) fn checked_len(&self, at: usize, n: u64, max: usize, err: Error) -> Result<usize> {
+        match usize::try_from(n) {
+            Ok(n) if n <= max => Ok(n),
+            _ => Err(self.canon_at(at, err)),
+        }
+    }
+
+    pub(crate) fn bytes(&mut self, at: usize, n: u64) -> Result<Vec<u8>> {
+        let n = self.checked_len(at, n, self.opts.max_bytes_len, Error::BytesTooLong)?;
+        Ok(self.take(n)?.to_vec())
+    }
+
+    /// UTF-8, NFC, no BOM, within `max_string_len`.
+    pub(crate) fn text(&mut self, at: usize, n: u64) -> Result<String> {
+        let n = self.checked_len(at, n, self.opts.max_string_len, Error::StringTooLong)?;
+        let raw = self.take(n)?;
+        let s = std::str::from_utf8(raw)
+            .map_err(|_| self.fail_at(at, CompatErrorKind::Malformed("invalid UTF-8")))?;
+        nrf_core::validate_nfc(s).map_err(|e| self.canon_at(at, e))?;
+        Ok(s.to_string())
+    }
+
+    pub(crate) fn array_len(&self, at: usize, n: u64) -> Result<usize> {
+        self.checked_len(at, n, self.opts.max_array_len, Error::ArrayTooLong)
+    }
+
+    pub(crate) fn map_len(&self, at: usize, n: u64) -> Result<usize> {
+        self.checked_len(at, n, self.opts.max_map_len, Error::MapTooLong)
+    }
+
+    /// Run `f` with `[i]` appended to the path.
+    pub(crate) fn at_index<T>(
+        &mut self,
+        i: usize,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let len = self.path.len();
+        nrf_core::push_path_index(&mut self.path, i);
+        let out = f(self);
+        self.path.truncate(len);
+        out
+    }
+
+    /// Run `f` with `.key` appended to the path.
+    pub(crate) fn at_key<T>(
+        &mut self,
+        key: &str,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let len = self.path.len();
+        nrf_core::push_path_key(&mut self.path, key);
+        let out = f(self);
+        self.path.truncate(len);
+        out
+    }
+}
+
+/// Validate before converting out: the bridges emit only canonical values.
+pub(crate) fn check_canonical(value: &Value) -> crate::Result<()> {
+    crate::try_encode(value).map(drop)
+}
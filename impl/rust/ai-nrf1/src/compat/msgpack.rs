@@ -0,0 +1,285 @@
+//! MessagePack ↔ ai-nrf1.
+//!
+//! | NRF    | MessagePack                                          |
+//! |--------|------------------------------------------------------|
+//! | Null   | nil (`c0`)                                           |
+//! | Bool   | false / true (`c2` / `c3`)                           |
+//! | Int    | positive / negative fixint, uint 8–64, int 8–64      |
+//! | String | fixstr, str 8/16/32, NFC                             |
+//! | Bytes  | bin 8/16/32                                          |
+//! | Array  | fixarray, array 16/32                                |
+//! | Map    | fixmap, map 16/32, str keys                          |
+//!
+//! MessagePack has no deterministic profile, so this bridge fixes one: every
+//! integer and length in its shortest form, non-negative integers as
+//! fixint/uint and negative ones as fixint/int, and map keys in NRF order
+//! (raw UTF-8 bytes). Decoding rejects anything else.
+
+use super::{check_canonical, CompatErrorKind, Reader, Result};
+use crate::{DecodeOpts, Value};
+use std::collections::BTreeMap;
+
+/// Strict decode with the default `DecodeOpts` limits.
+pub fn from_slice(bytes: &[u8]) -> Result<Value> {
+    from_slice_with_opts(bytes, &DecodeOpts::default())
+}
+
+pub fn from_slice_with_opts(bytes: &[u8], opts: &DecodeOpts) -> Result<Value> {
+    let mut r = Reader::new(bytes, opts)?;
+    let v = item(&mut r, 0)?;
+    r.finish(v)
+}
+
+/// Shortest-form encoding of a canonical value (errors as `try_encode`).
+pub fn to_vec(value: &Value) -> crate::Result<Vec<u8>> {
+    check_canonical(value)?;
+    let mut out = Vec::new();
+    write_item(&mut out, value);
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Decode
+// ---------------------------------------------------------------------------
+
+const NOT_SHORTEST: CompatErrorKind = CompatErrorKind::NotDeterministic("non-shortest form");
+
+/// `n` read from a 1/2/4/8-byte field that must not fit the next smaller form.
+fn sized(r: &mut Reader, at: usize, width: usize, min: u64) -> Result<u64> {
+    let n = r.be(width)?;
+    if n < min {
+        return Err(r.fail_at(at, NOT_SHORTEST));
+    }
+    Ok(n)
+}
+
+/// Signed `int 8/16/32/64`: only negatives below the next smaller form.
+fn signed(r: &mut Reader, at: usize, width: usize, max: i64) -> Result<i64> {
+    let raw = r.be(width)?;
+    let shift = 64 - 8 * width as u32;
+    let n = ((raw << shift) as i64) >> shift; // sign-extend
+    if n > max {
+        return Err(r.fail_at(at, NOT_SHORTEST));
+    }
+    Ok(n)
+}
+
+fn item(r: &mut Reader, depth: usize) -> Result<Value> {
+    let at = r.pos();
+    r.check_depth(at, depth)?;
+    let b = r.byte()?;
+    Ok(match b {
+        0x00..=0x7f => Value::Int(i64::from(b)),
+        0x80..=0x8f => map(r, at, u64::from(b & 0x0f), depth)?,
+        0x90..=0x9f => array(r, at, u64::from(b & 0x0f), depth)?,
+        0xa0..=0xbf => Value::String(r.text(at, u64::from(b & 0x1f))?),
+        0xc0 => Value::Null,
+        0xc1 => return Err(r.fail_at(at, CompatErrorKind::Malformed("reserved byte 0xc1"))),
+        0xc2 => Value::Bool(false),
+        0xc3 => Value::Bool(true),
+        0xc4 => {
+            let n = r.be(1)?;
+            Value::Bytes(r.bytes(at, n)?)
+        }
+        0xc5 => {
+            let n = sized(r, at, 2, 0x100)?;
+            Value::Bytes(r.bytes(at, n)?)
+        }
+        0xc6 => {
+            let n = sized(r, at, 4, 0x1_0000)?;
+            Value::Bytes(r.bytes(at, n)?)
+        }
+        0xc7..=0xc9 => {
+            r.be(1 << (b - 0xc7))?; // data length
+            let ext_type = r.byte()? as i8;
+            return Err(r.fail_at(at, CompatErrorKind::Ext(ext_type)));
+        }
+        0xca | 0xcb => return Err(r.fail_at(at, CompatErrorKind::Float)),
+        0xcc => Value::Int(sized(r, at, 1, 0x80)? as i64),
+        0xcd => Value::Int(sized(r, at, 2, 0x100)? as i64),
+        0xce => Value::Int(sized(r, at, 4, 0x1_0000)? as i64),
+        0xcf => match i64::try_from(sized(r, at, 8, 0x1_0000_0000)?) {
+            Ok(n) => Value::Int(n),
+            Err(_) => return Err(r.fail_at(at, CompatErrorKind::IntegerOverflow)),
+        },
+        0xd0 => Value::Int(signed(r, at, 1, -33)?),
+        0xd1 => Value::Int(signed(r, at, 2, i64::from(i8::MIN) - 1)?),
+        0xd2 => Value::Int(signed(r, at, 4, i64::from(i16::MIN) - 1)?),
+        0xd3 => Value::Int(signed(r, at, 8, i64::from(i32::MIN) - 1)?),
+        0xd4..=0xd8 => {
+            let ext_type = r.byte()? as i8;
+            return Err(r.fail_at(at, CompatErrorKind::Ext(ext_type)));
+        }
+        0xd9 => {
+            let n = sized(r, at, 1, 32)?;
+            Value::String(r.text(at, n)?)
+        }
+        0xda => {
+            let n = sized(r, at, 2, 0x100)?;
+            Value::String(r.text(at, n)?)
+        }
+        0xdb => {
+            let n = sized(r, at, 4, 0x1_0000)?;
+            Value::String(r.text(at, n)?)
+        }
+        0xdc => {
+            let n = sized(r, at, 2, 16)?;
+            array(r, at, n, depth)?
+        }
+        0xdd => {
+            let n = sized(r, at, 4, 0x1_0000)?;
+            array(r, at, n, depth)?
+        }
+        0xde => {
+            let n = sized(r, at, 2, 16)?;
+            map(r, at, n, depth)?
+        }
+        0xdf => {
+            let n = sized(r, at, 4, 0x1_0000)?;
+            map(r, at, n, depth)?
+        }
+        0xe0..=0xff => Value::Int(i64::from(b as i8)),
+    })
+}
+
+fn array(r: &mut Reader, at: usize, n: u64, depth: usize) -> Result<Value> {
+    let n = r.array_len(at, n)?;
+    let mut items = Vec::with_capacity(n.min(r.remaining()));
+    for i in 0..n {
+        items.push(r.at_index(i, |r| item(r, depth + 1))?);
+    }
+    Ok(Value::Array(items))
+}
+
+fn map(r: &mut Reader, at: usize, n: u64, depth: usize) -> Result<Value> {
+    let n = r.map_len(at, n)?;
+    let mut map = BTreeMap::new();
+    let mut prev: Option<String> = None;
+    for _ in 0..n {
+        let key_at = r.pos();
+        let key = match item(r, depth + 1)? {
+            Value::String(s) => s,
+            _ => return Err(r.fail_at(key_at, CompatErrorKind::NonStringKey)),
+        };
+        if let Some(p) = &prev {
+            match p.as_bytes().cmp(key.as_bytes()) {
+                std::cmp::Ordering::Less => {}
+                std::cmp::Ordering::Equal => {
+                    return Err(r.canon_at(key_at, crate::Error::DuplicateKey))
+                }
+                std::cmp::Ordering::Greater => {
+                    return Err(r.fail_at(
+                        key_at,
+                        CompatErrorKind::NotDeterministic("map keys out of order"),
+                    ))
+                }
+            }
+        }
+        let val = r.at_key(&key, |r| item(r, depth + 1))?;
+        map.insert(key.clone(), val);
+        prev = Some(key);
+    }
+    Ok(Value::Map(map))
+}
+
+// ---------------------------------------------------------------------------
+// Encode
+// ---------------------------------------------------------------------------
+
+/// Header for str / bin / array / map: the fix form when `fix` allows it
+/// (`fix = (base, max)`), else the 8/16/32-bit form starting at `wide`.
+fn write_len(out: &mut Vec<u8>, n: usize, fix: Option<(u8, usize)>, wide: [Option<u8>; 3]) {
+    match (fix, wide) {
+        (Some((base, max)), _) if n <= max => out.push(base | n as u8),
+        (_, [Some(tag), _, _]) if n <= 0xff => out.extend_from_slice(&[tag, n as u8]),
+        (_, [_, Some(tag), _]) if n <= 0xffff => {
+            out.push(tag);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        (_, [_, _, Some(tag)]) => {
+            out.push(tag);
+            out.extend_from_slice(&(n as u32).to_be_bytes());
+        }
+        _ => unreachable!("every header has a 32-bit form"),
+    }
+}
+
+fn write_int(out: &mut Vec<u8>, n: i64) {
+    match n {
+        0..=0x7f => out.push(n as u8),
+        -32..=-1 => out.push(n as i8 as u8),
+        0x80..=0xff => out.extend_from_slice(&[0xcc, n as u8]),
+        0x100..=0xffff => {
+            out.push(0xcd);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xce);
+            out.extend_from_slice(&(n as u32).to_be_bytes());
+        }
+        0x1_0000_0000.. => {
+            out.push(0xcf);
+            out.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+        -128..=-33 => out.extend_from_slice(&[0xd0, n as i8 as u8]),
+        -32768..=-129 => {
+            out.push(0xd1);
+            out.extend_from_slice(&(n as i16).to_be_bytes());
+        }
+        -2_147_483_648..=-32769 => {
+            out.push(0xd2);
+            out.extend_from_slice(&(n as i32).to_be_bytes());
+        }
+        _ => {
+            out.push(0xd3);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_len(
+        out,
+        s.len(),
+        Some((0xa0, 31)),
+        [Some(0xd9), Some(0xda), Some(0xdb)],
+    );
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_item(out: &mut Vec<u8>, v: &Value) {
+    match v {
+        Value::Null => out.push(0xc0),
+        Value::Bool(false) => out.push(0xc2),
+        Value::Bool(true) => out.push(0xc3),
+        Value::Int(n) => write_int(out, *n),
+        Value::String(s) => write_str(out, s),
+        Value::Bytes(b) => {
+            write_len(out, b.len(), None, [Some(0xc4), Some(0xc5), Some(0xc6)]);
+            out.extend_from_slice(b);
+        }
+        Value::Array(items) => {
+            write_len(
+                out,
+                items.len(),
+                Some((0x90, 15)),
+                [None, Some(0xdc), Some(0xdd)],
+            );
+            for it in items {
+                write_item(out, it);
+            }
+        }
+        Value::Map(m) => {
+            write_len(
+                out,
+                m.len(),
+                Some((0x80, 15)),
+                [None, Some(0xde), Some(0xdf)],
+            );
+            for (k, val) in m {
+                write_str(out, k);
+                write_item(out, val);
+            }
+        }
+    }
+}
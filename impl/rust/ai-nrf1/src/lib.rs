@@ -10,14 +10,32 @@
 pub use nrf_core::Error;
 pub use nrf_core::Result;
 pub use nrf_core::Value;
+pub use nrf_core::nrf;
 pub use nrf_core::MAGIC;
 
 pub use nrf_core::blake3_cid;
 pub use nrf_core::decode;
+pub use nrf_core::{decode_located, DecodeError};
+pub use nrf_core::{from_diag, to_diag, to_diag_pretty};
+pub use nrf_core::{diff, diff_bytes, Change, ChangeKind};
+pub use nrf_core::merkle;
+pub use nrf_core::query;
+pub use nrf_core::rho;
 pub use nrf_core::encode;
+pub use nrf_core::{encode_into, encoded_len, HashSink};
+pub use nrf_core::{encode_with_opts, try_encode, DecodeOpts};
 pub use nrf_core::encode_stream;
 pub use nrf_core::hash_bytes;
 pub use nrf_core::hash_value;
+pub use nrf_core::{NrfDecimal, RoundingMode};
+pub use nrf_core::serde;
 
+#[cfg(any(feature = "compat_cbor", feature = "compat_msgpack"))]
+pub mod compat;
+
+/// The old path of the CBOR bridge (`compat_cbor::cbor`), kept for one release.
 #[cfg(feature = "compat_cbor")]
-pub mod compat_cbor;
+#[deprecated(note = "use `ai_nrf1::compat::cbor`")]
+pub mod compat_cbor {
+    pub use crate::compat::cbor;
+}
@@ -1,7 +1,13 @@
 #![cfg(feature = "compat_cbor")]
-use ai_nrf1::{decode, encode, Value};
+use ai_nrf1::compat::cbor;
+use ai_nrf1::compat::CompatErrorKind as K;
+use ai_nrf1::{decode, encode, nrf, Error, Value};
 use std::collections::BTreeMap;
 
+fn kind(bytes: &[u8]) -> K {
+    cbor::from_slice(bytes).unwrap_err().kind
+}
+
 #[test]
 fn cbor_roundtrip_simple_map() {
     // {"name":"test","value":42}
@@ -11,8 +17,8 @@ fn cbor_roundtrip_simple_map() {
     let v = Value::Map(m);
 
     // to CBOR then back
-    let cbor = ai_nrf1::compat_cbor::cbor::to_vec(&v).unwrap();
-    let v2 = ai_nrf1::compat_cbor::cbor::from_slice(&cbor).unwrap();
+    let cbor = cbor::to_vec(&v).unwrap();
+    let v2 = cbor::from_slice(&cbor).unwrap();
     assert_eq!(v, v2);
 
     // Ensure NRF encoding still canonical
@@ -23,33 +29,188 @@ fn cbor_roundtrip_simple_map() {
 
 #[test]
 fn cbor_reject_non_text_key() {
-    // CBOR map with bytestring key should be rejected
-    // d: { h'01' : 0 } -> a1 41 01 00
-    let bad = vec![0xa1, 0x41, 0x01, 0x00];
-    let err = ai_nrf1::compat_cbor::cbor::from_slice(&bad).unwrap_err();
-    // must map to NonStringKey or InvalidTypeTag
-    // We can't pattern-match foreign error types here; just assert it is an error.
-    let _ = err;
+    // { h'01' : 0 } -> a1 41 01 00
+    let err = cbor::from_slice(&[0xa1, 0x41, 0x01, 0x00]).unwrap_err();
+    assert_eq!(err.kind, K::NonStringKey);
+    assert_eq!(err.offset, 1);
+    assert_eq!(kind(&[0xa1, 0x01, 0x00]), K::NonStringKey);
 }
 
 #[test]
 fn cbor_reject_float() {
     // 1.5 in CBOR = fb 3ff8000000000000
     let bad = vec![0xfb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
-    let err = ai_nrf1::compat_cbor::cbor::from_slice(&bad).unwrap_err();
-    let _ = err;
+    assert_eq!(kind(&bad), K::Float);
+    // half-precision 0.0, nested: {"a": [f9 0000]}
+    let err = cbor::from_slice(&[0xa1, 0x61, b'a', 0x81, 0xf9, 0x00, 0x00]).unwrap_err();
+    assert_eq!(
+        (err.kind, err.offset, err.path.as_str()),
+        (K::Float, 4, "a[0]")
+    );
 }
 
 #[test]
 fn cbor_text_must_be_nfc() {
-    // A text that is NFD should be rejected; using "e\u0301" (é decomposed).
-    // We'll construct CBOR: 0x61 'é[NFD]' as a map key
-    // For simplicity we serialize via ciborium
+    // A text that is NFD should be rejected; using "é" (é decomposed).
     use ciborium::value::Value as Cbor;
     let key = Cbor::Text("e\u{0301}".to_string()); // NFD
     let m = Cbor::Map(vec![(key, Cbor::Integer(0i64.into()))]);
     let mut buf = Vec::new();
     ciborium::ser::into_writer(&m, &mut buf).unwrap();
-    let err = ai_nrf1::compat_cbor::cbor::from_slice(&buf).unwrap_err();
-    let _ = err;
+    assert_eq!(kind(&buf), K::Canon(Error::NotNFC));
+    assert_eq!(kind(&[0x63, 0xef, 0xbb, 0xbf]), K::Canon(Error::BOMPresent));
+    assert_eq!(kind(&[0x62, 0xc3, 0x28]), K::Malformed("invalid UTF-8"));
+}
+
+#[test]
+fn cbor_rfc8949_vectors() {
+    // Appendix A examples that fall inside the NRF model.
+    let cases: &[(&[u8], Value)] = &[
+        (&[0x00], nrf!(0)),
+        (&[0x17], nrf!(23)),
+        (&[0x18, 0x18], nrf!(24)),
+        (&[0x19, 0x03, 0xe8], nrf!(1000)),
+        (&[0x1a, 0x00, 0x0f, 0x42, 0x40], nrf!(1_000_000)),
+        (
+            &[0x1b, 0x00, 0x00, 0x00, 0xe8, 0xd4, 0xa5, 0x10, 0x00],
+            nrf!(1_000_000_000_000i64),
+        ),
+        (&[0x20], nrf!(-1)),
+        (&[0x38, 0x63], nrf!(-100)),
+        (&[0x39, 0x03, 0xe7], nrf!(-1000)),
+        (&[0xf4], nrf!(false)),
+        (&[0xf5], nrf!(true)),
+        (&[0xf6], nrf!(null)),
+        (&[0x40], nrf!(bytes(vec![]))),
+        (
+            &[0x44, 0x01, 0x02, 0x03, 0x04],
+            nrf!(bytes(vec![1u8, 2, 3, 4])),
+        ),
+        (&[0x60], nrf!("")),
+        (&[0x64, 0x49, 0x45, 0x54, 0x46], nrf!("IETF")),
+        (&[0x62, 0xc3, 0xbc], nrf!("ü")),
+        (&[0x83, 0x01, 0x02, 0x03], nrf!([1, 2, 3])),
+        (&[0xa0], nrf!({})),
+        (
+            &[0xa2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x82, 0x02, 0x03],
+            nrf!({ "a": 1, "b": [2, 3] }),
+        ),
+    ];
+    for (bytes, want) in cases {
+        assert_eq!(&cbor::from_slice(bytes).unwrap(), want, "{bytes:02x?}");
+        assert_eq!(&cbor::to_vec(want).unwrap(), bytes, "{want:?}");
+    }
+}
+
+#[test]
+fn cbor_int64_edges_and_overflow() {
+    let max = [0x1b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    let min = [0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    assert_eq!(cbor::from_slice(&max).unwrap(), Value::Int(i64::MAX));
+    assert_eq!(cbor::from_slice(&min).unwrap(), Value::Int(i64::MIN));
+    assert_eq!(cbor::to_vec(&Value::Int(i64::MIN)).unwrap(), min);
+    // 2^63 and -2^63 - 1
+    assert_eq!(kind(&[0x1b, 0x80, 0, 0, 0, 0, 0, 0, 0]), K::IntegerOverflow);
+    assert_eq!(kind(&[0x3b, 0x80, 0, 0, 0, 0, 0, 0, 0]), K::IntegerOverflow);
+}
+
+#[test]
+fn cbor_rejects_non_deterministic_encodings() {
+    // 10 as a one-byte argument
+    assert_eq!(
+        kind(&[0x18, 0x0a]),
+        K::NotDeterministic("non-shortest argument")
+    );
+    // 255 as two bytes
+    assert_eq!(
+        kind(&[0x19, 0x00, 0xff]),
+        K::NotDeterministic("non-shortest argument")
+    );
+    // indefinite-length text and array
+    assert_eq!(
+        kind(&[0x7f, 0x61, 0x61, 0xff]),
+        K::NotDeterministic("indefinite length")
+    );
+    assert_eq!(
+        kind(&[0x9f, 0x01, 0xff]),
+        K::NotDeterministic("indefinite length")
+    );
+    // {"b": 1, "a": 2}: keys out of order
+    let err = cbor::from_slice(&[0xa2, 0x61, b'b', 0x01, 0x61, b'a', 0x02]).unwrap_err();
+    assert_eq!(err.kind, K::NotDeterministic("map keys out of order"));
+    assert_eq!(err.offset, 4);
+    // {"aa": 1, "b": 2}: NRF order, but CBOR sorts shorter keys first
+    assert_eq!(
+        kind(&[0xa2, 0x62, b'a', b'a', 0x01, 0x61, b'b', 0x02]),
+        K::NotDeterministic("map keys out of order")
+    );
+    assert_eq!(
+        kind(&[0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02]),
+        K::Canon(Error::DuplicateKey)
+    );
+}
+
+#[test]
+fn cbor_rejects_outside_the_model() {
+    // 1(0): epoch-time tag
+    assert_eq!(kind(&[0xc1, 0x00]), K::Tag(1));
+    assert_eq!(kind(&[0xd8, 0x20, 0x60]), K::Tag(32));
+    assert_eq!(kind(&[0xf7]), K::Simple(23)); // undefined
+    assert_eq!(kind(&[0xf8, 0xff]), K::Simple(255));
+    assert_eq!(
+        kind(&[0xf8, 0x10]),
+        K::Malformed("two-byte simple value below 32")
+    );
+    assert_eq!(
+        kind(&[0x1c]),
+        K::Malformed("reserved additional information")
+    );
+    assert_eq!(kind(&[0xff]), K::Malformed("unexpected break"));
+    assert_eq!(kind(&[0x82, 0x01]), K::UnexpectedEOF);
+    assert_eq!(kind(&[0x01, 0x02]), K::TrailingData);
+    assert_eq!(
+        kind(&[0x5a, 0xff, 0xff, 0xff, 0xff]),
+        K::Canon(Error::BytesTooLong)
+    );
+    let deep = [&[0x81u8; 65][..], &[0x00]].concat();
+    assert_eq!(kind(&deep), K::Canon(Error::DepthExceeded));
+    let err = cbor::from_slice(&[0x83, 0x01, 0x02, 0xc1, 0x00]).unwrap_err();
+    assert_eq!(err.to_string(), "Tag(1) at byte 3 ([2])");
+}
+
+#[test]
+fn cbor_output_matches_ciborium() {
+    use ciborium::value::Value as Cbor;
+    let v = nrf!({
+        "zz": -70000,
+        "a": [0, 23, 24, 255, 256, 65535, 65536, 4294967296i64, -24, -25, -257],
+        "bytes": bytes(vec![7u8; 300]),
+        "long": "x".repeat(24),
+        "n": null,
+        "t": true,
+    });
+    let ours = cbor::to_vec(&v).unwrap();
+    let theirs: Cbor = ciborium::de::from_reader(&ours[..]).unwrap();
+    let mut again = Vec::new();
+    ciborium::ser::into_writer(&theirs, &mut again).unwrap();
+    assert_eq!(ours, again);
+    // Keys come out shortest first: "a", "n", "t", "zz", "long", "bytes".
+    let Cbor::Map(entries) = theirs else { panic!() };
+    let keys: Vec<_> = entries.iter().map(|(k, _)| k.as_text().unwrap()).collect();
+    assert_eq!(keys, ["a", "n", "t", "zz", "long", "bytes"]);
+}
+
+#[test]
+fn cbor_rejects_non_canonical_values_on_output() {
+    assert_eq!(cbor::to_vec(&nrf!("e\u{301}")), Err(Error::NotNFC));
+}
+
+#[test]
+#[allow(deprecated)]
+fn old_compat_cbor_path_still_resolves() {
+    use ai_nrf1::compat_cbor::cbor as old;
+    let v = nrf!({"a": 1, "b": [true, null]});
+    let bytes = old::to_vec(&v).unwrap();
+    assert_eq!(bytes, cbor::to_vec(&v).unwrap());
+    assert_eq!(old::from_slice(&bytes).unwrap(), v);
 }
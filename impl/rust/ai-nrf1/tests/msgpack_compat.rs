@@ -0,0 +1,179 @@
+#![cfg(feature = "compat_msgpack")]
+use ai_nrf1::compat::msgpack;
+use ai_nrf1::compat::CompatErrorKind as K;
+use ai_nrf1::{nrf, DecodeOpts, Error, Value};
+
+fn kind(bytes: &[u8]) -> K {
+    msgpack::from_slice(bytes).unwrap_err().kind
+}
+
+fn roundtrip(v: &Value) {
+    let bytes = msgpack::to_vec(v).unwrap();
+    assert_eq!(&msgpack::from_slice(&bytes).unwrap(), v);
+}
+
+#[test]
+fn msgpack_roundtrip_all_kinds() {
+    roundtrip(&nrf!({
+        "name": "test",
+        "value": 42,
+        "neg": -1000,
+        "ok": true,
+        "nothing": null,
+        "blob": bytes(vec![0u8, 1, 2]),
+        "list": [1, "two", [3], {"four": 4}],
+        "ü": "é",
+    }));
+    roundtrip(&nrf!([]));
+    roundtrip(&nrf!({}));
+}
+
+#[test]
+fn msgpack_int_boundaries() {
+    let edges = [
+        0,
+        127,
+        128,
+        255,
+        256,
+        65535,
+        65536,
+        u32::MAX as i64,
+        u32::MAX as i64 + 1,
+        i64::MAX,
+        -1,
+        -32,
+        -33,
+        -128,
+        -129,
+        -32768,
+        -32769,
+        i32::MIN as i64,
+        i32::MIN as i64 - 1,
+        i64::MIN,
+    ];
+    for n in edges {
+        let ours = msgpack::to_vec(&Value::Int(n)).unwrap();
+        let mut theirs = Vec::new();
+        rmp::encode::write_sint(&mut theirs, n).unwrap();
+        assert_eq!(ours, theirs, "{n}");
+        assert_eq!(msgpack::from_slice(&ours).unwrap(), Value::Int(n));
+    }
+    // 2^63
+    let big = [0xcf, 0x80, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(kind(&big), K::IntegerOverflow);
+}
+
+#[test]
+fn msgpack_lengths_match_rmp() {
+    for n in [0usize, 15, 16, 31, 32, 255, 256, 65535, 65536] {
+        let s = "x".repeat(n);
+        let mut theirs = Vec::new();
+        rmp::encode::write_str(&mut theirs, &s).unwrap();
+        assert_eq!(
+            msgpack::to_vec(&Value::String(s)).unwrap(),
+            theirs,
+            "str {n}"
+        );
+
+        let b = vec![7u8; n];
+        let mut theirs = Vec::new();
+        rmp::encode::write_bin(&mut theirs, &b).unwrap();
+        assert_eq!(
+            msgpack::to_vec(&Value::Bytes(b)).unwrap(),
+            theirs,
+            "bin {n}"
+        );
+
+        let a = vec![Value::Null; n];
+        let mut theirs = Vec::new();
+        rmp::encode::write_array_len(&mut theirs, n as u32).unwrap();
+        theirs.extend(std::iter::repeat_n(0xc0, n));
+        let ours = msgpack::to_vec(&Value::Array(a.clone())).unwrap();
+        assert_eq!(ours, theirs, "array {n}");
+        assert_eq!(msgpack::from_slice(&ours).unwrap(), Value::Array(a));
+    }
+}
+
+#[test]
+fn msgpack_map_keys_in_nrf_order() {
+    // NRF order is raw bytes: "aa" before "b" (unlike deterministic CBOR).
+    let ours = msgpack::to_vec(&nrf!({"b": 2, "aa": 1})).unwrap();
+    let mut theirs = Vec::new();
+    rmp::encode::write_map_len(&mut theirs, 2).unwrap();
+    rmp::encode::write_str(&mut theirs, "aa").unwrap();
+    rmp::encode::write_sint(&mut theirs, 1).unwrap();
+    rmp::encode::write_str(&mut theirs, "b").unwrap();
+    rmp::encode::write_sint(&mut theirs, 2).unwrap();
+    assert_eq!(ours, theirs);
+
+    let err = msgpack::from_slice(&[0x82, 0xa1, b'b', 0x02, 0xa2, b'a', b'a', 0x01]).unwrap_err();
+    assert_eq!(err.kind, K::NotDeterministic("map keys out of order"));
+    assert_eq!(err.offset, 4);
+    assert_eq!(
+        kind(&[0x82, 0xa1, b'a', 0x01, 0xa1, b'a', 0x02]),
+        K::Canon(Error::DuplicateKey)
+    );
+}
+
+#[test]
+fn msgpack_rejects_non_shortest_forms() {
+    let cases: &[&[u8]] = &[
+        &[0xcc, 0x05],             // uint8 5
+        &[0xcd, 0x00, 0xff],       // uint16 255
+        &[0xd0, 0xff],             // int8 -1
+        &[0xd0, 0x05],             // int8 5 (non-negative in a signed form)
+        &[0xd1, 0xff, 0x80],       // int16 -128
+        &[0xd9, 0x01, b'a'],       // str8 of length 1
+        &[0xc5, 0x00, 0x01, 0x00], // bin16 of length 1
+        &[0xdc, 0x00, 0x00],       // array16 of length 0
+        &[0xde, 0x00, 0x00],       // map16 of length 0
+    ];
+    for bytes in cases {
+        assert_eq!(
+            kind(bytes),
+            K::NotDeterministic("non-shortest form"),
+            "{bytes:02x?}"
+        );
+    }
+}
+
+#[test]
+fn msgpack_rejects_outside_the_model() {
+    assert_eq!(kind(&[0xca, 0, 0, 0, 0]), K::Float);
+    assert_eq!(kind(&[0xcb, 0, 0, 0, 0, 0, 0, 0, 0]), K::Float);
+    // fixext 4, type -1: timestamp
+    assert_eq!(kind(&[0xd6, 0xff, 0, 0, 0, 0]), K::Ext(-1));
+    assert_eq!(kind(&[0xc7, 0x01, 0x05, 0x00]), K::Ext(5));
+    assert_eq!(kind(&[0x81, 0x01, 0x02]), K::NonStringKey);
+    assert_eq!(kind(&[0x81, 0xc4, 0x01, 0x00, 0x02]), K::NonStringKey);
+    assert_eq!(kind(&[0xc1]), K::Malformed("reserved byte 0xc1"));
+    assert_eq!(kind(&[0xa3, b'e', 0xcc, 0x81]), K::Canon(Error::NotNFC));
+    assert_eq!(kind(&[0xa3, 0xef, 0xbb, 0xbf]), K::Canon(Error::BOMPresent));
+    assert_eq!(kind(&[0x92, 0x01]), K::UnexpectedEOF);
+    assert_eq!(kind(&[0x01, 0x02]), K::TrailingData);
+
+    // {"a": [1, 1.0]}
+    let err = msgpack::from_slice(&[0x81, 0xa1, b'a', 0x92, 0x01, 0xca, 0, 0, 0, 0]).unwrap_err();
+    assert_eq!(err.to_string(), "Float at byte 5 (a[1])");
+}
+
+#[test]
+fn msgpack_respects_decode_opts() {
+    let opts = DecodeOpts {
+        max_depth: 2,
+        ..DecodeOpts::default()
+    };
+    assert!(msgpack::from_slice_with_opts(&[0x91, 0x91, 0x01], &opts).is_ok());
+    assert_eq!(
+        msgpack::from_slice_with_opts(&[0x91, 0x91, 0x91, 0x01], &opts)
+            .unwrap_err()
+            .kind,
+        K::Canon(Error::DepthExceeded)
+    );
+    // A hostile array32 header allocates nothing.
+    assert_eq!(
+        kind(&[0xdd, 0x7f, 0xff, 0xff, 0xff]),
+        K::Canon(Error::ArrayTooLong)
+    );
+}
@@ -66,12 +66,15 @@ fn test_varint_overflow() {
     let data = vec![0x6E, 0x72, 0x66, 0x31, 0x05, 0xFF, 0xFF, 0xFF, 0xFF, 0x1F];
     assert!(decode(&data).is_err());
 
-    // True non-minimal: leading 0x80 (zero with continuation bit)
-    let non_minimal = vec![0x6E, 0x72, 0x66, 0x31, 0x05, 0x80, 0x01];
+    // True non-minimal: 0 as 0x80 0x00 (a trailing zero group)
+    let non_minimal = vec![0x6E, 0x72, 0x66, 0x31, 0x05, 0x80, 0x00];
     assert!(matches!(
         decode(&non_minimal),
         Err(ai_nrf1::Error::NonMinimalVarint)
     ));
+    // 0x80 0x01 is the minimal form of 128, not a non-minimal 0.
+    let len128 = [&[0x6E, 0x72, 0x66, 0x31, 0x05, 0x80, 0x01][..], &[0xAA; 128]].concat();
+    assert_eq!(decode(&len128).unwrap(), Value::Bytes(vec![0xAA; 128]));
 }
 
 #[test]
@@ -0,0 +1,286 @@
+// ---------------------------------------------------------------------------
+// Value ergonomics — typed accessors, conversions, indexing
+//
+// `as_*` return None on a kind mismatch; `get` / `get_mut` take a query path
+// ("a.b.0", "/a/b", "$.a[0]") and return None when it points at nothing.
+// `v["key"]` / `v[0]` mirror serde_json: a missing key, an out-of-range
+// index or a wrong kind gives `&Value::Null` instead of panicking.
+//
+// `From` covers the NRF scalar kinds only: integers that fit in i64, bool,
+// strings, `Vec<Value>` and maps. There is deliberately no `From<f64>` (NRF
+// has no floats), no `From<Vec<u8>>` (use `Value::Bytes` or `bytes(..)` in
+// `nrf!`, so a byte string is never mistaken for an array) and no
+// `From<Option<T>>` (absence ≠ null: leave optional fields out instead).
+// ---------------------------------------------------------------------------
+
+use crate::query::Query;
+use crate::Value;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::{Index, IndexMut};
+
+static NULL: Value = Value::Null;
+
+impl Value {
+    /// Kind name as used in error messages: "null", "bool", "int", ...
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::String(_) => "string",
+            Value::Bytes(_) => "bytes",
+            Value::Array(_) => "array",
+            Value::Map(_) => "map",
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    pub fn as_map_mut(&mut self) -> Option<&mut BTreeMap<String, Value>> {
+        match self {
+            Value::Map(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// The value at a singular query path. For errors (bad syntax, which
+    /// segment was missing) use `query::get`.
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        Query::parse(path).ok()?.get(self).ok()
+    }
+
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut Value> {
+        Query::parse(path).ok()?.get_mut(self).ok()
+    }
+}
+
+impl Index<&str> for Value {
+    type Output = Value;
+
+    /// Map lookup by key (not a path); `Null` if absent.
+    fn index(&self, key: &str) -> &Value {
+        match self {
+            Value::Map(m) => m.get(key).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+impl Index<usize> for Value {
+    type Output = Value;
+
+    /// Array element; `Null` if out of range.
+    fn index(&self, i: usize) -> &Value {
+        match self {
+            Value::Array(items) => items.get(i).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+impl IndexMut<&str> for Value {
+    /// Map entry, inserted as `Null` if absent. A `Null` value becomes an
+    /// empty map first; any other kind panics.
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        if self.is_null() {
+            *self = Value::Map(BTreeMap::new());
+        }
+        match self {
+            Value::Map(m) => m.entry(key.to_string()).or_insert(Value::Null),
+            other => panic!("cannot index {} with \"{key}\"", other.type_name()),
+        }
+    }
+}
+
+impl IndexMut<usize> for Value {
+    /// Array element; panics if out of range or not an array.
+    fn index_mut(&mut self, i: usize) -> &mut Value {
+        match self {
+            Value::Array(items) => {
+                let len = items.len();
+                items
+                    .get_mut(i)
+                    .unwrap_or_else(|| panic!("index {i} out of range for array of {len}"))
+            }
+            other => panic!("cannot index {} with [{i}]", other.type_name()),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Conversions
+// ---------------------------------------------------------------------------
+
+macro_rules! from_int {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Value {
+            fn from(n: $t) -> Value {
+                Value::Int(i64::from(n))
+            }
+        }
+    )*};
+}
+
+from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+impl From<&String> for Value {
+    fn from(s: &String) -> Value {
+        Value::String(s.clone())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(items: Vec<Value>) -> Value {
+        Value::Array(items)
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(m: BTreeMap<String, Value>) -> Value {
+        Value::Map(m)
+    }
+}
+
+impl FromIterator<Value> for Value {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Value {
+        Value::Array(iter.into_iter().collect())
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Value {
+        Value::Map(iter.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+    use alloc::{format, vec};
+
+    #[test]
+    fn macro_builds_every_kind() {
+        let blob = vec![0u8, 0xff];
+        let name = "alice";
+        let n: i64 = -7;
+        let v = nrf!({
+            "z": null,
+            "b": true,
+            "n": n,
+            "neg": -1,
+            "s": name,
+            "owned": format!("{name}!"),
+            "bytes": bytes(blob.clone()),
+            "arr": [1, "two", [3], {}, bytes(&b"x"[..])],
+            "nested": { "k": { "deep": 42 }, },
+            (name): "computed key",
+        });
+        let want = crate::from_diag(
+            r#"{"alice": "computed key", "arr": [1, "two", [3], {}, h'78'], "b": true, "bytes": h'00ff', "n": -7, "neg": -1, "nested": {"k": {"deep": 42}}, "owned": "alice!", "s": "alice", "z": null}"#,
+        )
+        .unwrap();
+        assert_eq!(v, want);
+        assert_eq!(nrf!([]), Value::Array(vec![]));
+        assert_eq!(nrf!(null), Value::Null);
+        assert_eq!(nrf!(3 + 4), Value::Int(7));
+    }
+
+    #[test]
+    fn accessors_and_index() {
+        let mut v = nrf!({ "a": { "b": [10, "x"] }, "blob": bytes(vec![1u8]) });
+        assert_eq!(v.get("a.b.0").and_then(Value::as_int), Some(10));
+        assert_eq!(v.get("$.a.b[1]").and_then(Value::as_str), Some("x"));
+        assert_eq!(v.get("/a/b/1"), Some(&Value::from("x")));
+        assert!(v.get("a.missing").is_none());
+        assert!(v.get("$[").is_none());
+        assert_eq!(v["a"]["b"][0].as_int(), Some(10));
+        assert!(v["a"]["nope"][3].is_null());
+        assert_eq!(v["blob"].as_bytes(), Some(&[1u8][..]));
+        assert_eq!(v["a"].type_name(), "map");
+        assert!(v["a"].as_str().is_none());
+
+        v["a"]["c"] = nrf!("new");
+        v["a"]["b"][0] = nrf!(11);
+        *v.get_mut("a.b.1").unwrap() = Value::Int(12);
+        assert_eq!(
+            v,
+            nrf!({ "a": { "b": [11, 12], "c": "new" }, "blob": bytes(vec![1u8]) })
+        );
+
+        let arr: Value = (1..=3).map(Value::from).collect();
+        assert_eq!(arr, nrf!([1, 2, 3]));
+        let map: Value = [("k", Value::from(true))].into_iter().collect();
+        assert_eq!(map, nrf!({ "k": true }));
+    }
+}
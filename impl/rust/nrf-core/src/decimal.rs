@@ -0,0 +1,550 @@
+// ---------------------------------------------------------------------------
+// NrfDecimal — exact decimal numbers for NRF (docs/base/DECIMALS-AND-NUMBERS.md)
+//
+// NRF has no floats: a decimal travels as a String in ρ-canonical form
+// ("19.9", "-0.15", "100"). NrfDecimal is the in-memory side of that string:
+//
+//   value = mantissa × 10^-scale     mantissa: i128 (38 significant digits)
+//                                    scale:    any u32
+//
+// Values are kept normalized (no trailing fractional zeros, no -0), so
+// `==` is numeric equality and `to_string()` is exactly the canonical
+// string `rho::normalize_decimal` would produce.
+//
+// Arithmetic is exact: + - × never round (overflow panics like integer
+// arithmetic; the checked_* forms return None). Division and `round` take
+// an explicit scale and rounding mode.
+// ---------------------------------------------------------------------------
+
+use crate::rho::RhoError;
+use crate::Value;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use core::str::FromStr;
+
+/// Exact decimal; see the module header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct NrfDecimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+/// How `div` and `round` resolve a discarded remainder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    /// Ties away from zero (2.5 → 3, -2.5 → -3).
+    #[default]
+    HalfUp,
+    /// Ties to even (2.5 → 2, 3.5 → 4).
+    Bankers,
+}
+
+impl FromStr for RoundingMode {
+    type Err = RhoError;
+
+    fn from_str(s: &str) -> Result<Self, RhoError> {
+        match s {
+            "half_up" => Ok(RoundingMode::HalfUp),
+            "bankers" => Ok(RoundingMode::Bankers),
+            other => Err(RhoError::InvalidDecimal(format!(
+                "unknown rounding mode '{other}' (half_up | bankers)"
+            ))),
+        }
+    }
+}
+
+fn pow10(n: u32) -> Option<i128> {
+    10i128.checked_pow(n)
+}
+
+impl NrfDecimal {
+    pub const ZERO: NrfDecimal = NrfDecimal {
+        mantissa: 0,
+        scale: 0,
+    };
+    pub const ONE: NrfDecimal = NrfDecimal {
+        mantissa: 1,
+        scale: 0,
+    };
+
+    /// `mantissa × 10^-scale`, normalized.
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        let (mut mantissa, mut scale) = (mantissa, scale);
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        if mantissa == 0 {
+            scale = 0;
+        }
+        NrfDecimal { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// Fractional digits of the normalized value.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    pub fn abs(&self) -> Self {
+        NrfDecimal {
+            mantissa: self.mantissa.abs(),
+            scale: self.scale,
+        }
+    }
+
+    /// Parse ρ decimal syntax (`^-?(0|[1-9][0-9]*)(\.[0-9]+)?$`); trailing
+    /// zeros and `-0` are accepted and normalized away.
+    pub fn parse(s: &str) -> Result<Self, RhoError> {
+        let bad = || RhoError::InvalidDecimal(s.to_string());
+        let (negative, body) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (int, frac) = match body.split_once('.') {
+            Some((i, f)) => (i, Some(f)),
+            None => (body, None),
+        };
+        let digits = |t: &str| !t.is_empty() && t.bytes().all(|b| b.is_ascii_digit());
+        if !digits(int) || (int.len() > 1 && int.starts_with('0')) {
+            return Err(bad());
+        }
+        if let Some(f) = frac {
+            if !digits(f) {
+                return Err(bad());
+            }
+        }
+        let frac = frac.unwrap_or("").trim_end_matches('0');
+        let mut mantissa: i128 = 0;
+        for b in int.bytes().chain(frac.bytes()) {
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(i128::from(b - b'0')))
+                .ok_or_else(|| {
+                    RhoError::InvalidDecimal(format!("{s}: more than 38 significant digits"))
+                })?;
+        }
+        let scale = u32::try_from(frac.len()).map_err(|_| bad())?;
+        Ok(NrfDecimal::new(
+            if negative { -mantissa } else { mantissa },
+            scale,
+        ))
+    }
+
+    /// Both operands at the larger scale; None if that overflows.
+    fn aligned(&self, rhs: &Self) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(rhs.scale);
+        let a = self.mantissa.checked_mul(pow10(scale - self.scale)?)?;
+        let b = rhs.mantissa.checked_mul(pow10(scale - rhs.scale)?)?;
+        Some((a, b, scale))
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        let (a, b, scale) = self.aligned(rhs)?;
+        Some(NrfDecimal::new(a.checked_add(b)?, scale))
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        let (a, b, scale) = self.aligned(rhs)?;
+        Some(NrfDecimal::new(a.checked_sub(b)?, scale))
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        let mantissa = self.mantissa.checked_mul(rhs.mantissa)?;
+        Some(NrfDecimal::new(
+            mantissa,
+            self.scale.checked_add(rhs.scale)?,
+        ))
+    }
+
+    /// `self / rhs` rounded to `scale` fractional digits. None on division
+    /// by zero or overflow.
+    pub fn div(&self, rhs: &Self, scale: u32, mode: RoundingMode) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        // self/rhs = (m1 / m2) × 10^(s2 - s1); we want q × 10^-scale.
+        let e = i64::from(scale) + i64::from(rhs.scale) - i64::from(self.scale);
+        let (num, den) = if e >= 0 {
+            (
+                self.mantissa.checked_mul(pow10(u32::try_from(e).ok()?)?)?,
+                rhs.mantissa,
+            )
+        } else {
+            (
+                self.mantissa,
+                rhs.mantissa.checked_mul(pow10(u32::try_from(-e).ok()?)?)?,
+            )
+        };
+        Some(NrfDecimal::new(div_round(num, den, mode)?, scale))
+    }
+
+    /// Round to at most `scale` fractional digits.
+    pub fn round(&self, scale: u32, mode: RoundingMode) -> Self {
+        if self.scale <= scale {
+            return *self;
+        }
+        match pow10(self.scale - scale) {
+            Some(d) => NrfDecimal::new(
+                div_round(self.mantissa, d, mode).expect("d > 0, no overflow"),
+                scale,
+            ),
+            // |mantissa| < 10^39 / 2: far below half a unit of the target scale.
+            None => NrfDecimal::ZERO,
+        }
+    }
+
+    /// The canonical NRF representation: `Value::String`.
+    pub fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+
+    /// (integer digits, fractional digits) of |self|, for display and ordering.
+    fn digits(&self) -> (String, String) {
+        let abs = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            (abs, String::new())
+        } else if abs.len() > scale {
+            let (i, f) = abs.split_at(abs.len() - scale);
+            (i.to_string(), f.to_string())
+        } else {
+            ("0".to_string(), "0".repeat(scale - abs.len()) + &abs)
+        }
+    }
+}
+
+/// n / d with the remainder resolved by `mode`. None only on overflow.
+fn div_round(n: i128, d: i128, mode: RoundingMode) -> Option<i128> {
+    let q = n.checked_div(d)?;
+    let r = n.checked_rem(d)?;
+    if r == 0 {
+        return Some(q);
+    }
+    let negative = (n < 0) != (d < 0);
+    let (r, d) = (r.unsigned_abs(), d.unsigned_abs());
+    let away = match r.cmp(&(d - r)) {
+        Ordering::Less => false,
+        Ordering::Greater => true,
+        Ordering::Equal => match mode {
+            RoundingMode::HalfUp => true,
+            RoundingMode::Bankers => q % 2 != 0,
+        },
+    };
+    match (away, negative) {
+        (false, _) => Some(q),
+        (true, true) => q.checked_sub(1),
+        (true, false) => q.checked_add(1),
+    }
+}
+
+impl PartialOrd for NrfDecimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NrfDecimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |d: &Self| d.mantissa.signum();
+        match sign(self).cmp(&sign(other)) {
+            Ordering::Equal => {}
+            o => return o,
+        }
+        let (ai, af) = self.digits();
+        let (bi, bf) = other.digits();
+        // Normalized: no leading zeros in the integer part, no trailing
+        // zeros in the fraction, so digit strings compare numerically.
+        let mag = ai
+            .len()
+            .cmp(&bi.len())
+            .then_with(|| ai.cmp(&bi))
+            .then_with(|| af.cmp(&bf));
+        if self.is_negative() {
+            mag.reverse()
+        } else {
+            mag
+        }
+    }
+}
+
+impl fmt::Display for NrfDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (int, frac) = self.digits();
+        if self.is_negative() {
+            f.write_str("-")?;
+        }
+        f.write_str(&int)?;
+        if !frac.is_empty() {
+            write!(f, ".{frac}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NrfDecimal {
+    type Err = RhoError;
+
+    fn from_str(s: &str) -> Result<Self, RhoError> {
+        NrfDecimal::parse(s)
+    }
+}
+
+impl From<i64> for NrfDecimal {
+    fn from(n: i64) -> Self {
+        NrfDecimal::new(i128::from(n), 0)
+    }
+}
+
+impl From<NrfDecimal> for Value {
+    fn from(d: NrfDecimal) -> Value {
+        d.to_value()
+    }
+}
+
+/// Accepts decimal strings and Ints — the two NRF spellings of a number.
+impl TryFrom<&Value> for NrfDecimal {
+    type Error = RhoError;
+
+    fn try_from(v: &Value) -> Result<Self, RhoError> {
+        match v {
+            Value::String(s) => NrfDecimal::parse(s),
+            Value::Int(n) => Ok(NrfDecimal::from(*n)),
+            _ => Err(RhoError::InvalidDecimal(
+                "expected a decimal string or Int".into(),
+            )),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Operators (panic on overflow, like integer arithmetic in debug builds)
+// ---------------------------------------------------------------------------
+
+impl Add for NrfDecimal {
+    type Output = NrfDecimal;
+    fn add(self, rhs: NrfDecimal) -> NrfDecimal {
+        self.checked_add(&rhs).expect("NrfDecimal overflow in add")
+    }
+}
+
+impl Sub for NrfDecimal {
+    type Output = NrfDecimal;
+    fn sub(self, rhs: NrfDecimal) -> NrfDecimal {
+        self.checked_sub(&rhs).expect("NrfDecimal overflow in sub")
+    }
+}
+
+impl Mul for NrfDecimal {
+    type Output = NrfDecimal;
+    fn mul(self, rhs: NrfDecimal) -> NrfDecimal {
+        self.checked_mul(&rhs).expect("NrfDecimal overflow in mul")
+    }
+}
+
+impl Neg for NrfDecimal {
+    type Output = NrfDecimal;
+    fn neg(self) -> NrfDecimal {
+        NrfDecimal::new(-self.mantissa, self.scale)
+    }
+}
+
+impl AddAssign for NrfDecimal {
+    fn add_assign(&mut self, rhs: NrfDecimal) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for NrfDecimal {
+    fn sub_assign(&mut self, rhs: NrfDecimal) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for NrfDecimal {
+    fn mul_assign(&mut self, rhs: NrfDecimal) {
+        *self = *self * rhs;
+    }
+}
+
+impl core::iter::Sum for NrfDecimal {
+    fn sum<I: Iterator<Item = NrfDecimal>>(iter: I) -> NrfDecimal {
+        iter.fold(NrfDecimal::ZERO, Add::add)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// serde: always serialized as the canonical string. Deserialization also
+// takes integers, and floats from config formats (YAML `19.90`) via their
+// shortest round-trip spelling — never via binary arithmetic.
+// ---------------------------------------------------------------------------
+
+impl serde::Serialize for NrfDecimal {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for NrfDecimal {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = NrfDecimal;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a decimal string or number")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<NrfDecimal, E> {
+                NrfDecimal::parse(s).map_err(E::custom)
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, n: i64) -> Result<NrfDecimal, E> {
+                Ok(NrfDecimal::from(n))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, n: u64) -> Result<NrfDecimal, E> {
+                Ok(NrfDecimal::new(i128::from(n), 0))
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, f: f64) -> Result<NrfDecimal, E> {
+                if !f.is_finite() {
+                    return Err(E::custom("Float: NaN/Infinity is not a decimal"));
+                }
+                // `{}` on f64 never uses an exponent and is the shortest
+                // string that round-trips: 19.9 stays "19.9".
+                NrfDecimal::parse(&format!("{f}")).map_err(E::custom)
+            }
+        }
+
+        d.deserialize_any(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn d(s: &str) -> NrfDecimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_display_are_canonical() {
+        for (input, canon) in [
+            ("0", "0"),
+            ("-0", "0"),
+            ("-0.000", "0"),
+            ("1.50", "1.5"),
+            ("1.0", "1"),
+            ("19.90", "19.9"),
+            ("-3.14", "-3.14"),
+            ("0.0001", "0.0001"),
+            ("100", "100"),
+        ] {
+            let v = d(input);
+            assert_eq!(v.to_string(), canon, "{input}");
+            assert_eq!(
+                crate::rho::normalize_decimal(input).unwrap(),
+                canon,
+                "{input}"
+            );
+        }
+        for bad in ["", "-", "01.5", "1e2", "1.", ".5", "+1", "1,5", "0x10"] {
+            assert!(NrfDecimal::parse(bad).is_err(), "{bad}");
+        }
+        assert!(NrfDecimal::parse(&"9".repeat(39)).is_err());
+        assert_eq!(d("1.10"), d("1.1"));
+    }
+
+    #[test]
+    fn exact_arithmetic() {
+        assert_eq!(d("0.1") + d("0.2"), d("0.3"));
+        assert_eq!(d("19.90") * d("3"), d("59.7"));
+        assert_eq!(d("1") - d("1.01"), d("-0.01"));
+        assert_eq!(-d("2.5"), d("-2.5"));
+        assert_eq!(
+            [d("1.1"), d("2.2")].into_iter().sum::<NrfDecimal>(),
+            d("3.3")
+        );
+        let big = NrfDecimal::new(i128::MAX, 0);
+        assert!(big.checked_add(&NrfDecimal::ONE).is_none());
+        assert!(NrfDecimal::new(1, 100).checked_add(&big).is_none());
+    }
+
+    #[test]
+    fn rounding_modes() {
+        use RoundingMode::*;
+        let cases = [
+            ("2.5", 0, HalfUp, "3"),
+            ("2.5", 0, Bankers, "2"),
+            ("3.5", 0, Bankers, "4"),
+            ("-2.5", 0, HalfUp, "-3"),
+            ("-2.5", 0, Bankers, "-2"),
+            ("-0.4", 0, HalfUp, "0"),
+            ("-0.6", 0, HalfUp, "-1"),
+            ("4.704", 2, HalfUp, "4.7"),
+            ("1.005", 2, HalfUp, "1.01"),
+            ("1.005", 2, Bankers, "1"),
+            ("1.2", 5, HalfUp, "1.2"),
+        ];
+        for (v, scale, mode, want) in cases {
+            assert_eq!(d(v).round(scale, mode), d(want), "{v} {scale} {mode:?}");
+        }
+        assert_eq!(NrfDecimal::new(5, 60).round(2, HalfUp), NrfDecimal::ZERO);
+    }
+
+    #[test]
+    fn division() {
+        use RoundingMode::*;
+        assert_eq!(d("1").div(&d("3"), 4, HalfUp), Some(d("0.3333")));
+        assert_eq!(d("2").div(&d("3"), 2, HalfUp), Some(d("0.67")));
+        assert_eq!(d("-2").div(&d("3"), 2, HalfUp), Some(d("-0.67")));
+        assert_eq!(d("20").div(&d("100"), 2, HalfUp), Some(d("0.2")));
+        assert_eq!(d("0.125").div(&d("1"), 2, Bankers), Some(d("0.12")));
+        assert_eq!(d("-0.001").div(&d("1"), 2, HalfUp), Some(d("0")));
+        assert_eq!(d("1").div(&d("0"), 2, HalfUp), None);
+    }
+
+    #[test]
+    fn ordering_is_numeric() {
+        let mut v = [d("10"), d("-1.5"), d("9.99"), d("0"), d("-10"), d("0.001")];
+        v.sort();
+        let s: Vec<String> = v.iter().map(|x| x.to_string()).collect();
+        assert_eq!(s, ["-10", "-1.5", "0", "0.001", "9.99", "10"]);
+        assert!(NrfDecimal::new(1, 60) < NrfDecimal::new(1, 59));
+    }
+
+    #[test]
+    fn value_and_serde_boundaries() {
+        assert_eq!(d("1.50").to_value(), Value::String("1.5".into()));
+        assert_eq!(NrfDecimal::try_from(&Value::Int(7)).unwrap(), d("7"));
+        assert!(NrfDecimal::try_from(&Value::Bool(true)).is_err());
+
+        let v: NrfDecimal = serde_json::from_str("19.90").unwrap();
+        assert_eq!(v, d("19.9"));
+        let v: NrfDecimal = serde_json::from_str("\"0.10\"").unwrap();
+        assert_eq!(serde_json::to_string(&v).unwrap(), "\"0.1\"");
+        let v: NrfDecimal = serde_json::from_str("42").unwrap();
+        assert_eq!(v, d("42"));
+        let m: RoundingMode = serde_json::from_str("\"bankers\"").unwrap();
+        assert_eq!(m, RoundingMode::Bankers);
+    }
+}
@@ -0,0 +1,522 @@
+// ---------------------------------------------------------------------------
+// Diagnostic notation — human-readable NRF text (in the spirit of CBOR diag)
+//
+//   {"a": 1, "b": h'00ff', "c": [null, true]}
+//
+//   null / true / false   Null, Bool
+//   -12                   Int (decimal; no leading zeros, no "-0")
+//   "text"                String (JSON escapes; must be NFC, no BOM)
+//   h'00ff'               Bytes (lowercase hex, even length)
+//   [a, b]   {"k": v}     Array, Map (keys byte-sorted and unique)
+//
+// The printer emits one spelling per value. The parser is strict: whatever
+// it accepts encodes to canonical bytes, and whatever would not (floats,
+// unsorted or duplicate keys, non-NFC text, uppercase hex) is rejected with
+// the text offset and logical path of the offending token. Whitespace
+// between tokens is free, so `to_diag_pretty` output parses as well.
+// ---------------------------------------------------------------------------
+
+use crate::{push_path_index, push_path_key, DecodeError, DecodeOpts, Error, Value};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// Single-line diagnostic notation.
+pub fn to_diag(v: &Value) -> String {
+    let mut out = String::new();
+    write_compact(&mut out, v);
+    out
+}
+
+/// Multi-line diagnostic notation, two-space indent.
+pub fn to_diag_pretty(v: &Value) -> String {
+    let mut out = String::new();
+    write_pretty(&mut out, v, 0);
+    out
+}
+
+/// Parse diagnostic notation into a `Value`. Errors carry the byte offset
+/// into `text` and the logical path of the rejected token.
+pub fn from_diag(text: &str) -> core::result::Result<Value, DecodeError> {
+    let mut p = Parser {
+        text,
+        pos: 0,
+        path: String::new(),
+        max_depth: DecodeOpts::default().max_depth,
+    };
+    let v = p.value(0)?;
+    p.ws();
+    if p.pos != text.len() {
+        return Err(p.err(Error::TrailingData, p.pos));
+    }
+    Ok(v)
+}
+
+/// Parse diagnostic notation straight to canonical NRF bytes.
+pub fn diag_to_bytes(text: &str) -> core::result::Result<Vec<u8>, DecodeError> {
+    Ok(crate::encode(&from_diag(text)?))
+}
+
+// ---------------------------------------------------------------------------
+// Printer
+// ---------------------------------------------------------------------------
+
+fn write_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 || c == '\u{FEFF}' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_compact(out: &mut String, v: &Value) {
+    match v {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Int(n) => {
+            let _ = write!(out, "{n}");
+        }
+        Value::String(s) => write_str(out, s),
+        Value::Bytes(b) => {
+            out.push_str("h'");
+            out.push_str(&crate::encode_hex_lower(b));
+            out.push('\'');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, it) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_compact(out, it);
+            }
+            out.push(']');
+        }
+        Value::Map(m) => {
+            out.push('{');
+            for (i, (k, val)) in m.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_str(out, k);
+                out.push_str(": ");
+                write_compact(out, val);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn newline(out: &mut String, indent: usize) {
+    out.push('\n');
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_pretty(out: &mut String, v: &Value, indent: usize) {
+    match v {
+        Value::Array(items) if !items.is_empty() => {
+            out.push('[');
+            for (i, it) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, indent + 1);
+                write_pretty(out, it, indent + 1);
+            }
+            newline(out, indent);
+            out.push(']');
+        }
+        Value::Map(m) if !m.is_empty() => {
+            out.push('{');
+            for (i, (k, val)) in m.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, indent + 1);
+                write_str(out, k);
+                out.push_str(": ");
+                write_pretty(out, val, indent + 1);
+            }
+            newline(out, indent);
+            out.push('}');
+        }
+        _ => write_compact(out, v),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Strict parser
+// ---------------------------------------------------------------------------
+
+type PResult<T> = core::result::Result<T, DecodeError>;
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    path: String,
+    max_depth: usize,
+}
+
+impl Parser<'_> {
+    fn err(&self, kind: Error, offset: usize) -> DecodeError {
+        DecodeError {
+            kind,
+            offset,
+            path: self.path.clone(),
+        }
+    }
+
+    fn syntax(&self, msg: &str) -> DecodeError {
+        self.err(Error::Diag(msg.to_string()), self.pos)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        let rest = &self.text.as_bytes()[self.pos..];
+        if rest.starts_with(word.as_bytes())
+            && !rest
+                .get(word.len())
+                .is_some_and(|c| c.is_ascii_alphanumeric())
+        {
+            self.pos += word.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> PResult<Value> {
+        self.ws();
+        if depth > self.max_depth {
+            return Err(self.err(Error::DepthExceeded, self.pos));
+        }
+        let start = self.pos;
+        match self.peek() {
+            None => Err(self.err(Error::UnexpectedEOF, start)),
+            Some(b'n') if self.keyword("null") => Ok(Value::Null),
+            Some(b't') if self.keyword("true") => Ok(Value::Bool(true)),
+            Some(b'f') if self.keyword("false") => Ok(Value::Bool(false)),
+            Some(b'"') => {
+                let s = self.string()?;
+                crate::validate_nfc(&s).map_err(|k| self.err(k, start))?;
+                Ok(Value::String(s))
+            }
+            Some(b'h') if self.text[start..].starts_with("h'") => self.bytes(),
+            Some(b'-' | b'0'..=b'9') => self.int(),
+            Some(b'[') => self.array(depth),
+            Some(b'{') => self.map(depth),
+            Some(_) => Err(self.syntax("expected a value")),
+        }
+    }
+
+    fn int(&mut self) -> PResult<Value> {
+        let start = self.pos;
+        let neg = self.eat(b'-');
+        let digits = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        let lit = &self.text[digits..self.pos];
+        if lit.is_empty() {
+            return Err(self.syntax("expected digits"));
+        }
+        if matches!(self.peek(), Some(b'.' | b'e' | b'E')) {
+            return Err(self.err(Error::Float, start));
+        }
+        if lit.len() > 1 && lit.starts_with('0') {
+            return Err(self.err(Error::Diag("leading zero".into()), start));
+        }
+        if neg && lit == "0" {
+            return Err(self.err(Error::Diag("-0 is not canonical".into()), start));
+        }
+        self.text[start..self.pos]
+            .parse::<i64>()
+            .map(Value::Int)
+            .map_err(|_| self.err(Error::IntegerOverflow, start))
+    }
+
+    fn bytes(&mut self) -> PResult<Value> {
+        let start = self.pos;
+        self.pos += 2; // h'
+        let Some(len) = self.text[self.pos..].find('\'') else {
+            return Err(self.err(Error::UnexpectedEOF, self.text.len()));
+        };
+        let hex = &self.text[self.pos..self.pos + len];
+        let b = crate::parse_hex_lower(hex).map_err(|k| self.err(k, start))?;
+        self.pos += len + 1;
+        Ok(Value::Bytes(b))
+    }
+
+    /// JSON string syntax. Leaves `pos` after the closing quote.
+    fn string(&mut self) -> PResult<String> {
+        self.pos += 1; // opening quote
+        let mut out = String::new();
+        loop {
+            let Some(c) = self.text[self.pos..].chars().next() else {
+                return Err(self.err(Error::UnexpectedEOF, self.pos));
+            };
+            match c {
+                '"' => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    let esc = self
+                        .peek()
+                        .ok_or_else(|| self.err(Error::UnexpectedEOF, self.pos))?;
+                    self.pos += 1;
+                    match esc {
+                        b'"' => out.push('"'),
+                        b'\\' => out.push('\\'),
+                        b'/' => out.push('/'),
+                        b'b' => out.push('\u{8}'),
+                        b'f' => out.push('\u{c}'),
+                        b'n' => out.push('\n'),
+                        b'r' => out.push('\r'),
+                        b't' => out.push('\t'),
+                        b'u' => out.push(self.unicode_escape()?),
+                        _ => {
+                            self.pos -= 1;
+                            return Err(self.syntax("invalid escape"));
+                        }
+                    }
+                }
+                c if (c as u32) < 0x20 => return Err(self.syntax("unescaped control character")),
+                c => {
+                    out.push(c);
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> PResult<u32> {
+        let at = self.pos;
+        let h = self
+            .text
+            .get(at..at + 4)
+            .ok_or_else(|| self.err(Error::UnexpectedEOF, at))?;
+        let n = u32::from_str_radix(h, 16)
+            .ok()
+            .filter(|_| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.err(Error::Diag("invalid \\u escape".into()), at))?;
+        self.pos += 4;
+        Ok(n)
+    }
+
+    fn unicode_escape(&mut self) -> PResult<char> {
+        let at = self.pos - 2;
+        let hi = self.hex4()?;
+        let cp = if (0xD800..0xDC00).contains(&hi) {
+            if !self.text[self.pos..].starts_with("\\u") {
+                return Err(self.err(Error::InvalidUTF8, at));
+            }
+            self.pos += 2;
+            let lo = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&lo) {
+                return Err(self.err(Error::InvalidUTF8, at));
+            }
+            0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)
+        } else {
+            hi
+        };
+        char::from_u32(cp).ok_or_else(|| self.err(Error::InvalidUTF8, at))
+    }
+
+    fn array(&mut self, depth: usize) -> PResult<Value> {
+        self.pos += 1; // [
+        let mut items = Vec::new();
+        self.ws();
+        if self.eat(b']') {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            let len = self.path.len();
+            push_path_index(&mut self.path, items.len());
+            items.push(self.value(depth + 1)?);
+            self.path.truncate(len);
+            self.ws();
+            if self.eat(b',') {
+                continue;
+            }
+            if self.eat(b']') {
+                return Ok(Value::Array(items));
+            }
+            return Err(self.syntax("expected ',' or ']'"));
+        }
+    }
+
+    fn map(&mut self, depth: usize) -> PResult<Value> {
+        self.pos += 1; // {
+        let mut map = alloc::collections::BTreeMap::new();
+        let mut prev: Option<String> = None;
+        self.ws();
+        if self.eat(b'}') {
+            return Ok(Value::Map(map));
+        }
+        loop {
+            self.ws();
+            let key_start = self.pos;
+            if self.peek() != Some(b'"') {
+                return Err(self.err(Error::NonStringKey, key_start));
+            }
+            let key = self.string()?;
+            let len = self.path.len();
+            push_path_key(&mut self.path, &key);
+            crate::validate_nfc(&key).map_err(|k| self.err(k, key_start))?;
+            if let Some(p) = &prev {
+                match p.as_bytes().cmp(key.as_bytes()) {
+                    core::cmp::Ordering::Less => {}
+                    core::cmp::Ordering::Equal => {
+                        return Err(self.err(Error::DuplicateKey, key_start))
+                    }
+                    core::cmp::Ordering::Greater => {
+                        return Err(self.err(Error::UnsortedKeys, key_start))
+                    }
+                }
+            }
+            self.ws();
+            if !self.eat(b':') {
+                return Err(self.syntax("expected ':'"));
+            }
+            let val = self.value(depth + 1)?;
+            self.path.truncate(len);
+            map.insert(key.clone(), val);
+            prev = Some(key);
+            self.ws();
+            if self.eat(b',') {
+                continue;
+            }
+            if self.eat(b'}') {
+                return Ok(Value::Map(map));
+            }
+            return Err(self.syntax("expected ',' or '}'"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+    use alloc::vec;
+
+    fn sample() -> Value {
+        let mut m = BTreeMap::new();
+        m.insert("a".into(), Value::Int(1));
+        m.insert("b".into(), Value::Bytes(vec![0x00, 0xff]));
+        m.insert(
+            "c".into(),
+            Value::Array(vec![Value::Null, Value::Bool(true)]),
+        );
+        Value::Map(m)
+    }
+
+    fn kind(text: &str) -> Error {
+        from_diag(text).unwrap_err().kind
+    }
+
+    #[test]
+    fn prints_compact_notation() {
+        assert_eq!(
+            to_diag(&sample()),
+            r#"{"a": 1, "b": h'00ff', "c": [null, true]}"#
+        );
+        let s = Value::String("q\"\\\n\u{1}é".into());
+        assert_eq!(to_diag(&s), r#""q\"\\\n\u0001é""#);
+    }
+
+    #[test]
+    fn roundtrips_to_canonical_bytes() {
+        let mut m = BTreeMap::new();
+        m.insert("".into(), Value::Int(i64::MIN));
+        m.insert("\u{e9}".into(), Value::Int(i64::MAX));
+        m.insert("nested".into(), sample());
+        m.insert("s".into(), Value::String("tab\there 😀".into()));
+        m.insert("empty".into(), Value::Array(vec![]));
+        m.insert("none".into(), Value::Map(BTreeMap::new()));
+        m.insert("raw".into(), Value::Bytes(vec![]));
+        let v = Value::Map(m);
+        for text in [to_diag(&v), to_diag_pretty(&v)] {
+            assert_eq!(from_diag(&text).unwrap(), v, "{text}");
+            assert_eq!(diag_to_bytes(&text).unwrap(), crate::encode(&v));
+        }
+    }
+
+    #[test]
+    fn accepts_escapes_and_whitespace() {
+        let v = from_diag(" [ \"\\u00e9\\ud83d\\ude00\\/\" ,\n-5 ] ").unwrap();
+        assert_eq!(
+            v,
+            Value::Array(vec![Value::String("é😀/".into()), Value::Int(-5)])
+        );
+    }
+
+    #[test]
+    fn rejects_non_canonical_input() {
+        assert_eq!(kind(r#"{"b": 1, "a": 2}"#), Error::UnsortedKeys);
+        assert_eq!(kind(r#"{"a": 1, "a": 2}"#), Error::DuplicateKey);
+        assert_eq!(kind("1.5"), Error::Float);
+        assert_eq!(kind("1e3"), Error::Float);
+        assert_eq!(kind("h'00FF'"), Error::HexUppercase);
+        assert_eq!(kind("h'0'"), Error::HexOddLength);
+        assert_eq!(kind("\"e\u{301}\""), Error::NotNFC);
+        assert_eq!(kind("\"\u{feff}\""), Error::BOMPresent);
+        assert_eq!(kind("\"\\ud800\""), Error::InvalidUTF8);
+        assert_eq!(kind("9223372036854775808"), Error::IntegerOverflow);
+        assert_eq!(kind("{1: 2}"), Error::NonStringKey);
+        assert_eq!(kind("null null"), Error::TrailingData);
+        assert_eq!(kind("[1, 2"), Error::Diag("expected ',' or ']'".into()));
+        assert_eq!(kind("[1,]"), Error::Diag("expected a value".into()));
+        assert_eq!(kind("01"), Error::Diag("leading zero".into()));
+        assert_eq!(kind("-0"), Error::Diag("-0 is not canonical".into()));
+        assert_eq!(kind("nul"), Error::Diag("expected a value".into()));
+        assert_eq!(
+            kind("\"a\nb\""),
+            Error::Diag("unescaped control character".into())
+        );
+        assert_eq!(kind(&"[".repeat(100)), Error::DepthExceeded);
+    }
+
+    #[test]
+    fn errors_point_at_offending_token() {
+        let text = r#"{"env": {"items": [1, {"sku": 2.5}]}}"#;
+        let err = from_diag(text).unwrap_err();
+        assert_eq!(err.kind, Error::Float);
+        assert_eq!(err.offset, text.find("2.5").unwrap());
+        assert_eq!(err.path, "env.items[1].sku");
+    }
+}
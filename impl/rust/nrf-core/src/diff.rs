@@ -0,0 +1,297 @@
+// ---------------------------------------------------------------------------
+// Structural diff — why do two CIDs differ?
+//
+// `diff(a, b)` walks both values in canonical order and reports every
+// difference at the deepest path where it occurs:
+//
+//   added / removed   map key or array tail element present on one side only
+//   changed           same type, different value (strings and bytes get a
+//                     note: first differing byte, or "same text, different
+//                     Unicode normalization" for NFC vs NFD)
+//   type_changed      different NRF types at the same path
+//
+// Arrays are compared index by index (no LCS): an insertion in the middle
+// shows up as a run of changes plus an `added` at the tail. That is the
+// honest answer for hashing — every shifted element changes the bytes.
+// ---------------------------------------------------------------------------
+
+use crate::{push_path_index, push_path_key, DecodeError, Value};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+    TypeChanged,
+}
+
+impl ChangeKind {
+    /// Stable lowercase name used in JSON renderings.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+            ChangeKind::TypeChanged => "type_changed",
+        }
+    }
+}
+
+/// One difference. `old` is absent for `Added`, `new` for `Removed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+    pub note: Option<String>,
+}
+
+impl core::fmt::Display for Change {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let path = if self.path.is_empty() {
+            "<root>"
+        } else {
+            &self.path
+        };
+        let show = |v: &Option<Value>| v.as_ref().map(crate::to_diag).unwrap_or_default();
+        match self.kind {
+            ChangeKind::Added => write!(f, "+ {path}: {}", show(&self.new))?,
+            ChangeKind::Removed => write!(f, "- {path}: {}", show(&self.old))?,
+            ChangeKind::Changed | ChangeKind::TypeChanged => {
+                write!(f, "~ {path}: {} -> {}", show(&self.old), show(&self.new))?
+            }
+        }
+        if let Some(note) = &self.note {
+            write!(f, " ({note})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two values. Empty result ⇔ `a == b` ⇔ same canonical bytes.
+pub fn diff(a: &Value, b: &Value) -> Vec<Change> {
+    let mut out = Vec::new();
+    let mut path = String::new();
+    walk(a, b, &mut path, &mut out);
+    out
+}
+
+/// Decode two NRF buffers (default limits) and compare them.
+pub fn diff_bytes(a: &[u8], b: &[u8]) -> core::result::Result<Vec<Change>, DecodeError> {
+    Ok(diff(&crate::decode_located(a)?, &crate::decode_located(b)?))
+}
+
+fn type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "Null",
+        Value::Bool(_) => "Bool",
+        Value::Int(_) => "Int",
+        Value::String(_) => "String",
+        Value::Bytes(_) => "Bytes",
+        Value::Array(_) => "Array",
+        Value::Map(_) => "Map",
+    }
+}
+
+fn first_difference(a: &[u8], b: &[u8]) -> String {
+    let at = a
+        .iter()
+        .zip(b)
+        .position(|(x, y)| x != y)
+        .unwrap_or(a.len().min(b.len()));
+    if a.len() == b.len() {
+        format!("first differing byte at {at}")
+    } else {
+        format!(
+            "first differing byte at {at}; length {} vs {}",
+            a.len(),
+            b.len()
+        )
+    }
+}
+
+fn string_note(a: &str, b: &str) -> String {
+    if a.nfc().eq(b.nfc()) {
+        let form = |s: &str| {
+            if unicode_normalization::is_nfc(s) {
+                "NFC"
+            } else {
+                "not NFC"
+            }
+        };
+        format!(
+            "same text, different Unicode normalization ({} vs {})",
+            form(a),
+            form(b)
+        )
+    } else {
+        first_difference(a.as_bytes(), b.as_bytes())
+    }
+}
+
+fn push(
+    out: &mut Vec<Change>,
+    path: &str,
+    kind: ChangeKind,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    note: Option<String>,
+) {
+    out.push(Change {
+        path: path.to_string(),
+        kind,
+        old: old.cloned(),
+        new: new.cloned(),
+        note,
+    });
+}
+
+fn walk(a: &Value, b: &Value, path: &mut String, out: &mut Vec<Change>) {
+    match (a, b) {
+        (Value::Map(ma), Value::Map(mb)) => {
+            let mut ia = ma.iter().peekable();
+            let mut ib = mb.iter().peekable();
+            loop {
+                let order = match (ia.peek(), ib.peek()) {
+                    (None, None) => break,
+                    (Some(_), None) => core::cmp::Ordering::Less,
+                    (None, Some(_)) => core::cmp::Ordering::Greater,
+                    (Some((ka, _)), Some((kb, _))) => ka.as_bytes().cmp(kb.as_bytes()),
+                };
+                let len = path.len();
+                match order {
+                    core::cmp::Ordering::Less => {
+                        let (k, v) = ia.next().expect("peeked");
+                        push_path_key(path, k);
+                        push(out, path, ChangeKind::Removed, Some(v), None, None);
+                    }
+                    core::cmp::Ordering::Greater => {
+                        let (k, v) = ib.next().expect("peeked");
+                        push_path_key(path, k);
+                        push(out, path, ChangeKind::Added, None, Some(v), None);
+                    }
+                    core::cmp::Ordering::Equal => {
+                        let (k, va) = ia.next().expect("peeked");
+                        let (_, vb) = ib.next().expect("peeked");
+                        push_path_key(path, k);
+                        walk(va, vb, path, out);
+                    }
+                }
+                path.truncate(len);
+            }
+        }
+        (Value::Array(xa), Value::Array(xb)) => {
+            for i in 0..xa.len().max(xb.len()) {
+                let len = path.len();
+                push_path_index(path, i);
+                match (xa.get(i), xb.get(i)) {
+                    (Some(va), Some(vb)) => walk(va, vb, path, out),
+                    (Some(va), None) => push(out, path, ChangeKind::Removed, Some(va), None, None),
+                    (None, Some(vb)) => push(out, path, ChangeKind::Added, None, Some(vb), None),
+                    (None, None) => unreachable!(),
+                }
+                path.truncate(len);
+            }
+        }
+        _ if a == b => {}
+        (Value::String(sa), Value::String(sb)) => {
+            let note = string_note(sa, sb);
+            push(out, path, ChangeKind::Changed, Some(a), Some(b), Some(note));
+        }
+        (Value::Bytes(ba), Value::Bytes(bb)) => {
+            let note = first_difference(ba, bb);
+            push(out, path, ChangeKind::Changed, Some(a), Some(b), Some(note));
+        }
+        _ if core::mem::discriminant(a) == core::mem::discriminant(b) => {
+            push(out, path, ChangeKind::Changed, Some(a), Some(b), None);
+        }
+        _ => {
+            let note = format!("{} -> {}", type_name(a), type_name(b));
+            push(
+                out,
+                path,
+                ChangeKind::TypeChanged,
+                Some(a),
+                Some(b),
+                Some(note),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_diag;
+    use alloc::vec;
+
+    fn d(text: &str) -> Value {
+        from_diag(text).unwrap()
+    }
+
+    #[test]
+    fn equal_values_have_no_changes() {
+        let v = d(r#"{"a": [1, h'00'], "b": {"c": null}}"#);
+        assert!(diff(&v, &v).is_empty());
+    }
+
+    #[test]
+    fn reports_path_addressed_changes() {
+        let a = d(r#"{"env": {"items": [{"qty": 1, "sku": "A"}, 2], "old": true}, "v": 1}"#);
+        let b = d(r#"{"env": {"items": [{"qty": 2, "sku": "A"}, "2", 3], "new": h'01'}, "v": 1}"#);
+        let got: Vec<(String, ChangeKind)> =
+            diff(&a, &b).into_iter().map(|c| (c.path, c.kind)).collect();
+        assert_eq!(
+            got,
+            vec![
+                ("env.items[0].qty".into(), ChangeKind::Changed),
+                ("env.items[1]".into(), ChangeKind::TypeChanged),
+                ("env.items[2]".into(), ChangeKind::Added),
+                ("env.new".into(), ChangeKind::Added),
+                ("env.old".into(), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn explains_nfc_vs_nfd() {
+        let a = Value::String("caf\u{e9}".into());
+        let b = Value::String("cafe\u{301}".into());
+        let c = &diff(&a, &b)[0];
+        assert_eq!(c.kind, ChangeKind::Changed);
+        assert_eq!(
+            c.note.as_deref(),
+            Some("same text, different Unicode normalization (NFC vs not NFC)")
+        );
+    }
+
+    #[test]
+    fn notes_first_differing_byte() {
+        let c = &diff(&Value::Bytes(vec![1, 2, 3]), &Value::Bytes(vec![1, 9]))[0];
+        assert_eq!(
+            c.note.as_deref(),
+            Some("first differing byte at 1; length 3 vs 2")
+        );
+        let c = &diff(&Value::String("abc".into()), &Value::String("abd".into()))[0];
+        assert_eq!(c.note.as_deref(), Some("first differing byte at 2"));
+        assert_eq!(
+            c.to_string(),
+            r#"~ <root>: "abc" -> "abd" (first differing byte at 2)"#
+        );
+    }
+
+    #[test]
+    fn diff_bytes_decodes_both_sides() {
+        let a = crate::encode(&d("[1]"));
+        let b = crate::encode(&d("[1, 2]"));
+        let changes = diff_bytes(&a, &b).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to_string(), "+ [1]: 2");
+        assert!(diff_bytes(b"nrf1\x08", &b).is_err());
+    }
+}
@@ -1,7 +1,50 @@
-use std::collections::BTreeMap;
+// `no_std + alloc` unless the default `std` feature is on. Everything but
+// `encode_into`, `stream` and `From<io::Error>` is available either way.
+// CI runs tests/no_std.rs and the lib tests with `--no-default-features` and
+// builds for thumbv7em-none-eabi, which has no std to fall back on.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io;
 
+#[macro_use]
+mod macros;
+/// Paths for `nrf!` that work with and without std.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::collections::BTreeMap;
+    pub use alloc::string::String;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+}
+mod access;
+pub mod decimal;
+pub mod diag;
+pub mod diff;
+pub mod merkle;
+pub mod query;
 pub mod rho;
+pub mod serde;
+#[cfg(feature = "std")]
+pub mod stream;
+pub mod value_ref;
+
+pub use decimal::{NrfDecimal, RoundingMode};
+pub use diag::{diag_to_bytes, from_diag, to_diag, to_diag_pretty};
+pub use diff::{diff, diff_bytes, Change, ChangeKind};
+pub use merkle::{merkle_cid, merkle_root, Disclosure, PathSeg, Proof};
+pub use query::Query;
+#[cfg(feature = "std")]
+pub use stream::{decode_reader, decode_reader_with_opts, Event, StreamDecoder};
+pub use value_ref::{decode_ref, decode_ref_located, decode_ref_with_opts, ValueRef};
 
 /// Magic "nrf1"
 pub const MAGIC: [u8; 4] = *b"nrf1";
@@ -10,7 +53,8 @@ pub const MAGIC: [u8; 4] = *b"nrf1";
 pub const MIME_NRF: &str = "application/ai-nrf1";
 pub const MIME_JSON: &str = "application/ai-json-nrf1+json";
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+/// Serialize/Deserialize live in `serde.rs` (JSON shape is externally tagged).
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Value {
     Null,
     Bool(bool),
@@ -69,8 +113,25 @@ pub enum Error {
     NotASCII,
     #[error("Float")]
     Float,
+    #[error("IntegerOverflow")]
+    IntegerOverflow,
+    #[error("Serde({0})")]
+    Serde(String),
+    #[error("Diag({0})")]
+    Diag(String),
+    #[error("PathNotFound({0})")]
+    PathNotFound(String),
+    #[error("Merkle({0})")]
+    Merkle(String),
+    #[error("Query({0})")]
+    Query(String),
+    #[error("Rho({0})")]
+    Rho(String),
+    #[error("Stream({0})")]
+    Stream(String),
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
         if e.kind() == io::ErrorKind::UnexpectedEof {
@@ -81,51 +142,277 @@ impl From<io::Error> for Error {
     }
 }
 
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// Encode value with magic prefix.
 #[cfg_attr(feature = "obs", tracing::instrument(level = "trace", skip_all))]
 pub fn encode(value: &Value) -> Vec<u8> {
-    let mut buf = Vec::new();
+    let mut buf = Vec::with_capacity(encoded_len(value));
     buf.extend_from_slice(&MAGIC);
     encode_value(&mut buf, value);
     buf
 }
 
-fn encode_value(buf: &mut Vec<u8>, v: &Value) {
+/// Fallible `encode` under the default decode limits: an error instead of
+/// bytes that `decode` would reject. Use before signing or persisting.
+pub fn try_encode(value: &Value) -> Result<Vec<u8>> {
+    encode_with_opts(value, &DecodeOpts::default())
+}
+
+/// Encode only if `decode_with_opts(&out, opts)` would accept the result:
+/// every length fits varint32 and its limit, nesting stays within
+/// `max_depth`, strings and keys are NFC without BOM, and the whole buffer
+/// fits `max_total_bytes`. Errors are the ones decoding would report.
+pub fn encode_with_opts(value: &Value, opts: &DecodeOpts) -> Result<Vec<u8>> {
+    let len = MAGIC.len() + checked_len(value, 0, opts)?;
+    if len > opts.max_total_bytes {
+        return Err(Error::SizeExceeded);
+    }
+    let mut buf = Vec::with_capacity(len);
+    buf.extend_from_slice(&MAGIC);
+    encode_value(&mut buf, value);
+    Ok(buf)
+}
+
+/// `value_len` plus every check `decode_value_opts` applies.
+pub(crate) fn checked_len(v: &Value, depth: usize, opts: &DecodeOpts) -> Result<usize> {
+    if depth > opts.max_depth {
+        return Err(Error::DepthExceeded);
+    }
+    Ok(1 + match v {
+        Value::Null | Value::Bool(_) => 0,
+        Value::Int(_) => 8,
+        Value::String(s) => checked_str_len(s, opts)?,
+        Value::Bytes(b) => {
+            check_count(b.len(), opts.max_bytes_len, Error::BytesTooLong)?;
+            varint32_len(b.len() as u32) + b.len()
+        }
+        Value::Array(items) => {
+            check_count(items.len(), opts.max_array_len, Error::ArrayTooLong)?;
+            let mut n = varint32_len(items.len() as u32);
+            for it in items {
+                n += checked_len(it, depth + 1, opts)?;
+            }
+            n
+        }
+        Value::Map(m) => {
+            check_count(m.len(), opts.max_map_len, Error::MapTooLong)?;
+            let mut n = varint32_len(m.len() as u32);
+            for (k, val) in m {
+                n += 1 + checked_str_len(k, opts)? + checked_len(val, depth + 1, opts)?;
+            }
+            n
+        }
+    })
+}
+
+fn checked_str_len(s: &str, opts: &DecodeOpts) -> Result<usize> {
+    check_count(s.len(), opts.max_string_len, Error::StringTooLong)?;
+    validate_nfc(s)?;
+    Ok(varint32_len(s.len() as u32) + s.len())
+}
+
+/// Lengths beyond u32 cannot be written as varint32, whatever the limits say.
+fn check_count(n: usize, max: usize, err: Error) -> Result<()> {
+    if n > max || u32::try_from(n).is_err() {
+        Err(err)
+    } else {
+        Ok(())
+    }
+}
+
+/// Stream the encoding (magic included) into any writer; nothing is
+/// buffered here, so wrap unbuffered files/sockets in a `BufWriter`.
+#[cfg(feature = "std")]
+pub fn encode_into<W: io::Write>(w: &mut W, value: &Value) -> io::Result<()> {
+    let mut sink = WriteSink { w, err: None };
+    sink.put(&MAGIC);
+    encode_value(&mut sink, value);
+    sink.err.map_or(Ok(()), Err)
+}
+
+/// Exact length of `encode(value)`, computed without allocating.
+pub fn encoded_len(value: &Value) -> usize {
+    MAGIC.len() + value_len(value)
+}
+
+fn value_len(v: &Value) -> usize {
+    1 + match v {
+        Value::Null | Value::Bool(_) => 0,
+        Value::Int(_) => 8,
+        Value::String(s) => varint32_len(s.len() as u32) + s.len(),
+        Value::Bytes(b) => varint32_len(b.len() as u32) + b.len(),
+        Value::Array(items) => {
+            varint32_len(items.len() as u32) + items.iter().map(value_len).sum::<usize>()
+        }
+        Value::Map(m) => {
+            varint32_len(m.len() as u32)
+                + m.iter()
+                    .map(|(k, val)| 1 + varint32_len(k.len() as u32) + k.len() + value_len(val))
+                    .sum::<usize>()
+        }
+    }
+}
+
+fn varint32_len(value: u32) -> usize {
+    match value {
+        0..=0x7F => 1,
+        0x80..=0x3FFF => 2,
+        0x4000..=0x1F_FFFF => 3,
+        0x20_0000..=0xFFF_FFFF => 4,
+        _ => 5,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Byte sinks — one encoder, three destinations (Vec, io::Write, BLAKE3)
+// ---------------------------------------------------------------------------
+
+/// Where `encode_value` puts bytes. Infallible by design; fallible writers
+/// go through `WriteSink`, which remembers the first error.
+trait Sink {
+    fn put(&mut self, bytes: &[u8]);
+
+    fn put_byte(&mut self, b: u8) {
+        self.put(&[b]);
+    }
+}
+
+impl Sink for Vec<u8> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+
+    fn put_byte(&mut self, b: u8) {
+        self.push(b);
+    }
+}
+
+#[cfg(feature = "std")]
+struct WriteSink<'a, W> {
+    w: &'a mut W,
+    err: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> Sink for WriteSink<'_, W> {
+    fn put(&mut self, bytes: &[u8]) {
+        if self.err.is_none() {
+            if let Err(e) = self.w.write_all(bytes) {
+                self.err = Some(e);
+            }
+        }
+    }
+}
+
+const HASH_SINK_BUF: usize = 8 * 1024;
+
+/// BLAKE3 sink: the encoder writes here and the hasher is fed in 8 KiB
+/// blocks, so a CID never needs the whole encoding in memory. Values that
+/// fit in one block are hashed in one shot (no incremental hasher at all).
+/// Also an `io::Write` (with `std`), for use with `encode_into`.
+pub struct HashSink {
+    hasher: Option<Box<blake3::Hasher>>,
+    buf: Vec<u8>,
+}
+
+impl HashSink {
+    pub fn new() -> Self {
+        Self {
+            hasher: None,
+            // Small values never reach the 8 KiB cap; don't pay for it up front.
+            buf: Vec::with_capacity(1024),
+        }
+    }
+
+    /// BLAKE3 of everything written so far.
+    pub fn finalize(self) -> [u8; 32] {
+        match self.hasher {
+            None => *blake3::hash(&self.buf).as_bytes(),
+            Some(mut hasher) => {
+                hasher.update(&self.buf);
+                *hasher.finalize().as_bytes()
+            }
+        }
+    }
+
+    #[cold]
+    fn spill(&mut self, bytes: &[u8]) {
+        let hasher = self.hasher.get_or_insert_with(Default::default);
+        hasher.update(&self.buf);
+        self.buf.clear();
+        if bytes.len() >= HASH_SINK_BUF {
+            // Large leaf: hand it to the hasher directly, no copy.
+            hasher.update(bytes);
+        } else {
+            self.buf.extend_from_slice(bytes);
+        }
+    }
+}
+
+impl Default for HashSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sink for HashSink {
+    #[inline]
+    fn put(&mut self, bytes: &[u8]) {
+        if self.buf.len() + bytes.len() > HASH_SINK_BUF {
+            self.spill(bytes);
+        } else {
+            self.buf.extend_from_slice(bytes);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Write for HashSink {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.put(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn encode_value(buf: &mut impl Sink, v: &Value) {
     match v {
-        Value::Null => buf.push(0x00),
-        Value::Bool(false) => buf.push(0x01),
-        Value::Bool(true) => buf.push(0x02),
+        Value::Null => buf.put_byte(0x00),
+        Value::Bool(false) => buf.put_byte(0x01),
+        Value::Bool(true) => buf.put_byte(0x02),
         Value::Int(n) => {
-            buf.push(0x03);
-            buf.extend_from_slice(&n.to_be_bytes());
+            buf.put_byte(0x03);
+            buf.put(&n.to_be_bytes());
         }
         Value::String(s) => {
-            buf.push(0x04);
+            buf.put_byte(0x04);
             encode_varint32(buf, s.len() as u32);
-            buf.extend_from_slice(s.as_bytes());
+            buf.put(s.as_bytes());
         }
         Value::Bytes(b) => {
-            buf.push(0x05);
+            buf.put_byte(0x05);
             encode_varint32(buf, b.len() as u32);
-            buf.extend_from_slice(b);
+            buf.put(b);
         }
         Value::Array(items) => {
-            buf.push(0x06);
+            buf.put_byte(0x06);
             encode_varint32(buf, items.len() as u32);
             for it in items {
                 encode_value(buf, it);
             }
         }
         Value::Map(m) => {
-            buf.push(0x07);
+            buf.put_byte(0x07);
             encode_varint32(buf, m.len() as u32);
             for (k, val) in m {
                 // key is encoded as String
-                buf.push(0x04);
+                buf.put_byte(0x04);
                 encode_varint32(buf, k.len() as u32);
-                buf.extend_from_slice(k.as_bytes());
+                buf.put(k.as_bytes());
                 encode_value(buf, val);
             }
         }
@@ -190,135 +477,349 @@ pub fn decode(data: &[u8]) -> Result<Value> {
 /// Decode with explicit resource limits. Canon 6: reject, never degrade.
 #[cfg_attr(feature = "obs", tracing::instrument(level = "trace", skip_all, fields(len = data.len())))]
 pub fn decode_with_opts(data: &[u8], opts: &DecodeOpts) -> Result<Value> {
-    if data.len() > opts.max_total_bytes {
-        return Err(Error::SizeExceeded);
+    decode_located_with_opts(data, opts).map_err(|e| e.kind)
+}
+
+// ---------------------------------------------------------------------------
+// Located decode errors — byte offset + logical path of the failure
+// ---------------------------------------------------------------------------
+
+/// A decode error with its location: `offset` is the byte offset (from the
+/// start of the buffer, magic included) of the value, key or trailing byte
+/// that was rejected; `path` is its logical path, e.g. `env.items[3].sku`
+/// (empty for the root value).
+#[derive(Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub kind: Error,
+    pub offset: usize,
+    pub path: String,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{} at byte {}", self.kind, self.offset)
+        } else {
+            write!(f, "{} at byte {} ({})", self.kind, self.offset, self.path)
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Self {
+        e.kind
+    }
+}
+
+/// Append a map key to an error path: `a.b`, or `a["odd key"]` when the
+/// key is not a plain identifier.
+pub fn push_path_key(path: &mut String, key: &str) {
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if plain {
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(key);
+    } else {
+        path.push_str(&format!("[{key:?}]"));
+    }
+}
+
+/// Append an array index to an error path: `a[3]`.
+pub fn push_path_index(path: &mut String, index: usize) {
+    path.push_str(&format!("[{index}]"));
+}
+
+/// Decode with default limits, reporting where a rejection happened.
+pub fn decode_located(data: &[u8]) -> core::result::Result<Value, DecodeError> {
+    decode_located_with_opts(data, &DecodeOpts::default())
+}
+
+/// Same acceptance as `decode_with_opts`; errors carry offset and path.
+pub fn decode_located_with_opts(
+    data: &[u8],
+    opts: &DecodeOpts,
+) -> core::result::Result<Value, DecodeError> {
+    decode_walk(data, opts)
+}
+
+/// Zero-copy decode with located errors.
+pub fn decode_ref_located_with_opts<'a>(
+    data: &'a [u8],
+    opts: &DecodeOpts,
+) -> core::result::Result<ValueRef<'a>, DecodeError> {
+    decode_walk(data, opts)
+}
+
+/// What the decode walk builds. `decode`, `decode_located` and
+/// `decode_ref` all run the one walk below; the owned decode builds
+/// `Value` directly instead of copying a `ValueRef` tree.
+trait Build<'a>: Sized {
+    type Pairs;
+    fn null() -> Self;
+    fn bool(b: bool) -> Self;
+    fn int(n: i64) -> Self;
+    fn string(s: &'a str) -> Self;
+    fn bytes(b: &'a [u8]) -> Self;
+    fn array(items: Vec<Self>) -> Self;
+    fn new_map(count: usize) -> Self::Pairs;
+    fn push_pair(m: &mut Self::Pairs, k: &'a str, v: Self);
+    fn map(m: Self::Pairs) -> Self;
+}
+
+impl<'a> Build<'a> for ValueRef<'a> {
+    type Pairs = Vec<(&'a str, ValueRef<'a>)>;
+    fn null() -> Self {
+        ValueRef::Null
+    }
+    fn bool(b: bool) -> Self {
+        ValueRef::Bool(b)
+    }
+    fn int(n: i64) -> Self {
+        ValueRef::Int(n)
     }
-    if data.len() < 4 {
-        return Err(Error::InvalidMagic);
+    fn string(s: &'a str) -> Self {
+        ValueRef::String(s)
     }
-    if data[..4] != MAGIC {
-        return Err(Error::InvalidMagic);
+    fn bytes(b: &'a [u8]) -> Self {
+        ValueRef::Bytes(b)
+    }
+    fn array(items: Vec<Self>) -> Self {
+        ValueRef::Array(items)
+    }
+    fn new_map(count: usize) -> Self::Pairs {
+        Vec::with_capacity(count.min(1024))
+    }
+    fn push_pair(m: &mut Self::Pairs, k: &'a str, v: Self) {
+        m.push((k, v));
+    }
+    fn map(m: Self::Pairs) -> Self {
+        ValueRef::Map(m)
+    }
+}
+
+impl<'a> Build<'a> for Value {
+    type Pairs = BTreeMap<String, Value>;
+    fn null() -> Self {
+        Value::Null
+    }
+    fn bool(b: bool) -> Self {
+        Value::Bool(b)
+    }
+    fn int(n: i64) -> Self {
+        Value::Int(n)
+    }
+    fn string(s: &'a str) -> Self {
+        Value::String(s.into())
+    }
+    fn bytes(b: &'a [u8]) -> Self {
+        Value::Bytes(b.to_vec())
+    }
+    fn array(items: Vec<Self>) -> Self {
+        Value::Array(items)
+    }
+    fn new_map(_: usize) -> Self::Pairs {
+        BTreeMap::new()
+    }
+    fn push_pair(m: &mut Self::Pairs, k: &'a str, v: Self) {
+        m.insert(k.into(), v);
+    }
+    fn map(m: Self::Pairs) -> Self {
+        Value::Map(m)
+    }
+}
+
+fn decode_walk<'a, T: Build<'a>>(
+    data: &'a [u8],
+    opts: &DecodeOpts,
+) -> core::result::Result<T, DecodeError> {
+    let root = |kind| DecodeError {
+        kind,
+        offset: 0,
+        path: String::new(),
+    };
+    if data.len() > opts.max_total_bytes {
+        return Err(root(Error::SizeExceeded));
+    }
+    if data.len() < 4 || data[..4] != MAGIC {
+        return Err(root(Error::InvalidMagic));
     }
     let mut cur = &data[4..];
-    let v = decode_value_opts(&mut cur, 0, opts)?;
+    let v = decode_value_opts(&mut cur, data.len(), 0, opts).map_err(|f| {
+        let mut path = String::new();
+        for seg in f.segs.iter().rev() {
+            match seg {
+                Seg::Key(k) => push_path_key(&mut path, k),
+                Seg::Index(i) => push_path_index(&mut path, *i),
+            }
+        }
+        DecodeError {
+            kind: f.kind,
+            offset: f.offset,
+            path,
+        }
+    })?;
     if !cur.is_empty() {
-        return Err(Error::TrailingData);
+        return Err(DecodeError {
+            kind: Error::TrailingData,
+            offset: data.len() - cur.len(),
+            path: String::new(),
+        });
     }
     Ok(v)
 }
 
-fn decode_value_opts(cur: &mut &[u8], depth: usize, opts: &DecodeOpts) -> Result<Value> {
-    if depth > opts.max_depth {
-        return Err(Error::DepthExceeded);
+enum Seg {
+    Key(String),
+    Index(usize),
+}
+
+/// Failure while walking; `segs` is innermost-first, filled on unwind so
+/// the happy path never builds a path.
+struct Fail {
+    kind: Error,
+    offset: usize,
+    segs: Vec<Seg>,
+}
+
+fn fail_at(offset: usize) -> impl Fn(Error) -> Fail {
+    move |kind| Fail {
+        kind,
+        offset,
+        segs: Vec::new(),
     }
+}
+
+fn take_key<'a>(cur: &mut &'a [u8], opts: &DecodeOpts) -> Result<&'a [u8]> {
     if cur.is_empty() {
         return Err(Error::UnexpectedEOF);
     }
+    let key_tag = cur[0];
+    *cur = &cur[1..];
+    if key_tag != 0x04 {
+        return Err(Error::NonStringKey);
+    }
+    let klen = decode_varint32(cur)? as usize;
+    if klen > opts.max_string_len {
+        return Err(Error::StringTooLong);
+    }
+    if cur.len() < klen {
+        return Err(Error::UnexpectedEOF);
+    }
+    let (kbytes, rest) = cur.split_at(klen);
+    *cur = rest;
+    Ok(kbytes)
+}
+
+fn decode_value_opts<'a, T: Build<'a>>(
+    cur: &mut &'a [u8],
+    total: usize,
+    depth: usize,
+    opts: &DecodeOpts,
+) -> core::result::Result<T, Fail> {
+    let start = total - cur.len();
+    let fail = fail_at(start);
+    if depth > opts.max_depth {
+        return Err(fail(Error::DepthExceeded));
+    }
+    if cur.is_empty() {
+        return Err(fail(Error::UnexpectedEOF));
+    }
     let tag = cur[0];
     *cur = &cur[1..];
     match tag {
-        0x00 => Ok(Value::Null),
-        0x01 => Ok(Value::Bool(false)),
-        0x02 => Ok(Value::Bool(true)),
+        0x00 => Ok(T::null()),
+        0x01 => Ok(T::bool(false)),
+        0x02 => Ok(T::bool(true)),
         0x03 => {
             if cur.len() < 8 {
-                return Err(Error::UnexpectedEOF);
+                return Err(fail(Error::UnexpectedEOF));
             }
             let (num, rest) = cur.split_at(8);
             *cur = rest;
             let mut arr = [0u8; 8];
             arr.copy_from_slice(num);
-            Ok(Value::Int(i64::from_be_bytes(arr)))
+            Ok(T::int(i64::from_be_bytes(arr)))
         }
         0x04 => {
-            let len = decode_varint32(cur)? as usize;
+            let len = decode_varint32(cur).map_err(&fail)? as usize;
             if len > opts.max_string_len {
-                return Err(Error::StringTooLong);
+                return Err(fail(Error::StringTooLong));
             }
             if cur.len() < len {
-                return Err(Error::UnexpectedEOF);
+                return Err(fail(Error::UnexpectedEOF));
             }
             let (bytes, rest) = cur.split_at(len);
             *cur = rest;
-            let s = std::str::from_utf8(bytes).map_err(|_| Error::InvalidUTF8)?;
-            if s.chars().any(|c| c == '\u{FEFF}') {
-                return Err(Error::BOMPresent);
-            }
-            if !unicode_normalization::is_nfc(s) {
-                return Err(Error::NotNFC);
-            }
-            Ok(Value::String(s.to_string()))
+            let s = core::str::from_utf8(bytes).map_err(|_| fail(Error::InvalidUTF8))?;
+            validate_nfc(s).map_err(&fail)?;
+            Ok(T::string(s))
         }
         0x05 => {
-            let len = decode_varint32(cur)? as usize;
+            let len = decode_varint32(cur).map_err(&fail)? as usize;
             if len > opts.max_bytes_len {
-                return Err(Error::BytesTooLong);
+                return Err(fail(Error::BytesTooLong));
             }
             if cur.len() < len {
-                return Err(Error::UnexpectedEOF);
+                return Err(fail(Error::UnexpectedEOF));
             }
             let (bytes, rest) = cur.split_at(len);
             *cur = rest;
-            Ok(Value::Bytes(bytes.to_vec()))
+            Ok(T::bytes(bytes))
         }
         0x06 => {
-            let count = decode_varint32(cur)? as usize;
+            let count = decode_varint32(cur).map_err(&fail)? as usize;
             if count > opts.max_array_len {
-                return Err(Error::ArrayTooLong);
+                return Err(fail(Error::ArrayTooLong));
             }
             let mut v = Vec::with_capacity(count.min(1024));
-            for _ in 0..count {
-                v.push(decode_value_opts(cur, depth + 1, opts)?);
+            for i in 0..count {
+                let item = decode_value_opts(cur, total, depth + 1, opts).map_err(|mut f| {
+                    f.segs.push(Seg::Index(i));
+                    f
+                })?;
+                v.push(item);
             }
-            Ok(Value::Array(v))
+            Ok(T::array(v))
         }
         0x07 => {
-            let count = decode_varint32(cur)? as usize;
+            let count = decode_varint32(cur).map_err(&fail)? as usize;
             if count > opts.max_map_len {
-                return Err(Error::MapTooLong);
+                return Err(fail(Error::MapTooLong));
             }
-            let mut map = BTreeMap::new();
-            let mut prev: Option<Vec<u8>> = None;
+            let mut pairs = T::new_map(count);
+            let mut prev: Option<&[u8]> = None;
             for _ in 0..count {
-                // key must be a string (0x04)
-                if cur.is_empty() {
-                    return Err(Error::UnexpectedEOF);
-                }
-                let key_tag = cur[0];
-                *cur = &cur[1..];
-                if key_tag != 0x04 {
-                    return Err(Error::NonStringKey);
-                }
-                let klen = decode_varint32(cur)? as usize;
-                if klen > opts.max_string_len {
-                    return Err(Error::StringTooLong);
-                }
-                if cur.len() < klen {
-                    return Err(Error::UnexpectedEOF);
-                }
-                let (kbytes, rest) = cur.split_at(klen);
-                *cur = rest;
-                let kstr = std::str::from_utf8(kbytes).map_err(|_| Error::InvalidUTF8)?;
-                if kstr.chars().any(|c| c == '\u{FEFF}') {
-                    return Err(Error::BOMPresent);
-                }
-                if !unicode_normalization::is_nfc(kstr) {
-                    return Err(Error::NotNFC);
-                }
-                if let Some(prevb) = prev.as_ref() {
-                    match prevb.as_slice().cmp(kbytes) {
-                        std::cmp::Ordering::Less => {}
-                        std::cmp::Ordering::Equal => return Err(Error::DuplicateKey),
-                        std::cmp::Ordering::Greater => return Err(Error::UnsortedKeys),
+                let key_fail = fail_at(total - cur.len());
+                let kbytes = take_key(cur, opts).map_err(&key_fail)?;
+                let kstr = core::str::from_utf8(kbytes).map_err(|_| key_fail(Error::InvalidUTF8))?;
+                let in_key = |kind| Fail {
+                    segs: vec![Seg::Key(kstr.to_string())],
+                    ..key_fail(kind)
+                };
+                validate_nfc(kstr).map_err(in_key)?;
+                if let Some(prev) = prev {
+                    match prev.cmp(kbytes) {
+                        core::cmp::Ordering::Less => {}
+                        core::cmp::Ordering::Equal => return Err(in_key(Error::DuplicateKey)),
+                        core::cmp::Ordering::Greater => return Err(in_key(Error::UnsortedKeys)),
                     }
                 }
-                prev = Some(kbytes.to_vec());
-                let val = decode_value_opts(cur, depth + 1, opts)?;
-                map.insert(kstr.to_string(), val);
+                let val = decode_value_opts(cur, total, depth + 1, opts).map_err(|mut f| {
+                    f.segs.push(Seg::Key(kstr.to_string()));
+                    f
+                })?;
+                T::push_pair(&mut pairs, kstr, val);
+                prev = Some(kbytes);
             }
-            Ok(Value::Map(map))
+            Ok(T::map(pairs))
         }
-        _ => Err(Error::InvalidTypeTag(tag)),
+        _ => Err(fail(Error::InvalidTypeTag(tag))),
     }
 }
 
@@ -329,8 +830,10 @@ pub fn hash_bytes(data: &[u8]) -> [u8; 32] {
 
 #[cfg_attr(feature = "obs", tracing::instrument(level = "trace", skip_all))]
 pub fn hash_value(v: &Value) -> [u8; 32] {
-    let bytes = encode(v);
-    hash_bytes(&bytes)
+    let mut sink = HashSink::new();
+    sink.put(&MAGIC);
+    encode_value(&mut sink, v);
+    sink.finalize()
 }
 
 /// Alias for `encode` — compat with crates/nrf1 API.
@@ -340,9 +843,7 @@ pub fn encode_stream(value: &Value) -> Vec<u8> {
 
 /// Compute BLAKE3 CID string from a Value: `b3:<hex>`.
 pub fn blake3_cid(value: &Value) -> String {
-    let bytes = encode(value);
-    let hash = blake3::hash(&bytes);
-    format!("b3:{}", hash.to_hex())
+    format!("b3:{}", blake3::Hash::from(hash_value(value)).to_hex())
 }
 
 /// Type alias for backward compat with crates/nrf1.
@@ -359,7 +860,7 @@ pub fn parse_hex_lower(s: &str) -> Result<Vec<u8>> {
     if s.is_empty() {
         return Ok(Vec::new());
     }
-    if s.len() % 2 != 0 {
+    if !s.len().is_multiple_of(2) {
         return Err(Error::HexOddLength);
     }
     for ch in s.chars() {
@@ -382,7 +883,7 @@ pub fn parse_hex_lower(s: &str) -> Result<Vec<u8>> {
 pub fn encode_hex_lower(bytes: &[u8]) -> String {
     let mut s = String::with_capacity(bytes.len() * 2);
     for b in bytes {
-        use std::fmt::Write;
+        use core::fmt::Write;
         let _ = write!(s, "{b:02x}");
     }
     s
@@ -421,12 +922,15 @@ fn decode_varint32(cur: &mut &[u8]) -> Result<u32> {
         *cur = &cur[1..];
         let payload = (byte & 0x7F) as u32;
 
-        // First byte 0x80 => Non-minimal (0 with continuation)
-        if i == 0 && byte == 0x80 {
+        // A final 0x00 after the first byte is a leading zero group in
+        // base-128 (e.g. 0x80 0x00 for 0). A first byte of 0x80 is fine:
+        // 0x80 0x01 is the minimal form of 128.
+        if i > 0 && byte == 0x00 {
             return Err(Error::NonMinimalVarint);
         }
-        // Disallow 0x00 as a continuation byte (leading zero in base-128)
-        if i > 0 && byte == 0x00 {
+        // The 5th byte carries bits 28..31 only; anything higher (or a
+        // continuation) would be dropped by the shift.
+        if i == 4 && byte > 0x0F {
             return Err(Error::NonMinimalVarint);
         }
 
@@ -439,15 +943,15 @@ fn decode_varint32(cur: &mut &[u8]) -> Result<u32> {
     Err(Error::NonMinimalVarint)
 }
 
-fn encode_varint32(buf: &mut Vec<u8>, mut value: u32) {
+fn encode_varint32(buf: &mut impl Sink, mut value: u32) {
     loop {
         let byte = (value & 0x7F) as u8;
         value >>= 7;
         if value == 0 {
-            buf.push(byte);
+            buf.put_byte(byte);
             break;
         } else {
-            buf.push(byte | 0x80);
+            buf.put_byte(byte | 0x80);
         }
     }
 }
@@ -610,6 +1114,88 @@ mod tests {
         assert_eq!(decode_with_opts(&enc, &DecodeOpts::default()).unwrap(), v);
     }
 
+    #[test]
+    fn streaming_paths_match_encode() {
+        let big = "x".repeat(3 * HASH_SINK_BUF);
+        let mut m = BTreeMap::new();
+        m.insert("big".to_string(), Value::String(big));
+        m.insert("bytes".to_string(), Value::Bytes(vec![7; 200]));
+        m.insert("n".to_string(), Value::Int(-1));
+        m.insert(
+            "xs".to_string(),
+            Value::Array((0..300).map(Value::Int).collect()),
+        );
+        for v in [Value::Null, Value::String(String::new()), Value::Map(m)] {
+            let enc = encode(&v);
+            assert_eq!(encoded_len(&v), enc.len());
+
+            #[cfg(feature = "std")]
+            {
+                let mut out = Vec::new();
+                encode_into(&mut out, &v).unwrap();
+                assert_eq!(out, enc);
+            }
+
+            assert_eq!(hash_value(&v), *blake3::hash(&enc).as_bytes());
+            assert_eq!(blake3_cid(&v), format!("b3:{}", blake3::hash(&enc).to_hex()));
+        }
+    }
+
+    #[test]
+    fn encode_with_opts_mirrors_decode_limits() {
+        let opts = DecodeOpts {
+            max_depth: 2,
+            max_total_bytes: 48,
+            max_string_len: 8,
+            max_bytes_len: 4,
+            max_array_len: 3,
+            max_map_len: 2,
+        };
+        let s = |x: &str| Value::String(x.into());
+        let map = |pairs: &[(&str, Value)]| {
+            Value::Map(pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+        };
+        let cases = [
+            (s("123456789"), Error::StringTooLong),
+            (map(&[("123456789", Value::Null)]), Error::StringTooLong),
+            (Value::Bytes(vec![0; 5]), Error::BytesTooLong),
+            (Value::Array(vec![Value::Null; 4]), Error::ArrayTooLong),
+            (
+                map(&[("a", Value::Null), ("b", Value::Null), ("c", Value::Null)]),
+                Error::MapTooLong,
+            ),
+            (
+                Value::Array(vec![Value::Array(vec![Value::Array(vec![Value::Null])])]),
+                Error::DepthExceeded,
+            ),
+            (
+                map(&[("a", Value::Array(vec![s("12345678"); 3])), ("b", s("12345678"))]),
+                Error::SizeExceeded,
+            ),
+            (s("cafe\u{301}"), Error::NotNFC),
+            (s("\u{feff}x"), Error::BOMPresent),
+        ];
+        for (v, want) in cases {
+            assert_eq!(encode_with_opts(&v, &opts), Err(want), "{v:?}");
+            let bytes = encode(&v);
+            assert!(decode_with_opts(&bytes, &opts).is_err(), "{v:?} decodes");
+        }
+
+        let ok = map(&[("a", Value::Array(vec![s("12345678"); 3]))]);
+        let bytes = encode_with_opts(&ok, &opts).unwrap();
+        assert_eq!(bytes, encode(&ok));
+        assert_eq!(decode_with_opts(&bytes, &opts).unwrap(), ok);
+        assert_eq!(try_encode(&ok).unwrap(), bytes);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn encode_into_reports_writer_errors() {
+        let mut short = [0u8; 6];
+        let err = encode_into(&mut &mut short[..], &Value::String("hello".into())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
     #[test]
     fn decode_opts_rejects_oversized_input() {
         let opts = DecodeOpts { max_total_bytes: 10, ..DecodeOpts::default() };
@@ -699,4 +1285,39 @@ mod tests {
         assert_eq!(decode_with_opts(&enc, &DecodeOpts::default()).unwrap_err(), Error::ArrayTooLong);
         assert!(decode_with_opts(&enc, &DecodeOpts::permissive()).is_ok());
     }
+
+    #[test]
+    fn located_error_reports_offset_and_path() {
+        let mut item = BTreeMap::new();
+        item.insert("sku".into(), Value::String("e\u{0301}".into()));
+        let mut items = vec![Value::Int(0); 3];
+        items.push(Value::Map(item));
+        let mut body = BTreeMap::new();
+        body.insert("items".into(), Value::Array(items));
+        let mut env = BTreeMap::new();
+        env.insert("body".into(), Value::Map(body));
+        let mut root = BTreeMap::new();
+        root.insert("env".into(), Value::Map(env));
+        let enc = encode(&Value::Map(root));
+
+        let needle = "e\u{0301}".as_bytes();
+        let at = enc.windows(needle.len()).position(|w| w == needle).unwrap() - 2;
+        let err = decode_located(&enc).unwrap_err();
+        assert_eq!(
+            err,
+            DecodeError { kind: Error::NotNFC, offset: at, path: "env.body.items[3].sku".into() }
+        );
+        assert_eq!(err.to_string(), format!("NotNFC at byte {at} (env.body.items[3].sku)"));
+        assert_eq!(decode(&enc).unwrap_err(), Error::NotNFC);
+    }
+
+    #[test]
+    fn located_key_and_trailing_errors() {
+        let err = decode_located(b"nrf1\x07\x02\x04\x01b\x00\x04\x01a\x00").unwrap_err();
+        assert_eq!((err.kind, err.offset, err.path.as_str()), (Error::UnsortedKeys, 10, "a"));
+        let err = decode_located(b"nrf1\x00\x00").unwrap_err();
+        assert_eq!((err.kind, err.offset, err.path.as_str()), (Error::TrailingData, 5, ""));
+        let err = decode_located(b"nrf1\x07\x01\x04\x03a b\x08").unwrap_err();
+        assert_eq!((err.kind, err.offset, err.path.as_str()), (Error::InvalidTypeTag(8), 11, "[\"a b\"]"));
+    }
 }
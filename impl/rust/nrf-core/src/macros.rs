@@ -0,0 +1,77 @@
+// ---------------------------------------------------------------------------
+// nrf! — Value literals, in the spirit of serde_json::json!
+//
+//   nrf!({
+//       "act": "ATTEST",
+//       "n": 1,
+//       "sig": bytes(sig.to_vec()),      // Bytes must be explicit
+//       "tags": ["a", "b"],
+//       (key_var): null,                 // computed keys in parentheses
+//   })
+//
+// Leaves go through `Value::from`, so floats, `Option`s and bare `Vec<u8>`
+// do not compile (see access.rs). Keys are sorted by the map, as always.
+// ---------------------------------------------------------------------------
+
+/// Build a `Value` from a JSON-like literal. See the module docs.
+#[macro_export]
+macro_rules! nrf {
+    (null) => {
+        $crate::Value::Null
+    };
+    (bytes($b:expr)) => {
+        $crate::Value::Bytes(::core::convert::Into::<$crate::__private::Vec<u8>>::into($b))
+    };
+    ([]) => {
+        $crate::Value::Array($crate::__private::Vec::new())
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::nrf_internal!(@array [] [] $($tt)+))
+    };
+    ({}) => {
+        $crate::Value::Map($crate::__private::BTreeMap::new())
+    };
+    ({ $($tt:tt)+ }) => {{
+        let mut map = $crate::__private::BTreeMap::new();
+        $crate::nrf_internal!(@map map $($tt)+);
+        $crate::Value::Map(map)
+    }};
+    ($other:expr) => {
+        $crate::Value::from($other)
+    };
+}
+
+/// Token munchers behind `nrf!`: split elements / entries on top-level commas.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! nrf_internal {
+    // Arrays: [done elements] [tokens of the current element] rest
+    (@array [$($done:expr,)*] [$($cur:tt)+] , $($rest:tt)*) => {
+        $crate::nrf_internal!(@array [$($done,)* $crate::nrf!($($cur)+),] [] $($rest)*)
+    };
+    (@array [$($done:expr,)*] [$($cur:tt)+]) => {
+        $crate::__private::vec![$($done,)* $crate::nrf!($($cur)+)]
+    };
+    (@array [$($done:expr,)*] []) => {
+        $crate::__private::vec![$($done,)*]
+    };
+    (@array [$($done:expr,)*] [$($cur:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::nrf_internal!(@array [$($done,)*] [$($cur)* $next] $($rest)*)
+    };
+
+    // Maps: a key is one token (string literal, identifier or (expr)).
+    (@map $m:ident) => {};
+    (@map $m:ident $key:tt : $($rest:tt)+) => {
+        $crate::nrf_internal!(@entry $m ($key) [] $($rest)+)
+    };
+    (@entry $m:ident ($key:tt) [$($cur:tt)+] , $($rest:tt)*) => {
+        $m.insert($crate::__private::String::from($key), $crate::nrf!($($cur)+));
+        $crate::nrf_internal!(@map $m $($rest)*);
+    };
+    (@entry $m:ident ($key:tt) [$($cur:tt)+]) => {
+        $m.insert($crate::__private::String::from($key), $crate::nrf!($($cur)+));
+    };
+    (@entry $m:ident ($key:tt) [$($cur:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::nrf_internal!(@entry $m ($key) [$($cur)* $next] $($rest)*)
+    };
+}
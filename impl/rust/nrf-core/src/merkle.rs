@@ -0,0 +1,849 @@
+// ---------------------------------------------------------------------------
+// Merkle commitment mode — prove one field without revealing the rest
+//
+// `blake3_cid` hashes the whole encoding; proving one field means shipping
+// the whole value. `merkle_root` instead commits to every map entry and array
+// element separately, so `prove` can produce a compact inclusion proof
+// (path keys + O(log n) sibling hashes per level) checked by `verify`.
+//
+// Node hashes (BLAKE3, one-byte domain tags):
+//
+//   scalar        H(0x00 || tagged NRF encoding of the scalar)
+//   map entry     H(0x01 || varint(len) || key || node(value))
+//   array elem    H(0x02 || node(elem))
+//   inner         H(0x03 || left || right)          RFC 6962 tree shape
+//   map           H(0x04 || varint(count) || tree(entries))
+//   array         H(0x05 || varint(count) || tree(elems))
+//
+// Empty trees use 32 zero bytes; the count disambiguates. Like `blake3_cid`,
+// the commitment is over the value as given — apply ρ first. Maps that look
+// like `$redacted` markers are ordinary data here.
+//
+// Selective disclosure: a `Disclosure` commits with a per-leaf salt, so a
+// withheld low-entropy field ("yes", an age) cannot be found by hashing
+// guesses. Salts come from a secret seed and the leaf's path and travel in a
+// salt tree shaped like the value:
+//
+//   salted scalar H(0x06 || salt || tagged NRF encoding of the scalar)
+//   salt tree     32-byte salt per disclosed scalar, same map/array shape
+//   withheld      0x07 || node hash (33 bytes) in the salt tree
+//
+// `Disclosure::redact` swaps a subtree for a withheld entry (and, for
+// readers, `{"$redacted": "b3m:<hex>"}` in the value), leaving `root`
+// unchanged. Only the salt tree says what is withheld, so data shaped like
+// a marker cannot stand in for a subtree. Empty maps and arrays carry no
+// salt: a withheld `{}` or `[]` is recognisable.
+// ---------------------------------------------------------------------------
+
+use crate::{encode_value, encode_varint32, push_path_index, push_path_key, Error, Result, Value};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+pub type Hash = [u8; 32];
+
+/// Secret that derives the per-leaf salts of a `Disclosure`.
+pub type Seed = [u8; 32];
+
+/// Key of the single-entry map shown in place of a withheld subtree.
+pub const REDACTED_KEY: &str = "$redacted";
+
+const TAG_SCALAR: u8 = 0x00;
+const TAG_ENTRY: u8 = 0x01;
+const TAG_ELEM: u8 = 0x02;
+const TAG_INNER: u8 = 0x03;
+const TAG_MAP: u8 = 0x04;
+const TAG_ARRAY: u8 = 0x05;
+const TAG_SALTED: u8 = 0x06;
+const TAG_WITHHELD: u8 = 0x07;
+
+/// One step of a path into a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSeg<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// One level of an inclusion proof: where the child sits in its container
+/// and the sibling hashes needed to rebuild the container's tree
+/// (deepest first).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofStep {
+    Map {
+        key: String,
+        index: usize,
+        count: usize,
+        siblings: Vec<Hash>,
+    },
+    Array {
+        index: usize,
+        count: usize,
+        siblings: Vec<Hash>,
+    },
+}
+
+/// Inclusion proof for one value inside a Merkle-committed value.
+/// Steps run from the root down to the proven value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub steps: Vec<ProofStep>,
+}
+
+/// Merkle root of a value.
+pub fn merkle_root(v: &Value) -> Hash {
+    node_hash(v)
+}
+
+/// `b3m:<hex>` — the Merkle root in CID form, distinct from flat `b3:` CIDs.
+pub fn merkle_cid(v: &Value) -> String {
+    format!("b3m:{}", crate::encode_hex_lower(&merkle_root(v)))
+}
+
+/// Build an inclusion proof for the value at `path`.
+pub fn prove(v: &Value, path: &[PathSeg<'_>]) -> Result<Proof> {
+    let mut steps = Vec::with_capacity(path.len());
+    let mut cur = v;
+    let mut at = String::new();
+    for seg in path {
+        match (seg, cur) {
+            (PathSeg::Key(k), Value::Map(m)) => {
+                let Some(index) = m.keys().position(|key| key == k) else {
+                    push_path_key(&mut at, k);
+                    return Err(Error::PathNotFound(at));
+                };
+                let leaves = map_leaves(m);
+                steps.push(ProofStep::Map {
+                    key: (*k).to_string(),
+                    index,
+                    count: leaves.len(),
+                    siblings: audit_path(&leaves, index),
+                });
+                push_path_key(&mut at, k);
+                cur = &m[*k];
+            }
+            (PathSeg::Index(i), Value::Array(items)) if *i < items.len() => {
+                let leaves = array_leaves(items);
+                steps.push(ProofStep::Array {
+                    index: *i,
+                    count: leaves.len(),
+                    siblings: audit_path(&leaves, *i),
+                });
+                push_path_index(&mut at, *i);
+                cur = &items[*i];
+            }
+            (PathSeg::Key(k), _) => {
+                push_path_key(&mut at, k);
+                return Err(Error::PathNotFound(at));
+            }
+            (PathSeg::Index(i), _) => {
+                push_path_index(&mut at, *i);
+                return Err(Error::PathNotFound(at));
+            }
+        }
+    }
+    Ok(Proof { steps })
+}
+
+/// Check that `leaf` sits at `proof.path()` inside the value committed by `root`.
+pub fn verify(root: &Hash, leaf: &Value, proof: &Proof) -> bool {
+    proof.root_for(leaf).is_some_and(|r| &r == root)
+}
+
+/// `verify` against a `b3m:<hex>` root as produced by `merkle_cid`.
+pub fn verify_cid(root_cid: &str, leaf: &Value, proof: &Proof) -> bool {
+    parse_cid(root_cid).is_some_and(|root| verify(&root, leaf, proof))
+}
+
+fn parse_cid(cid: &str) -> Option<Hash> {
+    cid.strip_prefix("b3m:")
+        .and_then(|h| crate::parse_hex_lower(h).ok())
+        .and_then(|b| Hash::try_from(b).ok())
+}
+
+impl Proof {
+    /// The proven path in the same notation as decode errors and diffs.
+    pub fn path(&self) -> String {
+        let mut out = String::new();
+        for step in &self.steps {
+            match step {
+                ProofStep::Map { key, .. } => push_path_key(&mut out, key),
+                ProofStep::Array { index, .. } => push_path_index(&mut out, *index),
+            }
+        }
+        out
+    }
+
+    /// Recompute the root implied by `leaf` and this proof; `None` if the
+    /// proof is malformed (index out of range, wrong number of siblings).
+    pub fn root_for(&self, leaf: &Value) -> Option<Hash> {
+        let mut h = node_hash(leaf);
+        for step in self.steps.iter().rev() {
+            h = match step {
+                ProofStep::Map {
+                    key,
+                    index,
+                    count,
+                    siblings,
+                } => {
+                    let leaf = entry_hash(key, &h);
+                    container_hash(
+                        TAG_MAP,
+                        *count,
+                        &root_from_path(leaf, *index, *count, siblings)?,
+                    )
+                }
+                ProofStep::Array {
+                    index,
+                    count,
+                    siblings,
+                } => {
+                    let leaf = tagged(TAG_ELEM, &[&h]);
+                    container_hash(
+                        TAG_ARRAY,
+                        *count,
+                        &root_from_path(leaf, *index, *count, siblings)?,
+                    )
+                }
+            };
+        }
+        Some(h)
+    }
+
+    /// NRF form, so proofs can be encoded and shipped next to a receipt:
+    /// `[{"count", "index", "key"?, "siblings": [bytes]}, ...]`.
+    pub fn to_value(&self) -> Value {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                let mut m = BTreeMap::new();
+                let (index, count, siblings) = match step {
+                    ProofStep::Map {
+                        key,
+                        index,
+                        count,
+                        siblings,
+                    } => {
+                        m.insert("key".to_string(), Value::String(key.clone()));
+                        (index, count, siblings)
+                    }
+                    ProofStep::Array {
+                        index,
+                        count,
+                        siblings,
+                    } => (index, count, siblings),
+                };
+                m.insert("count".to_string(), Value::Int(*count as i64));
+                m.insert("index".to_string(), Value::Int(*index as i64));
+                let sib = siblings.iter().map(|s| Value::Bytes(s.to_vec())).collect();
+                m.insert("siblings".to_string(), Value::Array(sib));
+                Value::Map(m)
+            })
+            .collect();
+        Value::Array(steps)
+    }
+
+    /// Inverse of `to_value`.
+    pub fn from_value(v: &Value) -> Result<Proof> {
+        let bad = |why: &str| Error::Merkle(why.to_string());
+        let Value::Array(items) = v else {
+            return Err(bad("proof must be an array"));
+        };
+        let mut steps = Vec::with_capacity(items.len());
+        for item in items {
+            let Value::Map(m) = item else {
+                return Err(bad("proof step must be a map"));
+            };
+            let uint = |name: &str| match m.get(name) {
+                Some(Value::Int(n)) if *n >= 0 => Ok(*n as usize),
+                _ => Err(bad(&format!("step.{name} must be a non-negative int"))),
+            };
+            let (index, count) = (uint("index")?, uint("count")?);
+            let Some(Value::Array(sib)) = m.get("siblings") else {
+                return Err(bad("step.siblings must be an array"));
+            };
+            let siblings = sib
+                .iter()
+                .map(|s| match s {
+                    Value::Bytes(b) => b
+                        .as_slice()
+                        .try_into()
+                        .map_err(|_| bad("sibling must be 32 bytes")),
+                    _ => Err(bad("sibling must be 32 bytes")),
+                })
+                .collect::<Result<Vec<Hash>>>()?;
+            let known = if m.contains_key("key") { 4 } else { 3 };
+            if m.len() != known {
+                return Err(bad("unknown field in proof step"));
+            }
+            steps.push(match m.get("key") {
+                Some(Value::String(key)) => ProofStep::Map {
+                    key: key.clone(),
+                    index,
+                    count,
+                    siblings,
+                },
+                Some(_) => return Err(bad("step.key must be a string")),
+                None => ProofStep::Array {
+                    index,
+                    count,
+                    siblings,
+                },
+            });
+        }
+        Ok(Proof { steps })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Selective disclosure
+// ---------------------------------------------------------------------------
+
+/// A value committed with per-leaf salts, some subtrees possibly withheld.
+/// `root` is the same before and after `redact`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disclosure {
+    /// The value, with `{"$redacted": "b3m:<hex>"}` in place of withheld
+    /// subtrees.
+    pub value: Value,
+    /// Same shape as `value`: a 32-byte salt per scalar, `0x07 || node hash`
+    /// per withheld subtree.
+    pub salts: Value,
+}
+
+impl Disclosure {
+    /// Disclose all of `v`, salting each scalar from `seed` and its path.
+    pub fn new(v: &Value, seed: &Seed) -> Disclosure {
+        Disclosure {
+            value: v.clone(),
+            salts: salt_tree(v, seed, &mut Vec::new()),
+        }
+    }
+
+    /// Withhold the subtree at `path`. Withholding the root or an already
+    /// withheld subtree is allowed.
+    pub fn redact(&mut self, path: &[PathSeg<'_>]) -> Result<()> {
+        let mut at = String::new();
+        let (v, s) = descend(&mut self.value, &mut self.salts, path, &mut at)?;
+        let h = disclosed_hash(v, s, &mut at)?;
+        *v = redacted_marker(&h);
+        *s = withheld(&h);
+        Ok(())
+    }
+
+    /// Salted Merkle root; fails if `salts` does not fit `value`.
+    pub fn root(&self) -> Result<Hash> {
+        disclosed_hash(&self.value, &self.salts, &mut String::new())
+    }
+
+    /// `b3m:<hex>` form of `root`.
+    pub fn cid(&self) -> Result<String> {
+        Ok(format!("b3m:{}", crate::encode_hex_lower(&self.root()?)))
+    }
+
+    pub fn verify(&self, root: &Hash) -> bool {
+        self.root().is_ok_and(|r| &r == root)
+    }
+
+    /// `verify` against a `b3m:<hex>` root as produced by `cid`.
+    pub fn verify_cid(&self, root_cid: &str) -> bool {
+        parse_cid(root_cid).is_some_and(|root| self.verify(&root))
+    }
+
+    /// NRF form: `{"salts": ..., "value": ...}`.
+    pub fn to_value(&self) -> Value {
+        Value::Map(BTreeMap::from([
+            ("salts".to_string(), self.salts.clone()),
+            ("value".to_string(), self.value.clone()),
+        ]))
+    }
+
+    /// Inverse of `to_value`. The salt tree is checked by `root`.
+    pub fn from_value(v: &Value) -> Result<Disclosure> {
+        match v {
+            Value::Map(m) if m.len() == 2 => match (m.get("salts"), m.get("value")) {
+                (Some(salts), Some(value)) => Ok(Disclosure {
+                    value: value.clone(),
+                    salts: salts.clone(),
+                }),
+                _ => Err(Error::Merkle("disclosure must be {salts, value}".into())),
+            },
+            _ => Err(Error::Merkle("disclosure must be {salts, value}".into())),
+        }
+    }
+}
+
+fn redacted_marker(h: &Hash) -> Value {
+    let cid = format!("b3m:{}", crate::encode_hex_lower(h));
+    Value::Map(BTreeMap::from([(
+        REDACTED_KEY.to_string(),
+        Value::String(cid),
+    )]))
+}
+
+fn withheld(h: &Hash) -> Value {
+    let mut b = Vec::with_capacity(33);
+    b.push(TAG_WITHHELD);
+    b.extend_from_slice(h);
+    Value::Bytes(b)
+}
+
+fn withheld_hash(salts: &Value) -> Option<Hash> {
+    match salts {
+        Value::Bytes(b) if b.len() == 33 && b[0] == TAG_WITHHELD => b[1..].try_into().ok(),
+        _ => None,
+    }
+}
+
+fn salt_tree(v: &Value, seed: &Seed, path: &mut Vec<Value>) -> Value {
+    match v {
+        Value::Map(m) => Value::Map(
+            m.iter()
+                .map(|(k, child)| {
+                    path.push(Value::String(k.clone()));
+                    let s = salt_tree(child, seed, path);
+                    path.pop();
+                    (k.clone(), s)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, child)| {
+                    path.push(Value::Int(i as i64));
+                    let s = salt_tree(child, seed, path);
+                    path.pop();
+                    s
+                })
+                .collect(),
+        ),
+        _ => {
+            let mut buf = Vec::new();
+            encode_value(&mut buf, &Value::Array(path.clone()));
+            Value::Bytes(blake3::keyed_hash(seed, &buf).as_bytes().to_vec())
+        }
+    }
+}
+
+fn descend<'v>(
+    mut v: &'v mut Value,
+    mut s: &'v mut Value,
+    path: &[PathSeg<'_>],
+    at: &mut String,
+) -> Result<(&'v mut Value, &'v mut Value)> {
+    for seg in path {
+        let child = match (seg, v, s) {
+            (PathSeg::Key(k), Value::Map(m), Value::Map(sm)) => {
+                push_path_key(at, k);
+                m.get_mut(*k).zip(sm.get_mut(*k))
+            }
+            (PathSeg::Index(i), Value::Array(items), Value::Array(si)) => {
+                push_path_index(at, *i);
+                items.get_mut(*i).zip(si.get_mut(*i))
+            }
+            (PathSeg::Key(k), _, _) => {
+                push_path_key(at, k);
+                None
+            }
+            (PathSeg::Index(i), _, _) => {
+                push_path_index(at, *i);
+                None
+            }
+        };
+        (v, s) = child.ok_or_else(|| Error::PathNotFound(at.clone()))?;
+    }
+    Ok((v, s))
+}
+
+fn disclosed_hash(v: &Value, salts: &Value, at: &mut String) -> Result<Hash> {
+    let bad = |at: &str, why: &str| Error::Merkle(format!("{at}: {why}"));
+    if let Some(h) = withheld_hash(salts) {
+        if *v != redacted_marker(&h) {
+            return Err(bad(at, "withheld subtree must show its marker"));
+        }
+        return Ok(h);
+    }
+    match (v, salts) {
+        (Value::Map(m), Value::Map(sm)) => {
+            if !m.keys().eq(sm.keys()) {
+                return Err(bad(at, "salt keys do not match"));
+            }
+            let mut leaves = Vec::with_capacity(m.len());
+            for ((k, child), s) in m.iter().zip(sm.values()) {
+                let len = at.len();
+                push_path_key(at, k);
+                leaves.push(entry_hash(k, &disclosed_hash(child, s, at)?));
+                at.truncate(len);
+            }
+            Ok(container_hash(TAG_MAP, m.len(), &tree_root(&leaves)))
+        }
+        (Value::Array(items), Value::Array(si)) => {
+            if items.len() != si.len() {
+                return Err(bad(at, "salt count does not match"));
+            }
+            let mut leaves = Vec::with_capacity(items.len());
+            for (i, (child, s)) in items.iter().zip(si).enumerate() {
+                let len = at.len();
+                push_path_index(at, i);
+                leaves.push(tagged(TAG_ELEM, &[&disclosed_hash(child, s, at)?]));
+                at.truncate(len);
+            }
+            Ok(container_hash(TAG_ARRAY, items.len(), &tree_root(&leaves)))
+        }
+        (Value::Map(_) | Value::Array(_), _) => Err(bad(at, "salt shape does not match")),
+        (scalar, Value::Bytes(salt)) if salt.len() == 32 => {
+            let mut buf = Vec::new();
+            encode_value(&mut buf, scalar);
+            Ok(tagged(TAG_SALTED, &[salt, &buf]))
+        }
+        _ => Err(bad(at, "scalar needs a 32-byte salt")),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Hashing
+// ---------------------------------------------------------------------------
+
+fn tagged(tag: u8, parts: &[&[u8]]) -> Hash {
+    let mut h = blake3::Hasher::new();
+    h.update(&[tag]);
+    for p in parts {
+        h.update(p);
+    }
+    *h.finalize().as_bytes()
+}
+
+fn node_hash(v: &Value) -> Hash {
+    match v {
+        Value::Map(m) => container_hash(TAG_MAP, m.len(), &tree_root(&map_leaves(m))),
+        Value::Array(items) => {
+            container_hash(TAG_ARRAY, items.len(), &tree_root(&array_leaves(items)))
+        }
+        scalar => {
+            let mut buf = Vec::new();
+            encode_value(&mut buf, scalar);
+            tagged(TAG_SCALAR, &[&buf])
+        }
+    }
+}
+
+fn container_hash(tag: u8, count: usize, root: &Hash) -> Hash {
+    let mut n = Vec::with_capacity(5);
+    encode_varint32(&mut n, count as u32);
+    tagged(tag, &[&n, root])
+}
+
+fn entry_hash(key: &str, child: &Hash) -> Hash {
+    let mut n = Vec::with_capacity(5);
+    encode_varint32(&mut n, key.len() as u32);
+    tagged(TAG_ENTRY, &[&n, key.as_bytes(), child])
+}
+
+fn map_leaves(m: &BTreeMap<String, Value>) -> Vec<Hash> {
+    m.iter()
+        .map(|(k, v)| entry_hash(k, &node_hash(v)))
+        .collect()
+}
+
+fn array_leaves(items: &[Value]) -> Vec<Hash> {
+    items
+        .iter()
+        .map(|v| tagged(TAG_ELEM, &[&node_hash(v)]))
+        .collect()
+}
+
+// RFC 6962 §2.1 shape: split at the largest power of two below n.
+fn split(n: usize) -> usize {
+    let mut k = 1;
+    while k * 2 < n {
+        k *= 2;
+    }
+    k
+}
+
+fn tree_root(leaves: &[Hash]) -> Hash {
+    match leaves.len() {
+        0 => [0u8; 32],
+        1 => leaves[0],
+        n => {
+            let k = split(n);
+            tagged(
+                TAG_INNER,
+                &[&tree_root(&leaves[..k]), &tree_root(&leaves[k..])],
+            )
+        }
+    }
+}
+
+fn audit_path(leaves: &[Hash], m: usize) -> Vec<Hash> {
+    let n = leaves.len();
+    if n <= 1 {
+        return Vec::new();
+    }
+    let k = split(n);
+    let (mut path, sibling) = if m < k {
+        (audit_path(&leaves[..k], m), tree_root(&leaves[k..]))
+    } else {
+        (audit_path(&leaves[k..], m - k), tree_root(&leaves[..k]))
+    };
+    path.push(sibling);
+    path
+}
+
+fn root_from_path(leaf: Hash, m: usize, n: usize, path: &[Hash]) -> Option<Hash> {
+    if m >= n {
+        return None;
+    }
+    if n == 1 {
+        return path.is_empty().then_some(leaf);
+    }
+    let (sibling, rest) = path.split_last()?;
+    let k = split(n);
+    Some(if m < k {
+        tagged(TAG_INNER, &[&root_from_path(leaf, m, k, rest)?, sibling])
+    } else {
+        tagged(
+            TAG_INNER,
+            &[sibling, &root_from_path(leaf, m - k, n - k, rest)?],
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_diag;
+    use alloc::vec;
+
+    fn body() -> Value {
+        from_diag(
+            r#"{"amount": 1200, "items": [{"sku": "A"}, {"sku": "B"}, {"sku": "C"}], "party": {"name": "ACME", "tax_id": "X-1"}, "z": null}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn proves_nested_fields() {
+        let v = body();
+        let root = merkle_root(&v);
+        for (path, leaf) in [
+            (vec![PathSeg::Key("amount")], Value::Int(1200)),
+            (
+                vec![PathSeg::Key("party"), PathSeg::Key("tax_id")],
+                Value::String("X-1".into()),
+            ),
+            (
+                vec![PathSeg::Key("items"), PathSeg::Index(2)],
+                from_diag(r#"{"sku": "C"}"#).unwrap(),
+            ),
+            (vec![], v.clone()),
+        ] {
+            let proof = prove(&v, &path).unwrap();
+            assert!(verify(&root, &leaf, &proof), "{}", proof.path());
+            assert!(!verify(&root, &Value::Int(1), &proof));
+        }
+    }
+
+    #[test]
+    fn every_index_verifies() {
+        for n in 0..9 {
+            let v = Value::Array((0..n).map(Value::Int).collect());
+            let root = merkle_root(&v);
+            for i in 0..n as usize {
+                let proof = prove(&v, &[PathSeg::Index(i)]).unwrap();
+                assert!(verify(&root, &Value::Int(i as i64), &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_proofs_fail() {
+        let v = body();
+        let root = merkle_root(&v);
+        let proof = prove(&v, &[PathSeg::Key("party"), PathSeg::Key("name")]).unwrap();
+        let leaf = Value::String("ACME".into());
+        assert_eq!(proof.path(), "party.name");
+
+        let mut renamed = proof.clone();
+        if let ProofStep::Map { key, .. } = &mut renamed.steps[1] {
+            *key = "tax_id".into();
+        }
+        assert!(!verify(&root, &leaf, &renamed));
+
+        let mut moved = proof.clone();
+        if let ProofStep::Map { index, .. } = &mut moved.steps[0] {
+            *index += 1;
+        }
+        assert!(!verify(&root, &leaf, &moved));
+
+        let mut short = proof;
+        if let ProofStep::Map { siblings, .. } = &mut short.steps[0] {
+            siblings.pop();
+        }
+        assert_eq!(short.root_for(&leaf), None);
+    }
+
+    #[test]
+    fn root_distinguishes_shapes() {
+        let roots = [
+            merkle_root(&from_diag("[]").unwrap()),
+            merkle_root(&from_diag("{}").unwrap()),
+            merkle_root(&from_diag("[null]").unwrap()),
+            merkle_root(&from_diag(r#"{"": null}"#).unwrap()),
+            merkle_root(&from_diag("null").unwrap()),
+            merkle_root(&from_diag("[[null]]").unwrap()),
+        ];
+        for i in 0..roots.len() {
+            for j in i + 1..roots.len() {
+                assert_ne!(roots[i], roots[j]);
+            }
+        }
+        assert!(merkle_cid(&Value::Null).starts_with("b3m:"));
+    }
+
+    #[test]
+    fn verify_cid_parses_root() {
+        let v = body();
+        let proof = prove(&v, &[PathSeg::Key("amount")]).unwrap();
+        let cid = merkle_cid(&v);
+        assert!(verify_cid(&cid, &Value::Int(1200), &proof));
+        assert!(!verify_cid(
+            &cid.replacen("b3m:", "b3:", 1),
+            &Value::Int(1200),
+            &proof
+        ));
+        assert!(!verify_cid("b3m:00", &Value::Int(1200), &proof));
+    }
+
+    const SEED: Seed = [7u8; 32];
+
+    #[test]
+    fn redaction_preserves_disclosure_root() {
+        let mut d = Disclosure::new(&body(), &SEED);
+        let root = d.root().unwrap();
+        assert_ne!(root, merkle_root(&body()));
+        d.redact(&[PathSeg::Key("party")]).unwrap();
+        d.redact(&[PathSeg::Key("items"), PathSeg::Index(0)])
+            .unwrap();
+        assert!(d.verify(&root));
+        assert!(d.verify_cid(&Disclosure::new(&body(), &SEED).cid().unwrap()));
+        let Value::Map(m) = &d.value else {
+            unreachable!()
+        };
+        assert!(matches!(&m["party"], Value::Map(p) if p.contains_key(REDACTED_KEY)));
+
+        // Withheld subtrees cannot be walked into; redacting twice is a no-op.
+        assert_eq!(
+            d.redact(&[PathSeg::Key("party"), PathSeg::Key("name")]),
+            Err(Error::PathNotFound("party.name".into()))
+        );
+        let again = d.clone();
+        d.redact(&[PathSeg::Key("party")]).unwrap();
+        assert_eq!(d, again);
+        d.redact(&[]).unwrap();
+        assert!(d.verify(&root));
+    }
+
+    #[test]
+    fn marker_shaped_data_is_not_a_commitment() {
+        // Data that looks like the marker of a withheld subtree hashes as a
+        // plain map, so it cannot stand in for that subtree.
+        let mut d = Disclosure::new(&body(), &SEED);
+        let root = d.root().unwrap();
+        d.redact(&[PathSeg::Key("amount")]).unwrap();
+        let forged = Disclosure::new(&d.value, &SEED);
+        assert!(!forged.verify(&root));
+        assert_ne!(merkle_root(&d.value), merkle_root(&body()));
+
+        // A marker in the value must agree with the salt tree.
+        let mut swapped = d.clone();
+        let Value::Map(m) = &mut swapped.value else {
+            unreachable!()
+        };
+        m.insert("amount".into(), Value::Int(1200));
+        assert!(swapped.root().is_err());
+    }
+
+    #[test]
+    fn salts_hide_withheld_leaves() {
+        let v = from_diag(r#"{"ok": true}"#).unwrap();
+        let mut a = Disclosure::new(&v, &SEED);
+        let mut b = Disclosure::new(&v, &[8u8; 32]);
+        a.redact(&[PathSeg::Key("ok")]).unwrap();
+        b.redact(&[PathSeg::Key("ok")]).unwrap();
+        assert_ne!(a.value, b.value);
+        assert_ne!(a.root().unwrap(), b.root().unwrap());
+    }
+
+    #[test]
+    fn tampered_disclosures_fail() {
+        let d = Disclosure::new(&body(), &SEED);
+        let root = d.root().unwrap();
+
+        let mut changed = d.clone();
+        let Value::Map(m) = &mut changed.value else {
+            unreachable!()
+        };
+        m.insert("amount".into(), Value::Int(1201));
+        assert!(!changed.verify(&root));
+
+        let mut short = d.clone();
+        let Value::Map(m) = &mut short.salts else {
+            unreachable!()
+        };
+        m.remove("z");
+        assert!(short.root().is_err());
+
+        let mut bad_salt = d;
+        let Value::Map(m) = &mut bad_salt.salts else {
+            unreachable!()
+        };
+        m.insert("z".into(), Value::Bytes(vec![0; 31]));
+        assert!(bad_salt.root().is_err());
+    }
+
+    #[test]
+    fn disclosure_value_roundtrip() {
+        let mut d = Disclosure::new(&body(), &SEED);
+        d.redact(&[PathSeg::Key("party"), PathSeg::Key("tax_id")])
+            .unwrap();
+        let bytes = crate::encode(&d.to_value());
+        let back = Disclosure::from_value(&crate::decode(&bytes).unwrap()).unwrap();
+        assert_eq!(back, d);
+        assert!(Disclosure::from_value(&d.value).is_err());
+    }
+
+    #[test]
+    fn missing_path_is_reported() {
+        let v = body();
+        assert_eq!(
+            prove(&v, &[PathSeg::Key("party"), PathSeg::Key("nope")]),
+            Err(Error::PathNotFound("party.nope".into()))
+        );
+        assert_eq!(
+            prove(&v, &[PathSeg::Key("items"), PathSeg::Index(3)]),
+            Err(Error::PathNotFound("items[3]".into()))
+        );
+    }
+
+    #[test]
+    fn proof_value_roundtrip() {
+        let v = body();
+        let proof = prove(
+            &v,
+            &[
+                PathSeg::Key("items"),
+                PathSeg::Index(1),
+                PathSeg::Key("sku"),
+            ],
+        )
+        .unwrap();
+        let bytes = crate::encode(&proof.to_value());
+        let back = Proof::from_value(&crate::decode(&bytes).unwrap()).unwrap();
+        assert_eq!(back, proof);
+        assert!(Proof::from_value(&Value::Int(0)).is_err());
+    }
+}
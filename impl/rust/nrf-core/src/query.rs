@@ -0,0 +1,808 @@
+// ---------------------------------------------------------------------------
+// Path queries over Value — one path language for every capability
+//
+// Three spellings, picked by the first character:
+//
+//   a.b.0             dotted (manifests): a segment is a map key, or an
+//                     array index where the value is an array
+//   /a/b/0            JSON Pointer (RFC 6901): ~1 = '/', ~0 = '~',
+//                     '-' = one past the end of an array (set only)
+//   $.a['b.c'][0]     JSONPath subset:
+//                       .key  ['key']  ["key"]  [n]  (n < 0 counts from the end)
+//                       .*  [*]                 every element / map value
+//                       [start:end:step]        array slice
+//                       [?(@.qty >= 1 && @.sku != 'X')]   predicate
+//                     (no recursive descent `..`, no unions)
+//
+// `select` returns every match. `get` / `get_mut` / `set` / `delete` need a
+// singular path (no wildcard, slice or predicate). Syntax errors and misuse
+// are `Error::Query`; a path that points at nothing is
+// `Error::PathNotFound(<concrete path>)`.
+// ---------------------------------------------------------------------------
+
+use crate::merkle::PathSeg;
+use crate::{push_path_index, push_path_key, Error, Result, Value};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A parsed path. Parse once, evaluate against many values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    segs: Vec<Seg>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Seg {
+    /// Dotted / pointer segment: map key, or array index on arrays.
+    Child(String),
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Slice(Option<i64>, Option<i64>, i64),
+    Filter(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Exists(Vec<Seg>),
+    Cmp(Vec<Seg>, Op, Value),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// All values matched by `path`.
+pub fn select<'a>(v: &'a Value, path: &str) -> Result<Vec<&'a Value>> {
+    Ok(Query::parse(path)?.select(v))
+}
+
+/// The single value at `path`.
+pub fn get<'a>(v: &'a Value, path: &str) -> Result<&'a Value> {
+    Query::parse(path)?.get(v)
+}
+
+/// Write `new` at `path`, creating intermediate maps.
+pub fn set(v: &mut Value, path: &str, new: Value) -> Result<()> {
+    Query::parse(path)?.set(v, new)
+}
+
+/// Remove and return the value at `path`.
+pub fn delete(v: &mut Value, path: &str) -> Result<Value> {
+    Query::parse(path)?.delete(v)
+}
+
+impl core::str::FromStr for Query {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Query> {
+        Query::parse(s)
+    }
+}
+
+impl Query {
+    pub fn parse(path: &str) -> Result<Query> {
+        let segs = if let Some(rest) = path.strip_prefix('$') {
+            let mut p = Parser { s: rest, pos: 0 };
+            let segs = p.segments(false)?;
+            if p.pos != rest.len() {
+                return Err(p.err("unexpected character"));
+            }
+            segs
+        } else if let Some(rest) = path.strip_prefix('/') {
+            rest.split('/')
+                .map(|t| unescape_pointer(t).map(Seg::Child))
+                .collect::<Result<_>>()?
+        } else if path.is_empty() {
+            Vec::new()
+        } else {
+            path.split('.')
+                .map(|t| match t {
+                    "" => Err(Error::Query(format!("empty segment in '{path}'"))),
+                    t => Ok(Seg::Child(t.to_string())),
+                })
+                .collect::<Result<_>>()?
+        };
+        Ok(Query { segs })
+    }
+
+    /// True if the path can match at most one value.
+    pub fn is_singular(&self) -> bool {
+        self.segs.iter().all(Seg::is_singular)
+    }
+
+    /// All matches, in document order.
+    pub fn select<'a>(&self, v: &'a Value) -> Vec<&'a Value> {
+        self.select_paths(v).into_iter().map(|(_, v)| v).collect()
+    }
+
+    /// All matches with their concrete paths, e.g. for `Disclosure::redact`.
+    pub fn select_paths<'a>(&self, v: &'a Value) -> Vec<(Vec<PathSeg<'a>>, &'a Value)> {
+        let mut cur = vec![(Vec::new(), v)];
+        for seg in &self.segs {
+            let mut next = Vec::new();
+            for (path, node) in cur {
+                for (step, child) in seg.step(node) {
+                    let mut p = path.clone();
+                    p.push(step);
+                    next.push((p, child));
+                }
+            }
+            cur = next;
+        }
+        cur
+    }
+
+    pub fn get<'a>(&self, v: &'a Value) -> Result<&'a Value> {
+        self.require_singular("get")?;
+        let mut at = String::new();
+        let mut cur = v;
+        for seg in &self.segs {
+            seg.push_to(&mut at, cur);
+            cur = match seg.step(cur).pop() {
+                Some((_, child)) => child,
+                None => return Err(Error::PathNotFound(at)),
+            };
+        }
+        Ok(cur)
+    }
+
+    pub fn get_mut<'a>(&self, v: &'a mut Value) -> Result<&'a mut Value> {
+        self.require_singular("get_mut")?;
+        walk_mut(v, &self.segs, &mut String::new())
+    }
+
+    /// Write `new` at this path. Missing map keys are created (as empty maps
+    /// on the way down); array elements must exist, except pointer `-` which
+    /// appends. Descending into a scalar is a collision error.
+    pub fn set(&self, v: &mut Value, new: Value) -> Result<()> {
+        self.require_singular("set")?;
+        set_at(v, &self.segs, new, &mut String::new())
+    }
+
+    /// Remove and return the value at this path.
+    pub fn delete(&self, v: &mut Value) -> Result<Value> {
+        self.require_singular("delete")?;
+        let Some((last, parent)) = self.segs.split_last() else {
+            return Err(Error::Query("cannot delete the root".into()));
+        };
+        let mut at = String::new();
+        let parent = walk_mut(v, parent, &mut at)?;
+        last.push_to(&mut at, parent);
+        let removed = match (last, parent) {
+            (Seg::Child(k) | Seg::Key(k), Value::Map(m)) => m.remove(k),
+            (seg, Value::Array(items)) => seg.index_in(items.len()).map(|i| items.remove(i)),
+            _ => None,
+        };
+        removed.ok_or(Error::PathNotFound(at))
+    }
+
+    fn require_singular(&self, what: &str) -> Result<()> {
+        if self.is_singular() {
+            Ok(())
+        } else {
+            Err(Error::Query(format!(
+                "{what} needs a singular path (no wildcard, slice or predicate)"
+            )))
+        }
+    }
+}
+
+fn walk_mut<'a>(v: &'a mut Value, segs: &[Seg], at: &mut String) -> Result<&'a mut Value> {
+    let Some((seg, rest)) = segs.split_first() else {
+        return Ok(v);
+    };
+    seg.push_to(at, v);
+    let child = match (seg, v) {
+        (Seg::Child(k) | Seg::Key(k), Value::Map(m)) => m.get_mut(k),
+        (seg, Value::Array(items)) => match seg.index_in(items.len()) {
+            Some(i) => items.get_mut(i),
+            None => None,
+        },
+        _ => None,
+    };
+    match child {
+        Some(c) => walk_mut(c, rest, at),
+        None => Err(Error::PathNotFound(at.clone())),
+    }
+}
+
+fn set_at(v: &mut Value, segs: &[Seg], new: Value, at: &mut String) -> Result<()> {
+    let Some((seg, rest)) = segs.split_first() else {
+        *v = new;
+        return Ok(());
+    };
+    let parent = at.clone();
+    seg.push_to(at, v);
+    match (seg, v) {
+        (Seg::Child(k) | Seg::Key(k), Value::Map(m)) => {
+            let child = m
+                .entry(k.clone())
+                .or_insert_with(|| Value::Map(BTreeMap::new()));
+            set_at(child, rest, new, at)
+        }
+        (Seg::Child(k), Value::Array(items)) if k == "-" && rest.is_empty() => {
+            items.push(new);
+            Ok(())
+        }
+        (seg, Value::Array(items)) if !matches!(seg, Seg::Key(_)) => {
+            match seg.index_in(items.len()) {
+                Some(i) => set_at(&mut items[i], rest, new, at),
+                None => Err(Error::PathNotFound(at.clone())),
+            }
+        }
+        _ => Err(Error::Query(format!(
+            "path collision at {}: not a container",
+            if parent.is_empty() { "<root>" } else { &parent }
+        ))),
+    }
+}
+
+impl Seg {
+    fn is_singular(&self) -> bool {
+        matches!(self, Seg::Child(_) | Seg::Key(_) | Seg::Index(_))
+    }
+
+    /// Array position for an index-like segment, if in bounds.
+    fn index_in(&self, len: usize) -> Option<usize> {
+        match self {
+            Seg::Child(s) => parse_index(s).filter(|i| *i < len),
+            Seg::Index(i) => resolve_index(*i, len),
+            _ => None,
+        }
+    }
+
+    /// Append this segment to a concrete path string (for error messages).
+    fn push_to(&self, at: &mut String, v: &Value) {
+        match (self, v) {
+            (Seg::Child(s), Value::Array(items)) => match parse_index(s) {
+                Some(i) if i < items.len() => push_path_index(at, i),
+                _ => push_path_key(at, s),
+            },
+            (Seg::Index(i), Value::Array(items)) => match resolve_index(*i, items.len()) {
+                Some(i) => push_path_index(at, i),
+                None => at.push_str(&format!("[{i}]")),
+            },
+            (Seg::Index(i), _) => at.push_str(&format!("[{i}]")),
+            (Seg::Child(k) | Seg::Key(k), _) => push_path_key(at, k),
+            _ => at.push_str("[*]"),
+        }
+    }
+
+    fn step<'a>(&self, v: &'a Value) -> Vec<(PathSeg<'a>, &'a Value)> {
+        match (self, v) {
+            (Seg::Child(k) | Seg::Key(k), Value::Map(m)) => m
+                .get_key_value(k.as_str())
+                .map(|(k, c)| (PathSeg::Key(k.as_str()), c))
+                .into_iter()
+                .collect(),
+            (Seg::Child(_) | Seg::Index(_), Value::Array(items)) => self
+                .index_in(items.len())
+                .map(|i| (PathSeg::Index(i), &items[i]))
+                .into_iter()
+                .collect(),
+            (Seg::Wildcard, _) => children(v),
+            (Seg::Slice(start, end, step), Value::Array(items)) => {
+                slice_indices(items.len(), *start, *end, *step)
+                    .into_iter()
+                    .map(|i| (PathSeg::Index(i), &items[i]))
+                    .collect()
+            }
+            (Seg::Filter(e), _) => children(v).into_iter().filter(|(_, c)| e.eval(c)).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn children(v: &Value) -> Vec<(PathSeg<'_>, &Value)> {
+    match v {
+        Value::Map(m) => m
+            .iter()
+            .map(|(k, c)| (PathSeg::Key(k.as_str()), c))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (PathSeg::Index(i), c))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Canonical decimal array index: `0`, `7`, `42` — no sign, no leading zeros.
+fn parse_index(s: &str) -> Option<usize> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) || (s.len() > 1 && s.starts_with('0'))
+    {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn resolve_index(i: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let i = if i < 0 { len + i } else { i };
+    (0..len).contains(&i).then_some(i as usize)
+}
+
+// Python slice semantics.
+fn slice_indices(len: usize, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let len = len as i64;
+    let clamp = |i: i64, lo: i64, hi: i64| {
+        let i = if i < 0 { i + len } else { i };
+        i.clamp(lo, hi)
+    };
+    let mut out = Vec::new();
+    if step > 0 {
+        let (mut i, end) = (
+            clamp(start.unwrap_or(0), 0, len),
+            clamp(end.unwrap_or(len), 0, len),
+        );
+        while i < end {
+            out.push(i as usize);
+            i += step;
+        }
+    } else {
+        let mut i = start.map_or(len - 1, |s| clamp(s, -1, len - 1));
+        let end = end.map_or(-1, |e| clamp(e, -1, len - 1));
+        while i > end {
+            out.push(i as usize);
+            i += step;
+        }
+    }
+    out
+}
+
+fn unescape_pointer(token: &str) -> Result<String> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c != '~' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('0') => out.push('~'),
+            Some('1') => out.push('/'),
+            _ => {
+                return Err(Error::Query(format!(
+                    "bad JSON Pointer escape in '{token}'"
+                )))
+            }
+        }
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Predicates
+// ---------------------------------------------------------------------------
+
+impl Expr {
+    fn eval(&self, node: &Value) -> bool {
+        match self {
+            Expr::Exists(rel) => lookup(node, rel).is_some(),
+            Expr::Cmp(rel, op, lit) => match lookup(node, rel) {
+                None => *op == Op::Ne,
+                Some(v) => match op {
+                    Op::Eq => v == lit,
+                    Op::Ne => v != lit,
+                    _ => {
+                        let ord = match (v, lit) {
+                            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+                            (Value::String(a), Value::String(b)) => a.as_bytes().cmp(b.as_bytes()),
+                            _ => return false,
+                        };
+                        match op {
+                            Op::Lt => ord.is_lt(),
+                            Op::Le => ord.is_le(),
+                            Op::Gt => ord.is_gt(),
+                            _ => ord.is_ge(),
+                        }
+                    }
+                },
+            },
+            Expr::Not(e) => !e.eval(node),
+            Expr::And(a, b) => a.eval(node) && b.eval(node),
+            Expr::Or(a, b) => a.eval(node) || b.eval(node),
+        }
+    }
+}
+
+fn lookup<'a>(v: &'a Value, rel: &[Seg]) -> Option<&'a Value> {
+    rel.iter()
+        .try_fold(v, |cur, seg| seg.step(cur).pop().map(|(_, c)| c))
+}
+
+// ---------------------------------------------------------------------------
+// JSONPath parser
+// ---------------------------------------------------------------------------
+
+struct Parser<'s> {
+    s: &'s str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn err(&self, what: &str) -> Error {
+        Error::Query(format!(
+            "{what} at offset {} of '${}'",
+            self.pos + 1,
+            self.s
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.s[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.err(&format!("expected '{token}'")))
+        }
+    }
+
+    fn ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// `.key`, `.*`, `[...]` until something else. Inside predicates, names
+    /// also stop at whitespace and operator characters, and wildcards,
+    /// slices and nested predicates are rejected.
+    fn segments(&mut self, relative: bool) -> Result<Vec<Seg>> {
+        let mut segs = Vec::new();
+        loop {
+            if self.eat("..") {
+                return Err(self.err("recursive descent '..' is not supported"));
+            } else if self.eat(".") {
+                if self.eat("*") {
+                    segs.push(Seg::Wildcard);
+                } else {
+                    let start = self.pos;
+                    while let Some(c) = self.peek() {
+                        let stop = c == '.'
+                            || c == '['
+                            || (relative && (c.is_whitespace() || "=!<>)&|]".contains(c)));
+                        if stop {
+                            break;
+                        }
+                        self.bump();
+                    }
+                    if self.pos == start {
+                        return Err(self.err("expected a key after '.'"));
+                    }
+                    segs.push(Seg::Key(self.s[start..self.pos].to_string()));
+                }
+            } else if self.eat("[") {
+                self.ws();
+                segs.push(self.bracket()?);
+                self.ws();
+                self.expect("]")?;
+            } else {
+                break;
+            }
+            if relative && !segs.last().is_some_and(Seg::is_singular) {
+                return Err(self.err("predicate paths must be singular"));
+            }
+        }
+        Ok(segs)
+    }
+
+    fn bracket(&mut self) -> Result<Seg> {
+        match self.peek() {
+            Some('*') => {
+                self.bump();
+                Ok(Seg::Wildcard)
+            }
+            Some('\'' | '"') => Ok(Seg::Key(self.quoted()?)),
+            Some('?') => {
+                self.bump();
+                self.ws();
+                Ok(Seg::Filter(self.or_expr()?))
+            }
+            _ => {
+                let start = self.int_opt()?;
+                self.ws();
+                if !self.eat(":") {
+                    return start
+                        .map(Seg::Index)
+                        .ok_or_else(|| self.err("expected an index, slice, '*', key or '?'"));
+                }
+                self.ws();
+                let end = self.int_opt()?;
+                self.ws();
+                let step = if self.eat(":") {
+                    self.ws();
+                    self.int_opt()?.unwrap_or(1)
+                } else {
+                    1
+                };
+                if step == 0 {
+                    return Err(self.err("slice step must not be 0"));
+                }
+                Ok(Seg::Slice(start, end, step))
+            }
+        }
+    }
+
+    fn int_opt(&mut self) -> Result<Option<i64>> {
+        let start = self.pos;
+        self.eat("-");
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        match &self.s[start..self.pos] {
+            "" => Ok(None),
+            "-" => Err(self.err("expected digits after '-'")),
+            t => t
+                .parse()
+                .map(Some)
+                .map_err(|_| self.err("integer out of range")),
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let q = self.bump().expect("caller peeked a quote");
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(self.err("unterminated string")),
+                Some(c) if c == q => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some(c @ ('\\' | '\'' | '"' | '/')) => out.push(c),
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('u') => {
+                        let hex = self.s.get(self.pos..self.pos + 4).unwrap_or("");
+                        let c = u32::from_str_radix(hex, 16)
+                            .ok()
+                            .filter(|_| hex.len() == 4)
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.err("invalid \\u escape"))?;
+                        self.pos += 4;
+                        out.push(c);
+                    }
+                    _ => return Err(self.err("invalid escape")),
+                },
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<Expr> {
+        let mut e = self.and_expr()?;
+        while self.eat("||") {
+            self.ws();
+            e = Expr::Or(Box::new(e), Box::new(self.and_expr()?));
+        }
+        Ok(e)
+    }
+
+    fn and_expr(&mut self) -> Result<Expr> {
+        let mut e = self.unary()?;
+        while self.eat("&&") {
+            self.ws();
+            e = Expr::And(Box::new(e), Box::new(self.unary()?));
+        }
+        Ok(e)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        let e = if self.eat("!") && !self.s[self.pos..].starts_with('=') {
+            self.ws();
+            Expr::Not(Box::new(self.unary()?))
+        } else if self.eat("(") {
+            self.ws();
+            let e = self.or_expr()?;
+            self.expect(")")?;
+            e
+        } else if self.eat("@") {
+            let rel = self.segments(true)?;
+            self.ws();
+            match self.op() {
+                Some(op) => {
+                    self.ws();
+                    Expr::Cmp(rel, op, self.literal()?)
+                }
+                None => Expr::Exists(rel),
+            }
+        } else {
+            return Err(self.err("expected '@', '!' or '('"));
+        };
+        self.ws();
+        Ok(e)
+    }
+
+    fn op(&mut self) -> Option<Op> {
+        for (token, op) in [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ] {
+            if self.eat(token) {
+                return Some(op);
+            }
+        }
+        None
+    }
+
+    fn literal(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Value::String(self.quoted()?)),
+            _ if self.eat("true") => Ok(Value::Bool(true)),
+            _ if self.eat("false") => Ok(Value::Bool(false)),
+            _ if self.eat("null") => Ok(Value::Null),
+            _ => self
+                .int_opt()?
+                .map(Value::Int)
+                .ok_or_else(|| self.err("expected a literal")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_diag;
+
+    fn doc() -> Value {
+        from_diag(
+            r#"{"a.b": 1, "items": [{"qty": 2, "sku": "A"}, {"qty": 0, "sku": "B"}, {"qty": 5, "sku": "C"}], "m": {"x": {"y": true}}, "s/t~": "odd"}"#,
+        )
+        .unwrap()
+    }
+
+    fn skus(v: &Value, path: &str) -> Vec<String> {
+        select(v, path)
+            .unwrap()
+            .into_iter()
+            .map(|v| match v {
+                Value::String(s) => s.clone(),
+                other => crate::to_diag(other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn three_spellings_agree() {
+        let v = doc();
+        for p in ["m.x.y", "/m/x/y", "$.m.x.y", "$['m'][\"x\"].y"] {
+            assert_eq!(get(&v, p).unwrap(), &Value::Bool(true), "{p}");
+        }
+        for p in [
+            "items.1.sku",
+            "/items/1/sku",
+            "$.items[1].sku",
+            "$.items[-2].sku",
+        ] {
+            assert_eq!(get(&v, p).unwrap(), &Value::String("B".into()), "{p}");
+        }
+        assert_eq!(get(&v, "$['a.b']").unwrap(), &Value::Int(1));
+        assert_eq!(get(&v, "/s~1t~0").unwrap(), &Value::String("odd".into()));
+        assert_eq!(get(&v, "").unwrap(), &v);
+        assert_eq!(get(&v, "$").unwrap(), &v);
+    }
+
+    #[test]
+    fn wildcards_slices_predicates() {
+        let v = doc();
+        assert_eq!(skus(&v, "$.items[*].sku"), ["A", "B", "C"]);
+        assert_eq!(skus(&v, "$.items[1:].sku"), ["B", "C"]);
+        assert_eq!(skus(&v, "$.items[::-1].sku"), ["C", "B", "A"]);
+        assert_eq!(skus(&v, "$.items[:-1:2].sku"), ["A"]);
+        assert_eq!(skus(&v, "$.items[?(@.qty > 0)].sku"), ["A", "C"]);
+        assert_eq!(skus(&v, "$.items[?@.qty >= 2 && @.sku != 'C'].sku"), ["A"]);
+        assert_eq!(
+            skus(&v, "$.items[?(@.sku == 'B' || !(@.qty < 5))].sku"),
+            ["B", "C"]
+        );
+        assert_eq!(skus(&v, "$.items[?(@.missing)].sku"), Vec::<String>::new());
+        assert_eq!(skus(&v, "$.m.*.y"), ["true"]);
+        assert_eq!(select(&v, "$.nope[*]").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn select_paths_are_concrete() {
+        let v = doc();
+        let q = Query::parse("$.items[?(@.qty == 0)].sku").unwrap();
+        let paths: Vec<_> = q.select_paths(&v).into_iter().map(|(p, _)| p).collect();
+        assert_eq!(
+            paths,
+            vec![vec![
+                PathSeg::Key("items"),
+                PathSeg::Index(1),
+                PathSeg::Key("sku")
+            ]]
+        );
+    }
+
+    #[test]
+    fn set_and_delete() {
+        let mut v = doc();
+        set(&mut v, "new.deep.key", Value::Int(7)).unwrap();
+        assert_eq!(get(&v, "$.new.deep.key").unwrap(), &Value::Int(7));
+        set(&mut v, "/items/-", Value::Null).unwrap();
+        assert_eq!(get(&v, "$.items[-1]").unwrap(), &Value::Null);
+        set(&mut v, "$.items[0].qty", Value::Int(9)).unwrap();
+        assert_eq!(get(&v, "items.0.qty").unwrap(), &Value::Int(9));
+
+        assert_eq!(delete(&mut v, "$.items[-1]").unwrap(), Value::Null);
+        assert_eq!(delete(&mut v, "$['a.b']").unwrap(), Value::Int(1));
+        assert_eq!(
+            delete(&mut v, "$['a.b']"),
+            Err(Error::PathNotFound(r#"["a.b"]"#.into()))
+        );
+        *get_mut_ok(&mut v, "m.x.y") = Value::Bool(false);
+        assert_eq!(get(&v, "/m/x/y").unwrap(), &Value::Bool(false));
+    }
+
+    fn get_mut_ok<'a>(v: &'a mut Value, p: &str) -> &'a mut Value {
+        Query::parse(p).unwrap().get_mut(v).unwrap()
+    }
+
+    #[test]
+    fn errors_are_typed() {
+        let mut v = doc();
+        assert_eq!(
+            get(&v, "items.7.sku"),
+            Err(Error::PathNotFound("items.7".into()))
+        );
+        assert_eq!(
+            get(&v, "m.x.y.z"),
+            Err(Error::PathNotFound("m.x.y.z".into()))
+        );
+        assert!(matches!(
+            set(&mut v, "m.x.y.z", Value::Null),
+            Err(Error::Query(msg)) if msg == "path collision at m.x.y: not a container"
+        ));
+        assert!(matches!(get(&v, "$.items[*]"), Err(Error::Query(_))));
+        assert!(matches!(delete(&mut v, ""), Err(Error::Query(_))));
+        for bad in [
+            "a..b",
+            "$..a",
+            "$.a[",
+            "$[1:2:0]",
+            "$[?(@.a == )]",
+            "/a~2",
+            "$['x",
+        ] {
+            assert!(matches!(Query::parse(bad), Err(Error::Query(_))), "{bad}");
+        }
+        assert!(!Query::parse("$.a[*]").unwrap().is_singular());
+        assert!("$.a[0]".parse::<Query>().unwrap().is_singular());
+    }
+}
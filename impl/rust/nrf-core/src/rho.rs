@@ -22,12 +22,20 @@
 //   7. Null/Bool/Int/Bytes → Pass through (already canonical)
 //
 // ρ is recursive. It normalizes the leaves, then the containers.
+//
+// Rules 2, 3 and 4 (and ASCII identifiers) depend on what a field MEANS, so
+// they are declared per artifact type in a `Profile` and applied by
+// `normalize_with_profile`.
 // ---------------------------------------------------------------------------
 
-use crate::Value;
-use regex::Regex;
-use std::collections::BTreeMap;
-use std::sync::LazyLock;
+use crate::merkle::PathSeg;
+use crate::query::Query;
+use crate::{push_path_index, push_path_key, Value};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use unicode_normalization::UnicodeNormalization;
 
 // ---------------------------------------------------------------------------
@@ -39,27 +47,79 @@ pub enum RhoError {
     InvalidUTF8,
     InvalidDecimal(String),
     InvalidTimestamp(String),
+    /// An `ascii-id` field holds whitespace, control or non-ASCII characters.
+    InvalidId(String),
+    /// A profile rule points at a value of the wrong kind.
+    ProfileMismatch(String),
+    /// `validate_with_profile`: the value changes under its profile.
+    NotCanonical(String),
+    /// A profile rule failed at this path.
+    AtPath {
+        path: String,
+        error: Box<RhoError>,
+    },
 }
 
-impl std::fmt::Display for RhoError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for RhoError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::InvalidUTF8 => write!(f, "Rho.InvalidUTF8"),
             Self::InvalidDecimal(s) => write!(f, "Rho.InvalidDecimal({s})"),
             Self::InvalidTimestamp(s) => write!(f, "Rho.InvalidTimestamp({s})"),
+            Self::InvalidId(s) => write!(f, "Rho.InvalidId({s})"),
+            Self::ProfileMismatch(s) => write!(f, "Rho.ProfileMismatch({s})"),
+            Self::NotCanonical(s) => write!(f, "Rho.NotCanonical({s})"),
+            Self::AtPath { path, error } => write!(f, "{error} at {path}"),
         }
     }
 }
 
 // ---------------------------------------------------------------------------
-// Regex patterns (compiled once)
+// Shape checks — hand-written, so ρ needs neither regex nor std
 // ---------------------------------------------------------------------------
 
-static RE_DECIMAL: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^-?(0|[1-9][0-9]*)(\.[0-9]+)?$").unwrap());
+/// `-?(0|[1-9][0-9]*)(\.[0-9]+)?`
+fn is_decimal_shape(s: &str) -> bool {
+    let b = s.as_bytes();
+    let b = b.strip_prefix(b"-").unwrap_or(b);
+    let (int, frac) = match b.iter().position(|&c| c == b'.') {
+        Some(dot) => (&b[..dot], Some(&b[dot + 1..])),
+        None => (b, None),
+    };
+    let int_ok = match int {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => all_digits(rest),
+        _ => false,
+    };
+    int_ok && frac.is_none_or(|f| !f.is_empty() && all_digits(f))
+}
+
+/// `YYYY-MM-DDTHH:MM:SS(.f+)?Z`, ASCII digits only. Field ranges are not
+/// checked here (the shape is what canonical form is defined over).
+fn is_timestamp_shape(s: &str) -> bool {
+    const SHAPE: &[u8; 19] = b"dddd-dd-ddTdd:dd:dd";
+    let Some(b) = s.as_bytes().strip_suffix(b"Z") else {
+        return false;
+    };
+    if b.len() < SHAPE.len() {
+        return false;
+    }
+    let (fixed, frac) = b.split_at(SHAPE.len());
+    let fixed_ok = fixed.iter().zip(SHAPE).all(|(&c, &p)| match p {
+        b'd' => c.is_ascii_digit(),
+        _ => c == p,
+    });
+    let frac_ok = match frac {
+        [] => true,
+        [b'.', digits @ ..] => !digits.is_empty() && all_digits(digits),
+        _ => false,
+    };
+    fixed_ok && frac_ok
+}
 
-static RE_TIMESTAMP: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z$").unwrap());
+fn all_digits(b: &[u8]) -> bool {
+    b.iter().all(u8::is_ascii_digit)
+}
 
 // ---------------------------------------------------------------------------
 // The ρ function — the heart of the system
@@ -112,10 +172,9 @@ pub fn canonical_encode(v: &Value) -> Result<Vec<u8>, RhoError> {
 }
 
 /// Normalize, encode, and hash. The ONE canonical CID.
+/// Streams into the hasher; the encoding is never materialized.
 pub fn canonical_cid(v: &Value) -> Result<String, RhoError> {
-    let bytes = canonical_encode(v)?;
-    let hash = blake3::hash(&bytes);
-    Ok(format!("b3:{}", hash.to_hex()))
+    Ok(crate::blake3_cid(&normalize(v)?))
 }
 
 /// Sort an array as a Set: sort by canonical NRF bytes, deduplicate.
@@ -166,7 +225,7 @@ fn normalize_string_raw(s: &str) -> Result<String, RhoError> {
 /// "2024-01-15T10:30:00.100Z" → "2024-01-15T10:30:00.1Z"
 /// "2024-01-15T10:30:00Z"     → "2024-01-15T10:30:00Z" (unchanged)
 pub fn normalize_timestamp(s: &str) -> Result<String, RhoError> {
-    if !RE_TIMESTAMP.is_match(s) {
+    if !is_timestamp_shape(s) {
         return Err(RhoError::InvalidTimestamp(s.to_string()));
     }
 
@@ -201,7 +260,7 @@ pub fn normalize_timestamp(s: &str) -> Result<String, RhoError> {
 /// "-0.0"   → "0"
 /// "1e2"    → rejected (exponent)
 pub fn normalize_decimal(s: &str) -> Result<String, RhoError> {
-    if !RE_DECIMAL.is_match(s) {
+    if !is_decimal_shape(s) {
         return Err(RhoError::InvalidDecimal(s.to_string()));
     }
 
@@ -238,6 +297,202 @@ pub fn normalize_decimal(s: &str) -> Result<String, RhoError> {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Profiles — the per-artifact half of ρ
+//
+// A Profile maps paths to rules: "this field is a timestamp", "this array is
+// a set", ... Declared once per artifact type, so every producer of that
+// artifact applies the same canonicalisation. Paths use the query language
+// (`a.b`, `/a/b`, `$.items[*].price`). A path that matches nothing is not an
+// error — optional fields stay optional.
+// ---------------------------------------------------------------------------
+
+/// What a profiled field is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// String → `normalize_timestamp`.
+    Timestamp,
+    /// String → `normalize_decimal` (an Int is already canonical).
+    Decimal,
+    /// Array → `normalize_as_set`.
+    Set,
+    /// String of printable ASCII, no whitespace (DIDs, CIDs, key ids). Checked, never rewritten.
+    AsciiId,
+}
+
+impl Rule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rule::Timestamp => "timestamp",
+            Rule::Decimal => "decimal",
+            Rule::Set => "set",
+            Rule::AsciiId => "ascii-id",
+        }
+    }
+}
+
+impl core::str::FromStr for Rule {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Rule> {
+        match s {
+            "timestamp" => Ok(Rule::Timestamp),
+            "decimal" => Ok(Rule::Decimal),
+            "set" => Ok(Rule::Set),
+            "ascii-id" => Ok(Rule::AsciiId),
+            other => Err(crate::Error::Rho(format!("unknown rule '{other}'"))),
+        }
+    }
+}
+
+/// A declared set of path → rule bindings.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    rules: Vec<(String, Query, Rule)>,
+}
+
+impl Profile {
+    /// Parse every path up front; a bad path is `Error::Query`.
+    pub fn new(rules: &[(&str, Rule)]) -> crate::Result<Profile> {
+        let rules = rules
+            .iter()
+            .map(|(path, rule)| Ok((path.to_string(), Query::parse(path)?, *rule)))
+            .collect::<crate::Result<_>>()?;
+        Ok(Profile { rules })
+    }
+
+    /// Declarative form: a map of path → rule name, e.g.
+    /// `{"ts": "timestamp", "$.items[*].price": "decimal"}`.
+    pub fn from_value(v: &Value) -> crate::Result<Profile> {
+        let Value::Map(m) = v else {
+            return Err(crate::Error::Rho(
+                "profile must be a map of path → rule".into(),
+            ));
+        };
+        let mut rules = Vec::with_capacity(m.len());
+        for (path, rule) in m {
+            let Value::String(rule) = rule else {
+                return Err(crate::Error::Rho(format!(
+                    "rule for '{path}' must be a string"
+                )));
+            };
+            rules.push((path.clone(), Query::parse(path)?, rule.parse()?));
+        }
+        Ok(Profile { rules })
+    }
+
+    /// The declared rules, in declaration order.
+    pub fn rules(&self) -> impl Iterator<Item = (&str, Rule)> {
+        self.rules.iter().map(|(p, _, r)| (p.as_str(), *r))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// ρ plus the profile's rules. Scalar rules run first; sets are sorted last,
+/// deepest first, so each set is ordered by its elements' final bytes.
+/// Still idempotent: `normalize_with_profile(normalize_with_profile(v)) = …(v)`.
+pub fn normalize_with_profile(v: &Value, profile: &Profile) -> Result<Value, RhoError> {
+    let mut out = normalize(v)?;
+    let mut sets = Vec::new();
+    for (_, query, rule) in &profile.rules {
+        for path in owned_paths(query, &out) {
+            if *rule == Rule::Set {
+                sets.push(path);
+            } else {
+                apply_rule(&mut out, &path, *rule)?;
+            }
+        }
+    }
+    sets.sort_by_key(|p| core::cmp::Reverse(p.len()));
+    for path in sets {
+        apply_rule(&mut out, &path, Rule::Set)?;
+    }
+    Ok(out)
+}
+
+/// Reject values that `normalize_with_profile` would change; the error
+/// names the first difference.
+pub fn validate_with_profile(v: &Value, profile: &Profile) -> Result<(), RhoError> {
+    let normalized = normalize_with_profile(v, profile)?;
+    match crate::diff(v, &normalized).into_iter().next() {
+        None => Ok(()),
+        Some(change) => Err(RhoError::NotCanonical(change.to_string())),
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+fn owned_paths(query: &Query, v: &Value) -> Vec<Vec<Step>> {
+    query
+        .select_paths(v)
+        .into_iter()
+        .map(|(path, _)| {
+            path.into_iter()
+                .map(|seg| match seg {
+                    PathSeg::Key(k) => Step::Key(k.to_string()),
+                    PathSeg::Index(i) => Step::Index(i),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn apply_rule(root: &mut Value, path: &[Step], rule: Rule) -> Result<(), RhoError> {
+    let mut at = String::new();
+    let mut node = root;
+    for step in path {
+        node = match (step, node) {
+            (Step::Key(k), Value::Map(m)) => {
+                push_path_key(&mut at, k);
+                match m.get_mut(k) {
+                    Some(child) => child,
+                    None => return Ok(()),
+                }
+            }
+            (Step::Index(i), Value::Array(items)) => {
+                push_path_index(&mut at, *i);
+                match items.get_mut(*i) {
+                    Some(child) => child,
+                    None => return Ok(()),
+                }
+            }
+            _ => return Ok(()),
+        };
+    }
+    let fail = |error| RhoError::AtPath {
+        path: at.clone(),
+        error: Box::new(error),
+    };
+    let new = match (rule, &*node) {
+        (Rule::Timestamp, Value::String(s)) => Value::String(normalize_timestamp(s).map_err(fail)?),
+        (Rule::Decimal, Value::String(s)) => Value::String(normalize_decimal(s).map_err(fail)?),
+        (Rule::Decimal, Value::Int(_)) => return Ok(()),
+        (Rule::Set, Value::Array(items)) => Value::Array(normalize_as_set(items).map_err(fail)?),
+        (Rule::AsciiId, Value::String(s)) => {
+            if s.bytes().all(|b| b.is_ascii_graphic()) {
+                return Ok(());
+            }
+            return Err(fail(RhoError::InvalidId(s.clone())));
+        }
+        (rule, other) => {
+            return Err(fail(RhoError::ProfileMismatch(format!(
+                "{} rule on {}",
+                rule.as_str(),
+                other.type_name()
+            ))))
+        }
+    };
+    *node = new;
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Validate — strict mode. Returns errors instead of rewriting.
 // Use this when you want to REJECT non-canonical input instead of fixing it.
@@ -275,6 +530,7 @@ pub fn validate(v: &Value) -> Result<(), RhoError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn test_normalize_null_passthrough() {
@@ -389,6 +645,31 @@ mod tests {
         assert_eq!(normalize_decimal("-3.14").unwrap(), "-3.14");
     }
 
+    #[test]
+    fn test_shape_checks_edge_cases() {
+        for ok in ["0", "-0.5", "10", "0.000"] {
+            assert!(is_decimal_shape(ok), "{ok}");
+        }
+        for bad in ["", "-", "+1", "1.", ".5", "1..2", "--1", "1 ", "١٢"] {
+            assert!(!is_decimal_shape(bad), "{bad}");
+        }
+        for ok in ["2024-01-15T10:30:00Z", "2024-01-15T10:30:00.123456789Z"] {
+            assert!(is_timestamp_shape(ok), "{ok}");
+        }
+        for bad in [
+            "",
+            "Z",
+            "2024-01-15T10:30:00",
+            "2024-01-15T10:30:00.Z",
+            "2024-01-15T10:30:00+00:00",
+            "2024-01-15t10:30:00Z",
+            "2024-1-15T10:30:00Z",
+            "２０２４-01-15T10:30:00Z",
+        ] {
+            assert!(!is_timestamp_shape(bad), "{bad}");
+        }
+    }
+
     #[test]
     fn test_set_sort_and_dedup() {
         let items = vec![
@@ -427,4 +708,63 @@ mod tests {
         m.insert("b".into(), Value::Int(1));
         assert!(validate(&Value::Map(m)).is_ok());
     }
+
+    #[test]
+    fn test_profile_applies_declared_rules() {
+        let profile = Profile::new(&[
+            ("ts", Rule::Timestamp),
+            ("$.items[*].price", Rule::Decimal),
+            ("$.items[*].tags", Rule::Set),
+            ("did", Rule::AsciiId),
+        ])
+        .unwrap();
+        let v = crate::from_diag(
+            r#"{"did": "did:ex:1", "items": [{"price": "1.50", "tags": ["b", "a", "b"]}, {"price": 3}], "ts": "2024-01-15T10:30:00.100Z"}"#,
+        )
+        .unwrap();
+        let want = crate::from_diag(
+            r#"{"did": "did:ex:1", "items": [{"price": "1.5", "tags": ["a", "b"]}, {"price": 3}], "ts": "2024-01-15T10:30:00.1Z"}"#,
+        )
+        .unwrap();
+        let got = normalize_with_profile(&v, &profile).unwrap();
+        assert_eq!(got, want);
+        assert_eq!(normalize_with_profile(&got, &profile).unwrap(), got);
+        assert!(validate_with_profile(&got, &profile).is_ok());
+        assert!(matches!(
+            validate_with_profile(&v, &profile),
+            Err(RhoError::NotCanonical(_))
+        ));
+        // Missing optional fields are fine.
+        assert!(normalize_with_profile(&Value::Map(BTreeMap::new()), &profile).is_ok());
+    }
+
+    #[test]
+    fn test_profile_errors_carry_path() {
+        let profile = Profile::new(&[("$.a[*]", Rule::AsciiId), ("$.t", Rule::Timestamp)]).unwrap();
+        let v = crate::from_diag(r#"{"a": ["ok", "not ok"]}"#).unwrap();
+        assert_eq!(
+            normalize_with_profile(&v, &profile).unwrap_err(),
+            RhoError::AtPath {
+                path: "a[1]".into(),
+                error: Box::new(RhoError::InvalidId("not ok".into())),
+            }
+        );
+        let v = crate::from_diag(r#"{"t": 5}"#).unwrap();
+        assert_eq!(
+            normalize_with_profile(&v, &profile)
+                .unwrap_err()
+                .to_string(),
+            "Rho.ProfileMismatch(timestamp rule on int) at t"
+        );
+    }
+
+    #[test]
+    fn test_profile_from_value() {
+        let decl = crate::from_diag(r#"{"$.roles": "set", "ts": "timestamp"}"#).unwrap();
+        let profile = Profile::from_value(&decl).unwrap();
+        let rules: Vec<_> = profile.rules().collect();
+        assert_eq!(rules, vec![("$.roles", Rule::Set), ("ts", Rule::Timestamp)]);
+        let bad = crate::from_diag(r#"{"ts": "clock"}"#).unwrap();
+        assert!(Profile::from_value(&bad).is_err());
+    }
 }
@@ -0,0 +1,1651 @@
+// ---------------------------------------------------------------------------
+// serde data format — any `#[derive(Serialize)]` type → canonical NRF
+//
+// Domain types used to hand-build a BTreeMap<String, Value> preimage that
+// had to be kept in sync with their serde derives. This module lets the
+// derive BE the preimage:
+//
+//   let cid = nrf_core::serde::cid(&permit)?;        // b3:<hex>
+//   let bytes = nrf_core::serde::to_bytes(&permit)?; // nrf1 || value
+//   let p: Permit = nrf_core::serde::from_bytes(&bytes)?;
+//
+// Mapping (zero-choice, same as the JSON view):
+//   bool → Bool          i8..i64, u8..u64 → Int (u64 > i64::MAX rejected)
+//   str/char → String    bytes (serde_bytes / `nrf_core::serde::bytes`) → Bytes
+//   None/()/unit struct → Null        Some(x)/newtype struct → x
+//   seq/tuple → Array    map/struct → Map (string keys, byte-sorted, unique)
+//   unit variant → "Variant"          other variants → {"Variant": payload}
+//   f32/f64 → REJECTED (Error::Float) non-NFC strings/keys → REJECTED
+//
+// A plain `Vec<u8>` is a sequence of Ints, exactly as serde sees it. Mark
+// byte fields with `#[serde(with = "nrf_core::serde::bytes")]` (or
+// serde_bytes) to get the Bytes tag.
+//
+// `nrf_core::Value` embedded in a struct serializes as itself (not as a
+// tagged enum), so `body: Value` fields round-trip losslessly. Its JSON
+// representation is unchanged.
+// ---------------------------------------------------------------------------
+
+use crate::{encode_varint32, Error, HashSink, Result, Sink, Value, MAGIC};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+use ::serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use ::serde::ser::{self, Serialize};
+use ::serde::{Deserialize, Deserializer};
+
+/// Private newtype name through which `Value` recognises this format.
+const VALUE_TOKEN: &str = "$nrf_core::private::Value";
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Serde(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Serde(msg.to_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Entry points
+// ---------------------------------------------------------------------------
+
+/// Serialize any `T: Serialize` into an NRF `Value`.
+pub fn to_value<T: ?Sized + Serialize>(value: &T) -> Result<Value> {
+    value.serialize(ValueSerializer)
+}
+
+/// Serialize into canonical NRF bytes (magic included), written directly
+/// without building a `Value` first.
+pub fn to_bytes<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut out = MAGIC.to_vec();
+    value.serialize(ByteSerializer { out: &mut out })?;
+    Ok(out)
+}
+
+/// BLAKE3 over the canonical bytes — the digest you sign. Streams into
+/// the hasher; the full encoding is never held in memory.
+pub fn hash<T: ?Sized + Serialize>(value: &T) -> Result<[u8; 32]> {
+    let mut sink = HashSink::new();
+    sink.put(&MAGIC);
+    value.serialize(ByteSerializer { out: &mut sink })?;
+    Ok(sink.finalize())
+}
+
+/// `b3:<hex>` CID over the canonical bytes.
+pub fn cid<T: ?Sized + Serialize>(value: &T) -> Result<String> {
+    Ok(format!("b3:{}", blake3::Hash::from(hash(value)?).to_hex()))
+}
+
+/// Deserialize a `T` from an NRF `Value`.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    T::deserialize(ValueDeserializer(value))
+}
+
+/// Decode canonical NRF bytes (default limits) and deserialize a `T`.
+pub fn from_bytes<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    from_value(crate::decode(data)?)
+}
+
+// ---------------------------------------------------------------------------
+// `Value` as a serde type
+//
+// Wrapped in a private newtype: transparent for JSON & friends (so the
+// externally-tagged shape `{"Int": 1}` is unchanged), but recognised by
+// this format so a Value is emitted / read back as itself.
+// ---------------------------------------------------------------------------
+
+struct Tagged<'a>(&'a Value);
+
+impl Serialize for Tagged<'_> {
+    fn serialize<S: ser::Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        match self.0 {
+            Value::Null => s.serialize_unit_variant("Value", 0, "Null"),
+            Value::Bool(b) => s.serialize_newtype_variant("Value", 1, "Bool", b),
+            Value::Int(n) => s.serialize_newtype_variant("Value", 2, "Int", n),
+            Value::String(st) => s.serialize_newtype_variant("Value", 3, "String", st),
+            Value::Bytes(b) => s.serialize_newtype_variant("Value", 4, "Bytes", &Raw(b)),
+            Value::Array(items) => s.serialize_newtype_variant("Value", 5, "Array", items),
+            Value::Map(m) => s.serialize_newtype_variant("Value", 6, "Map", m),
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: ser::Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        s.serialize_newtype_struct(VALUE_TOKEN, &Tagged(self))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Value")]
+enum TaggedOwned {
+    Null,
+    Bool(bool),
+    Int(i64),
+    String(String),
+    Bytes(#[serde(with = "bytes")] Vec<u8>),
+    Array(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> core::result::Result<Self, D::Error> {
+        struct V;
+        impl<'de> Visitor<'de> for V {
+            type Value = Value;
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.write_str("an nrf_core::Value")
+            }
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                d: D,
+            ) -> core::result::Result<Value, D::Error> {
+                Ok(match TaggedOwned::deserialize(d)? {
+                    TaggedOwned::Null => Value::Null,
+                    TaggedOwned::Bool(b) => Value::Bool(b),
+                    TaggedOwned::Int(n) => Value::Int(n),
+                    TaggedOwned::String(s) => Value::String(s),
+                    TaggedOwned::Bytes(b) => Value::Bytes(b),
+                    TaggedOwned::Array(a) => Value::Array(a),
+                    TaggedOwned::Map(m) => Value::Map(m),
+                })
+            }
+        }
+        d.deserialize_newtype_struct(VALUE_TOKEN, V)
+    }
+}
+
+struct Raw<'a>(&'a [u8]);
+
+impl Serialize for Raw<'_> {
+    fn serialize<S: ser::Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+        s.serialize_bytes(self.0)
+    }
+}
+
+/// `#[serde(with = "nrf_core::serde::bytes")]` for `Vec<u8>` fields:
+/// NRF Bytes in this format, unchanged (array of numbers) in JSON.
+pub mod bytes {
+    use ::serde::de::{SeqAccess, Visitor};
+    use alloc::vec::Vec;
+    use ::serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(v)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        struct V;
+        impl<'de> Visitor<'de> for V {
+            type Value = Vec<u8>;
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.write_str("bytes or a sequence of u8")
+            }
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(v.to_vec())
+            }
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+                let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(b) = seq.next_element::<u8>()? {
+                    out.push(b);
+                }
+                Ok(out)
+            }
+        }
+        d.deserialize_byte_buf(V)
+    }
+}
+
+/// `#[serde(with = "nrf_core::serde::bytes_seq")]` for `Vec<Vec<u8>>`
+/// fields: an Array of NRF Bytes here, arrays of numbers in JSON.
+pub mod bytes_seq {
+    use ::serde::{Deserialize, Deserializer, Serializer};
+    use alloc::vec::Vec;
+
+    pub fn serialize<S: Serializer>(v: &[Vec<u8>], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(v.iter().map(|b| super::Raw(b)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Vec<u8>>, D::Error> {
+        #[derive(Deserialize)]
+        struct Buf(#[serde(with = "super::bytes")] Vec<u8>);
+        let bufs = Vec::<Buf>::deserialize(d)?;
+        Ok(bufs.into_iter().map(|b| b.0).collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Serializer: T → Value
+// ---------------------------------------------------------------------------
+
+struct ValueSerializer;
+
+fn int<N: TryInto<i64>>(n: N) -> Result<Value> {
+    n.try_into()
+        .map(Value::Int)
+        .map_err(|_| Error::IntegerOverflow)
+}
+
+fn string(s: &str) -> Result<Value> {
+    crate::validate_nfc(s)?;
+    Ok(Value::String(s.to_string()))
+}
+
+fn variant(name: &'static str, payload: Value) -> Result<Value> {
+    crate::validate_nfc(name)?;
+    Ok(Value::Map(BTreeMap::from([(name.to_string(), payload)])))
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        Ok(Value::Bool(v))
+    }
+    fn serialize_i8(self, v: i8) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_i16(self, v: i16) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_i32(self, v: i32) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_u16(self, v: u16) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_u32(self, v: u32) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        int(v)
+    }
+    fn serialize_f32(self, _v: f32) -> Result<Value> {
+        Err(Error::Float)
+    }
+    fn serialize_f64(self, _v: f64) -> Result<Value> {
+        Err(Error::Float)
+    }
+    fn serialize_char(self, v: char) -> Result<Value> {
+        string(v.encode_utf8(&mut [0u8; 4]))
+    }
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        string(v)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        Ok(Value::Null)
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        string(variant)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        if name == VALUE_TOKEN {
+            return value.serialize(UntagValue);
+        }
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        v: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        variant(v, value.serialize(self)?)
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer> {
+        Ok(SeqSerializer {
+            items: Vec::with_capacity(len.unwrap_or(0).min(1024)),
+            variant: None,
+        })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        v: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer> {
+        Ok(SeqSerializer {
+            items: Vec::with_capacity(len.min(1024)),
+            variant: Some(v),
+        })
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer> {
+        Ok(MapSerializer {
+            map: BTreeMap::new(),
+            key: None,
+            variant: None,
+        })
+    }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<MapSerializer> {
+        self.serialize_map(None)
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        v: &'static str,
+        _len: usize,
+    ) -> Result<MapSerializer> {
+        Ok(MapSerializer {
+            map: BTreeMap::new(),
+            key: None,
+            variant: Some(v),
+        })
+    }
+}
+
+struct SeqSerializer {
+    items: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl SeqSerializer {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.items.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+    fn finish(self) -> Result<Value> {
+        let arr = Value::Array(self.items);
+        match self.variant {
+            Some(v) => variant(v, arr),
+            None => Ok(arr),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+struct MapSerializer {
+    map: BTreeMap<String, Value>,
+    key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl MapSerializer {
+    fn insert(&mut self, key: String, value: Value) -> Result<()> {
+        if self.map.insert(key, value).is_some() {
+            return Err(Error::DuplicateKey);
+        }
+        Ok(())
+    }
+    fn finish(self) -> Result<Value> {
+        let m = Value::Map(self.map);
+        match self.variant {
+            Some(v) => variant(v, m),
+            None => Ok(m),
+        }
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Serde("map value without key".into()))?;
+        let value = value.serialize(ValueSerializer)?;
+        self.insert(key, value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        crate::validate_nfc(key)?;
+        let value = value.serialize(ValueSerializer)?;
+        self.insert(key.to_string(), value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer {
+    type Ok = Value;
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+/// Map keys: strings only (plus chars and unit variants, which ARE strings).
+struct KeySerializer;
+
+fn non_string_key<T>() -> Result<T> {
+    Err(Error::NonStringKey)
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+    type SerializeSeq = ser::Impossible<String, Error>;
+    type SerializeTuple = ser::Impossible<String, Error>;
+    type SerializeTupleStruct = ser::Impossible<String, Error>;
+    type SerializeTupleVariant = ser::Impossible<String, Error>;
+    type SerializeMap = ser::Impossible<String, Error>;
+    type SerializeStruct = ser::Impossible<String, Error>;
+    type SerializeStructVariant = ser::Impossible<String, Error>;
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        crate::validate_nfc(v)?;
+        Ok(v.to_string())
+    }
+    fn serialize_char(self, v: char) -> Result<String> {
+        self.serialize_str(v.encode_utf8(&mut [0u8; 4]))
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        variant: &'static str,
+    ) -> Result<String> {
+        self.serialize_str(variant)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String> {
+        value.serialize(self)
+    }
+    fn serialize_bool(self, _v: bool) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_i8(self, _v: i8) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_i16(self, _v: i16) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_i32(self, _v: i32) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_i64(self, _v: i64) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_u8(self, _v: u8) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_u16(self, _v: u16) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_u32(self, _v: u32) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_u64(self, _v: u64) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_f32(self, _v: f32) -> Result<String> {
+        Err(Error::Float)
+    }
+    fn serialize_f64(self, _v: f64) -> Result<String> {
+        Err(Error::Float)
+    }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_none(self) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_unit(self) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        _v: &'static str,
+        _value: &T,
+    ) -> Result<String> {
+        non_string_key()
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        non_string_key()
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        non_string_key()
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        non_string_key()
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        _v: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        non_string_key()
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        non_string_key()
+    }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        non_string_key()
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        _v: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        non_string_key()
+    }
+}
+
+/// Receives `Tagged`'s variant calls and hands back the payload as a Value.
+struct UntagValue;
+
+fn not_tagged<T>() -> Result<T> {
+    Err(Error::Serde("expected a tagged nrf_core::Value".into()))
+}
+
+impl ser::Serializer for UntagValue {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = ser::Impossible<Value, Error>;
+    type SerializeTuple = ser::Impossible<Value, Error>;
+    type SerializeTupleStruct = ser::Impossible<Value, Error>;
+    type SerializeTupleVariant = ser::Impossible<Value, Error>;
+    type SerializeMap = ser::Impossible<Value, Error>;
+    type SerializeStruct = ser::Impossible<Value, Error>;
+    type SerializeStructVariant = ser::Impossible<Value, Error>;
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        _v: &'static str,
+    ) -> Result<Value> {
+        Ok(Value::Null)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        _v: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(ValueSerializer)
+    }
+    fn serialize_bool(self, _v: bool) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_i8(self, _v: i8) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_i16(self, _v: i16) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_i32(self, _v: i32) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_i64(self, _v: i64) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_u8(self, _v: u8) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_u16(self, _v: u16) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_u32(self, _v: u32) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_u64(self, _v: u64) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_f32(self, _v: f32) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_f64(self, _v: f64) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_char(self, _v: char) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_str(self, _v: &str) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_none(self) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_unit(self) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<Value> {
+        not_tagged()
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        not_tagged()
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        not_tagged()
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        not_tagged()
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        _v: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        not_tagged()
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        not_tagged()
+    }
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        not_tagged()
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        _v: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        not_tagged()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Serializer: T → canonical bytes, no intermediate Value
+//
+// Scalars and arrays are written straight to the sink. Map entries are
+// buffered per map (struct fields arrive in declaration order, not byte
+// order), then sorted and written. Same acceptance and output as
+// `encode(&to_value(x)?)`.
+// ---------------------------------------------------------------------------
+
+struct ByteSerializer<'a, W: Sink> {
+    out: &'a mut W,
+}
+
+fn put_int<W: Sink, N: TryInto<i64>>(out: &mut W, n: N) -> Result<()> {
+    let n: i64 = n.try_into().map_err(|_| Error::IntegerOverflow)?;
+    out.put_byte(0x03);
+    out.put(&n.to_be_bytes());
+    Ok(())
+}
+
+/// Length prefix as varint32; anything past `u32::MAX` has no encoding.
+fn len32(n: usize, err: Error) -> Result<u32> {
+    u32::try_from(n).map_err(|_| err)
+}
+
+/// String body (tag, length, UTF-8) of an already NFC-checked string.
+fn put_str<W: Sink>(out: &mut W, s: &str) -> Result<()> {
+    let n = len32(s.len(), Error::StringTooLong)?;
+    out.put_byte(0x04);
+    encode_varint32(out, n);
+    out.put(s.as_bytes());
+    Ok(())
+}
+
+/// `{"Variant": ` — the payload follows.
+fn put_variant<W: Sink>(out: &mut W, name: &str) -> Result<()> {
+    crate::validate_nfc(name)?;
+    out.put_byte(0x07);
+    encode_varint32(out, 1);
+    put_str(out, name)
+}
+
+impl<'a, W: Sink> ser::Serializer for ByteSerializer<'a, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = ByteSeq<'a, W>;
+    type SerializeTuple = ByteSeq<'a, W>;
+    type SerializeTupleStruct = ByteSeq<'a, W>;
+    type SerializeTupleVariant = ByteSeq<'a, W>;
+    type SerializeMap = ByteMap<'a, W>;
+    type SerializeStruct = ByteMap<'a, W>;
+    type SerializeStructVariant = ByteMap<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.out.put_byte(if v { 0x02 } else { 0x01 });
+        Ok(())
+    }
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        put_int(self.out, v)
+    }
+    fn serialize_f32(self, _v: f32) -> Result<()> {
+        Err(Error::Float)
+    }
+    fn serialize_f64(self, _v: f64) -> Result<()> {
+        Err(Error::Float)
+    }
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.serialize_str(v.encode_utf8(&mut [0u8; 4]))
+    }
+    fn serialize_str(self, v: &str) -> Result<()> {
+        crate::validate_nfc(v)?;
+        put_str(self.out, v)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        let n = len32(v.len(), Error::BytesTooLong)?;
+        self.out.put_byte(0x05);
+        encode_varint32(self.out, n);
+        self.out.put(v);
+        Ok(())
+    }
+    fn serialize_none(self) -> Result<()> {
+        self.out.put_byte(0x00);
+        Ok(())
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<()> {
+        self.serialize_none()
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_none()
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.serialize_str(variant)
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        if name == VALUE_TOKEN {
+            // Same NFC and length checks the rest of this writer applies;
+            // no depth cap, since nothing else here has one either.
+            let v = value.serialize(UntagValue)?;
+            let opts = crate::DecodeOpts {
+                max_depth: usize::MAX,
+                ..crate::DecodeOpts::permissive()
+            };
+            crate::checked_len(&v, 0, &opts)?;
+            crate::encode_value(self.out, &v);
+            return Ok(());
+        }
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        v: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        put_variant(self.out, v)?;
+        value.serialize(self)
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<ByteSeq<'a, W>> {
+        ByteSeq::new(self.out, len)
+    }
+    fn serialize_tuple(self, len: usize) -> Result<ByteSeq<'a, W>> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<ByteSeq<'a, W>> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        v: &'static str,
+        len: usize,
+    ) -> Result<ByteSeq<'a, W>> {
+        put_variant(self.out, v)?;
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<ByteMap<'a, W>> {
+        Ok(ByteMap {
+            out: self.out,
+            entries: Vec::new(),
+            key: None,
+        })
+    }
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<ByteMap<'a, W>> {
+        Ok(ByteMap {
+            out: self.out,
+            entries: Vec::with_capacity(len),
+            key: None,
+        })
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        v: &'static str,
+        len: usize,
+    ) -> Result<ByteMap<'a, W>> {
+        put_variant(self.out, v)?;
+        self.serialize_struct(v, len)
+    }
+}
+
+/// Known length: header first, elements straight to the sink (and the
+/// count is checked at the end). Unknown length: elements are buffered.
+struct ByteSeq<'a, W: Sink> {
+    out: &'a mut W,
+    buf: Option<Vec<u8>>,
+    expected: usize,
+    count: usize,
+}
+
+impl<'a, W: Sink> ByteSeq<'a, W> {
+    fn new(out: &'a mut W, len: Option<usize>) -> Result<Self> {
+        let buf = match len {
+            Some(n) => {
+                let n = len32(n, Error::ArrayTooLong)?;
+                out.put_byte(0x06);
+                encode_varint32(out, n);
+                None
+            }
+            None => Some(Vec::new()),
+        };
+        Ok(Self {
+            out,
+            buf,
+            expected: len.unwrap_or(0),
+            count: 0,
+        })
+    }
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.count += 1;
+        match &mut self.buf {
+            Some(buf) => value.serialize(ByteSerializer { out: buf }),
+            None => value.serialize(ByteSerializer {
+                out: &mut *self.out,
+            }),
+        }
+    }
+    fn finish(self) -> Result<()> {
+        match self.buf {
+            Some(buf) => {
+                let n = len32(self.count, Error::ArrayTooLong)?;
+                self.out.put_byte(0x06);
+                encode_varint32(self.out, n);
+                self.out.put(&buf);
+            }
+            None if self.count != self.expected => {
+                return Err(Error::Serde(format!(
+                    "sequence announced {} elements, produced {}",
+                    self.expected, self.count
+                )));
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl<W: Sink> ser::SerializeSeq for ByteSeq<'_, W> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Sink> ser::SerializeTuple for ByteSeq<'_, W> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Sink> ser::SerializeTupleStruct for ByteSeq<'_, W> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Sink> ser::SerializeTupleVariant for ByteSeq<'_, W> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+/// Entries are (NFC-checked key, encoded value), sorted on `end`.
+struct ByteMap<'a, W: Sink> {
+    out: &'a mut W,
+    entries: Vec<(String, Vec<u8>)>,
+    key: Option<String>,
+}
+
+impl<W: Sink> ByteMap<'_, W> {
+    fn insert<T: ?Sized + Serialize>(&mut self, key: String, value: &T) -> Result<()> {
+        let mut buf = Vec::new();
+        value.serialize(ByteSerializer { out: &mut buf })?;
+        self.entries.push((key, buf));
+        Ok(())
+    }
+    fn finish(mut self) -> Result<()> {
+        self.entries
+            .sort_unstable_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+        if self.entries.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err(Error::DuplicateKey);
+        }
+        let n = len32(self.entries.len(), Error::MapTooLong)?;
+        self.out.put_byte(0x07);
+        encode_varint32(self.out, n);
+        for (k, v) in &self.entries {
+            put_str(self.out, k)?;
+            self.out.put(v);
+        }
+        Ok(())
+    }
+}
+
+impl<W: Sink> ser::SerializeMap for ByteMap<'_, W> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Serde("map value without key".into()))?;
+        self.insert(key, value)
+    }
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Sink> ser::SerializeStruct for ByteMap<'_, W> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        crate::validate_nfc(key)?;
+        self.insert(key.to_string(), value)
+    }
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Sink> ser::SerializeStructVariant for ByteMap<'_, W> {
+    type Ok = ();
+    type Error = Error;
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Deserializer: Value → T
+// ---------------------------------------------------------------------------
+
+/// Deserializer over an owned NRF `Value`.
+pub struct ValueDeserializer(pub Value);
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = ValueDeserializer;
+    fn into_deserializer(self) -> ValueDeserializer {
+        ValueDeserializer(self)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Int(n) => visitor.visit_i64(n),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Bytes(b) => visitor.visit_byte_buf(b),
+            Value::Array(items) => visitor.visit_seq(SeqDeserializer(items.into_iter())),
+            Value::Map(m) => visitor.visit_map(MapDeserializer {
+                iter: m.into_iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Error::Float)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Error::Float)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            other => visitor.visit_some(ValueDeserializer(other)),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            // Plain `Vec<u8>` fields read NRF Bytes as a sequence of u8.
+            Value::Bytes(b) => {
+                let items: Vec<Value> = b.into_iter().map(|x| Value::Int(x as i64)).collect();
+                visitor.visit_seq(SeqDeserializer(items.into_iter()))
+            }
+            other => ValueDeserializer(other).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        if name == VALUE_TOKEN {
+            return visitor.visit_newtype_struct(TaggedDeserializer(self.0));
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.0 {
+            Value::String(variant) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                value: None,
+            }),
+            Value::Map(m) if m.len() == 1 => {
+                let (variant, value) = m.into_iter().next().expect("len == 1");
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            _ => Err(Error::Serde(
+                "expected a string or single-key map for enum".into(),
+            )),
+        }
+    }
+
+    ::serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string
+        bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqDeserializer(alloc::vec::IntoIter<Value>);
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer {
+    type Error = Error;
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        match self.0.next() {
+            Some(v) => seed.deserialize(ValueDeserializer(v)).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct MapDeserializer {
+    iter: alloc::collections::btree_map::IntoIter<String, Value>,
+    value: Option<Value>,
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer {
+    type Error = Error;
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.iter.next() {
+            Some((k, v)) => {
+                self.value = Some(v);
+                seed.deserialize(ValueDeserializer(Value::String(k)))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let v = self
+            .value
+            .take()
+            .ok_or_else(|| Error::Serde("map value without key".into()))?;
+        seed.deserialize(ValueDeserializer(v))
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<Value>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer)> {
+        let v = seed.deserialize(ValueDeserializer(Value::String(self.variant)))?;
+        Ok((v, VariantDeserializer(self.value)))
+    }
+}
+
+struct VariantDeserializer(Option<Value>);
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+    fn unit_variant(self) -> Result<()> {
+        match self.0 {
+            None | Some(Value::Null) => Ok(()),
+            Some(_) => Err(Error::Serde("expected unit variant".into())),
+        }
+    }
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        match self.0 {
+            Some(v) => seed.deserialize(ValueDeserializer(v)),
+            None => Err(Error::Serde("expected newtype variant payload".into())),
+        }
+    }
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Some(Value::Array(items)) => visitor.visit_seq(SeqDeserializer(items.into_iter())),
+            _ => Err(Error::Serde(
+                "expected tuple variant payload (array)".into(),
+            )),
+        }
+    }
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.0 {
+            Some(Value::Map(m)) => visitor.visit_map(MapDeserializer {
+                iter: m.into_iter(),
+                value: None,
+            }),
+            _ => Err(Error::Serde("expected struct variant payload (map)".into())),
+        }
+    }
+}
+
+/// Presents a raw Value to `TaggedOwned` as the variant it already is.
+struct TaggedDeserializer(Value);
+
+impl<'de> de::Deserializer<'de> for TaggedDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let variant = match &self.0 {
+            Value::Null => "Null",
+            Value::Bool(_) => "Bool",
+            Value::Int(_) => "Int",
+            Value::String(_) => "String",
+            Value::Bytes(_) => "Bytes",
+            Value::Array(_) => "Array",
+            Value::Map(_) => "Map",
+        };
+        let value = match self.0 {
+            Value::Null => None,
+            other => Some(other),
+        };
+        visitor.visit_enum(EnumDeserializer {
+            variant: variant.to_string(),
+            value,
+        })
+    }
+
+    ::serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::serde::Serialize;
+    use alloc::vec;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Kind {
+        Plain,
+        Tagged(i32),
+        Pair(u8, u8),
+        Named { a: bool },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Doc {
+        name: String,
+        n: u32,
+        neg: i64,
+        #[serde(with = "bytes")]
+        blob: Vec<u8>,
+        raw: Vec<u8>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        opt: Option<String>,
+        kinds: Vec<Kind>,
+        tags: BTreeMap<String, bool>,
+        body: Value,
+    }
+
+    fn doc() -> Doc {
+        Doc {
+            name: "caf\u{00E9}".into(),
+            n: 7,
+            neg: -1,
+            blob: vec![0xde, 0xad],
+            raw: vec![1, 2],
+            opt: None,
+            kinds: vec![
+                Kind::Plain,
+                Kind::Tagged(-3),
+                Kind::Pair(1, 2),
+                Kind::Named { a: true },
+            ],
+            tags: BTreeMap::from([("z".into(), true), ("a".into(), false)]),
+            body: Value::Map(BTreeMap::from([
+                ("b".into(), Value::Bytes(vec![9])),
+                ("i".into(), Value::Array(vec![Value::Null, Value::Int(1)])),
+            ])),
+        }
+    }
+
+    #[test]
+    fn struct_maps_to_expected_value() {
+        let v = to_value(&doc()).unwrap();
+        let Value::Map(m) = &v else {
+            panic!("expected map")
+        };
+        assert_eq!(m["name"], Value::String("caf\u{00E9}".into()));
+        assert_eq!(m["n"], Value::Int(7));
+        assert_eq!(m["blob"], Value::Bytes(vec![0xde, 0xad]));
+        assert_eq!(m["raw"], Value::Array(vec![Value::Int(1), Value::Int(2)]));
+        assert!(!m.contains_key("opt"));
+        assert_eq!(
+            m["kinds"],
+            Value::Array(vec![
+                Value::String("Plain".into()),
+                Value::Map(BTreeMap::from([("Tagged".into(), Value::Int(-3))])),
+                Value::Map(BTreeMap::from([(
+                    "Pair".into(),
+                    Value::Array(vec![Value::Int(1), Value::Int(2)])
+                )])),
+                Value::Map(BTreeMap::from([(
+                    "Named".into(),
+                    Value::Map(BTreeMap::from([("a".into(), Value::Bool(true))]))
+                )])),
+            ])
+        );
+        // Embedded Value is emitted as itself, not as {"Map": ...}
+        assert_eq!(m["body"], doc().body);
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        let d = doc();
+        let bytes = to_bytes(&d).unwrap();
+        assert_eq!(bytes, crate::encode(&to_value(&d).unwrap()));
+        let back: Doc = from_bytes(&bytes).unwrap();
+        assert_eq!(back, d);
+        assert_eq!(
+            cid(&d).unwrap(),
+            crate::blake3_cid(&crate::decode(&bytes).unwrap())
+        );
+        assert_eq!(hash(&d).unwrap(), crate::hash_bytes(&bytes));
+    }
+
+    #[test]
+    fn direct_bytes_match_the_value_path() {
+        // Unknown-length sequence (buffered) inside a map inside a variant.
+        struct Odd(Vec<i64>);
+        impl Serialize for Odd {
+            fn serialize<S: ser::Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+                s.collect_seq(self.0.iter().filter(|n| **n != 0))
+            }
+        }
+        #[derive(Serialize)]
+        enum E {
+            Wrap { odd: Odd, z: (), a: Option<u8> },
+        }
+        let e = E::Wrap {
+            odd: Odd(vec![3, 0, -4]),
+            z: (),
+            a: Some(1),
+        };
+        assert_eq!(to_bytes(&e).unwrap(), crate::encode(&to_value(&e).unwrap()));
+        assert_eq!(
+            to_bytes(&doc()).unwrap(),
+            crate::encode(&to_value(&doc()).unwrap())
+        );
+
+        for (bytes, value) in [
+            (to_bytes(&1.5f64), to_value(&1.5f64)),
+            (to_bytes(&u64::MAX), to_value(&u64::MAX)),
+            (to_bytes("e\u{0301}"), to_value("e\u{0301}")),
+            (
+                to_bytes(&BTreeMap::from([(1u8, true)])),
+                to_value(&BTreeMap::from([(1u8, true)])),
+            ),
+        ] {
+            assert_eq!(bytes.map(|_| ()), value.map(|_| ()));
+        }
+        let dup = BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        struct Twice(BTreeMap<String, i32>);
+        impl Serialize for Twice {
+            fn serialize<S: ser::Serializer>(&self, s: S) -> core::result::Result<S::Ok, S::Error> {
+                s.collect_map(self.0.iter().chain(self.0.iter()))
+            }
+        }
+        assert_eq!(to_bytes(&Twice(dup)).unwrap_err(), Error::DuplicateKey);
+    }
+
+    #[test]
+    fn plain_vec_u8_reads_nrf_bytes() {
+        let v = Value::Map(BTreeMap::from([("raw".into(), Value::Bytes(vec![3, 4]))]));
+        #[derive(Deserialize)]
+        struct R {
+            raw: Vec<u8>,
+        }
+        let r: R = from_value(v).unwrap();
+        assert_eq!(r.raw, vec![3, 4]);
+    }
+
+    #[test]
+    fn rejects_floats() {
+        assert_eq!(to_value(&1.5f64).unwrap_err(), Error::Float);
+        assert_eq!(from_value::<f64>(Value::Int(1)).unwrap_err(), Error::Float);
+    }
+
+    #[test]
+    fn rejects_u64_overflow() {
+        assert_eq!(to_value(&u64::MAX).unwrap_err(), Error::IntegerOverflow);
+        assert_eq!(to_value(&(i64::MAX as u64)).unwrap(), Value::Int(i64::MAX));
+    }
+
+    #[test]
+    fn rejects_non_nfc_and_non_string_keys() {
+        assert_eq!(to_value("e\u{0301}").unwrap_err(), Error::NotNFC);
+        let m = BTreeMap::from([(1u8, true)]);
+        assert_eq!(to_value(&m).unwrap_err(), Error::NonStringKey);
+        let m = BTreeMap::from([("e\u{0301}".to_string(), true)]);
+        assert_eq!(to_value(&m).unwrap_err(), Error::NotNFC);
+    }
+
+    #[test]
+    fn embedded_value_is_nfc_checked_by_the_byte_writer() {
+        #[derive(Serialize)]
+        struct Wrap {
+            v: Value,
+        }
+        let bad = Wrap {
+            v: Value::Map(BTreeMap::from([("e\u{0301}".into(), Value::Null)])),
+        };
+        assert_eq!(to_bytes(&bad).unwrap_err(), Error::NotNFC);
+        assert_eq!(hash(&bad).unwrap_err(), Error::NotNFC);
+        let good = Wrap {
+            v: Value::Array(vec![Value::String("\u{00E9}".into())]),
+        };
+        assert_eq!(
+            crate::decode(&to_bytes(&good).unwrap()).unwrap(),
+            to_value(&good).unwrap()
+        );
+    }
+
+    #[test]
+    fn lengths_past_u32_have_no_encoding() {
+        assert_eq!(len32(u32::MAX as usize, Error::MapTooLong), Ok(u32::MAX));
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            len32(u32::MAX as usize + 1, Error::MapTooLong),
+            Err(Error::MapTooLong)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn map_keys_come_out_sorted() {
+        let mut hm = std::collections::HashMap::new();
+        for k in ["zeta", "alpha", "\u{00E9}", "beta"] {
+            hm.insert(k.to_string(), 1);
+        }
+        let bytes = to_bytes(&hm).unwrap();
+        // The strict decoder rejects unsorted keys — so this proves ordering.
+        assert!(crate::decode(&bytes).is_ok());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn value_json_shape_unchanged() {
+        let v = Value::Map(BTreeMap::from([
+            ("b".into(), Value::Bytes(vec![1, 2])),
+            ("n".into(), Value::Null),
+        ]));
+        let j = serde_json::to_value(&v).unwrap();
+        assert_eq!(
+            j,
+            serde_json::json!({"Map": {"b": {"Bytes": [1, 2]}, "n": "Null"}})
+        );
+        let back: Value = serde_json::from_value(j).unwrap();
+        assert_eq!(back, v);
+    }
+}
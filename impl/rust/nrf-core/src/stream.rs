@@ -0,0 +1,574 @@
+// ---------------------------------------------------------------------------
+// Streaming decoder — pull-based NRF decoding over `std::io::Read`
+//
+// `decode_with_opts` needs the whole capsule in memory before it can say
+// "too big" or "malformed". The StreamDecoder reads the same canonical
+// grammar incrementally and enforces every DecodeOpts limit as bytes
+// arrive, so hostile input is rejected after at most one over-limit read.
+//
+// Two ways to consume it:
+//   - `next_event()` — pull events (StartMap, Key, Scalar, End, ...) and
+//     process large arrays element by element without materialising them
+//   - `decode_value()` / `decode_reader()` — build a full `Value`
+//
+// The accepted language is identical to `decode_with_opts`: same tags,
+// minimal varints, NFC/no-BOM strings, sorted unique keys, no trailing data.
+// ---------------------------------------------------------------------------
+
+use crate::{DecodeOpts, Error, Result, Value, MAGIC};
+use std::collections::BTreeMap;
+use std::io::Read;
+
+/// One step of a streaming decode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Start of an array with the given element count.
+    StartArray(usize),
+    /// Start of a map with the given pair count.
+    StartMap(usize),
+    /// A map key. Always followed by the events of its value.
+    Key(String),
+    /// A leaf value (Null, Bool, Int, String or Bytes).
+    Scalar(Value),
+    /// End of the innermost open array or map.
+    End,
+}
+
+enum Frame {
+    Array {
+        remaining: usize,
+    },
+    Map {
+        remaining: usize,
+        expect_key: bool,
+        prev: Option<Vec<u8>>,
+    },
+}
+
+/// Pull-based decoder over any `io::Read`.
+///
+/// Reads are issued in small pieces (tags, varints, payloads), so wrap
+/// unbuffered sources such as files or sockets in a `BufReader`.
+pub struct StreamDecoder<R: Read> {
+    reader: R,
+    opts: DecodeOpts,
+    consumed: usize,
+    stack: Vec<Frame>,
+    started: bool,
+    root_done: bool,
+    finished: bool,
+}
+
+impl<R: Read> StreamDecoder<R> {
+    /// Decoder with default (conservative) resource limits.
+    pub fn new(reader: R) -> Self {
+        Self::with_opts(reader, DecodeOpts::default())
+    }
+
+    /// Decoder with explicit resource limits. Canon 6: reject, never degrade.
+    pub fn with_opts(reader: R, opts: DecodeOpts) -> Self {
+        Self {
+            reader,
+            opts,
+            consumed: 0,
+            stack: Vec::new(),
+            started: false,
+            root_done: false,
+            finished: false,
+        }
+    }
+
+    /// Total bytes consumed from the reader so far (magic included).
+    pub fn bytes_consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Current nesting depth (0 at the root).
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Give back the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Pull the next event. Returns `Ok(None)` once the root value has been
+    /// fully read and the reader is confirmed to be at EOF.
+    pub fn next_event(&mut self) -> Result<Option<Event>> {
+        if self.finished {
+            return Ok(None);
+        }
+        if !self.started {
+            let mut magic = [0u8; 4];
+            self.read_exact(&mut magic).map_err(|e| match e {
+                Error::UnexpectedEOF => Error::InvalidMagic,
+                other => other,
+            })?;
+            if magic != MAGIC {
+                return Err(Error::InvalidMagic);
+            }
+            self.started = true;
+        }
+        if self.root_done {
+            self.finish()?;
+            return Ok(None);
+        }
+
+        match self.stack.last_mut() {
+            Some(Frame::Array { remaining: 0 })
+            | Some(Frame::Map {
+                remaining: 0,
+                expect_key: true,
+                ..
+            }) => {
+                self.stack.pop();
+                if self.stack.is_empty() {
+                    self.root_done = true;
+                }
+                return Ok(Some(Event::End));
+            }
+            Some(Frame::Map {
+                expect_key: true, ..
+            }) => {
+                let key = self.read_key()?;
+                return Ok(Some(Event::Key(key)));
+            }
+            Some(Frame::Array { remaining }) => *remaining -= 1,
+            Some(Frame::Map { expect_key, .. }) => *expect_key = true,
+            None => {}
+        }
+        self.read_value_header().map(Some)
+    }
+
+    /// Read the whole root value into memory.
+    ///
+    /// Must be called before any event has been pulled; otherwise it
+    /// returns `Error::Stream`.
+    pub fn decode_value(&mut self) -> Result<Value> {
+        if self.started {
+            return Err(Error::Stream("decode_value after next_event".into()));
+        }
+        let mut builders: Vec<(Value, Option<String>)> = Vec::new();
+        let mut pending_key: Option<String> = None;
+        while let Some(ev) = self.next_event()? {
+            let done = match ev {
+                Event::StartArray(n) => {
+                    builders.push((
+                        Value::Array(Vec::with_capacity(n.min(1024))),
+                        pending_key.take(),
+                    ));
+                    None
+                }
+                Event::StartMap(_) => {
+                    builders.push((Value::Map(BTreeMap::new()), pending_key.take()));
+                    None
+                }
+                Event::Key(k) => {
+                    pending_key = Some(k);
+                    None
+                }
+                Event::Scalar(v) => Some((v, pending_key.take())),
+                Event::End => builders.pop(),
+            };
+            let Some((v, key)) = done else { continue };
+            match builders.last_mut() {
+                Some((Value::Array(items), _)) => items.push(v),
+                Some((Value::Map(m), _)) => {
+                    let key = key.ok_or_else(|| Error::Stream("map value without key".into()))?;
+                    m.insert(key, v);
+                }
+                Some(_) => return Err(Error::Stream("scalar on the container stack".into())),
+                None => {
+                    // Root complete — confirm EOF before handing it back.
+                    self.next_event()?;
+                    return Ok(v);
+                }
+            }
+        }
+        Err(Error::UnexpectedEOF)
+    }
+
+    // ----- internals -----
+
+    fn finish(&mut self) -> Result<()> {
+        let mut probe = [0u8; 1];
+        loop {
+            match self.reader.read(&mut probe) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if self.consumed >= self.opts.max_total_bytes {
+                        return Err(Error::SizeExceeded);
+                    }
+                    return Err(Error::TrailingData);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.finished = true;
+        Ok(())
+    }
+
+    fn read_value_header(&mut self) -> Result<Event> {
+        if self.stack.len() > self.opts.max_depth {
+            return Err(Error::DepthExceeded);
+        }
+        let tag = self.read_u8()?;
+        let ev = match tag {
+            0x00 => Event::Scalar(Value::Null),
+            0x01 => Event::Scalar(Value::Bool(false)),
+            0x02 => Event::Scalar(Value::Bool(true)),
+            0x03 => {
+                let mut arr = [0u8; 8];
+                self.read_exact(&mut arr)?;
+                Event::Scalar(Value::Int(i64::from_be_bytes(arr)))
+            }
+            0x04 => {
+                let s = self.read_string()?;
+                Event::Scalar(Value::String(s))
+            }
+            0x05 => {
+                let len = self.read_varint32()? as usize;
+                if len > self.opts.max_bytes_len {
+                    return Err(Error::BytesTooLong);
+                }
+                Event::Scalar(Value::Bytes(self.read_payload(len)?))
+            }
+            0x06 => {
+                let count = self.read_varint32()? as usize;
+                if count > self.opts.max_array_len {
+                    return Err(Error::ArrayTooLong);
+                }
+                self.stack.push(Frame::Array { remaining: count });
+                Event::StartArray(count)
+            }
+            0x07 => {
+                let count = self.read_varint32()? as usize;
+                if count > self.opts.max_map_len {
+                    return Err(Error::MapTooLong);
+                }
+                self.stack.push(Frame::Map {
+                    remaining: count,
+                    expect_key: true,
+                    prev: None,
+                });
+                Event::StartMap(count)
+            }
+            _ => return Err(Error::InvalidTypeTag(tag)),
+        };
+        if matches!(ev, Event::Scalar(_)) && self.stack.is_empty() {
+            self.root_done = true;
+        }
+        Ok(ev)
+    }
+
+    fn read_key(&mut self) -> Result<String> {
+        if self.read_u8()? != 0x04 {
+            return Err(Error::NonStringKey);
+        }
+        let key = self.read_string()?;
+        let Some(Frame::Map {
+            remaining,
+            expect_key,
+            prev,
+        }) = self.stack.last_mut()
+        else {
+            unreachable!("read_key outside a map");
+        };
+        if let Some(prevb) = prev.as_ref() {
+            match prevb.as_slice().cmp(key.as_bytes()) {
+                std::cmp::Ordering::Less => {}
+                std::cmp::Ordering::Equal => return Err(Error::DuplicateKey),
+                std::cmp::Ordering::Greater => return Err(Error::UnsortedKeys),
+            }
+        }
+        *prev = Some(key.as_bytes().to_vec());
+        *remaining -= 1;
+        *expect_key = false;
+        Ok(key)
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let len = self.read_varint32()? as usize;
+        if len > self.opts.max_string_len {
+            return Err(Error::StringTooLong);
+        }
+        let bytes = self.read_payload(len)?;
+        let s = String::from_utf8(bytes).map_err(|_| Error::InvalidUTF8)?;
+        crate::validate_nfc(&s)?;
+        Ok(s)
+    }
+
+    /// Read a length-prefixed payload, checking the total budget BEFORE
+    /// allocating so a forged length can't make us reserve gigabytes.
+    fn read_payload(&mut self, len: usize) -> Result<Vec<u8>> {
+        self.charge(len)?;
+        let mut buf = vec![0u8; len];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn read_varint32(&mut self) -> Result<u32> {
+        let mut result: u32 = 0;
+        let mut shift = 0u32;
+        for i in 0..5 {
+            let byte = self.read_u8()?;
+            let payload = (byte & 0x7F) as u32;
+            if i > 0 && byte == 0x00 {
+                return Err(Error::NonMinimalVarint);
+            }
+            if i == 4 && byte > 0x0F {
+                return Err(Error::NonMinimalVarint);
+            }
+            result |= payload << shift;
+            if (byte & 0x80) == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+        }
+        Err(Error::NonMinimalVarint)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        let mut b = [0u8; 1];
+        self.read_exact(&mut b)?;
+        Ok(b[0])
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.charge(buf.len())?;
+        self.reader.read_exact(buf)?;
+        Ok(())
+    }
+
+    fn charge(&mut self, n: usize) -> Result<()> {
+        let total = self.consumed.checked_add(n).ok_or(Error::SizeExceeded)?;
+        if total > self.opts.max_total_bytes {
+            return Err(Error::SizeExceeded);
+        }
+        self.consumed = total;
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for StreamDecoder<R> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_event() {
+            Ok(Some(ev)) => Some(Ok(ev)),
+            Ok(None) => None,
+            Err(e) => {
+                // Errors are terminal: a canonical stream has one valid parse.
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Decode a full value from a reader with default limits.
+pub fn decode_reader<R: Read>(reader: R) -> Result<Value> {
+    decode_reader_with_opts(reader, &DecodeOpts::default())
+}
+
+/// Decode a full value from a reader with explicit limits.
+pub fn decode_reader_with_opts<R: Read>(reader: R, opts: &DecodeOpts) -> Result<Value> {
+    StreamDecoder::with_opts(reader, opts.clone()).decode_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_with_opts, encode};
+
+    fn sample() -> Value {
+        let mut inner = BTreeMap::new();
+        inner.insert("sku".into(), Value::String("A-1".into()));
+        inner.insert("qty".into(), Value::Int(3));
+        let mut m = BTreeMap::new();
+        m.insert("blob".into(), Value::Bytes(vec![0, 0xff]));
+        m.insert(
+            "items".into(),
+            Value::Array(vec![Value::Map(inner), Value::Null, Value::Bool(true)]),
+        );
+        m.insert("empty".into(), Value::Array(vec![]));
+        Value::Map(m)
+    }
+
+    #[test]
+    fn stream_matches_slice_decoder() {
+        let v = sample();
+        let enc = encode(&v);
+        assert_eq!(decode_reader(enc.as_slice()).unwrap(), v);
+    }
+
+    #[test]
+    fn scalar_root() {
+        let enc = encode(&Value::Int(-7));
+        assert_eq!(decode_reader(enc.as_slice()).unwrap(), Value::Int(-7));
+    }
+
+    #[test]
+    fn event_sequence() {
+        let mut m = BTreeMap::new();
+        m.insert("a".into(), Value::Array(vec![Value::Int(1), Value::Int(2)]));
+        let enc = encode(&Value::Map(m));
+        let events: Vec<Event> = StreamDecoder::new(enc.as_slice())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::StartMap(1),
+                Event::Key("a".into()),
+                Event::StartArray(2),
+                Event::Scalar(Value::Int(1)),
+                Event::Scalar(Value::Int(2)),
+                Event::End,
+                Event::End,
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_trailing_data() {
+        let mut enc = encode(&Value::Null);
+        enc.push(0x00);
+        assert_eq!(
+            decode_reader(enc.as_slice()).unwrap_err(),
+            Error::TrailingData
+        );
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_truncation() {
+        assert_eq!(decode_reader(&b"nrf"[..]).unwrap_err(), Error::InvalidMagic);
+        assert_eq!(
+            decode_reader(&b"xxxx\x00"[..]).unwrap_err(),
+            Error::InvalidMagic
+        );
+        let enc = encode(&Value::String("hello".into()));
+        assert_eq!(
+            decode_reader(&enc[..enc.len() - 1]).unwrap_err(),
+            Error::UnexpectedEOF
+        );
+    }
+
+    #[test]
+    fn decode_value_after_events_is_an_error() {
+        let enc = encode(&sample());
+        let mut d = StreamDecoder::new(enc.as_slice());
+        d.next_event().unwrap();
+        d.next_event().unwrap();
+        assert!(matches!(d.decode_value(), Err(Error::Stream(_))));
+    }
+
+    #[test]
+    fn varint_minimality_matches_slice_decoder() {
+        // 0x80 0x01 is 128; 0x80 0x00 and a 5th byte above 0x0F are not minimal.
+        let mut len128 = b"nrf1\x05\x80\x01".to_vec();
+        len128.extend([0xAA; 128]);
+        assert_eq!(
+            decode_reader(len128.as_slice()).unwrap(),
+            Value::Bytes(vec![0xAA; 128])
+        );
+        for bad in [&b"nrf1\x04\x80\x00"[..], b"nrf1\x04\x80\x80\x80\x80\x10"] {
+            assert_eq!(decode_reader(bad).unwrap_err(), Error::NonMinimalVarint);
+            assert_eq!(crate::decode(bad).unwrap_err(), Error::NonMinimalVarint);
+        }
+    }
+
+    #[test]
+    fn enforces_limits_like_slice_decoder() {
+        let cases: Vec<(Value, DecodeOpts)> = vec![
+            (
+                Value::String("a".repeat(200)),
+                DecodeOpts {
+                    max_string_len: 100,
+                    ..DecodeOpts::default()
+                },
+            ),
+            (
+                Value::Bytes(vec![1; 200]),
+                DecodeOpts {
+                    max_bytes_len: 100,
+                    ..DecodeOpts::default()
+                },
+            ),
+            (
+                Value::Array(vec![Value::Null; 200]),
+                DecodeOpts {
+                    max_array_len: 100,
+                    ..DecodeOpts::default()
+                },
+            ),
+            (
+                Value::String("hello".into()),
+                DecodeOpts {
+                    max_total_bytes: 10,
+                    ..DecodeOpts::default()
+                },
+            ),
+            (
+                (0..5).fold(Value::Int(1), |v, _| Value::Array(vec![v])),
+                DecodeOpts {
+                    max_depth: 3,
+                    ..DecodeOpts::default()
+                },
+            ),
+        ];
+        for (v, opts) in cases {
+            let enc = encode(&v);
+            assert_eq!(
+                decode_reader_with_opts(enc.as_slice(), &opts).unwrap_err(),
+                decode_with_opts(&enc, &opts).unwrap_err()
+            );
+        }
+    }
+
+    #[test]
+    fn forged_length_rejected_before_allocation() {
+        // Bytes tag claiming ~256 MiB with a tiny body.
+        let data = [
+            b'n', b'r', b'f', b'1', 0x05, 0x81, 0x80, 0x80, 0x80, 0x01, 0xAA,
+        ];
+        let opts = DecodeOpts {
+            max_bytes_len: usize::MAX,
+            ..DecodeOpts::default()
+        };
+        assert_eq!(
+            decode_reader_with_opts(&data[..], &opts).unwrap_err(),
+            Error::SizeExceeded
+        );
+    }
+
+    #[test]
+    fn rejects_unsorted_keys_and_nfd() {
+        // {"b":null,"a":null} hand-assembled
+        let data = b"nrf1\x07\x02\x04\x01b\x00\x04\x01a\x00";
+        assert_eq!(decode_reader(&data[..]).unwrap_err(), Error::UnsortedKeys);
+
+        let mut m = BTreeMap::new();
+        m.insert("e\u{0301}".to_string(), Value::Int(1));
+        let enc = encode(&Value::Map(m));
+        assert_eq!(decode_reader(enc.as_slice()).unwrap_err(), Error::NotNFC);
+    }
+
+    #[test]
+    fn large_array_streams_elementwise() {
+        let v = Value::Array((0..1000).map(Value::Int).collect());
+        let enc = encode(&v);
+        let mut dec = StreamDecoder::new(std::io::BufReader::new(enc.as_slice()));
+        assert_eq!(dec.next_event().unwrap(), Some(Event::StartArray(1000)));
+        let mut sum = 0i64;
+        while let Some(ev) = dec.next_event().unwrap() {
+            if let Event::Scalar(Value::Int(n)) = ev {
+                sum += n;
+            }
+        }
+        assert_eq!(sum, (0..1000).sum::<i64>());
+        assert_eq!(dec.bytes_consumed(), enc.len());
+    }
+}
@@ -0,0 +1,220 @@
+// ---------------------------------------------------------------------------
+// ValueRef — zero-copy borrowed view of an NRF buffer
+//
+// `decode` allocates an owned String / Vec<u8> for every leaf. Verifiers
+// that only read a few fields (or hash sub-slices) pay for allocations they
+// never use. `decode_ref` runs the same walk as `decode` (it IS the crate's
+// one slice decoder; `decode` copies its result out), so the canonical
+// checks and DecodeOpts limits are shared, and strings and bytes stay
+// slices of the input.
+//
+// Maps are kept as a Vec of (key, value) pairs in wire order — which, for
+// canonical input, IS sorted byte order — so `get` is a binary search.
+// ---------------------------------------------------------------------------
+
+use crate::{decode_ref_located_with_opts, DecodeError, DecodeOpts, Result, Value};
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// Borrowed NRF value. Leaves point into the decoded buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueRef<'a> {
+    Null,
+    Bool(bool),
+    Int(i64),
+    String(&'a str),
+    Bytes(&'a [u8]),
+    Array(Vec<ValueRef<'a>>),
+    /// Pairs in canonical (byte-sorted, unique) key order.
+    Map(Vec<(&'a str, ValueRef<'a>)>),
+}
+
+impl<'a> ValueRef<'a> {
+    /// Copy into an owned `Value`.
+    pub fn to_owned_value(&self) -> Value {
+        match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Bool(b) => Value::Bool(*b),
+            ValueRef::Int(n) => Value::Int(*n),
+            ValueRef::String(s) => Value::String((*s).to_string()),
+            ValueRef::Bytes(b) => Value::Bytes(b.to_vec()),
+            ValueRef::Array(items) => {
+                Value::Array(items.iter().map(ValueRef::to_owned_value).collect())
+            }
+            ValueRef::Map(pairs) => Value::Map(
+                pairs
+                    .iter()
+                    .map(|(k, v)| ((*k).to_string(), v.to_owned_value()))
+                    .collect::<BTreeMap<_, _>>(),
+            ),
+        }
+    }
+
+    /// Look up a map key. `None` if not a map or key absent.
+    pub fn get(&self, key: &str) -> Option<&ValueRef<'a>> {
+        match self {
+            ValueRef::Map(pairs) => pairs
+                .binary_search_by(|(k, _)| k.as_bytes().cmp(key.as_bytes()))
+                .ok()
+                .map(|i| &pairs[i].1),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            ValueRef::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            ValueRef::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            ValueRef::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for Value {
+    fn from(v: ValueRef<'_>) -> Self {
+        v.to_owned_value()
+    }
+}
+
+/// Borrow an owned `Value` as a `ValueRef`, so code written against the
+/// borrowed view also accepts values that were built in memory.
+impl<'a> From<&'a Value> for ValueRef<'a> {
+    fn from(v: &'a Value) -> Self {
+        match v {
+            Value::Null => ValueRef::Null,
+            Value::Bool(b) => ValueRef::Bool(*b),
+            Value::Int(n) => ValueRef::Int(*n),
+            Value::String(s) => ValueRef::String(s),
+            Value::Bytes(b) => ValueRef::Bytes(b),
+            Value::Array(items) => ValueRef::Array(items.iter().map(ValueRef::from).collect()),
+            Value::Map(m) => ValueRef::Map(
+                m.iter()
+                    .map(|(k, v)| (k.as_str(), ValueRef::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Zero-copy decode with default (conservative) resource limits.
+#[cfg_attr(feature = "obs", tracing::instrument(level = "trace", skip_all, fields(len = data.len())))]
+pub fn decode_ref(data: &[u8]) -> Result<ValueRef<'_>> {
+    decode_ref_with_opts(data, &DecodeOpts::default())
+}
+
+/// Zero-copy decode with explicit resource limits. Same acceptance as
+/// `decode_with_opts`: any buffer one accepts, the other accepts.
+#[cfg_attr(feature = "obs", tracing::instrument(level = "trace", skip_all, fields(len = data.len())))]
+pub fn decode_ref_with_opts<'a>(data: &'a [u8], opts: &DecodeOpts) -> Result<ValueRef<'a>> {
+    decode_ref_located_with_opts(data, opts).map_err(|e| e.kind)
+}
+
+/// Zero-copy decode with default limits, reporting where a rejection happened.
+pub fn decode_ref_located(data: &[u8]) -> core::result::Result<ValueRef<'_>, DecodeError> {
+    decode_ref_located_with_opts(data, &DecodeOpts::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, encode, Error};
+    use alloc::vec;
+
+    fn sample() -> Value {
+        let mut m = BTreeMap::new();
+        m.insert("b".into(), Value::Bytes(vec![1, 2, 3]));
+        m.insert("n".into(), Value::Int(-5));
+        m.insert("s".into(), Value::String("\u{00E9}t\u{00E9}".into()));
+        m.insert(
+            "xs".into(),
+            Value::Array(vec![Value::Null, Value::Bool(false)]),
+        );
+        Value::Map(m)
+    }
+
+    #[test]
+    fn roundtrip_matches_owned_decode() {
+        let v = sample();
+        let enc = encode(&v);
+        let r = decode_ref(&enc).unwrap();
+        assert_eq!(r.to_owned_value(), decode(&enc).unwrap());
+        assert_eq!(Value::from(r), v);
+    }
+
+    #[test]
+    fn leaves_borrow_from_input() {
+        let enc = encode(&sample());
+        let r = decode_ref(&enc).unwrap();
+        let b = r.get("b").and_then(ValueRef::as_bytes).unwrap();
+        let range = enc.as_ptr_range();
+        assert!(range.contains(&b.as_ptr()));
+        assert_eq!(
+            r.get("s").and_then(ValueRef::as_str),
+            Some("\u{00E9}t\u{00E9}")
+        );
+        assert_eq!(r.get("n").and_then(ValueRef::as_int), Some(-5));
+        assert!(r.get("missing").is_none());
+    }
+
+    #[test]
+    fn borrowed_view_of_owned_value() {
+        let v = sample();
+        assert_eq!(ValueRef::from(&v).to_owned_value(), v);
+    }
+
+    #[test]
+    fn same_rejections_as_owned_decoder() {
+        let mut nfd = BTreeMap::new();
+        nfd.insert("e\u{0301}".to_string(), Value::Null);
+        let cases: Vec<Vec<u8>> = vec![
+            b"nrf1\x07\x02\x04\x01b\x00\x04\x01a\x00".to_vec(), // unsorted
+            b"nrf1\x07\x02\x04\x01a\x00\x04\x01a\x00".to_vec(), // duplicate
+            b"nrf1\x07\x01\x03\x00\x00\x00\x00\x00\x00\x00\x00\x00".to_vec(), // non-string key
+            b"nrf1\x04\x80\x00".to_vec(),                       // non-minimal varint
+            b"nrf1\x04\x02\xff\xfe".to_vec(),                   // invalid UTF-8
+            b"nrf1\x00\x00".to_vec(),                           // trailing
+            b"nrf1\x08".to_vec(),                               // bad tag
+            b"nrf0\x00".to_vec(),                               // bad magic
+            encode(&Value::Map(nfd)),                           // NFD key
+        ];
+        for data in cases {
+            assert_eq!(
+                decode_ref(&data).unwrap_err(),
+                decode(&data).unwrap_err(),
+                "{data:02x?}"
+            );
+            assert_eq!(
+                decode_ref_located(&data).unwrap_err(),
+                crate::decode_located(&data).unwrap_err(),
+                "{data:02x?}"
+            );
+        }
+    }
+
+    #[test]
+    fn enforces_decode_opts() {
+        let enc = encode(&Value::Array(vec![Value::Null; 10]));
+        let opts = DecodeOpts {
+            max_array_len: 5,
+            ..DecodeOpts::default()
+        };
+        assert_eq!(
+            decode_ref_with_opts(&enc, &opts).unwrap_err(),
+            Error::ArrayTooLong
+        );
+    }
+}
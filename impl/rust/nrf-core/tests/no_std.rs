@@ -0,0 +1,68 @@
+//! Exercises the codec through the `no_std + alloc` surface. The test file
+//! itself is `no_std`, so it only compiles if everything used here exists
+//! without std. CI runs it against the library built without std too:
+//!
+//!     cargo test -p nrf-core --no-default-features --test no_std
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::ToString;
+use alloc::vec;
+use nrf_core::rho::{self, Profile, Rule};
+use nrf_core::{
+    blake3_cid, decode, encode, from_diag, hash_value, nrf, to_diag, try_encode, Error, NrfDecimal,
+    Value,
+};
+
+#[test]
+fn codec_roundtrip_without_std() {
+    let v = nrf!({
+        "act": "ATTEST",
+        "n": -42,
+        "ok": true,
+        "sig": bytes(vec![0u8, 1, 2]),
+        "tags": ["a", "b"],
+        "z": null,
+    });
+    let bytes = encode(&v);
+    assert_eq!(decode(&bytes).unwrap(), v);
+    assert_eq!(try_encode(&v).unwrap(), bytes);
+    assert_eq!(hash_value(&v), *blake3::hash(&bytes).as_bytes());
+    assert!(blake3_cid(&v).starts_with("b3:"));
+    assert_eq!(from_diag(&to_diag(&v)).unwrap(), v);
+}
+
+#[test]
+fn canonical_checks_without_std() {
+    let mut bytes = encode(&nrf!({ "a": 1, "b": 2 }));
+    // Swap the two keys in place: "a" → "b" and "b" → "a".
+    let a = bytes.iter().position(|&b| b == b'a').unwrap();
+    let b = bytes.iter().position(|&b| b == b'b').unwrap();
+    bytes.swap(a, b);
+    assert_eq!(decode(&bytes), Err(Error::UnsortedKeys));
+    assert_eq!(decode(&[]), Err(Error::InvalidMagic));
+    assert_eq!(try_encode(&Value::from("e\u{301}")), Err(Error::NotNFC));
+}
+
+#[test]
+fn rho_validators_without_std() {
+    assert_eq!(rho::normalize_decimal("1.50").unwrap(), "1.5");
+    assert!(rho::normalize_decimal("01.5").is_err());
+    assert!(rho::normalize_decimal("1e2").is_err());
+    assert_eq!(
+        rho::normalize_timestamp("2024-01-15T10:30:00.100Z").unwrap(),
+        "2024-01-15T10:30:00.1Z"
+    );
+    assert!(rho::normalize_timestamp("2024-01-15 10:30:00Z").is_err());
+
+    let profile = Profile::new(&[("ts", Rule::Timestamp), ("amount", Rule::Decimal)]).unwrap();
+    let v = nrf!({ "amount": "10.00", "ts": "2024-01-15T10:30:00.000Z" });
+    let n = rho::normalize_with_profile(&v, &profile).unwrap();
+    assert_eq!(n["amount"].as_str(), Some("10"));
+    assert_eq!(n["ts"].as_str(), Some("2024-01-15T10:30:00Z"));
+
+    let total = NrfDecimal::parse("0.1").unwrap() + NrfDecimal::parse("0.2").unwrap();
+    assert_eq!(total.to_string(), "0.3");
+}
@@ -0,0 +1,416 @@
+//! Builder — one call from header fields to a signed capsule.
+//!
+//! ```text
+//! let c = CapsuleBuilder::new("EVALUATE", "did:ubl:alice")
+//!     .body(json!({"score": 7}))
+//!     .dst("did:ubl:bob")
+//!     .ttl(Duration::from_secs(60))
+//!     .sign(&sk)?;
+//! ```
+//!
+//! The builder generates the nonce, stamps `hdr.ts` from a [`Clock`]
+//! (host time unless one is injected), validates the act and the DIDs,
+//! keeps `seal.aud` consistent with `hdr.dst`, then computes the ID and
+//! signs ([`crate::seal::sign`]).
+
+use crate::seal;
+use crate::types::{Capsule, CoSig, Envelope, Header, Links, Seal, DOMAIN};
+use std::collections::BTreeSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Acts a capsule may carry.
+pub const ACTS: [&str; 3] = ["ATTEST", "EVALUATE", "TRANSACT"];
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BuildError {
+    #[error("Err.Hdr.BadAct: {0} is not one of ATTEST, EVALUATE, TRANSACT")]
+    BadAct(String),
+    #[error("Err.Canon.NotASCII: {field} must be a non-empty printable ASCII identifier")]
+    NotASCII { field: &'static str },
+    #[error("Err.Seal.DuplicateSigner: {kid} appears more than once in the seal")]
+    DuplicateSigner { kid: String },
+    #[error("Err.Seal.BadAudience: seal.aud does not match hdr.dst")]
+    BadAudience,
+    #[error("Err.Hdr.Expired: exp {exp} is not after ts {ts_ns} (epoch-nanos)")]
+    ExpBeforeTs { exp: i64, ts_ns: i64 },
+    /// ID or signature could not be computed (`Err.Canon.*`, `Err.Enc.*`).
+    #[error("{0}")]
+    Sign(String),
+}
+
+// ---------------------------------------------------------------------------
+// Clock
+// ---------------------------------------------------------------------------
+
+/// Source of `hdr.ts` (epoch-milliseconds).
+pub trait Clock {
+    fn now_ms(&self) -> i64;
+}
+
+/// Host time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        let d = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        i64::try_from(d.as_millis()).unwrap_or(i64::MAX)
+    }
+}
+
+/// Plain closures, e.g. `|| 1_700_000_000_000` in tests.
+impl<F> Clock for F
+where
+    F: Fn() -> i64,
+{
+    fn now_ms(&self) -> i64 {
+        self()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Builder
+// ---------------------------------------------------------------------------
+
+enum Expiry {
+    /// Absolute, epoch-nanos.
+    At(i64),
+    /// Relative to `hdr.ts`.
+    Ttl(Duration),
+}
+
+/// Fluent constructor for signed capsules.
+pub struct CapsuleBuilder {
+    act: String,
+    src: String,
+    dst: Option<String>,
+    aud: Option<String>,
+    kid: Option<String>,
+    scope: Option<String>,
+    exp: Option<Expiry>,
+    body: serde_json::Value,
+    prev: Option<String>,
+    evidence: Vec<String>,
+    cosigners: Vec<String>,
+    nonce: Option<[u8; 16]>,
+    clock: Box<dyn Clock>,
+}
+
+impl CapsuleBuilder {
+    pub fn new(act: impl Into<String>, src: impl Into<String>) -> Self {
+        Self {
+            act: act.into(),
+            src: src.into(),
+            dst: None,
+            aud: None,
+            kid: None,
+            scope: None,
+            exp: None,
+            body: serde_json::Value::Null,
+            prev: None,
+            evidence: Vec::new(),
+            cosigners: Vec::new(),
+            nonce: None,
+            clock: Box::new(SystemClock),
+        }
+    }
+
+    pub fn body(mut self, body: serde_json::Value) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Recipient. The seal's audience follows unless [`Self::aud`] is set.
+    pub fn dst(mut self, did: impl Into<String>) -> Self {
+        self.dst = Some(did.into());
+        self
+    }
+
+    /// Explicit `seal.aud`; must equal `hdr.dst`.
+    pub fn aud(mut self, did: impl Into<String>) -> Self {
+        self.aud = Some(did.into());
+        self
+    }
+
+    /// Seal key id (default: `src`).
+    pub fn kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    /// Absolute expiry, epoch-nanos.
+    pub fn exp(mut self, exp_ns: i64) -> Self {
+        self.exp = Some(Expiry::At(exp_ns));
+        self
+    }
+
+    /// Expiry relative to the stamped `hdr.ts`.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.exp = Some(Expiry::Ttl(ttl));
+        self
+    }
+
+    /// `env.links.prev`: CID of the prior capsule in a pipeline.
+    pub fn prev(mut self, cid: impl Into<String>) -> Self {
+        self.prev = Some(cid.into());
+        self
+    }
+
+    pub fn evidence(mut self, cid: impl Into<String>) -> Self {
+        self.evidence.push(cid.into());
+        self
+    }
+
+    /// Add an unsigned co-signer slot (see [`crate::seal::cosign`]).
+    pub fn cosigner(mut self, kid: impl Into<String>) -> Self {
+        self.cosigners.push(kid.into());
+        self
+    }
+
+    /// Fixed nonce, for reproducible vectors (default: 16 random bytes).
+    pub fn nonce(mut self, nonce: [u8; 16]) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Validate, stamp, compute the ID and sign with the author key.
+    pub fn sign(self, sk: &ed25519_dalek::SigningKey) -> Result<Capsule, BuildError> {
+        let mut c = self.build()?;
+        seal::sign(&mut c, sk).map_err(BuildError::Sign)?;
+        Ok(c)
+    }
+
+    /// Validate and stamp, leaving `id` and `seal.sig` zeroed (e.g. to
+    /// encrypt the body before signing).
+    pub fn build(self) -> Result<Capsule, BuildError> {
+        if !ACTS.contains(&self.act.as_str()) {
+            return Err(BuildError::BadAct(self.act));
+        }
+        let kid = self.kid.unwrap_or_else(|| self.src.clone());
+        let aud = match (self.aud, &self.dst) {
+            (None, dst) => dst.clone(),
+            (Some(aud), Some(dst)) if aud == *dst => Some(aud),
+            (Some(_), _) => return Err(BuildError::BadAudience),
+        };
+        check_ascii("hdr.src", Some(&self.src))?;
+        check_ascii("hdr.dst", self.dst.as_ref())?;
+        check_ascii("seal.kid", Some(&kid))?;
+        check_ascii("env.links.prev", self.prev.as_ref())?;
+        let mut seen = BTreeSet::from([kid.as_str()]);
+        for cosigner in &self.cosigners {
+            check_ascii("seal.cosigs[].kid", Some(cosigner))?;
+            if !seen.insert(cosigner.as_str()) {
+                return Err(BuildError::DuplicateSigner {
+                    kid: cosigner.clone(),
+                });
+            }
+        }
+
+        let ts = self.clock.now_ms();
+        let ts_ns = ts.saturating_mul(1_000_000);
+        let exp = match self.exp {
+            None => None,
+            Some(Expiry::At(exp)) => Some(exp),
+            Some(Expiry::Ttl(ttl)) => {
+                let ttl_ns = i64::try_from(ttl.as_nanos()).unwrap_or(i64::MAX);
+                Some(ts_ns.saturating_add(ttl_ns))
+            }
+        };
+        if let Some(exp) = exp {
+            if exp <= ts_ns {
+                return Err(BuildError::ExpBeforeTs { exp, ts_ns });
+            }
+        }
+        let nonce = self.nonce.unwrap_or_else(|| {
+            use rand_core::RngCore;
+            let mut n = [0u8; 16];
+            rand_core::OsRng.fill_bytes(&mut n);
+            n
+        });
+
+        Ok(Capsule {
+            domain: DOMAIN.into(),
+            id: [0u8; 32],
+            hdr: Header {
+                src: self.src,
+                dst: self.dst,
+                nonce,
+                ts,
+                act: self.act,
+                scope: self.scope,
+                exp,
+            },
+            env: Envelope {
+                body: self.body,
+                links: self.prev.map(|prev| Links { prev: Some(prev) }),
+                evidence: self.evidence,
+                enc: None,
+                merkle: None,
+            },
+            seal: Seal {
+                kid,
+                sig: [0u8; 64],
+                scope: "capsule".into(),
+                aud,
+                cosigs: self.cosigners.into_iter().map(CoSig::new).collect(),
+            },
+            receipts: vec![],
+        })
+    }
+}
+
+fn check_ascii(field: &'static str, value: Option<&String>) -> Result<(), BuildError> {
+    match value {
+        Some(v) if v.is_empty() || !v.bytes().all(|b| b.is_ascii_graphic()) => Err(BuildError::NotASCII { field }),
+        _ => Ok(()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T: i64 = 1_700_000_000_000;
+
+    fn sk() -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng)
+    }
+
+    #[test]
+    fn signs_in_one_call() {
+        let sk = sk();
+        let c = CapsuleBuilder::new("EVALUATE", "did:ubl:alice")
+            .kid("did:ubl:alice#k1")
+            .body(serde_json::json!({"score": 7}))
+            .dst("did:ubl:bob")
+            .ttl(Duration::from_secs(60))
+            .prev("b3:00ff")
+            .evidence("b3:abcd")
+            .clock(|| T)
+            .sign(&sk)
+            .unwrap();
+        assert_eq!(c.hdr.ts, T);
+        assert_eq!(c.hdr.exp, Some(T * 1_000_000 + 60_000_000_000));
+        assert_eq!(c.seal.aud.as_deref(), Some("did:ubl:bob"));
+        assert_eq!(
+            c.env.links.as_ref().unwrap().prev.as_deref(),
+            Some("b3:00ff")
+        );
+        assert_eq!(c.id, crate::compute_id(&c).unwrap());
+        let opts = seal::VerifyOpts {
+            now_ns: Some(T * 1_000_000),
+            ..Default::default()
+        };
+        seal::verify_with_opts(&c, &sk.verifying_key(), &opts).unwrap();
+
+        // Fresh nonce per capsule; fixed on request.
+        let again = |b: CapsuleBuilder| b.clock(|| T).build().unwrap().hdr.nonce;
+        let b = || CapsuleBuilder::new("ATTEST", "did:ubl:alice");
+        assert_ne!(again(b()), again(b()));
+        assert_eq!(again(b().nonce([9; 16])), [9; 16]);
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let b = || CapsuleBuilder::new("TRANSACT", "did:ubl:alice").clock(|| T);
+        assert_eq!(
+            CapsuleBuilder::new("PAY", "did:ubl:alice")
+                .build()
+                .unwrap_err(),
+            BuildError::BadAct("PAY".into())
+        );
+        assert_eq!(
+            CapsuleBuilder::new("ATTEST", "did:ubl:álice")
+                .build()
+                .unwrap_err(),
+            BuildError::NotASCII { field: "hdr.src" }
+        );
+        assert_eq!(
+            b().cosigner("").build().unwrap_err(),
+            BuildError::NotASCII {
+                field: "seal.cosigs[].kid"
+            }
+        );
+        assert_eq!(
+            b().kid("did:ubl:alice #k1").build().unwrap_err(),
+            BuildError::NotASCII { field: "seal.kid" }
+        );
+        assert_eq!(
+            b().cosigner("did:ubl:bob#k1")
+                .cosigner("did:ubl:bob#k1")
+                .build()
+                .unwrap_err(),
+            BuildError::DuplicateSigner {
+                kid: "did:ubl:bob#k1".into()
+            }
+        );
+        assert_eq!(
+            b().cosigner("did:ubl:alice").build().unwrap_err(),
+            BuildError::DuplicateSigner {
+                kid: "did:ubl:alice".into()
+            }
+        );
+        assert_eq!(
+            b().aud("did:ubl:bob").build().unwrap_err(),
+            BuildError::BadAudience
+        );
+        assert_eq!(
+            b().dst("did:ubl:carol")
+                .aud("did:ubl:bob")
+                .build()
+                .unwrap_err(),
+            BuildError::BadAudience
+        );
+        assert_eq!(
+            b().exp(T * 1_000_000).build().unwrap_err(),
+            BuildError::ExpBeforeTs {
+                exp: T * 1_000_000,
+                ts_ns: T * 1_000_000
+            }
+        );
+        assert!(matches!(
+            b().body(serde_json::json!({"x": 1.5})).sign(&sk()).unwrap_err(),
+            BuildError::Sign(e) if e.starts_with("Err.Canon.Float")
+        ));
+    }
+
+    #[test]
+    fn cosigners_get_empty_slots() {
+        let (alice, bob) = (sk(), sk());
+        let mut c = CapsuleBuilder::new("TRANSACT", "did:ubl:alice")
+            .cosigner("did:ubl:bob#k1")
+            .sign(&alice)
+            .unwrap();
+        assert_eq!(c.seal.cosigs.len(), 1);
+        assert_eq!(c.seal.cosigs[0].sig, [0u8; 64]);
+        seal::cosign(&mut c, "did:ubl:bob#k1", &bob).unwrap();
+        let opts = seal::VerifyOpts {
+            threshold: Some(seal::ThresholdPolicy {
+                k: 2,
+                keys: [("did:ubl:bob#k1".to_string(), bob.verifying_key())].into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        seal::verify_with_opts(&c, &alice.verifying_key(), &opts).unwrap();
+    }
+}
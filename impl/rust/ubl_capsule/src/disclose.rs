@@ -0,0 +1,228 @@
+//! Disclosure mode — withhold body fields without touching the ID or seal.
+//!
+//! [`commit_body`] salts `env.body` (one salt per leaf, from a seed) and
+//! stores the salted Merkle root and salt tree in `env.merkle`; the ID then
+//! covers the root instead of the body. [`redact`] later swaps body
+//! subtrees for `{"$redacted": "b3m:<hex>"}` markers and their salt entries
+//! for withheld hashes, so the redacted capsule has the same ID and every
+//! seal and receipt still verifies. Without the salts a withheld field
+//! cannot be recovered by hashing guesses.
+//!
+//! Commit before signing: committing changes the ID.
+
+use crate::id::{compute_id, json_to_nrf_strict, nrf_to_json_strict};
+use crate::types::{Capsule, MerkleBody};
+use nrf_core::merkle::Seed;
+use nrf_core::{Disclosure, Query, Value};
+
+/// Commit `env.body` by its salted Merkle root and recompute the ID. The
+/// capsule is left untouched on error.
+pub fn commit_body(c: &mut Capsule, seed: &Seed) -> Result<(), String> {
+    if c.env.merkle.is_some() {
+        return Err("Err.Merkle.AlreadyCommitted: env.merkle is already present".into());
+    }
+    // Canon 2,6: the body must be valid before it is committed.
+    compute_id(c)?;
+    let body = json_to_nrf_strict(&c.env.body)?;
+    let d = Disclosure::new(&body, seed);
+    let mut out = c.clone();
+    out.env.merkle = Some(MerkleBody {
+        root: d.cid().map_err(|e| format!("Err.Merkle.BadSalts: {e}"))?,
+        salts: salts_to_json(&d.salts),
+    });
+    out.id = compute_id(&out)?;
+    *c = out;
+    Ok(())
+}
+
+/// Withhold every match of each query path (`a.b.0`, `/a/b`,
+/// `$.items[*].card`) in a committed body. The ID is unchanged; a path
+/// matching nothing is an error.
+pub fn redact(c: &mut Capsule, paths: &[&str]) -> Result<(), String> {
+    let mb = c
+        .env
+        .merkle
+        .as_ref()
+        .ok_or("Err.Merkle.NotCommitted: capsule has no env.merkle")?;
+    let mut d = Disclosure {
+        value: json_to_nrf_strict(&c.env.body)?,
+        salts: salts_from_json(&mb.salts)?,
+    };
+    for p in paths {
+        let q = Query::parse(p).map_err(|e| format!("Err.Merkle.BadPath: {p}: {e}"))?;
+        let matches = q.select_paths(&d.value);
+        if matches.is_empty() {
+            return Err(format!("Err.Merkle.PathNotFound: {p}"));
+        }
+        let mut next = d.clone();
+        for (at, _) in matches {
+            next.redact(&at).map_err(|e| format!("Err.Merkle.{e}"))?;
+        }
+        d = next;
+    }
+    let mut out = c.clone();
+    out.env.body = nrf_to_json_strict(&d.value)?;
+    out.env.merkle = Some(MerkleBody {
+        root: mb.root.clone(),
+        salts: salts_to_json(&d.salts),
+    });
+    if compute_id(&out)? != compute_id(c)? {
+        return Err("Err.Merkle.RootMismatch: redaction changed the ID".into());
+    }
+    *c = out;
+    Ok(())
+}
+
+/// Salt tree from its JSON form: hex strings become bytes.
+pub(crate) fn salts_from_json(j: &serde_json::Value) -> Result<Value, String> {
+    match j {
+        serde_json::Value::String(h) => hex::decode(h)
+            .map(Value::Bytes)
+            .map_err(|e| format!("Err.Merkle.BadSalts: {e}")),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(salts_from_json)
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        serde_json::Value::Object(obj) => obj
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), salts_from_json(v)?)))
+            .collect::<Result<_, String>>()
+            .map(Value::Map),
+        _ => Err("Err.Merkle.BadSalts: salt tree holds hex strings, maps and arrays".into()),
+    }
+}
+
+fn salts_to_json(v: &Value) -> serde_json::Value {
+    match v {
+        Value::Bytes(b) => serde_json::Value::String(hex::encode(b)),
+        Value::Array(items) => serde_json::Value::Array(items.iter().map(salts_to_json).collect()),
+        Value::Map(m) => serde_json::Value::Object(
+            m.iter()
+                .map(|(k, v)| (k.clone(), salts_to_json(v)))
+                .collect(),
+        ),
+        // `Disclosure` salt trees hold nothing else.
+        _ => serde_json::Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receipt::{add_hop, verify_chain};
+    use crate::seal;
+    use crate::types::*;
+
+    const SEED: Seed = [5u8; 32];
+
+    fn make_capsule() -> Capsule {
+        Capsule {
+            domain: DOMAIN.into(),
+            id: [0u8; 32],
+            hdr: Header {
+                src: "did:ubl:alice#k1".into(),
+                dst: Some("did:ubl:bob#x1".into()),
+                nonce: [7u8; 16],
+                ts: 1_700_000_000_000,
+                act: "ATTEST".into(),
+                scope: None,
+                exp: None,
+            },
+            env: Envelope {
+                body: serde_json::json!({"age": 41, "name": "Ana", "tags": ["a", "b"]}),
+                links: None,
+                evidence: vec![],
+                enc: None,
+                merkle: None,
+            },
+            seal: Seal {
+                kid: "did:ubl:alice#k1".into(),
+                sig: [0u8; 64],
+                scope: "capsule".into(),
+                aud: None,
+                cosigs: vec![],
+            },
+            receipts: vec![],
+        }
+    }
+
+    fn sealed() -> (Capsule, ed25519_dalek::SigningKey) {
+        let author = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
+        let mut c = make_capsule();
+        commit_body(&mut c, &SEED).unwrap();
+        seal::sign(&mut c, &author).unwrap();
+        (c, author)
+    }
+
+    #[test]
+    fn redacted_capsule_keeps_id_seal_and_hops() {
+        let (mut c, author) = sealed();
+        let relay = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
+        let hop = add_hop(c.id, [0u8; 32], "relay", "did:ubl:relay#k1", 1, &relay).unwrap();
+        c.receipts.push(hop);
+        let id = c.id;
+
+        redact(&mut c, &["age", "$.tags[0]"]).unwrap();
+        assert_eq!(c.id, id);
+        assert_eq!(compute_id(&c).unwrap(), id);
+        assert!(c.env.body["age"]["$redacted"].is_string());
+        assert_eq!(c.env.body["name"], "Ana");
+        seal::verify(&c, &author.verifying_key()).unwrap();
+        let rpk = relay.verifying_key();
+        let resolve = |_: &str| Some(rpk);
+        verify_chain(&c.id, &c.receipts, &resolve).unwrap();
+
+        // The JSON form round-trips.
+        let back: Capsule = serde_json::from_str(&serde_json::to_string(&c).unwrap()).unwrap();
+        seal::verify(&back, &author.verifying_key()).unwrap();
+    }
+
+    #[test]
+    fn tampered_or_forged_bodies_are_rejected() {
+        let (c, author) = sealed();
+
+        let mut t = c.clone();
+        t.env.body["name"] = serde_json::json!("Eve");
+        assert!(compute_id(&t)
+            .unwrap_err()
+            .starts_with("Err.Merkle.RootMismatch"));
+        assert!(seal::verify(&t, &author.verifying_key()).is_err());
+
+        // Pasting a marker into the body without its salt entry is caught.
+        let mut r = c.clone();
+        redact(&mut r, &["age"]).unwrap();
+        let mut t = c.clone();
+        t.env.body["age"] = r.env.body["age"].clone();
+        assert!(compute_id(&t).is_err());
+
+        let mut t = c.clone();
+        t.env.merkle.as_mut().unwrap().salts["age"] = serde_json::json!("00");
+        assert!(compute_id(&t)
+            .unwrap_err()
+            .starts_with("Err.Merkle.BadSalts"));
+    }
+
+    #[test]
+    fn commit_and_redact_reject_bad_input() {
+        let mut c = make_capsule();
+        assert!(redact(&mut c, &["age"])
+            .unwrap_err()
+            .starts_with("Err.Merkle.NotCommitted"));
+        commit_body(&mut c, &SEED).unwrap();
+        assert!(commit_body(&mut c, &SEED)
+            .unwrap_err()
+            .starts_with("Err.Merkle.AlreadyCommitted"));
+        let before = c.clone();
+        assert!(redact(&mut c, &["age", "nope"])
+            .unwrap_err()
+            .starts_with("Err.Merkle.PathNotFound"));
+        assert_eq!(c.env.body, before.env.body);
+
+        let mut c = make_capsule();
+        c.env.body = serde_json::json!({"x": 1.5});
+        assert!(commit_body(&mut c, &SEED)
+            .unwrap_err()
+            .starts_with("Err.Canon.Float"));
+    }
+}
@@ -0,0 +1,241 @@
+//! Encrypted-body mode — `env.body` sealed to the destination.
+//!
+//! The clear body, as canonical NRF bytes of its ρ-normal form, is sealed with the `envelope` crate
+//! (X25519 + HKDF-SHA256 + XChaCha20-Poly1305) to `hdr.dst`'s X25519 key and
+//! stored in `env.enc`; `env.body` becomes `null`.
+//!
+//! Binding, in both directions:
+//!   - AAD = the capsule ID computed with `env.enc.ct` empty, so the
+//!     ciphertext cannot be moved to another capsule or header.
+//!   - The final `id` (and therefore every seal and receipt) commits to the
+//!     ciphertext, so relays verify and append hops without the key.
+//!
+//! Encrypt before signing: encrypting changes the ID.
+
+use crate::id::{compute_id, json_to_nrf_strict, nrf_to_json_strict};
+use crate::types::{Capsule, EncryptedBody, ENC_ALG};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Replace `env.body` with its encryption to `rcpt` (the key of `hdr.dst`)
+/// and recompute the ID. The capsule is left untouched on error.
+pub fn encrypt_body(c: &mut Capsule, rcpt: &PublicKey) -> Result<(), String> {
+    if c.env.enc.is_some() {
+        return Err("Err.Enc.AlreadyEncrypted: env.enc is already present".into());
+    }
+    if c.hdr.dst.is_none() {
+        return Err("Err.Enc.NoRecipient: encrypted body requires hdr.dst".into());
+    }
+    // Canon 2,6: the clear body must be valid before it disappears.
+    compute_id(c)?;
+    let body = nrf_core::rho::normalize(&json_to_nrf_strict(&c.env.body)?)
+        .map_err(|e| format!("Err.Canon.Rho: {e}"))?;
+    let plaintext = nrf_core::try_encode(&body).map_err(|e| format!("Err.Canon.{e}"))?;
+
+    let mut out = c.clone();
+    out.env.body = serde_json::Value::Null;
+    out.env.enc = Some(EncryptedBody {
+        alg: ENC_ALG.into(),
+        ct: Vec::new(),
+    });
+    let aad = compute_id(&out)?;
+    let ct = envelope::Envelope::seal(rcpt, &plaintext, &aad)
+        .map_err(|e| format!("Err.Enc.Seal: {e}"))?;
+    if let Some(enc) = out.env.enc.as_mut() {
+        enc.ct = ct;
+    }
+    out.id = compute_id(&out)?;
+    *c = out;
+    Ok(())
+}
+
+/// Open `env.enc` with the recipient's secret key and return the clear body.
+///
+/// The plaintext must be canonical NRF (strict decode: NFC strings, Int64
+/// only, default limits) already in ρ-normal form, as `encrypt_body` writes
+/// it. Anything else is rejected, never repaired.
+pub fn decrypt_body(c: &Capsule, sk: &StaticSecret) -> Result<serde_json::Value, String> {
+    let enc = c
+        .env
+        .enc
+        .as_ref()
+        .ok_or("Err.Enc.NotEncrypted: capsule has no env.enc")?;
+    if enc.alg != ENC_ALG {
+        return Err(format!("Err.Enc.Alg: unsupported {}", enc.alg));
+    }
+    let aad = aad(c)?;
+    let plaintext = envelope::Envelope::open(sk, &enc.ct, &aad)
+        .map_err(|_| "Err.Enc.Open: wrong key, or ciphertext/header tampered".to_string())?;
+    // Canon 6: the sender's key does not make its plaintext canonical.
+    let body = nrf_core::decode(&plaintext).map_err(|e| format!("Err.Enc.Body: {e}"))?;
+    nrf_core::rho::validate(&body).map_err(|e| format!("Err.Canon.Rho: {e}"))?;
+    nrf_to_json_strict(&body)
+}
+
+/// The AAD of an encrypted capsule: its ID with `env.enc.ct` empty.
+fn aad(c: &Capsule) -> Result<[u8; 32], String> {
+    let mut bare = c.clone();
+    if let Some(enc) = bare.env.enc.as_mut() {
+        enc.ct.clear();
+    }
+    compute_id(&bare)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receipt::{add_hop, verify_chain};
+    use crate::seal;
+    use crate::types::*;
+    use nrf_core::Value;
+
+    fn make_capsule() -> Capsule {
+        Capsule {
+            domain: DOMAIN.into(),
+            id: [0u8; 32],
+            hdr: Header {
+                src: "did:ubl:alice#k1".into(),
+                dst: Some("did:ubl:bob#x1".into()),
+                nonce: [7u8; 16],
+                ts: 1_700_000_000_000,
+                act: "TRANSACT".into(),
+                scope: None,
+                exp: None,
+            },
+            env: Envelope {
+                body: serde_json::json!({"amount": 10, "memo": "secret"}),
+                links: None,
+                evidence: vec![],
+                enc: None,
+                merkle: None,
+            },
+            seal: Seal {
+                kid: "did:ubl:alice#k1".into(),
+                sig: [0u8; 64],
+                scope: "capsule".into(),
+                aud: None,
+                cosigs: vec![],
+            },
+            receipts: vec![],
+        }
+    }
+
+    fn x25519() -> (StaticSecret, PublicKey) {
+        let sk = StaticSecret::random_from_rng(rand_core::OsRng);
+        let pk = PublicKey::from(&sk);
+        (sk, pk)
+    }
+
+    #[test]
+    fn encrypt_seal_relay_decrypt() {
+        let (xsk, xpk) = x25519();
+        let author = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
+        let relay = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
+        let mut c = make_capsule();
+        let body = c.env.body.clone();
+
+        encrypt_body(&mut c, &xpk).unwrap();
+        assert!(c.env.body.is_null());
+        assert_eq!(c.id, compute_id(&c).unwrap());
+        seal::sign(&mut c, &author).unwrap();
+
+        // A relay holds no X25519 key: it verifies and appends a hop.
+        seal::verify(&c, &author.verifying_key()).unwrap();
+        let hop = add_hop(c.id, [0u8; 32], "relay", "did:ubl:relay#k1", 1, &relay).unwrap();
+        c.receipts.push(hop);
+        let rpk = relay.verifying_key();
+        let resolve = |_: &str| Some(rpk);
+        verify_chain(&c.id, &c.receipts, &resolve).unwrap();
+
+        assert_eq!(decrypt_body(&c, &xsk).unwrap(), body);
+        // The JSON form round-trips.
+        let back: Capsule = serde_json::from_str(&serde_json::to_string(&c).unwrap()).unwrap();
+        assert_eq!(decrypt_body(&back, &xsk).unwrap(), body);
+    }
+
+    #[test]
+    fn id_commits_to_ciphertext_and_aad_to_header() {
+        let (xsk, xpk) = x25519();
+        let author = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
+        let mut c = make_capsule();
+        encrypt_body(&mut c, &xpk).unwrap();
+        seal::sign(&mut c, &author).unwrap();
+
+        // Flipping a ciphertext byte breaks the ID (and the seal).
+        let mut t = c.clone();
+        t.env.enc.as_mut().unwrap().ct[60] ^= 1;
+        assert_ne!(compute_id(&t).unwrap(), c.id);
+        assert!(seal::verify(&t, &author.verifying_key()).is_err());
+        assert!(decrypt_body(&t, &xsk)
+            .unwrap_err()
+            .starts_with("Err.Enc.Open"));
+
+        // Moving the ciphertext to another header fails to open.
+        let mut moved = c.clone();
+        moved.hdr.ts += 1;
+        assert!(decrypt_body(&moved, &xsk)
+            .unwrap_err()
+            .starts_with("Err.Enc.Open"));
+
+        // Wrong recipient key.
+        let (other, _) = x25519();
+        assert!(decrypt_body(&c, &other)
+            .unwrap_err()
+            .starts_with("Err.Enc.Open"));
+    }
+
+    #[test]
+    fn decrypt_rejects_non_canonical_plaintext() {
+        let (xsk, xpk) = x25519();
+        let mut c = make_capsule();
+        encrypt_body(&mut c, &xpk).unwrap();
+
+        // A sender with the recipient's public key can seal any plaintext.
+        let reseal = |plaintext: &[u8]| {
+            let mut t = c.clone();
+            let ct = envelope::Envelope::seal(&xpk, plaintext, &aad(&t).unwrap()).unwrap();
+            t.env.enc.as_mut().unwrap().ct = ct;
+            t.id = compute_id(&t).unwrap();
+            t
+        };
+        let nrf = |v: Value| nrf_core::encode(&Value::Map([("k".to_string(), v)].into()));
+
+        let cases = [
+            (br#"{"x":1.5}"#.to_vec(), "Err.Enc.Body"),
+            (nrf(Value::String("e\u{301}".into())), "Err.Enc.Body"),
+            (nrf(Value::Null), "Err.Canon.Rho"),
+            (nrf(Value::Bytes(vec![1])), "Err.Canon.Bytes"),
+        ];
+        for (plaintext, want) in cases {
+            let err = decrypt_body(&reseal(&plaintext), &xsk).unwrap_err();
+            assert!(err.starts_with(want), "{want}: {err}");
+        }
+    }
+
+    #[test]
+    fn encrypt_rejects_bad_input() {
+        let (_, xpk) = x25519();
+        let mut c = make_capsule();
+        c.hdr.dst = None;
+        assert!(encrypt_body(&mut c, &xpk)
+            .unwrap_err()
+            .starts_with("Err.Enc.NoRecipient"));
+
+        let mut c = make_capsule();
+        c.env.body = serde_json::json!({"x": 1.5});
+        let before = c.env.body.clone();
+        assert!(encrypt_body(&mut c, &xpk)
+            .unwrap_err()
+            .starts_with("Err.Canon.Float"));
+        assert_eq!(c.env.body, before);
+
+        let mut c = make_capsule();
+        encrypt_body(&mut c, &xpk).unwrap();
+        assert!(encrypt_body(&mut c, &xpk)
+            .unwrap_err()
+            .starts_with("Err.Enc.AlreadyEncrypted"));
+
+        // A clear body next to a ciphertext has no valid ID.
+        c.env.body = serde_json::json!({"leak": true});
+        assert!(compute_id(&c).unwrap_err().starts_with("Err.Enc.ClearBody"));
+    }
+}
@@ -1,10 +1,14 @@
 //! Capsule ID — stable content-address that does NOT change when
 //! receipts or signatures are added/removed.
 //!
-//! `capsule_id = blake3(nrf.encode(capsule \ {id, seal.sig, receipts[*].sig}))`
+//! `capsule_id = blake3(nrf.encode(capsule \ {id, seal.sig, seal.cosigs[*].sig, receipts}))`
 //!
 //! The excluded fields are exactly those that depend on the ID itself
-//! (seal.sig signs over the ID) or are append-only metadata (receipt sigs).
+//! (seal signatures sign over the ID) or are append-only metadata (receipts).
+//! Co-signer kids stay in: the signer set is part of what gets signed.
+//!
+//! In disclosure mode (`env.merkle`) `env.body` is replaced by its salted
+//! Merkle root, checked against the body and salts first.
 
 use crate::types::Capsule;
 use nrf_core::Value;
@@ -13,20 +17,21 @@ use std::collections::BTreeMap;
 /// Compute the stable capsule ID.
 ///
 /// The ID is `blake3(nrf.encode(ρ(core)))` where `core` is the capsule
-/// with `id`, `seal.sig`, and all `receipts[*].sig` zeroed out.
+/// without `id`, the seal signatures and the receipts.
 ///
-/// Returns Err if env.body contains floats or non-i64 numbers.
+/// Returns Err if env.body contains floats or non-i64 numbers, or if the
+/// core exceeds the default decode limits.
 /// Canon 2: no floats. Canon 3: ρ is the law. Canon 6: reject, never degrade.
 pub fn compute_id(c: &Capsule) -> Result<[u8; 32], String> {
     let core = capsule_core_value(c)?;
     let normalized = nrf_core::rho::normalize(&core)
         .map_err(|e| format!("Err.Canon.Rho: {e}"))?;
-    let bytes = nrf_core::encode(&normalized);
+    let bytes = nrf_core::try_encode(&normalized).map_err(|e| format!("Err.Canon.{e}"))?;
     Ok(*blake3::hash(&bytes).as_bytes())
 }
 
 /// Build the NRF Value representing the "core" of the capsule
-/// (everything except id, seal.sig, and ALL receipts).
+/// (everything except id, seal signatures, and ALL receipts).
 ///
 /// Receipts are entirely excluded because they are append-only metadata
 /// that arrives after the capsule is created. The ID must be stable
@@ -38,16 +43,32 @@ fn capsule_core_value(c: &Capsule) -> Result<Value, String> {
     root.insert("domain".into(), Value::String(c.domain.clone()));
 
     // hdr
+    if c.env.enc.is_some() && c.hdr.dst.is_none() {
+        return Err("Err.Enc.NoRecipient: encrypted body requires hdr.dst".into());
+    }
     root.insert("hdr".into(), header_value(&c.hdr));
 
     // env — Canon 2,6: json_to_nrf_strict rejects floats
     root.insert("env".into(), envelope_value(&c.env)?);
 
-    // seal (without sig — only kid, scope, aud)
+    // seal (without sigs — only kid, scope, aud and co-signer kids)
     let mut seal_map = BTreeMap::new();
     if let Some(aud) = &c.seal.aud {
         seal_map.insert("aud".into(), Value::String(aud.clone()));
     }
+    // Absent for single-seal capsules, so their IDs are unchanged.
+    if !c.seal.cosigs.is_empty() {
+        seal_map.insert(
+            "cosigners".into(),
+            Value::Array(
+                c.seal
+                    .cosigs
+                    .iter()
+                    .map(|s| Value::String(s.kid.clone()))
+                    .collect(),
+            ),
+        );
+    }
     seal_map.insert("kid".into(), Value::String(c.seal.kid.clone()));
     seal_map.insert("scope".into(), Value::String(c.seal.scope.clone()));
     root.insert("seal".into(), Value::Map(seal_map));
@@ -75,10 +96,35 @@ fn header_value(h: &crate::types::Header) -> Value {
     Value::Map(m)
 }
 
-fn envelope_value(e: &crate::types::Envelope) -> Result<Value, String> {
+pub(crate) fn envelope_value(e: &crate::types::Envelope) -> Result<Value, String> {
+    if e.enc.is_some() && !e.body.is_null() {
+        return Err("Err.Enc.ClearBody: env.body must be null when env.enc is present".into());
+    }
     let mut m = BTreeMap::new();
     // body: Canon 2,6 — reject floats, never degrade
-    m.insert("body".into(), json_to_nrf_strict(&e.body)?);
+    let body = json_to_nrf_strict(&e.body)?;
+    match &e.merkle {
+        // Disclosure mode: the ID covers the root, which must match the body.
+        Some(mb) => {
+            if e.enc.is_some() {
+                return Err("Err.Merkle.Encrypted: env.merkle and env.enc are exclusive".into());
+            }
+            let d = nrf_core::Disclosure {
+                value: body,
+                salts: crate::disclose::salts_from_json(&mb.salts)?,
+            };
+            let root = d.cid().map_err(|e| format!("Err.Merkle.BadSalts: {e}"))?;
+            if root != mb.root {
+                return Err(
+                    "Err.Merkle.RootMismatch: env.body does not match env.merkle.root".into(),
+                );
+            }
+            m.insert("root".into(), Value::String(root));
+        }
+        None => {
+            m.insert("body".into(), body);
+        }
+    }
     if !e.evidence.is_empty() {
         m.insert(
             "evidence".into(),
@@ -99,12 +145,18 @@ fn envelope_value(e: &crate::types::Envelope) -> Result<Value, String> {
             m.insert("links".into(), Value::Map(lm));
         }
     }
+    if let Some(enc) = &e.enc {
+        let mut em = BTreeMap::new();
+        em.insert("alg".into(), Value::String(enc.alg.clone()));
+        em.insert("ct".into(), Value::Bytes(enc.ct.clone()));
+        m.insert("enc".into(), Value::Map(em));
+    }
     Ok(Value::Map(m))
 }
 
 /// Convert serde_json::Value to nrf_core::Value.
 /// Canon 2: no floats. Canon 6: reject, never degrade.
-fn json_to_nrf_strict(j: &serde_json::Value) -> Result<Value, String> {
+pub(crate) fn json_to_nrf_strict(j: &serde_json::Value) -> Result<Value, String> {
     match j {
         serde_json::Value::Null => Ok(Value::Null),
         serde_json::Value::Bool(b) => Ok(Value::Bool(*b)),
@@ -133,6 +185,29 @@ fn json_to_nrf_strict(j: &serde_json::Value) -> Result<Value, String> {
     }
 }
 
+/// Convert a body nrf_core::Value back to serde_json::Value.
+/// The inverse of [`json_to_nrf_strict`]: a body holds no bytes.
+pub(crate) fn nrf_to_json_strict(v: &Value) -> Result<serde_json::Value, String> {
+    Ok(match v {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Int(i) => serde_json::Value::from(*i),
+        Value::String(s) => serde_json::Value::String(s.clone()),
+        Value::Array(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(nrf_to_json_strict)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Map(m) => serde_json::Value::Object(
+            m.iter()
+                .map(|(k, v)| Ok((k.clone(), nrf_to_json_strict(v)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        Value::Bytes(_) => return Err("Err.Canon.Bytes: body cannot hold bytes".into()),
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -159,12 +234,15 @@ mod tests {
                 body: serde_json::json!({"name": "test", "value": 42}),
                 links: None,
                 evidence: vec![],
+                enc: None,
+                merkle: None,
             },
             seal: Seal {
                 kid: "did:ubl:alice#key-1".into(),
                 sig: [0u8; 64],
                 scope: "capsule".into(),
                 aud: Some("did:ubl:bob".into()),
+                cosigs: vec![],
             },
             receipts: vec![],
         }
@@ -233,6 +311,17 @@ mod tests {
         assert_eq!(id1, id2, "ID must NOT change when seal.sig changes");
     }
 
+    #[test]
+    fn id_binds_cosigners_but_not_their_sigs() {
+        let c1 = make_capsule();
+        let mut c2 = make_capsule();
+        c2.seal.cosigs.push(CoSig::new("did:ubl:bob#key-1"));
+        let id2 = compute_id(&c2).unwrap();
+        assert_ne!(compute_id(&c1).unwrap(), id2, "signer set is in the ID");
+        c2.seal.cosigs[0].sig = [0xFF; 64];
+        assert_eq!(compute_id(&c2).unwrap(), id2);
+    }
+
     #[test]
     fn id_changes_when_hdr_changes() {
         let c1 = make_capsule();
@@ -10,13 +10,26 @@
 //!   - `receipts`: append-only chain of SIRP hops
 //!
 //! The `id` is stable: it does NOT change when receipts/signatures are added.
+//!
+//! [`builder::CapsuleBuilder`] produces a signed capsule in one call.
+//!
+//! With the `encrypt` feature, [`encrypt`] seals `env.body` to `hdr.dst`.
+//! [`disclose`] commits the body by a salted Merkle root so fields can be
+//! withheld later without changing the ID.
 
+pub mod builder;
+pub mod disclose;
+#[cfg(feature = "encrypt")]
+pub mod encrypt;
 pub mod id;
 #[cfg(feature = "metrics")]
 mod metrics_support;
 pub mod receipt;
+pub mod resolver;
 pub mod seal;
 pub mod types;
 
+pub use builder::CapsuleBuilder;
 pub use id::compute_id;
+pub use resolver::KeyResolver;
 pub use types::*;
@@ -9,9 +9,11 @@
 //! Chain verification: prev links must form a contiguous chain
 //! starting from `prev = 0x00…00` (first hop).
 
+use crate::resolver::{KeyResolver, ResolveError};
 use crate::types::{Receipt, RECEIPT_DOMAIN};
 use nrf_core::Value;
-use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use thiserror::Error;
 
 #[cfg(feature = "metrics")]
@@ -35,6 +37,8 @@ pub enum HopError {
     NotASCII,
     #[error("Err.Hop.Fork: duplicate prev detected at receipt[{0}]")]
     Fork(usize),
+    #[error("Err.Hop.Key: receipt[{0}]: {1}")]
+    Key(usize, ResolveError),
 }
 
 #[cfg(feature = "metrics")]
@@ -46,6 +50,7 @@ impl HopError {
             HopError::BadDomain => "BadDomain",
             HopError::NotASCII => "NotASCII",
             HopError::Fork(_) => "Fork",
+            HopError::Key(..) => "Key",
         }
     }
 }
@@ -120,7 +125,8 @@ pub fn verify_receipt(r: &Receipt, pk: &ed25519_dalek::VerifyingKey) -> Result<(
 ///   1. First receipt's `prev` must be `[0u8; 32]` (genesis)
 ///   2. Each subsequent receipt's `prev` must equal the previous receipt's `id`
 ///   3. All `of` fields must equal `capsule_id`
-///   4. All signatures must be valid (caller provides verifier fn)
+///   4. All signatures must be valid, under the key `resolver` gives for
+///      `node` at the receipt's `ts` (a plain `Fn(&str) -> Option<_>` works)
 ///   5. No forks (no duplicate `prev` values)
 #[cfg_attr(
     feature = "obs",
@@ -129,7 +135,7 @@ pub fn verify_receipt(r: &Receipt, pk: &ed25519_dalek::VerifyingKey) -> Result<(
 pub fn verify_chain(
     capsule_id: &[u8; 32],
     receipts: &[Receipt],
-    resolve_pk: &dyn Fn(&str) -> Option<ed25519_dalek::VerifyingKey>,
+    resolver: &dyn KeyResolver,
 ) -> Result<(), HopError> {
     #[cfg(feature = "metrics")]
     let t0 = std::time::Instant::now();
@@ -168,7 +174,9 @@ pub fn verify_chain(
             }
 
             // Verify signature
-            let pk = resolve_pk(&r.node).ok_or(HopError::BadSignature(i))?;
+            let pk = resolver
+                .resolve(&r.node, r.ts)
+                .map_err(|e| HopError::Key(i, e))?;
             let sig = ed25519_dalek::Signature::from_bytes(&r.sig);
             use ed25519_dalek::Verifier;
             pk.verify(&r.id, &sig)
@@ -200,6 +208,157 @@ pub fn verify_chain(
     result
 }
 
+// ---------------------------------------------------------------------------
+// Chain report (every hop, no bail-out)
+// ---------------------------------------------------------------------------
+
+/// Outcome of checking one hop. Only the first failing check is reported,
+/// in the order linkage → `of` → ASCII → id → key → signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HopStatus {
+    Ok,
+    /// `prev` is neither genesis nor the id of an earlier hop.
+    PrevGap,
+    /// `prev` was already extended by hop `with`.
+    Fork {
+        with: usize,
+    },
+    /// `of` is not the capsule id.
+    WrongOf,
+    NotAscii,
+    /// `id` does not match the receipt payload.
+    IdMismatch,
+    /// No key for `node`.
+    UnknownKey,
+    /// A key exists for `node` but not at `ts` (expired, not yet valid, ...).
+    KeyRejected {
+        reason: String,
+    },
+    BadSignature,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HopReport {
+    pub index: usize,
+    pub id: String,
+    pub prev: String,
+    pub kind: String,
+    pub node: String,
+    pub ts: i64,
+    #[serde(flatten)]
+    pub status: HopStatus,
+}
+
+/// Hops sharing the same `prev`, in chain order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForkReport {
+    pub prev: String,
+    pub hops: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainReport {
+    pub ok: bool,
+    /// Number of leading hops that form a valid chain on their own,
+    /// i.e. `verify_chain(&receipts[..valid_prefix])` succeeds.
+    pub valid_prefix: usize,
+    pub hops: Vec<HopReport>,
+    pub forks: Vec<ForkReport>,
+}
+
+impl ChainReport {
+    pub fn is_ok(&self) -> bool {
+        self.ok
+    }
+}
+
+/// Like [`verify_chain`], but checks every hop instead of stopping at the
+/// first error. A hop whose `prev` is the id of any earlier hop is linked;
+/// hops after a gap are still checked on their own.
+#[cfg_attr(
+    feature = "obs",
+    tracing::instrument(level = "debug", skip_all, fields(receipts_len = receipts.len()))
+)]
+pub fn verify_chain_report(
+    capsule_id: &[u8; 32],
+    receipts: &[Receipt],
+    resolver: &dyn KeyResolver,
+) -> ChainReport {
+    // prev → hops extending it; genesis is always a valid parent.
+    let mut children: BTreeMap<[u8; 32], Vec<usize>> = BTreeMap::new();
+    let mut known: HashMap<[u8; 32], usize> = HashMap::new();
+    let mut hops = Vec::with_capacity(receipts.len());
+
+    for (i, r) in receipts.iter().enumerate() {
+        let siblings = children.entry(r.prev).or_default();
+        siblings.push(i);
+        let status = if r.prev != [0u8; 32] && !known.contains_key(&r.prev) {
+            HopStatus::PrevGap
+        } else if siblings.len() > 1 {
+            HopStatus::Fork { with: siblings[0] }
+        } else if r.of != *capsule_id {
+            HopStatus::WrongOf
+        } else if !r.node.is_ascii() {
+            HopStatus::NotAscii
+        } else if r.id != compute_receipt_id(r) {
+            HopStatus::IdMismatch
+        } else {
+            match resolver.resolve(&r.node, r.ts) {
+                Err(ResolveError::NotFound(_)) => HopStatus::UnknownKey,
+                Err(e) => HopStatus::KeyRejected {
+                    reason: e.to_string(),
+                },
+                Ok(pk) => {
+                    use ed25519_dalek::Verifier;
+                    let sig = ed25519_dalek::Signature::from_bytes(&r.sig);
+                    match pk.verify(&r.id, &sig) {
+                        Ok(()) => HopStatus::Ok,
+                        Err(_) => HopStatus::BadSignature,
+                    }
+                }
+            }
+        };
+        known.entry(r.id).or_insert(i);
+        hops.push(HopReport {
+            index: i,
+            id: hex::encode(r.id),
+            prev: hex::encode(r.prev),
+            kind: r.kind.clone(),
+            node: r.node.clone(),
+            ts: r.ts,
+            status,
+        });
+    }
+
+    let mut expected_prev = [0u8; 32];
+    let mut valid_prefix = 0;
+    for (r, h) in receipts.iter().zip(&hops) {
+        if h.status != HopStatus::Ok || r.prev != expected_prev {
+            break;
+        }
+        expected_prev = r.id;
+        valid_prefix += 1;
+    }
+
+    let mut forks: Vec<ForkReport> = children
+        .into_iter()
+        .filter(|(_, hops)| hops.len() > 1)
+        .map(|(prev, hops)| ForkReport {
+            prev: hex::encode(prev),
+            hops,
+        })
+        .collect();
+    forks.sort_by_key(|f| f.hops[0]);
+
+    ChainReport {
+        ok: valid_prefix == receipts.len(),
+        valid_prefix,
+        hops,
+        forks,
+    }
+}
+
 /// Create a new receipt hop and sign it.
 #[cfg_attr(
     feature = "obs",
@@ -399,4 +558,156 @@ mod tests {
 
         assert_eq!(r1.id, r2.id);
     }
+
+    #[test]
+    fn chain_checks_key_validity_at_receipt_time() {
+        use crate::resolver::{KeyRecord, Keyring};
+        let capsule_id = [0x11; 32];
+        let (old_sk, old_vk) = keypair();
+        let (new_sk, new_vk) = keypair();
+        let node = "did:ubl:relay#key-1";
+        // Rotated at t=2000: hop 0 signed before with the old key, hop 1 after.
+        let r0 = add_hop(capsule_id, [0u8; 32], "relay", node, 1_500, &old_sk).unwrap();
+        let r1 = add_hop(capsule_id, r0.id, "deliver", node, 2_500, &new_sk).unwrap();
+        let mut ring = Keyring::new();
+        for (key, nb, na) in [(old_vk, None, Some(2_000)), (new_vk, Some(2_000), None)] {
+            ring.insert(KeyRecord {
+                kid: node.into(),
+                key,
+                not_before: nb,
+                not_after: na,
+            });
+        }
+        assert!(verify_chain(&capsule_id, &[r0.clone(), r1], &ring).is_ok());
+
+        // Old key used after rotation: resolved to the new key, bad signature.
+        let late = add_hop(capsule_id, r0.id, "deliver", node, 2_500, &old_sk).unwrap();
+        assert_eq!(
+            verify_chain(&capsule_id, &[r0.clone(), late], &ring).unwrap_err(),
+            HopError::BadSignature(1)
+        );
+
+        // No key known before the first window.
+        let mut early_ring = Keyring::new();
+        early_ring.insert(KeyRecord {
+            kid: node.into(),
+            key: old_vk,
+            not_before: Some(1_600),
+            not_after: None,
+        });
+        assert!(matches!(
+            verify_chain(&capsule_id, &[r0], &early_ring).unwrap_err(),
+            HopError::Key(0, ResolveError::NotYetValid { .. })
+        ));
+    }
+
+    #[test]
+    fn chain_resolves_did_key_nodes() {
+        let capsule_id = [0x22; 32];
+        let (sk, vk) = keypair();
+        let node = crate::resolver::did_key_for(&vk);
+        let r = add_hop(capsule_id, [0u8; 32], "exec", &node, 1, &sk).unwrap();
+        assert!(verify_chain(&capsule_id, &[r], &crate::resolver::DidKey).is_ok());
+    }
+
+    fn statuses(report: &ChainReport) -> Vec<HopStatus> {
+        report.hops.iter().map(|h| h.status.clone()).collect()
+    }
+
+    #[test]
+    fn report_checks_every_hop() {
+        let capsule_id = [0x33; 32];
+        let (mut receipts, keys) = build_chain(capsule_id, 5);
+        let mut ring = HashMap::new();
+        for (r, (_, vk)) in receipts.iter().zip(&keys) {
+            ring.insert(r.node.clone(), *vk);
+        }
+        let report = verify_chain_report(&capsule_id, &receipts, &ring);
+        assert!(report.is_ok());
+        assert_eq!(report.valid_prefix, 5);
+
+        // Hop 1 re-signed by the wrong key, hop 3 tampered, hop 4 unknown node.
+        sign_receipt(&mut receipts[1], &keys[0].0);
+        receipts[3].kind = "exec".into();
+        ring.remove(&receipts[4].node);
+        let report = verify_chain_report(&capsule_id, &receipts, &ring);
+        assert!(!report.is_ok());
+        assert_eq!(report.valid_prefix, 1);
+        assert_eq!(
+            statuses(&report),
+            [
+                HopStatus::Ok,
+                HopStatus::BadSignature,
+                HopStatus::Ok,
+                HopStatus::IdMismatch,
+                HopStatus::UnknownKey,
+            ]
+        );
+        assert!(report.forks.is_empty());
+        // The stale id of hop 3 no longer links to anything.
+        assert_eq!(report.hops[3].id, hex::encode(receipts[3].id));
+    }
+
+    #[test]
+    fn report_finds_forks_gaps_and_foreign_hops() {
+        let capsule_id = [0x44; 32];
+        let (sk, vk) = keypair();
+        let resolve = move |_: &str| -> Option<ed25519_dalek::VerifyingKey> { Some(vk) };
+        let node = "did:ubl:relay#key-1";
+        let r0 = add_hop(capsule_id, [0u8; 32], "relay", node, 1, &sk).unwrap();
+        let r1 = add_hop(capsule_id, r0.id, "relay", node, 2, &sk).unwrap();
+        let r2 = add_hop(capsule_id, r0.id, "deliver", node, 3, &sk).unwrap();
+        let r3 = add_hop(capsule_id, [0x99; 32], "exec", node, 4, &sk).unwrap();
+        let r4 = add_hop([0x55; 32], r1.id, "exec", node, 5, &sk).unwrap();
+        let report = verify_chain_report(&capsule_id, &[r0.clone(), r1, r2, r3, r4], &resolve);
+        assert_eq!(
+            statuses(&report),
+            [
+                HopStatus::Ok,
+                HopStatus::Ok,
+                HopStatus::Fork { with: 1 },
+                HopStatus::PrevGap,
+                HopStatus::WrongOf,
+            ]
+        );
+        assert_eq!(report.valid_prefix, 2);
+        assert_eq!(
+            report.forks,
+            [ForkReport {
+                prev: hex::encode(r0.id),
+                hops: vec![1, 2],
+            }]
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["hops"][2]["status"], "fork");
+        assert_eq!(json["hops"][2]["with"], 1);
+        assert_eq!(json["hops"][3]["status"], "prev_gap");
+        assert_eq!(json["valid_prefix"], 2);
+        assert_eq!(json["ok"], false);
+    }
+
+    #[test]
+    fn report_matches_verify_chain_on_key_windows() {
+        use crate::resolver::{KeyRecord, Keyring};
+        let capsule_id = [0x66; 32];
+        let (sk, vk) = keypair();
+        let node = "did:ubl:relay#key-1";
+        let r = add_hop(capsule_id, [0u8; 32], "relay", node, 5_000, &sk).unwrap();
+        let mut ring = Keyring::new();
+        ring.insert(KeyRecord {
+            kid: node.into(),
+            key: vk,
+            not_before: None,
+            not_after: Some(4_000),
+        });
+        let report = verify_chain_report(&capsule_id, std::slice::from_ref(&r), &ring);
+        assert!(matches!(
+            &report.hops[0].status,
+            HopStatus::KeyRejected { reason } if reason.starts_with("Err.Key.Expired")
+        ));
+        assert!(verify_chain(&capsule_id, &[r], &ring).is_err());
+        assert_eq!(report.valid_prefix, 0);
+        assert!(verify_chain_report(&capsule_id, &[], &ring).is_ok());
+    }
 }
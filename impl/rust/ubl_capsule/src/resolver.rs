@@ -0,0 +1,607 @@
+//! Key resolution — kid → Ed25519 verifying key, at a point in time.
+//!
+//! Seal and receipt-chain verification look keys up through [`KeyResolver`]
+//! instead of ad-hoc closures. Built-in resolvers:
+//!
+//!   - [`DidKey`]: `did:key:z6Mk…` — the key is the identifier (multibase
+//!     base58btc, multicodec `ed25519-pub`); always valid.
+//!   - [`Keyring::from_dir`]: a local directory of JSON key records.
+//!   - [`Keyring::from_did_document`]: a static `did:web`-style DID document.
+//!
+//! Keyring entries may carry a validity window (`not_before` / `not_after`,
+//! epoch-millis, same clock as `hdr.ts` and `receipt.ts`). A window is only
+//! as good as the time it is checked at. Seal and receipt verification pass
+//! the signer-asserted `hdr.ts` / `receipt.ts` unless told otherwise, which
+//! keeps old signatures valid across a rotation but lets anyone still
+//! holding a retired key backdate into its window. Where that matters, give
+//! seal verification a trusted time ([`crate::seal::VerifyOpts::key_time_ms`]).
+
+use ed25519_dalek::VerifyingKey;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use thiserror::Error;
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    #[error("Err.Key.NotFound: no key for {0}")]
+    NotFound(String),
+    #[error("Err.Key.NotYetValid: {kid} is valid from {not_before}, used at {at}")]
+    NotYetValid {
+        kid: String,
+        not_before: i64,
+        at: i64,
+    },
+    #[error("Err.Key.Expired: {kid} was valid until {not_after}, used at {at}")]
+    Expired {
+        kid: String,
+        not_after: i64,
+        at: i64,
+    },
+    #[error("Err.Key.Malformed: {0}")]
+    Malformed(String),
+    #[error("Err.Key.Io: {0}")]
+    Io(String),
+}
+
+// ---------------------------------------------------------------------------
+// Trait
+// ---------------------------------------------------------------------------
+
+/// Resolve a key id (`DID#fragment`) to the key that was valid for it at
+/// `at_ms` (epoch-millis).
+pub trait KeyResolver {
+    fn resolve(&self, kid: &str, at_ms: i64) -> Result<VerifyingKey, ResolveError>;
+}
+
+/// Plain lookup closures: no validity windows, `None` → `NotFound`.
+impl<F> KeyResolver for F
+where
+    F: Fn(&str) -> Option<VerifyingKey>,
+{
+    fn resolve(&self, kid: &str, _at_ms: i64) -> Result<VerifyingKey, ResolveError> {
+        self(kid).ok_or_else(|| ResolveError::NotFound(kid.into()))
+    }
+}
+
+/// A fixed kid → key map (e.g. `--keyring` JSON): no validity windows.
+impl KeyResolver for HashMap<String, VerifyingKey> {
+    fn resolve(&self, kid: &str, _at_ms: i64) -> Result<VerifyingKey, ResolveError> {
+        self.get(kid)
+            .copied()
+            .ok_or_else(|| ResolveError::NotFound(kid.into()))
+    }
+}
+
+/// Try resolvers in order; the first key wins. When all fail, a specific
+/// error (expired, malformed, ...) is preferred over `NotFound`.
+#[derive(Default)]
+pub struct FirstMatch(pub Vec<Box<dyn KeyResolver>>);
+
+impl KeyResolver for FirstMatch {
+    fn resolve(&self, kid: &str, at_ms: i64) -> Result<VerifyingKey, ResolveError> {
+        let mut err = ResolveError::NotFound(kid.into());
+        for r in &self.0 {
+            match r.resolve(kid, at_ms) {
+                Ok(pk) => return Ok(pk),
+                Err(ResolveError::NotFound(_)) => {}
+                Err(e) => err = e,
+            }
+        }
+        Err(err)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// did:key
+// ---------------------------------------------------------------------------
+
+/// Multicodec prefix for `ed25519-pub` (varint 0xed).
+const ED25519_PUB: [u8; 2] = [0xed, 0x01];
+
+/// `did:key` resolver: decodes the key from the identifier itself.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DidKey;
+
+impl KeyResolver for DidKey {
+    fn resolve(&self, kid: &str, _at_ms: i64) -> Result<VerifyingKey, ResolveError> {
+        let did = kid.split('#').next().unwrap_or(kid);
+        let Some(mb) = did.strip_prefix("did:key:") else {
+            return Err(ResolveError::NotFound(kid.into()));
+        };
+        decode_multibase_ed25519(mb)
+    }
+}
+
+/// `did:key:z…` for an Ed25519 public key.
+pub fn did_key_for(pk: &VerifyingKey) -> String {
+    format!("did:key:{}", encode_multibase_ed25519(pk))
+}
+
+/// `z` + base58btc(`0xed 0x01` ‖ key).
+pub fn encode_multibase_ed25519(pk: &VerifyingKey) -> String {
+    let mut raw = ED25519_PUB.to_vec();
+    raw.extend_from_slice(pk.as_bytes());
+    format!("z{}", base58_encode(&raw))
+}
+
+/// Inverse of [`encode_multibase_ed25519`]. The `ed25519-pub` multicodec
+/// prefix is required: a bare key has no type and is rejected.
+pub fn decode_multibase_ed25519(mb: &str) -> Result<VerifyingKey, ResolveError> {
+    let malformed = |why: &str| ResolveError::Malformed(format!("{mb}: {why}"));
+    let b58 = mb
+        .strip_prefix('z')
+        .ok_or_else(|| malformed("expected multibase base58btc ('z')"))?;
+    let raw = base58_decode(b58).ok_or_else(|| malformed("invalid base58"))?;
+    let key = raw
+        .strip_prefix(&ED25519_PUB[..])
+        .ok_or_else(|| malformed("not an ed25519-pub multikey"))?;
+    let arr: [u8; 32] = key
+        .try_into()
+        .map_err(|_| malformed("expected 32 key bytes"))?;
+    VerifyingKey::from_bytes(&arr).map_err(|_| malformed("not a valid Ed25519 point"))
+}
+
+const B58: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    // Little-endian base-58 digits.
+    let mut digits: Vec<u8> = Vec::new();
+    for &b in &bytes[zeros..] {
+        let mut carry = u32::from(b);
+        for d in digits.iter_mut() {
+            carry += u32::from(*d) << 8;
+            *d = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut out = "1".repeat(zeros);
+    out.extend(digits.iter().rev().map(|&d| B58[d as usize] as char));
+    out
+}
+
+fn base58_decode(s: &str) -> Option<Vec<u8>> {
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    // Little-endian base-256 bytes.
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in s.bytes().skip(zeros) {
+        let mut carry = B58.iter().position(|&a| a == c)? as u32;
+        for b in bytes.iter_mut() {
+            carry += u32::from(*b) * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut out = vec![0u8; zeros];
+    out.extend(bytes.iter().rev());
+    Some(out)
+}
+
+// ---------------------------------------------------------------------------
+// Keyring with validity windows
+// ---------------------------------------------------------------------------
+
+/// One key for a kid, valid on `[not_before, not_after)` (epoch-millis;
+/// `None` = unbounded).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRecord {
+    pub kid: String,
+    pub key: VerifyingKey,
+    pub not_before: Option<i64>,
+    pub not_after: Option<i64>,
+}
+
+impl KeyRecord {
+    fn check(&self, at: i64) -> Result<VerifyingKey, ResolveError> {
+        if let Some(nb) = self.not_before {
+            if at < nb {
+                return Err(ResolveError::NotYetValid {
+                    kid: self.kid.clone(),
+                    not_before: nb,
+                    at,
+                });
+            }
+        }
+        if let Some(na) = self.not_after {
+            if at >= na {
+                return Err(ResolveError::Expired {
+                    kid: self.kid.clone(),
+                    not_after: na,
+                    at,
+                });
+            }
+        }
+        Ok(self.key)
+    }
+}
+
+/// kid → key records. A kid may have several records (one per rotation
+/// period); the one whose window contains the signing time is used.
+#[derive(Debug, Clone, Default)]
+pub struct Keyring {
+    records: BTreeMap<String, Vec<KeyRecord>>,
+}
+
+impl KeyResolver for Keyring {
+    fn resolve(&self, kid: &str, at_ms: i64) -> Result<VerifyingKey, ResolveError> {
+        let records = self
+            .records
+            .get(kid)
+            .ok_or_else(|| ResolveError::NotFound(kid.into()))?;
+        let mut err = ResolveError::NotFound(kid.into());
+        for r in records {
+            match r.check(at_ms) {
+                Ok(pk) => return Ok(pk),
+                Err(e) => err = e,
+            }
+        }
+        Err(err)
+    }
+}
+
+/// A key record file in a keyring directory.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyRecordJson {
+    kid: String,
+    /// Ed25519 public key, 64 lowercase hex chars
+    pk: String,
+    #[serde(default)]
+    not_before: Option<i64>,
+    #[serde(default)]
+    not_after: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyFileJson {
+    One(KeyRecordJson),
+    Many(Vec<KeyRecordJson>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidDocJson {
+    id: String,
+    #[serde(default)]
+    verification_method: Vec<VerificationMethodJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationMethodJson {
+    id: String,
+    public_key_multibase: String,
+    #[serde(default)]
+    not_before: Option<i64>,
+    #[serde(default)]
+    not_after: Option<i64>,
+}
+
+impl Keyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, record: KeyRecord) {
+        self.records
+            .entry(record.kid.clone())
+            .or_default()
+            .push(record);
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Every record, by kid.
+    pub fn records(&self) -> impl Iterator<Item = &KeyRecord> {
+        self.records.values().flatten()
+    }
+
+    /// Load every `*.json` file in `dir`. Each holds one record or an array:
+    /// `{"kid": "did:ubl:node1#key-1", "pk": "<hex>", "not_before": ms?, "not_after": ms?}`.
+    pub fn from_dir(dir: &Path) -> Result<Self, ResolveError> {
+        let io = |e: std::io::Error| ResolveError::Io(format!("{}: {e}", dir.display()));
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(io)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<_, _>>()
+            .map_err(io)?;
+        paths.retain(|p| p.extension().is_some_and(|x| x == "json"));
+        paths.sort();
+
+        let mut ring = Keyring::new();
+        for path in paths {
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| ResolveError::Io(format!("{}: {e}", path.display())))?;
+            let file: KeyFileJson = serde_json::from_str(&text)
+                .map_err(|e| ResolveError::Malformed(format!("{}: {e}", path.display())))?;
+            let records = match file {
+                KeyFileJson::One(r) => vec![r],
+                KeyFileJson::Many(rs) => rs,
+            };
+            for r in records {
+                let key = key_from_hex(&r.pk)
+                    .map_err(|why| ResolveError::Malformed(format!("{}: {why}", path.display())))?;
+                ring.insert(KeyRecord {
+                    kid: r.kid,
+                    key,
+                    not_before: r.not_before,
+                    not_after: r.not_after,
+                });
+            }
+        }
+        Ok(ring)
+    }
+
+    /// Load a static DID document (e.g. a mirrored `did:web` `did.json`).
+    pub fn from_did_document(path: &Path) -> Result<Self, ResolveError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ResolveError::Io(format!("{}: {e}", path.display())))?;
+        Self::from_did_document_json(&text)
+    }
+
+    /// Parse a DID document's `verificationMethod` entries. Relative ids
+    /// (`#key-1`) are taken against the document `id`; `publicKeyMultibase`
+    /// is an Ed25519 multikey. `notBefore` / `notAfter` (epoch-millis) are
+    /// optional per-method validity bounds. A method whose id names another
+    /// DID is rejected: a document only speaks for its own `id`.
+    pub fn from_did_document_json(text: &str) -> Result<Self, ResolveError> {
+        let doc: DidDocJson = serde_json::from_str(text)
+            .map_err(|e| ResolveError::Malformed(format!("DID document: {e}")))?;
+        let mut ring = Keyring::new();
+        for vm in doc.verification_method {
+            let kid = match vm.id.strip_prefix('#') {
+                Some(frag) => format!("{}#{frag}", doc.id),
+                None => vm.id,
+            };
+            if kid.split_once('#').map_or(kid.as_str(), |(did, _)| did) != doc.id {
+                return Err(ResolveError::Malformed(format!(
+                    "DID document {}: verificationMethod {kid} is not a key of it",
+                    doc.id
+                )));
+            }
+            ring.insert(KeyRecord {
+                kid,
+                key: decode_multibase_ed25519(&vm.public_key_multibase)?,
+                not_before: vm.not_before,
+                not_after: vm.not_after,
+            });
+        }
+        Ok(ring)
+    }
+}
+
+fn key_from_hex(s: &str) -> Result<VerifyingKey, String> {
+    let bytes = hex::decode(s.trim()).map_err(|e| format!("pk: {e}"))?;
+    let arr: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| "pk: expected 32 bytes (64 hex chars)".to_string())?;
+    VerifyingKey::from_bytes(&arr).map_err(|_| "pk: not a valid Ed25519 point".to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> VerifyingKey {
+        ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng).verifying_key()
+    }
+
+    #[test]
+    fn base58_roundtrip_and_leading_zeros() {
+        for raw in [
+            &b""[..],
+            b"\x00",
+            b"\x00\x00\x01",
+            b"hello world",
+            &[0xff; 34],
+        ] {
+            assert_eq!(base58_decode(&base58_encode(raw)).unwrap(), raw);
+        }
+        assert_eq!(base58_encode(b"hello world"), "StV1DL6CwTryKyV");
+        assert_eq!(base58_decode("0OIl"), None);
+    }
+
+    #[test]
+    fn did_key_known_vector() {
+        // W3C did:key test vector (RFC 8032 test 1 public key).
+        let pk = hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+            .unwrap();
+        let pk = VerifyingKey::from_bytes(&pk.try_into().unwrap()).unwrap();
+        let did = did_key_for(&pk);
+        assert_eq!(
+            did,
+            "did:key:z6MktwupdmLXVVqTzCw4i46r4uGyosGXRnR3XjN4Zq7oMMsw"
+        );
+        let kid = format!("{did}#{}", did.trim_start_matches("did:key:"));
+        assert_eq!(DidKey.resolve(&kid, 0).unwrap(), pk);
+    }
+
+    #[test]
+    fn did_key_rejects_other_methods_and_garbage() {
+        assert_eq!(
+            DidKey.resolve("did:ubl:alice#k", 0).unwrap_err(),
+            ResolveError::NotFound("did:ubl:alice#k".into())
+        );
+        assert!(matches!(
+            DidKey.resolve("did:key:mABC", 0).unwrap_err(),
+            ResolveError::Malformed(_)
+        ));
+        assert!(matches!(
+            DidKey.resolve("did:key:z0OIl", 0).unwrap_err(),
+            ResolveError::Malformed(_)
+        ));
+        // 32 key bytes without the ed25519-pub multicodec prefix.
+        let bare = format!("did:key:z{}", base58_encode(key().as_bytes()));
+        assert!(matches!(
+            DidKey.resolve(&bare, 0).unwrap_err(),
+            ResolveError::Malformed(_)
+        ));
+    }
+
+    #[test]
+    fn keyring_windows_follow_rotation() {
+        let (old, new) = (key(), key());
+        let mut ring = Keyring::new();
+        let kid = "did:ubl:node1#key-1";
+        ring.insert(KeyRecord {
+            kid: kid.into(),
+            key: old,
+            not_before: Some(1_000),
+            not_after: Some(2_000),
+        });
+        ring.insert(KeyRecord {
+            kid: kid.into(),
+            key: new,
+            not_before: Some(2_000),
+            not_after: None,
+        });
+        assert_eq!(ring.resolve(kid, 1_999).unwrap(), old);
+        assert_eq!(ring.resolve(kid, 2_000).unwrap(), new);
+        assert_eq!(
+            ring.resolve(kid, 999).unwrap_err(),
+            ResolveError::NotYetValid {
+                kid: kid.into(),
+                not_before: 2_000,
+                at: 999
+            }
+        );
+        assert_eq!(
+            ring.resolve("did:ubl:other#k", 1_500).unwrap_err(),
+            ResolveError::NotFound("did:ubl:other#k".into())
+        );
+    }
+
+    #[test]
+    fn keyring_from_dir() {
+        let (a, b) = (key(), key());
+        let dir = std::env::temp_dir().join(format!("ubl-keyring-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let one = format!(
+            r#"{{"kid": "did:ubl:a#k", "pk": "{}", "not_after": 5000}}"#,
+            hex::encode(a.as_bytes())
+        );
+        let many = format!(
+            r#"[{{"kid": "did:ubl:b#k", "pk": "{}"}}]"#,
+            hex::encode(b.as_bytes())
+        );
+        std::fs::write(dir.join("a.json"), one).unwrap();
+        std::fs::write(dir.join("b.json"), many).unwrap();
+        std::fs::write(dir.join("README.txt"), "ignored").unwrap();
+
+        let ring = Keyring::from_dir(&dir).unwrap();
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.resolve("did:ubl:a#k", 4_999).unwrap(), a);
+        assert!(matches!(
+            ring.resolve("did:ubl:a#k", 5_000).unwrap_err(),
+            ResolveError::Expired {
+                not_after: 5_000,
+                ..
+            }
+        ));
+        assert_eq!(ring.resolve("did:ubl:b#k", i64::MAX).unwrap(), b);
+
+        std::fs::write(dir.join("c.json"), r#"{"kid": "x", "pk": "zz"}"#).unwrap();
+        assert!(matches!(
+            Keyring::from_dir(&dir).unwrap_err(),
+            ResolveError::Malformed(_)
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keyring_from_did_document() {
+        let (k1, k2) = (key(), key());
+        let doc = format!(
+            r##"{{
+                "@context": ["https://www.w3.org/ns/did/v1"],
+                "id": "did:web:example.com",
+                "verificationMethod": [
+                    {{"id": "#key-1", "type": "Multikey", "controller": "did:web:example.com",
+                      "publicKeyMultibase": "{}", "notAfter": 2000}},
+                    {{"id": "did:web:example.com#key-2", "type": "Multikey",
+                      "controller": "did:web:example.com", "publicKeyMultibase": "{}"}}
+                ],
+                "assertionMethod": ["#key-1", "#key-2"]
+            }}"##,
+            encode_multibase_ed25519(&k1),
+            encode_multibase_ed25519(&k2)
+        );
+        let ring = Keyring::from_did_document_json(&doc).unwrap();
+        assert_eq!(
+            ring.resolve("did:web:example.com#key-1", 1_000).unwrap(),
+            k1
+        );
+        assert!(ring.resolve("did:web:example.com#key-1", 3_000).is_err());
+        assert_eq!(
+            ring.resolve("did:web:example.com#key-2", 3_000).unwrap(),
+            k2
+        );
+    }
+
+    #[test]
+    fn did_document_rejects_foreign_methods() {
+        let doc = |vm_id: &str| {
+            format!(
+                r#"{{"id": "did:web:example.com", "verificationMethod": [
+                    {{"id": "{vm_id}", "publicKeyMultibase": "{}"}}]}}"#,
+                encode_multibase_ed25519(&key())
+            )
+        };
+        for good in ["did:web:example.com#k", "did:web:example.com", "#k"] {
+            assert!(Keyring::from_did_document_json(&doc(good)).is_ok());
+        }
+        for bad in ["did:web:evil.com#k", "did:web:example.com.evil#k", "k"] {
+            assert!(matches!(
+                Keyring::from_did_document_json(&doc(bad)),
+                Err(ResolveError::Malformed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn first_match_prefers_specific_errors() {
+        let k = key();
+        let mut ring = Keyring::new();
+        ring.insert(KeyRecord {
+            kid: "did:ubl:a#k".into(),
+            key: k,
+            not_before: None,
+            not_after: Some(10),
+        });
+        let all = FirstMatch(vec![Box::new(DidKey), Box::new(ring)]);
+        assert_eq!(all.resolve("did:ubl:a#k", 5).unwrap(), k);
+        assert!(matches!(
+            all.resolve("did:ubl:a#k", 10).unwrap_err(),
+            ResolveError::Expired { .. }
+        ));
+        assert_eq!(all.resolve(&did_key_for(&k), 99).unwrap(), k);
+        assert_eq!(
+            all.resolve("did:ubl:zz#k", 0).unwrap_err(),
+            ResolveError::NotFound("did:ubl:zz#k".into())
+        );
+    }
+}
@@ -4,11 +4,20 @@
 //!   `blake3(nrf.encode({domain, id, hdr, env}))`
 //! with checks for `domain == "ubl-capsule/1.0"`, `scope == "capsule"`,
 //! and `aud == hdr.dst` (if aud is present).
+//!
+//! Multi-seal: co-signers listed in `seal.cosigs` sign the same payload
+//! ([`cosign`]). The signer set is bound into the ID, and a K-of-N
+//! [`ThresholdPolicy`] in [`VerifyOpts`] decides how many must be valid.
+//!
+//! Keys come either from the caller ([`verify_with_opts`]) or from a
+//! [`KeyResolver`] ([`verify_resolved`]), looked up at
+//! [`VerifyOpts::key_time_ms`] or, by default, at the capsule's own `hdr.ts`.
 
 use crate::id::compute_id;
+use crate::resolver::{KeyResolver, ResolveError};
 use crate::types::{Capsule, DOMAIN};
 use nrf_core::Value;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
@@ -35,6 +44,22 @@ pub enum SealError {
     IdMismatch,
     #[error("Err.Hdr.Expired: capsule expired at {exp}, now={now}")]
     Expired { exp: i64, now: i64 },
+    #[error("Err.Seal.BadCoSignature: co-signature of {kid} is invalid")]
+    BadCoSignature { kid: String },
+    #[error("Err.Seal.DuplicateSigner: {kid} appears more than once in the seal")]
+    DuplicateSigner { kid: String },
+    #[error("Err.Seal.UnknownSigner: no verifying key for {kid}")]
+    UnknownSigner { kid: String },
+    #[error("Err.Seal.UntrustedSigner: {kid} is not an allowed co-signer")]
+    UntrustedSigner { kid: String },
+    #[error("Err.Seal.ForeignSigner: seal.kid {kid} is not a key of hdr.src {src}")]
+    ForeignSigner { kid: String, src: String },
+    #[error("Err.Seal.BadThreshold: threshold {k} outside 1..={n}")]
+    BadThreshold { k: usize, n: usize },
+    #[error("Err.Seal.ThresholdNotMet: {valid} valid of {k} required ({n} signers)")]
+    ThresholdNotMet { valid: usize, k: usize, n: usize },
+    #[error("Err.Seal.Key: {0}")]
+    Key(ResolveError),
 }
 
 #[cfg(feature = "metrics")]
@@ -47,6 +72,14 @@ impl SealError {
             SealError::BadSignature => "BadSignature",
             SealError::IdMismatch => "IdMismatch",
             SealError::Expired { .. } => "Expired",
+            SealError::BadCoSignature { .. } => "BadCoSignature",
+            SealError::DuplicateSigner { .. } => "DuplicateSigner",
+            SealError::UnknownSigner { .. } => "UnknownSigner",
+            SealError::UntrustedSigner { .. } => "UntrustedSigner",
+            SealError::ForeignSigner { .. } => "ForeignSigner",
+            SealError::BadThreshold { .. } => "BadThreshold",
+            SealError::ThresholdNotMet { .. } => "ThresholdNotMet",
+            SealError::Key(_) => "Key",
         }
     }
 }
@@ -58,6 +91,34 @@ pub struct VerifyOpts {
     pub allowed_skew_ns: i64,
     /// Override current time (epoch-nanos). When `None`, uses host time.
     pub now_ns: Option<i64>,
+    /// K-of-N policy over the author and co-signers. When `None`, only the
+    /// author's seal is checked.
+    pub threshold: Option<ThresholdPolicy>,
+    /// Trusted time (epoch-millis) for resolving keys. When `None`, keys are
+    /// looked up at `hdr.ts`, which the signer chose: whoever holds a
+    /// retired key can backdate `hdr.ts` into its validity window. Set this
+    /// to a time you trust the capsule existed by — the verifier's clock on
+    /// arrival, or the `ts` of a timestamp receipt from a trusted node.
+    pub key_time_ms: Option<i64>,
+}
+
+/// K-of-N multi-seal policy.
+///
+/// The N signers are the author (`seal.kid`, checked against the `pk`
+/// passed to [`verify_with_opts`]) plus every `seal.cosigs[*].kid`. Unsigned
+/// slots (all-zero sig) don't count; a present signature that fails, has
+/// no key, or comes from a co-signer outside `keys` and `signers`, rejects
+/// the capsule. The capsule names its own co-signers, so the verifier's
+/// allowlist is what stops one party from meeting K with kids it minted.
+#[derive(Debug, Clone, Default)]
+pub struct ThresholdPolicy {
+    /// Valid signatures required.
+    pub k: usize,
+    /// Co-signer verifying keys by kid. These kids are allowed.
+    pub keys: HashMap<String, ed25519_dalek::VerifyingKey>,
+    /// Further allowed co-signer kids, whose keys [`verify_resolved`]
+    /// looks up in its resolver. Ignored by [`verify_with_opts`].
+    pub signers: BTreeSet<String>,
 }
 
 /// Current time as epoch-nanoseconds (i64).
@@ -76,7 +137,8 @@ pub fn now_nanos_i64() -> i64 {
 /// Sign a capsule: compute its ID, build the signing payload, and produce
 /// an Ed25519 signature. Mutates `c.id` and `c.seal.sig` in place.
 ///
-/// Returns Err if env.body contains floats or non-i64 numbers.
+/// Returns Err if env.body contains floats or non-i64 numbers, or if the
+/// signed payload would exceed the default decode limits (`Err.Canon.*`).
 /// Canon 2: no floats. Canon 3: ρ is the law. Canon 6: reject, never degrade.
 #[cfg_attr(
     feature = "obs",
@@ -96,17 +158,37 @@ pub fn sign(c: &mut Capsule, sk: &ed25519_dalek::SigningKey) -> Result<(), Strin
     Ok(())
 }
 
+/// Co-sign a multi-seal capsule as `kid`, which must already be listed in
+/// `c.seal.cosigs`. Computes `c.id` like [`sign`], so co-signers and the
+/// author may sign in any order; the signature covers the same payload.
+pub fn cosign(c: &mut Capsule, kid: &str, sk: &ed25519_dalek::SigningKey) -> Result<(), String> {
+    c.id = compute_id(c)?;
+    let payload_hash = signing_hash(c)?;
+    let slot = c
+        .seal
+        .cosigs
+        .iter_mut()
+        .find(|s| s.kid == kid)
+        .ok_or_else(|| format!("Err.Seal.UnknownSigner: {kid} is not a co-signer"))?;
+    use ed25519_dalek::Signer;
+    slot.sig = sk.sign(&payload_hash).to_bytes();
+    Ok(())
+}
+
 /// Verify a capsule's seal:
 ///   1. domain == "ubl-capsule/1.0"
 ///   2. scope == "capsule"
 ///   3. aud == hdr.dst (if aud present)
 ///   4. id matches computed ID
 ///   5. Ed25519 signature valid
+///
+/// Co-signatures are not checked; use [`verify_with_opts`] with a
+/// [`ThresholdPolicy`] for multi-seal capsules.
 pub fn verify(c: &Capsule, pk: &ed25519_dalek::VerifyingKey) -> Result<(), SealError> {
     verify_with_opts(c, pk, &VerifyOpts::default())
 }
 
-/// Verify with configurable options (clock skew, etc.).
+/// Verify with configurable options (clock skew, K-of-N threshold).
 #[cfg_attr(
     feature = "obs",
     tracing::instrument(level = "debug", skip_all, fields(src = %c.hdr.src, act = %c.hdr.act))
@@ -116,6 +198,75 @@ pub fn verify_with_opts(
     pk: &ed25519_dalek::VerifyingKey,
     opts: &VerifyOpts,
 ) -> Result<(), SealError> {
+    verify_with_keys(c, Keys::Given(pk), opts)
+}
+
+/// Verify with keys from `resolver`, looked up at `opts.key_time_ms` (or
+/// `hdr.ts`): the author's `seal.kid`, and under a threshold policy every
+/// signed co-signer in `policy.signers` but not `policy.keys`.
+///
+/// `seal.kid` must be a key of `hdr.src` (same DID before `#`): the
+/// resolver vouches for the key, not for who the capsule claims to be from.
+#[cfg_attr(
+    feature = "obs",
+    tracing::instrument(level = "debug", skip_all, fields(src = %c.hdr.src, act = %c.hdr.act))
+)]
+pub fn verify_resolved(
+    c: &Capsule,
+    resolver: &dyn KeyResolver,
+    opts: &VerifyOpts,
+) -> Result<(), SealError> {
+    verify_with_keys(c, Keys::Resolved(resolver), opts)
+}
+
+/// Where verification gets its keys.
+enum Keys<'a> {
+    /// The author's key from the caller; co-signers only from `policy.keys`.
+    Given(&'a ed25519_dalek::VerifyingKey),
+    Resolved(&'a dyn KeyResolver),
+}
+
+impl Keys<'_> {
+    fn author(&self, c: &Capsule, at_ms: i64) -> Result<ed25519_dalek::VerifyingKey, SealError> {
+        match self {
+            Keys::Given(pk) => Ok(**pk),
+            Keys::Resolved(r) => {
+                if did_of(&c.seal.kid) != did_of(&c.hdr.src) {
+                    return Err(SealError::ForeignSigner {
+                        kid: c.seal.kid.clone(),
+                        src: c.hdr.src.clone(),
+                    });
+                }
+                r.resolve(&c.seal.kid, at_ms).map_err(SealError::Key)
+            }
+        }
+    }
+
+    fn cosigner(
+        &self,
+        kid: &str,
+        at_ms: i64,
+        policy: &ThresholdPolicy,
+    ) -> Result<ed25519_dalek::VerifyingKey, SealError> {
+        if let Some(pk) = policy.keys.get(kid) {
+            return Ok(*pk);
+        }
+        let unknown = || SealError::UnknownSigner { kid: kid.into() };
+        match self {
+            Keys::Given(_) => Err(unknown()),
+            Keys::Resolved(_) if !policy.signers.contains(kid) => {
+                Err(SealError::UntrustedSigner { kid: kid.into() })
+            }
+            Keys::Resolved(r) => match r.resolve(kid, at_ms) {
+                Ok(pk) => Ok(pk),
+                Err(ResolveError::NotFound(_)) => Err(unknown()),
+                Err(e) => Err(SealError::Key(e)),
+            },
+        }
+    }
+}
+
+fn verify_with_keys(c: &Capsule, keys: Keys<'_>, opts: &VerifyOpts) -> Result<(), SealError> {
     #[cfg(feature = "metrics")]
     let t0 = std::time::Instant::now();
     #[cfg(feature = "metrics")]
@@ -158,10 +309,12 @@ pub fn verify_with_opts(
         // Verify Ed25519 signature
         let payload_hash = signing_hash(c)
             .map_err(|_| SealError::IdMismatch)?;
-        let sig = ed25519_dalek::Signature::from_bytes(&c.seal.sig);
-        use ed25519_dalek::Verifier;
-        pk.verify(&payload_hash, &sig)
-            .map_err(|_| SealError::BadSignature)
+        let at_ms = opts.key_time_ms.unwrap_or(c.hdr.ts);
+        match &opts.threshold {
+            None => verify_sig(&keys.author(c, at_ms)?, &payload_hash, &c.seal.sig)
+                .map_err(|_| SealError::BadSignature),
+            Some(policy) => verify_threshold(c, &keys, at_ms, &payload_hash, policy),
+        }
     })();
 
     #[cfg(feature = "metrics")]
@@ -181,20 +334,83 @@ pub fn verify_with_opts(
     result
 }
 
+/// The DID a kid belongs to: everything before the `#` fragment.
+fn did_of(kid: &str) -> &str {
+    kid.split_once('#').map_or(kid, |(did, _)| did)
+}
+
+fn verify_sig(
+    pk: &ed25519_dalek::VerifyingKey,
+    payload_hash: &[u8; 32],
+    sig: &[u8; 64],
+) -> Result<(), ed25519_dalek::SignatureError> {
+    use ed25519_dalek::Verifier;
+    pk.verify(payload_hash, &ed25519_dalek::Signature::from_bytes(sig))
+}
+
+/// Count valid signatures among the author and co-signers against `policy.k`.
+/// Signatures count per distinct key: two kids that resolve to the same key
+/// are one signer, however the seal names them.
+fn verify_threshold(
+    c: &Capsule,
+    keys: &Keys<'_>,
+    at_ms: i64,
+    payload_hash: &[u8; 32],
+    policy: &ThresholdPolicy,
+) -> Result<(), SealError> {
+    let n = 1 + c.seal.cosigs.len();
+    if policy.k == 0 || policy.k > n {
+        return Err(SealError::BadThreshold { k: policy.k, n });
+    }
+    let mut seen = BTreeSet::from([c.seal.kid.as_str()]);
+    for s in &c.seal.cosigs {
+        if !seen.insert(s.kid.as_str()) {
+            return Err(SealError::DuplicateSigner { kid: s.kid.clone() });
+        }
+    }
+
+    let mut counted = BTreeSet::new();
+    if c.seal.sig != [0u8; 64] {
+        let key = keys.author(c, at_ms)?;
+        verify_sig(&key, payload_hash, &c.seal.sig).map_err(|_| SealError::BadSignature)?;
+        counted.insert(key.to_bytes());
+    }
+    for s in &c.seal.cosigs {
+        if s.sig == [0u8; 64] {
+            continue;
+        }
+        let key = keys.cosigner(&s.kid, at_ms, policy)?;
+        verify_sig(&key, payload_hash, &s.sig)
+            .map_err(|_| SealError::BadCoSignature { kid: s.kid.clone() })?;
+        counted.insert(key.to_bytes());
+    }
+    let valid = counted.len();
+    if valid < policy.k {
+        return Err(SealError::ThresholdNotMet {
+            valid,
+            k: policy.k,
+            n,
+        });
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Signing payload
 // ---------------------------------------------------------------------------
 
 /// The hash that gets signed: blake3(nrf.encode(ρ({domain, id, hdr, env})))
+/// with `env` in its ID form (the body's root in disclosure mode).
 fn signing_hash(c: &Capsule) -> Result<[u8; 32], String> {
     let mut root = BTreeMap::new();
     root.insert("domain".into(), Value::String(c.domain.clone()));
     root.insert("id".into(), Value::Bytes(c.id.to_vec()));
     root.insert("hdr".into(), header_value(&c.hdr));
-    root.insert("env".into(), envelope_value(&c.env)?);
+    root.insert("env".into(), crate::id::envelope_value(&c.env)?);
     let normalized = nrf_core::rho::normalize(&Value::Map(root))
         .map_err(|e| format!("Err.Canon.Rho: {e}"))?;
-    let nrf = nrf_core::encode(&normalized);
+    // Canon 6: never sign bytes that a decoder with default limits rejects.
+    let nrf = nrf_core::try_encode(&normalized).map_err(|e| format!("Err.Canon.{e}"))?;
     Ok(*blake3::hash(&nrf).as_bytes())
 }
 
@@ -216,64 +432,6 @@ fn header_value(h: &crate::types::Header) -> Value {
     Value::Map(m)
 }
 
-fn envelope_value(e: &crate::types::Envelope) -> Result<Value, String> {
-    let mut m = BTreeMap::new();
-    // Canon 2,6: reject floats, never degrade
-    m.insert("body".into(), json_to_nrf_strict(&e.body)?);
-    if !e.evidence.is_empty() {
-        m.insert(
-            "evidence".into(),
-            Value::Array(
-                e.evidence
-                    .iter()
-                    .map(|s| Value::String(s.clone()))
-                    .collect(),
-            ),
-        );
-    }
-    if let Some(links) = &e.links {
-        let mut lm = BTreeMap::new();
-        if let Some(prev) = &links.prev {
-            lm.insert("prev".into(), Value::String(prev.clone()));
-        }
-        if !lm.is_empty() {
-            m.insert("links".into(), Value::Map(lm));
-        }
-    }
-    Ok(Value::Map(m))
-}
-
-/// Convert serde_json::Value to nrf_core::Value.
-/// Canon 2: no floats. Canon 6: reject, never degrade.
-fn json_to_nrf_strict(j: &serde_json::Value) -> Result<Value, String> {
-    match j {
-        serde_json::Value::Null => Ok(Value::Null),
-        serde_json::Value::Bool(b) => Ok(Value::Bool(*b)),
-        serde_json::Value::Number(n) => {
-            if let Some(i) = n.as_i64() {
-                Ok(Value::Int(i))
-            } else {
-                Err(format!("Err.Canon.Float: {n} is not Int64 — no floats, period"))
-            }
-        }
-        serde_json::Value::String(s) => Ok(Value::String(s.clone())),
-        serde_json::Value::Array(items) => {
-            let mut out = Vec::with_capacity(items.len());
-            for item in items {
-                out.push(json_to_nrf_strict(item)?);
-            }
-            Ok(Value::Array(out))
-        }
-        serde_json::Value::Object(obj) => {
-            let mut m = BTreeMap::new();
-            for (k, v) in obj {
-                m.insert(k.clone(), json_to_nrf_strict(v)?);
-            }
-            Ok(Value::Map(m))
-        }
-    }
-}
-
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -306,12 +464,15 @@ mod tests {
                 body: serde_json::json!({"name": "test", "value": 42}),
                 links: None,
                 evidence: vec![],
+                enc: None,
+                merkle: None,
             },
             seal: Seal {
                 kid: "did:ubl:alice#key-1".into(),
                 sig: [0u8; 64],
                 scope: "capsule".into(),
                 aud: Some("did:ubl:bob".into()),
+                cosigs: vec![],
             },
             receipts: vec![],
         }
@@ -327,6 +488,17 @@ mod tests {
         assert!(verify(&c, &vk).is_ok());
     }
 
+    #[test]
+    fn sign_refuses_payload_beyond_decode_limits() {
+        let (sk, _) = keypair();
+        let mut c = make_capsule();
+        // 2 MiB string: encodable, but over the 1 MiB default string limit.
+        c.env.body = serde_json::json!({"blob": "x".repeat(2 * 1024 * 1024)});
+        let err = sign(&mut c, &sk).unwrap_err();
+        assert_eq!(err, "Err.Canon.StringTooLong");
+        assert_eq!(c.seal.sig, [0u8; 64]);
+    }
+
     #[test]
     fn tamper_env_fails() {
         let (sk, vk) = keypair();
@@ -434,7 +606,7 @@ mod tests {
         assert!(verify(&c, &vk).is_err());
         let opts = VerifyOpts {
             allowed_skew_ns: 2_000_000_000,
-            now_ns: None,
+            ..VerifyOpts::default()
         };
         assert!(verify_with_opts(&c, &vk, &opts).is_ok());
     }
@@ -447,4 +619,263 @@ mod tests {
         sign(&mut c, &sk).unwrap();
         assert!(verify(&c, &vk).is_ok());
     }
+
+    // -- multi-seal --------------------------------------------------------
+
+    /// Author plus buyer and seller co-signers, none signed yet.
+    fn make_multi() -> (
+        Capsule,
+        Vec<(ed25519_dalek::SigningKey, ed25519_dalek::VerifyingKey)>,
+    ) {
+        let mut c = make_capsule();
+        c.hdr.act = "TRANSACT".into();
+        c.seal.cosigs = vec![
+            CoSig::new("did:ubl:buyer#k"),
+            CoSig::new("did:ubl:seller#k"),
+        ];
+        (c, vec![keypair(), keypair(), keypair()])
+    }
+
+    fn policy(
+        k: usize,
+        keys: &[(ed25519_dalek::SigningKey, ed25519_dalek::VerifyingKey)],
+    ) -> VerifyOpts {
+        VerifyOpts {
+            threshold: Some(ThresholdPolicy {
+                k,
+                keys: HashMap::from([
+                    ("did:ubl:buyer#k".to_string(), keys[1].1),
+                    ("did:ubl:seller#k".to_string(), keys[2].1),
+                ]),
+                ..Default::default()
+            }),
+            ..VerifyOpts::default()
+        }
+    }
+
+    #[test]
+    fn multi_seal_any_signing_order() {
+        let (mut c, keys) = make_multi();
+        cosign(&mut c, "did:ubl:seller#k", &keys[2].0).unwrap();
+        sign(&mut c, &keys[0].0).unwrap();
+        cosign(&mut c, "did:ubl:buyer#k", &keys[1].0).unwrap();
+        assert!(verify_with_opts(&c, &keys[0].1, &policy(3, &keys)).is_ok());
+        // The author's seal alone still verifies without a policy.
+        assert!(verify(&c, &keys[0].1).is_ok());
+    }
+
+    #[test]
+    fn multi_seal_threshold_counts_present_sigs() {
+        let (mut c, keys) = make_multi();
+        cosign(&mut c, "did:ubl:buyer#k", &keys[1].0).unwrap();
+        cosign(&mut c, "did:ubl:seller#k", &keys[2].0).unwrap();
+        // Author never signed: 2-of-3 passes, 3-of-3 does not.
+        assert!(verify_with_opts(&c, &keys[0].1, &policy(2, &keys)).is_ok());
+        assert_eq!(
+            verify_with_opts(&c, &keys[0].1, &policy(3, &keys)).unwrap_err(),
+            SealError::ThresholdNotMet {
+                valid: 2,
+                k: 3,
+                n: 3
+            }
+        );
+        assert_eq!(
+            verify_with_opts(&c, &keys[0].1, &policy(4, &keys)).unwrap_err(),
+            SealError::BadThreshold { k: 4, n: 3 }
+        );
+        assert_eq!(
+            verify_with_opts(&c, &keys[0].1, &policy(0, &keys)).unwrap_err(),
+            SealError::BadThreshold { k: 0, n: 3 }
+        );
+    }
+
+    #[test]
+    fn multi_seal_rejects_bad_or_unverifiable_cosig() {
+        let (mut c, keys) = make_multi();
+        sign(&mut c, &keys[0].0).unwrap();
+        // Buyer's slot signed with the seller's key.
+        cosign(&mut c, "did:ubl:buyer#k", &keys[2].0).unwrap();
+        assert_eq!(
+            verify_with_opts(&c, &keys[0].1, &policy(1, &keys)).unwrap_err(),
+            SealError::BadCoSignature {
+                kid: "did:ubl:buyer#k".into()
+            }
+        );
+
+        cosign(&mut c, "did:ubl:buyer#k", &keys[1].0).unwrap();
+        let mut opts = policy(2, &keys);
+        opts.threshold
+            .as_mut()
+            .unwrap()
+            .keys
+            .remove("did:ubl:buyer#k");
+        assert_eq!(
+            verify_with_opts(&c, &keys[0].1, &opts).unwrap_err(),
+            SealError::UnknownSigner {
+                kid: "did:ubl:buyer#k".into()
+            }
+        );
+    }
+
+    #[test]
+    fn multi_seal_signer_set_is_bound() {
+        let (mut c, keys) = make_multi();
+        sign(&mut c, &keys[0].0).unwrap();
+        cosign(&mut c, "did:ubl:buyer#k", &keys[1].0).unwrap();
+        // Dropping a co-signer changes the ID.
+        let mut dropped = c.clone();
+        dropped.seal.cosigs.pop();
+        assert_eq!(
+            verify(&dropped, &keys[0].1).unwrap_err(),
+            SealError::IdMismatch
+        );
+
+        assert_eq!(
+            cosign(&mut c, "did:ubl:eve#k", &keys[1].0).unwrap_err(),
+            "Err.Seal.UnknownSigner: did:ubl:eve#k is not a co-signer"
+        );
+
+        let mut dup = make_capsule();
+        dup.seal.cosigs = vec![CoSig::new("did:ubl:alice#key-1")];
+        sign(&mut dup, &keys[0].0).unwrap();
+        assert_eq!(
+            verify_with_opts(&dup, &keys[0].1, &policy(1, &keys)).unwrap_err(),
+            SealError::DuplicateSigner {
+                kid: "did:ubl:alice#key-1".into()
+            }
+        );
+    }
+
+    #[test]
+    fn multi_seal_counts_each_key_once() {
+        let (mut c, keys) = make_multi();
+        sign(&mut c, &keys[0].0).unwrap();
+        cosign(&mut c, "did:ubl:buyer#k", &keys[1].0).unwrap();
+        cosign(&mut c, "did:ubl:seller#k", &keys[1].0).unwrap();
+        // Both co-signer kids map to the buyer's key: 2 signers, not 3.
+        let mut opts = policy(3, &keys);
+        let th = opts.threshold.as_mut().unwrap();
+        th.keys.insert("did:ubl:seller#k".into(), keys[1].1);
+        assert_eq!(
+            verify_with_opts(&c, &keys[0].1, &opts).unwrap_err(),
+            SealError::ThresholdNotMet {
+                valid: 2,
+                k: 3,
+                n: 3
+            }
+        );
+        opts.threshold.as_mut().unwrap().k = 2;
+        assert!(verify_with_opts(&c, &keys[0].1, &opts).is_ok());
+    }
+
+    // -- key resolution ----------------------------------------------------
+
+    #[test]
+    fn verify_resolved_uses_did_key_and_keyring_windows() {
+        use crate::resolver::{did_key_for, DidKey, FirstMatch, KeyRecord, Keyring};
+        let (sk, vk) = keypair();
+        let mut c = make_capsule();
+        c.hdr.src = did_key_for(&vk);
+        c.seal.kid = format!("{}#k", did_key_for(&vk));
+        sign(&mut c, &sk).unwrap();
+        let opts = VerifyOpts::default();
+        assert!(verify_resolved(&c, &DidKey, &opts).is_ok());
+
+        // A co-signer from a keyring whose key was retired before hdr.ts.
+        let (bob_sk, bob_vk) = keypair();
+        c.seal.cosigs = vec![CoSig::new("did:ubl:bob#key-1")];
+        sign(&mut c, &sk).unwrap();
+        cosign(&mut c, "did:ubl:bob#key-1", &bob_sk).unwrap();
+        let ring = |not_after| {
+            let mut ring = Keyring::new();
+            ring.insert(KeyRecord {
+                kid: "did:ubl:bob#key-1".into(),
+                key: bob_vk,
+                not_before: None,
+                not_after: Some(not_after),
+            });
+            FirstMatch(vec![Box::new(DidKey), Box::new(ring)])
+        };
+        let opts = VerifyOpts {
+            threshold: Some(ThresholdPolicy {
+                k: 2,
+                signers: ["did:ubl:bob#key-1".to_string()].into(),
+                ..Default::default()
+            }),
+            ..VerifyOpts::default()
+        };
+        assert!(verify_resolved(&c, &ring(c.hdr.ts + 1), &opts).is_ok());
+        assert!(matches!(
+            verify_resolved(&c, &ring(c.hdr.ts), &opts).unwrap_err(),
+            SealError::Key(ResolveError::Expired { .. })
+        ));
+        assert_eq!(
+            verify_resolved(&c, &DidKey, &opts).unwrap_err(),
+            SealError::UnknownSigner {
+                kid: "did:ubl:bob#key-1".into()
+            }
+        );
+
+        // A backdated hdr.ts no longer helps once the verifier brings its
+        // own time: bob's key is looked up after it was retired.
+        let trusted = VerifyOpts {
+            key_time_ms: Some(c.hdr.ts + 1),
+            threshold: Some(ThresholdPolicy {
+                k: 2,
+                signers: ["did:ubl:bob#key-1".to_string()].into(),
+                ..Default::default()
+            }),
+            ..VerifyOpts::default()
+        };
+        assert!(matches!(
+            verify_resolved(&c, &ring(c.hdr.ts + 1), &trusted).unwrap_err(),
+            SealError::Key(ResolveError::Expired { .. })
+        ));
+        assert!(verify_resolved(&c, &ring(c.hdr.ts + 2), &trusted).is_ok());
+    }
+
+    #[test]
+    fn resolved_cosigners_must_be_allowlisted() {
+        use crate::resolver::{did_key_for, DidKey};
+        // One party, two keys: the second is a did:key it minted itself.
+        let (sk, vk) = keypair();
+        let (sock_sk, sock_vk) = keypair();
+        let sock = format!("{}#k", did_key_for(&sock_vk));
+        let mut c = make_capsule();
+        c.hdr.src = did_key_for(&vk);
+        c.seal.kid = format!("{}#k", did_key_for(&vk));
+        c.seal.cosigs = vec![CoSig::new(&sock)];
+        sign(&mut c, &sk).unwrap();
+        cosign(&mut c, &sock, &sock_sk).unwrap();
+
+        let mut opts = VerifyOpts {
+            threshold: Some(ThresholdPolicy {
+                k: 2,
+                ..Default::default()
+            }),
+            ..VerifyOpts::default()
+        };
+        assert_eq!(
+            verify_resolved(&c, &DidKey, &opts).unwrap_err(),
+            SealError::UntrustedSigner { kid: sock.clone() }
+        );
+        opts.threshold.as_mut().unwrap().signers.insert(sock);
+        assert!(verify_resolved(&c, &DidKey, &opts).is_ok());
+    }
+
+    #[test]
+    fn resolved_author_must_belong_to_src() {
+        use crate::resolver::{did_key_for, DidKey};
+        let (sk, vk) = keypair();
+        let mut c = make_capsule();
+        c.seal.kid = format!("{}#k", did_key_for(&vk));
+        sign(&mut c, &sk).unwrap();
+        // hdr.src still claims alice; a did:key seal cannot speak for her.
+        assert!(matches!(
+            verify_resolved(&c, &DidKey, &VerifyOpts::default()).unwrap_err(),
+            SealError::ForeignSigner { .. }
+        ));
+        // The caller-supplied key path is unchanged: the caller vouches.
+        assert!(verify(&c, &vk).is_ok());
+    }
 }
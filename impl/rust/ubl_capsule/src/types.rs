@@ -4,12 +4,14 @@ use serde::{Deserialize, Serialize};
 
 pub const DOMAIN: &str = "ubl-capsule/1.0";
 pub const RECEIPT_DOMAIN: &str = "ubl-receipt/1.0";
+/// The only `env.enc.alg`: X25519 + HKDF-SHA256 + XChaCha20-Poly1305.
+pub const ENC_ALG: &str = "X25519-XChaCha20Poly1305";
 
 /// A complete UBL Capsule.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Capsule {
     pub domain: String,
-    /// Stable content-address — blake3(nrf.encode(capsule \ {id, seal.sig, receipts[*].sig}))
+    /// Stable content-address — blake3(nrf.encode(capsule \ {id, seal sigs, receipts}))
     #[serde(with = "hex_bytes_32")]
     pub id: [u8; 32],
     pub hdr: Header,
@@ -45,11 +47,41 @@ pub struct Header {
 /// The payload envelope.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Envelope {
+    /// Clear body; `null` when `enc` is present
     pub body: serde_json::Value,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub links: Option<Links>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub evidence: Vec<String>,
+    /// Body sealed to `hdr.dst` (encrypted-body mode)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enc: Option<EncryptedBody>,
+    /// Salted Merkle commitment to `body` (disclosure mode)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merkle: Option<MerkleBody>,
+}
+
+/// A body committed by its salted Merkle root: the ID covers `root`, not
+/// the body, so body fields can be withheld after sealing (see
+/// [`crate::disclose`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleBody {
+    /// `b3m:<hex>` root of the body under `salts`
+    pub root: String,
+    /// Salt tree shaped like the body: 64-hex salt per scalar, `07` +
+    /// 64-hex node hash per withheld subtree
+    pub salts: serde_json::Value,
+}
+
+/// An encrypted body: only `hdr.dst` can open it, but the ID commits to the
+/// ciphertext, so relays verify seals and append hops without decrypting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedBody {
+    /// Always [`ENC_ALG`]
+    pub alg: String,
+    /// `ephemeral_pub(32) ‖ nonce(24) ‖ ciphertext ‖ tag(16)`
+    #[serde(with = "hex_bytes")]
+    pub ct: Vec<u8>,
 }
 
 /// Links to prior capsules (for pipeline composition).
@@ -61,6 +93,10 @@ pub struct Links {
 }
 
 /// Author seal: signature over {domain, id, hdr, env}.
+///
+/// A multi-seal adds `cosigs`: further parties (e.g. buyer and seller in a
+/// TRANSACT) who each sign the same payload. Their kids are part of the ID,
+/// so the signer set is fixed before anyone signs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Seal {
     /// Key ID (ASCII-only DID#fragment)
@@ -74,6 +110,29 @@ pub struct Seal {
     /// Audience (must match hdr.dst if present)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub aud: Option<String>,
+    /// Co-signers (multi-seal), in declaration order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cosigs: Vec<CoSig>,
+}
+
+/// One co-signer slot of a multi-seal; `sig` is all zeros until signed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoSig {
+    /// Key ID (ASCII-only DID#fragment)
+    pub kid: String,
+    /// Ed25519 signature (64 bytes)
+    #[serde(with = "hex_bytes_64")]
+    pub sig: [u8; 64],
+}
+
+impl CoSig {
+    /// An unsigned slot for `kid`.
+    pub fn new(kid: impl Into<String>) -> Self {
+        CoSig {
+            kid: kid.into(),
+            sig: [0u8; 64],
+        }
+    }
 }
 
 fn default_scope() -> String {
@@ -107,6 +166,23 @@ pub struct Receipt {
 // Hex serde helpers for fixed-size byte arrays
 // ---------------------------------------------------------------------------
 
+mod hex_bytes {
+    use serde::{self, Deserialize, Deserializer, Serializer};
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 mod hex_bytes_32 {
     use serde::{self, Deserialize, Deserializer, Serializer};
     pub fn serialize<S>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error>
@@ -32,12 +32,15 @@ fn make_capsule(act: &str, body: serde_json::Value) -> Capsule {
             body,
             links: None,
             evidence: vec![],
+            enc: None,
+            merkle: None,
         },
         seal: Seal {
             kid: "did:ubl:alice#key-1".into(),
             sig: [0u8; 64],
             scope: "capsule".into(),
             aud: Some("did:ubl:bob".into()),
+            cosigs: vec![],
         },
         receipts: vec![],
     }
@@ -411,7 +414,7 @@ fn non_ascii_did_rejected_in_json_view() {
 #[test]
 fn capsule_rejects_float_in_body() {
     let (sk, _) = keypair();
-    let mut c = make_capsule("ATTEST", serde_json::json!({"price": 3.14}));
+    let mut c = make_capsule("ATTEST", serde_json::json!({"price": 2.5}));
     assert!(seal::sign(&mut c, &sk).is_err(), "Canon 2: no floats, period");
 }
 
@@ -493,8 +496,8 @@ fn timestamp_no_fraction_unchanged() {
 
 #[test]
 fn varint_non_minimal_rejected() {
-    // 0x80 0x01 encodes 0 non-minimally (should be just 0x00)
-    let data = vec![0x6E, 0x72, 0x66, 0x31, 0x05, 0x80, 0x01];
+    // 0x80 0x00 encodes 0 non-minimally (should be just 0x00)
+    let data = vec![0x6E, 0x72, 0x66, 0x31, 0x05, 0x80, 0x00];
     assert!(nrf_core::decode(&data).is_err());
 }
 
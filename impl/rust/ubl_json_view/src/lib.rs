@@ -9,7 +9,7 @@
 //! Integers: only Int64 (no floats). Decimals as canonical strings.
 //! Strings: NFC, no BOM, ASCII-only enforced for DID/KID fields.
 
-use nrf_core::Value;
+use nrf_core::{Value, ValueRef};
 use regex::Regex;
 use std::collections::BTreeMap;
 use std::sync::LazyLock;
@@ -46,6 +46,21 @@ pub enum JsonViewError {
     NonMinimalVarint,
     #[error("Err.JsonView.NrfDecode: {0}")]
     NrfDecode(String),
+    #[error("Err.JsonView.PathNotFound")]
+    PathNotFound,
+    #[error("Err.JsonView.BadPath: {0}")]
+    BadPath(String),
+    #[error("Err.JsonView.BadDisclosure: {0}")]
+    BadDisclosure(String),
+}
+
+/// A `JsonViewError` plus the logical path of the offending JSON value
+/// (e.g. `env.items[3].sku`; empty for the root).
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{error} at {}", if path.is_empty() { "<root>" } else { path })]
+pub struct JsonViewErrorAt {
+    pub error: JsonViewError,
+    pub path: String,
 }
 
 // ---------------------------------------------------------------------------
@@ -77,21 +92,39 @@ pub fn to_json(v: &Value) -> serde_json::Value {
     }
 }
 
+/// Convert a borrowed `ValueRef` (zero-copy decode) to canonical JSON.
+/// Same output as `to_json` on the equivalent owned value.
+pub fn to_json_ref(v: &ValueRef<'_>) -> serde_json::Value {
+    match v {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Bool(b) => serde_json::Value::Bool(*b),
+        ValueRef::Int(n) => serde_json::json!(*n),
+        ValueRef::String(s) => serde_json::Value::String((*s).to_string()),
+        ValueRef::Bytes(b) => bytes_to_json(b),
+        ValueRef::Array(items) => serde_json::Value::Array(items.iter().map(to_json_ref).collect()),
+        ValueRef::Map(pairs) => {
+            let obj: serde_json::Map<String, serde_json::Value> =
+                pairs.iter().map(|(k, val)| ((*k).to_string(), to_json_ref(val))).collect();
+            serde_json::Value::Object(obj)
+        }
+    }
+}
+
 /// Convert NRF bytes to a full NRF-encoded buffer, then to JSON.
 /// Uses default (conservative) decode limits.
 pub fn nrf_bytes_to_json(bytes: &[u8]) -> Result<serde_json::Value, JsonViewError> {
-    let v = nrf_core::decode(bytes).map_err(|e| JsonViewError::NrfDecode(e.to_string()))?;
-    Ok(to_json(&v))
+    nrf_bytes_to_json_with_opts(bytes, &nrf_core::DecodeOpts::default())
 }
 
 /// Convert NRF bytes to JSON with explicit decode limits.
+/// Decodes zero-copy: leaves are copied once, straight into the JSON tree.
 pub fn nrf_bytes_to_json_with_opts(
     bytes: &[u8],
     opts: &nrf_core::DecodeOpts,
 ) -> Result<serde_json::Value, JsonViewError> {
-    let v = nrf_core::decode_with_opts(bytes, opts)
+    let v = nrf_core::decode_ref_located_with_opts(bytes, opts)
         .map_err(|e| JsonViewError::NrfDecode(e.to_string()))?;
-    Ok(to_json(&v))
+    Ok(to_json_ref(&v))
 }
 
 /// Canon 4: ALL bytes → {"$bytes": "<lowercase hex>"}. No exceptions.
@@ -108,6 +141,17 @@ fn bytes_to_json(b: &[u8]) -> serde_json::Value {
 /// Convert a canonical JSON representation back to an NRF `Value`.
 /// Rejects floats, non-NFC strings, BOM, and invalid byte prefixes.
 pub fn from_json(j: &serde_json::Value) -> Result<Value, JsonViewError> {
+    from_json_at(j).map_err(|e| e.error)
+}
+
+/// Like `from_json`, but the error names the offending value's path.
+pub fn from_json_at(j: &serde_json::Value) -> Result<Value, JsonViewErrorAt> {
+    let mut path = String::new();
+    from_json_walk(j, &mut path).map_err(|error| JsonViewErrorAt { error, path })
+}
+
+/// On error `path` is left pointing at the offending value.
+fn from_json_walk(j: &serde_json::Value, path: &mut String) -> Result<Value, JsonViewError> {
     match j {
         serde_json::Value::Null => Ok(Value::Null),
         serde_json::Value::Bool(b) => Ok(Value::Bool(*b)),
@@ -127,8 +171,11 @@ pub fn from_json(j: &serde_json::Value) -> Result<Value, JsonViewError> {
         }
         serde_json::Value::Array(items) => {
             let mut out = Vec::with_capacity(items.len());
-            for item in items {
-                out.push(from_json(item)?);
+            for (i, item) in items.iter().enumerate() {
+                let len = path.len();
+                nrf_core::push_path_index(path, i);
+                out.push(from_json_walk(item, path)?);
+                path.truncate(len);
             }
             Ok(Value::Array(out))
         }
@@ -152,8 +199,11 @@ pub fn from_json(j: &serde_json::Value) -> Result<Value, JsonViewError> {
             }
             let mut map = BTreeMap::new();
             for (k, val) in obj {
+                let len = path.len();
+                nrf_core::push_path_key(path, k);
                 validate_string(k)?;
-                map.insert(k.clone(), from_json(val)?);
+                map.insert(k.clone(), from_json_walk(val, path)?);
+                path.truncate(len);
             }
             Ok(Value::Map(map))
         }
@@ -163,9 +213,16 @@ pub fn from_json(j: &serde_json::Value) -> Result<Value, JsonViewError> {
 /// Convert JSON to NRF bytes (encode after parsing).
 /// Canon 3: passes through ρ before encode.
 pub fn json_to_nrf_bytes(j: &serde_json::Value) -> Result<Vec<u8>, JsonViewError> {
-    let v = from_json(j)?;
-    let normalized = nrf_core::rho::normalize(&v)
-        .map_err(|e| JsonViewError::NrfDecode(e.to_string()))?;
+    json_to_nrf_bytes_at(j).map_err(|e| e.error)
+}
+
+/// Like `json_to_nrf_bytes`, but the error names the offending value's path.
+pub fn json_to_nrf_bytes_at(j: &serde_json::Value) -> Result<Vec<u8>, JsonViewErrorAt> {
+    let v = from_json_at(j)?;
+    let normalized = nrf_core::rho::normalize(&v).map_err(|e| JsonViewErrorAt {
+        error: JsonViewError::NrfDecode(e.to_string()),
+        path: String::new(),
+    })?;
     Ok(nrf_core::encode(&normalized))
 }
 
@@ -272,6 +329,91 @@ impl JsonView {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Selective disclosure: redact subtrees, keep the Merkle CID
+// ---------------------------------------------------------------------------
+
+/// `b3m:` Merkle CID of a JSON view document (see `nrf_core::merkle`).
+/// Every map is data here, `$redacted`-shaped ones included; disclosures
+/// are checked with `disclosure_cid_json`.
+pub fn merkle_cid_json(j: &serde_json::Value) -> Result<String, JsonViewErrorAt> {
+    Ok(nrf_core::merkle_cid(&from_json_at(j)?))
+}
+
+/// Salt `j` from `seed` (see `nrf_core::Disclosure`) and withhold every
+/// match of each query path (`a.b.0`, `/a/b`, `$.items[*].card`). Returns
+/// `{"salts": ..., "value": ...}` with `$redacted` markers in `value`. A
+/// path matching nothing fails with `PathNotFound`; a malformed one with
+/// its parse error.
+pub fn disclose_json(
+    j: &serde_json::Value,
+    seed: &nrf_core::merkle::Seed,
+    paths: &[&str],
+) -> Result<serde_json::Value, JsonViewErrorAt> {
+    let not_found = |path: &str| JsonViewErrorAt {
+        error: JsonViewError::PathNotFound,
+        path: path.to_string(),
+    };
+    let v = from_json_at(j)?;
+    let mut d = nrf_core::Disclosure::new(&v, seed);
+    for p in paths {
+        let q = nrf_core::Query::parse(p).map_err(|e| JsonViewErrorAt {
+            error: JsonViewError::BadPath(e.to_string()),
+            path: p.to_string(),
+        })?;
+        let matches = q.select_paths(&d.value);
+        if matches.is_empty() {
+            return Err(not_found(p));
+        }
+        let mut next = d.clone();
+        for (at, _) in matches {
+            next.redact(&at).map_err(|_| not_found(p))?;
+        }
+        d = next;
+    }
+    Ok(to_json(&d.to_value()))
+}
+
+/// Salted `b3m:` root of a `{"salts", "value"}` document from
+/// `disclose_json`; equal to the root before anything was withheld.
+pub fn disclosure_cid_json(j: &serde_json::Value) -> Result<String, JsonViewErrorAt> {
+    let bad = |e: nrf_core::Error| JsonViewErrorAt {
+        error: JsonViewError::BadDisclosure(e.to_string()),
+        path: String::new(),
+    };
+    nrf_core::Disclosure::from_value(&from_json_at(j)?)
+        .and_then(|d| d.cid())
+        .map_err(bad)
+}
+
+// ---------------------------------------------------------------------------
+// diff_to_json: structural diff → JSON report
+// ---------------------------------------------------------------------------
+
+/// Render an `nrf_core::diff` result as a JSON array of
+/// `{"path", "op", "old"?, "new"?, "note"?}` objects; values use the JSON view.
+pub fn diff_to_json(changes: &[nrf_core::Change]) -> serde_json::Value {
+    let items = changes
+        .iter()
+        .map(|c| {
+            let mut obj = serde_json::Map::new();
+            obj.insert("path".into(), serde_json::Value::String(c.path.clone()));
+            obj.insert("op".into(), serde_json::Value::String(c.kind.as_str().into()));
+            if let Some(old) = &c.old {
+                obj.insert("old".into(), to_json(old));
+            }
+            if let Some(new) = &c.new {
+                obj.insert("new".into(), to_json(new));
+            }
+            if let Some(note) = &c.note {
+                obj.insert("note".into(), serde_json::Value::String(note.clone()));
+            }
+            serde_json::Value::Object(obj)
+        })
+        .collect();
+    serde_json::Value::Array(items)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -396,6 +538,17 @@ mod tests {
         assert_eq!(from_json(&j).unwrap(), v);
     }
 
+    #[test]
+    fn to_json_ref_matches_to_json() {
+        let mut m = BTreeMap::new();
+        m.insert("id".into(), Value::Bytes(vec![0xAA; 4]));
+        m.insert("xs".into(), Value::Array(vec![Value::Int(1), Value::String("a".into())]));
+        let v = Value::Map(m);
+        let nrf = nrf_core::encode(&v);
+        let r = nrf_core::decode_ref(&nrf).unwrap();
+        assert_eq!(to_json_ref(&r), to_json(&v));
+    }
+
     #[test]
     fn roundtrip_nrf_bytes() {
         let mut m = BTreeMap::new();
@@ -415,6 +568,22 @@ mod tests {
         assert_eq!(from_json(&j).unwrap_err(), JsonViewError::Float);
     }
 
+    #[test]
+    fn from_json_at_names_offending_path() {
+        let j = serde_json::json!({"env": {"items": [{"sku": "a"}, {"sku": 1.5}]}});
+        let err = from_json_at(&j).unwrap_err();
+        assert_eq!(err.error, JsonViewError::Float);
+        assert_eq!(err.path, "env.items[1].sku");
+        let j = serde_json::json!({"ok": [1], "bad key": "e\u{0301}"});
+        assert_eq!(from_json_at(&j).unwrap_err().path, r#"["bad key"]"#);
+    }
+
+    #[test]
+    fn nrf_decode_error_carries_location() {
+        let err = nrf_bytes_to_json(b"nrf1\x07\x02\x04\x01b\x00\x04\x01a\x00").unwrap_err();
+        assert_eq!(err, JsonViewError::NrfDecode("UnsortedKeys at byte 10 (a)".into()));
+    }
+
     #[test]
     fn reject_bom() {
         let j = serde_json::Value::String("\u{FEFF}hello".into());
@@ -550,4 +719,57 @@ mod tests {
         let back_view = canon.to_json_view().unwrap();
         assert_eq!(view.as_json()["name"], back_view.as_json()["name"]);
     }
+
+    #[test]
+    fn diff_to_json_renders_changes() {
+        let a = from_json(&serde_json::json!({"k": {"$bytes": "00"}, "x": 1})).unwrap();
+        let b = from_json(&serde_json::json!({"k": {"$bytes": "01"}, "y": true})).unwrap();
+        let j = diff_to_json(&nrf_core::diff(&a, &b));
+        assert_eq!(
+            j,
+            serde_json::json!([
+                {"path": "k", "op": "changed", "old": {"$bytes": "00"}, "new": {"$bytes": "01"},
+                 "note": "first differing byte at 0"},
+                {"path": "x", "op": "removed", "old": 1},
+                {"path": "y", "op": "added", "new": true}
+            ])
+        );
+    }
+
+    #[test]
+    fn disclose_json_keeps_disclosure_cid() {
+        let j = serde_json::json!({"a": {"secret": "x", "k": [1, 2]}, "b": true});
+        let seed = [3u8; 32];
+        let full = disclose_json(&j, &seed, &[]).unwrap();
+        let r = disclose_json(&j, &seed, &["a.secret", "$.a.k[-1]"]).unwrap();
+        assert_eq!(
+            disclosure_cid_json(&r).unwrap(),
+            disclosure_cid_json(&full).unwrap()
+        );
+        let marker = r["value"]["a"]["secret"]["$redacted"].as_str().unwrap();
+        assert!(marker.starts_with("b3m:"));
+        assert_eq!(r["value"]["a"]["k"][0], 1);
+        assert!(r["value"]["a"]["k"][1]["$redacted"].is_string());
+        assert!(!r.to_string().contains("\"x\""));
+
+        // The value alone is plain data: its markers are not commitments.
+        assert_ne!(
+            merkle_cid_json(&r["value"]).unwrap(),
+            merkle_cid_json(&j).unwrap()
+        );
+        let mut forged = r.clone();
+        forged["value"]["b"] = serde_json::json!(false);
+        assert_ne!(
+            disclosure_cid_json(&forged).unwrap(),
+            disclosure_cid_json(&full).unwrap()
+        );
+        let err = disclosure_cid_json(&r["value"]).unwrap_err();
+        assert!(matches!(err.error, JsonViewError::BadDisclosure(_)));
+
+        let err = disclose_json(&j, &seed, &["a.nope"]).unwrap_err();
+        assert_eq!(err.error, JsonViewError::PathNotFound);
+        assert_eq!(err.path, "a.nope");
+        let err = disclose_json(&j, &seed, &["$.a["]).unwrap_err();
+        assert!(matches!(err.error, JsonViewError::BadPath(_)));
+    }
 }
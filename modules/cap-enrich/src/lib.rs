@@ -3,10 +3,18 @@
 //!
 //! Drivers are declarative — the module returns `Artifact` blobs and `Effect`
 //! requests; the runtime's `EffectExecutor` handles actual IO.
+//!
+//! What gets published is a salted disclosure of the env (`{salts, value}`,
+//! see `nrf1::merkle::Disclosure`) with the `redaction` paths withheld. Its
+//! root goes into an `AppendReceipt`, so the node key signs it; readers
+//! check the published disclosure against that root.
 
-use modules_core::{Artifact, CapInput, CapOutput, Capability, Effect};
+use anyhow::Context;
+use modules_core::{Artifact, CapInput, CapOutput, Capability, Cid, Effect};
+use nrf1::merkle::{Disclosure, Seed};
+use nrf1::query::Query;
 use serde::Deserialize;
-use serde_json::{json, Value};
+use serde_json::Value;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -26,44 +34,59 @@ struct Config {
     drivers: Vec<Driver>,
     #[serde(default)]
     redaction: Vec<String>,
+    /// `b3:` CID of a 32-byte secret asset; required to withhold anything.
+    /// Each deployment provisions its own (see OPERATIONS.md, "Assets").
+    #[serde(default)]
+    salt_asset: Option<String>,
     #[serde(default)]
     webhook_binding: Option<String>,
 }
 
+fn parse_cid(cid: &str) -> anyhow::Result<Cid> {
+    hex::decode(cid.strip_prefix("b3:").unwrap_or(cid))
+        .ok()
+        .and_then(|b| Cid::try_from(b).ok())
+        .with_context(|| {
+            format!("invalid salt_asset '{cid}': expected b3:<64 hex> of a provisioned asset")
+        })
+}
+
 #[derive(Default)]
 pub struct EnrichModule;
 
 impl EnrichModule {
-    fn redact(mut j: Value, paths: &[String]) -> Value {
+    /// Withhold every match of each query path. The disclosure root does
+    /// not change. Paths matching nothing have nothing to hide.
+    fn redact(d: &mut Disclosure, paths: &[String]) -> anyhow::Result<()> {
         for p in paths {
-            if let Some((parent, leaf)) = p.rsplit_once('.') {
-                if let Some(obj) = Self::get_mut(&mut j, parent) {
-                    if let Some(m) = obj.as_object_mut() {
-                        m.insert(leaf.to_string(), json!("<redacted>"));
-                    }
-                }
+            let q = Query::parse(p).with_context(|| format!("invalid redaction path '{p}'"))?;
+            let mut next = d.clone();
+            for (at, _) in q.select_paths(&d.value) {
+                next.redact(&at)?;
             }
+            *d = next;
         }
-        j
+        Ok(())
     }
 
-    fn get_mut<'a>(root: &'a mut Value, path: &str) -> Option<&'a mut Value> {
-        let mut cur = root;
-        for seg in path.split('.') {
-            match cur {
-                Value::Object(m) => {
-                    cur = m.get_mut(seg)?;
-                }
-                Value::Array(a) => {
-                    cur = a.get_mut(seg.parse::<usize>().ok()?)?;
-                }
-                _ => return None,
-            }
-        }
-        Some(cur)
+    /// Salt seed for this run: the secret `salt_asset` keyed with the run
+    /// ID. Without the secret, withheld low-entropy fields could be found
+    /// by hashing guesses; with nothing withheld every salt is published
+    /// anyway, so a fixed seed does.
+    fn seed(cfg: &Config, input: &CapInput) -> anyhow::Result<Seed> {
+        let Some(cid) = &cfg.salt_asset else {
+            anyhow::ensure!(cfg.redaction.is_empty(), "redaction requires salt_asset");
+            return Ok([0u8; 32]);
+        };
+        let asset = input.assets.get(&parse_cid(cid)?)?;
+        let secret: [u8; 32] = asset
+            .bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("salt_asset must be 32 bytes"))?;
+        Ok(*blake3::keyed_hash(&secret, input.meta.run_id.as_bytes()).as_bytes())
     }
 
-    fn html_status(name: &str, redacted_json: &Value) -> Artifact {
+    fn html_status(name: &str, redacted_json: &Value, root: &str) -> Artifact {
         let pretty = serde_json::to_string_pretty(redacted_json).unwrap_or_else(|_| "{}".into());
         let html = format!(
             r#"<!doctype html>
@@ -71,10 +94,12 @@ impl EnrichModule {
 <head><meta charset="utf-8"><title>Status — {name}</title></head>
 <body>
   <h1>Status</h1>
+  <p>Merkle root: <code>{root}</code></p>
   <pre>{escaped}</pre>
 </body>
 </html>"#,
             name = name,
+            root = root,
             escaped = html_escape(&pretty),
         );
         Artifact {
@@ -102,8 +127,15 @@ impl Capability for EnrichModule {
     }
 
     fn validate_config(&self, config: &Value) -> anyhow::Result<()> {
-        let _cfg: Config = serde_json::from_value(config.clone())?;
-        Ok(())
+        let cfg: Config = serde_json::from_value(config.clone())?;
+        for p in &cfg.redaction {
+            Query::parse(p).with_context(|| format!("invalid redaction path '{p}'"))?;
+        }
+        match &cfg.salt_asset {
+            Some(cid) => parse_cid(cid).map(|_| ()),
+            None if cfg.redaction.is_empty() => Ok(()),
+            None => anyhow::bail!("redaction requires salt_asset"),
+        }
     }
 
     fn execute(&self, input: CapInput) -> anyhow::Result<CapOutput> {
@@ -111,13 +143,23 @@ impl Capability for EnrichModule {
         let mut artifacts = vec![];
         let mut effects = vec![];
 
-        let j = ubl_json_view::to_json(&input.env);
-        let j = Self::redact(j, &cfg.redaction);
+        let mut disclosure = Disclosure::new(&input.env, &Self::seed(&cfg, &input)?);
+        Self::redact(&mut disclosure, &cfg.redaction)?;
+        let root = disclosure.cid()?;
+        let j = ubl_json_view::to_json(&disclosure.to_value());
+
+        let mut receipt = std::collections::BTreeMap::new();
+        receipt.insert("kind".into(), nrf1::Value::String("disclosure".into()));
+        receipt.insert("root".into(), nrf1::Value::String(root.clone()));
+        effects.push(Effect::AppendReceipt {
+            payload_nrf: nrf1::encode(&nrf1::Value::Map(receipt)),
+            signer_binding: "NODE_KEY".into(),
+        });
 
         for d in &cfg.drivers {
             match d.kind {
                 DriverKind::StatusPage => {
-                    let art = Self::html_status("capsule", &j);
+                    let art = Self::html_status("capsule", &j["value"], &root);
                     effects.push(Effect::WriteStorage {
                         path: "status.html".into(),
                         bytes: art.bytes.clone(),
@@ -144,3 +186,102 @@ impl Capability for EnrichModule {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use modules_core::{Asset, AssetResolver, ExecutionMeta};
+
+    #[derive(Clone)]
+    struct SecretResolver;
+    impl AssetResolver for SecretResolver {
+        fn get(&self, cid: &Cid) -> anyhow::Result<Asset> {
+            Ok(Asset {
+                cid: *cid,
+                bytes: vec![9u8; 32],
+                mime: "application/octet-stream".into(),
+            })
+        }
+        fn box_clone(&self) -> Box<dyn AssetResolver> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn env() -> nrf1::Value {
+        ubl_json_view::from_json(&serde_json::json!({
+            "req": {"headers": {"authorization": "Bearer s3cret"}, "path": "/x"},
+            "items": [{"sku": "A"}, {"sku": "B"}]
+        }))
+        .unwrap()
+    }
+
+    fn input(config: Value) -> CapInput {
+        CapInput {
+            env: env(),
+            config,
+            assets: Box::new(SecretResolver),
+            prev_receipts: vec![],
+            meta: ExecutionMeta {
+                run_id: "run-enrich-001".into(),
+                tenant: None,
+                trace_id: None,
+                ts_nanos: 1_700_000_000_000_000_000,
+            },
+        }
+    }
+
+    #[test]
+    fn redacted_env_keeps_disclosure_root() {
+        let seed = [1u8; 32];
+        let mut d = Disclosure::new(&env(), &seed);
+        let root = d.cid().unwrap();
+        let paths = vec![
+            "req.headers.authorization".to_string(),
+            "$.items[?(@.sku == 'B')]".to_string(),
+            "req.missing".to_string(),
+        ];
+        EnrichModule::redact(&mut d, &paths).unwrap();
+        let j = ubl_json_view::to_json(&d.to_value());
+        assert!(j["value"]["req"]["headers"]["authorization"]["$redacted"].is_string());
+        assert!(j["value"]["items"][1]["$redacted"].is_string());
+        assert_eq!(j["value"]["req"]["path"], "/x");
+        assert!(!j.to_string().contains("s3cret"));
+        assert_eq!(ubl_json_view::disclosure_cid_json(&j).unwrap(), root);
+    }
+
+    #[test]
+    fn root_is_bound_into_an_append_receipt() {
+        let cfg = serde_json::json!({
+            "drivers": [{ "kind": "webhook" }],
+            "redaction": ["req.headers"],
+            "salt_asset": "b3:0000000000000000000000000000000000000000000000000000000000000000"
+        });
+        EnrichModule.validate_config(&cfg).unwrap();
+        let out = EnrichModule.execute(input(cfg)).unwrap();
+        let Effect::AppendReceipt { payload_nrf, .. } = &out.effects[0] else {
+            panic!("expected AppendReceipt first");
+        };
+        let Effect::Webhook { body, .. } = &out.effects[1] else {
+            panic!("expected Webhook");
+        };
+        let published: Value = serde_json::from_slice(body).unwrap();
+        let receipt = ubl_json_view::to_json(&nrf1::decode(payload_nrf).unwrap());
+        assert_eq!(
+            receipt["root"].as_str().unwrap(),
+            ubl_json_view::disclosure_cid_json(&published).unwrap()
+        );
+        assert!(!published.to_string().contains("s3cret"));
+    }
+
+    #[test]
+    fn redaction_needs_a_salt_asset() {
+        let cfg = serde_json::json!({ "redaction": ["req.headers"] });
+        assert!(EnrichModule.validate_config(&cfg).is_err());
+        assert!(EnrichModule.execute(input(cfg)).is_err());
+        let cfg = serde_json::json!({ "redaction": ["req"], "salt_asset": "b3:zz" });
+        assert!(EnrichModule.validate_config(&cfg).is_err());
+        EnrichModule
+            .validate_config(&serde_json::json!({ "drivers": [] }))
+            .unwrap();
+    }
+}
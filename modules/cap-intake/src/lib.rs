@@ -4,7 +4,9 @@
 use anyhow::Context;
 use modules_core::{CapInput, CapOutput, Capability};
 use serde::Deserialize;
-use serde_json::{json, Value};
+use nrf1::query::Query;
+use nrf1::NrfError;
+use serde_json::Value;
 
 #[derive(Debug, Deserialize)]
 struct MapRule {
@@ -24,95 +26,39 @@ struct Config {
 pub struct IntakeModule;
 
 impl IntakeModule {
-    fn get<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
-        let mut cur = root;
-        for seg in path.split('.') {
-            match cur {
-                Value::Object(m) => {
-                    cur = m.get(seg)?;
-                }
-                Value::Array(a) => {
-                    let idx: usize = seg.parse().ok()?;
-                    cur = a.get(idx)?;
-                }
-                _ => return None,
-            }
-        }
-        Some(cur)
+    fn parse(path: &str) -> anyhow::Result<Query> {
+        Query::parse(path).with_context(|| format!("invalid path '{path}'"))
     }
 
-    fn ensure_object_path<'a>(root: &'a mut Value, path: &str) -> anyhow::Result<&'a mut Value> {
-        let mut cur = root;
-        for seg in path.split('.') {
-            match cur {
-                Value::Object(m) => {
-                    if !m.contains_key(seg) {
-                        m.insert(seg.to_string(), json!({}));
-                    }
-                    cur = m.get_mut(seg).unwrap();
-                }
-                _ => anyhow::bail!("path collision at '{}'", seg),
-            }
-        }
-        Ok(cur)
-    }
-
-    fn set(root: &mut Value, path: &str, val: Value) -> anyhow::Result<()> {
-        if let Some((parent, leaf)) = path.rsplit_once('.') {
-            let obj = Self::ensure_object_path(root, parent)?;
-            match obj {
-                Value::Object(m) => {
-                    m.insert(leaf.to_string(), val);
-                    Ok(())
-                }
-                _ => anyhow::bail!("non-object at parent path '{}'", parent),
-            }
-        } else {
-            // Single-segment path: insert as key in root object (don't replace root)
-            match root {
-                Value::Object(m) => {
-                    m.insert(path.to_string(), val);
-                    Ok(())
-                }
-                _ => {
-                    // Root is not an object — wrap it
-                    let mut m = serde_json::Map::new();
-                    m.insert(path.to_string(), val);
-                    *root = Value::Object(m);
-                    Ok(())
-                }
-            }
+    fn transform(&self, env: &nrf1::Value, cfg: &Config) -> anyhow::Result<nrf1::Value> {
+        let mut v = env.clone();
+        if !(cfg.defaults.is_empty() && cfg.mapping.is_empty())
+            && !matches!(v, nrf1::Value::Map(_))
+        {
+            // Root is not a map — start from an empty one (keys go into the root)
+            v = nrf1::Value::Map(Default::default());
         }
-    }
 
-    fn apply_defaults(
-        dst: &mut Value,
-        defaults: &serde_json::Map<String, Value>,
-    ) -> anyhow::Result<()> {
-        for (k, v) in defaults {
-            if dst.get(k).is_none() {
-                Self::set(dst, k, v.clone())?;
+        for (k, dv) in &cfg.defaults {
+            let q = Self::parse(k)?;
+            if q.get(&v).is_err() {
+                let dv = ubl_json_view::from_json(dv).context("invalid default value")?;
+                q.set(&mut v, dv)?;
             }
         }
-        Ok(())
-    }
-
-    fn transform(&self, env: &nrf1::Value, cfg: &Config) -> anyhow::Result<nrf1::Value> {
-        let mut j = ubl_json_view::to_json(env);
-
-        if !cfg.defaults.is_empty() {
-            Self::apply_defaults(&mut j, &cfg.defaults)?;
-        }
 
         for rule in &cfg.mapping {
-            let val = Self::get(&j, &rule.from)
-                .cloned()
-                .unwrap_or(Value::Null);
-            Self::set(&mut j, &rule.to, val)?;
+            let val = match Self::parse(&rule.from)?.get(&v) {
+                Ok(x) => x.clone(),
+                Err(NrfError::PathNotFound(_)) => nrf1::Value::Null,
+                Err(e) => return Err(e).context(format!("mapping from '{}'", rule.from)),
+            };
+            Self::parse(&rule.to)?
+                .set(&mut v, val)
+                .with_context(|| format!("mapping to '{}'", rule.to))?;
         }
 
-        let out = ubl_json_view::from_json(&j).context("from_json(view) failed")?;
-        Ok(out)
+        Ok(v)
     }
 }
 
@@ -125,8 +71,15 @@ impl Capability for IntakeModule {
     }
 
     fn validate_config(&self, config: &Value) -> anyhow::Result<()> {
-        let _cfg: Config =
+        let cfg: Config =
             serde_json::from_value(config.clone()).context("invalid cap-intake config")?;
+        for k in cfg.defaults.keys() {
+            Self::parse(k)?;
+        }
+        for rule in &cfg.mapping {
+            Self::parse(&rule.from)?;
+            Self::parse(&rule.to)?;
+        }
         Ok(())
     }
 
@@ -144,6 +97,7 @@ impl Capability for IntakeModule {
 mod tests {
     use super::*;
     use modules_core::{AssetResolver, ExecutionMeta};
+    use serde_json::json;
     use std::collections::BTreeMap;
 
     #[derive(Clone)]
@@ -224,4 +178,28 @@ mod tests {
         let j = ubl_json_view::to_json(out.new_env.as_ref().unwrap());
         assert!(j["dest"].is_null());
     }
+
+    // Shared query language: JSONPath / JSON Pointer on either side
+    #[test]
+    fn mapping_accepts_jsonpath_and_pointer() {
+        let env = nrf_map(&[("a.b", "dotted key"), ("x", "1")]);
+        let cfg = json!({ "mapping": [
+            { "from": "$['a.b']", "to": "/out/key~1slash" },
+            { "from": "/x", "to": "$.out.x" }
+        ] });
+        let out = IntakeModule.execute(make_input(env, cfg)).unwrap();
+        let j = ubl_json_view::to_json(out.new_env.as_ref().unwrap());
+        assert_eq!(j["out"]["key/slash"], "dotted key");
+        assert_eq!(j["out"]["x"], "1");
+    }
+
+    #[test]
+    fn validate_rejects_bad_paths() {
+        let cfg = json!({ "mapping": [{ "from": "$.items[", "to": "dest" }] });
+        assert!(IntakeModule.validate_config(&cfg).is_err());
+        let cfg = json!({ "mapping": [{ "from": "$.items[*]", "to": "dest" }] });
+        assert!(IntakeModule.validate_config(&cfg).is_ok());
+        let env = nrf_map(&[("a", "1")]);
+        assert!(IntakeModule.execute(make_input(env, cfg)).is_err());
+    }
 }
@@ -7,6 +7,7 @@
 
 use anyhow::Context;
 use modules_core::{Artifact, CapInput, CapOutput, Capability, Cid, Effect};
+use nrf1::query;
 use serde::Deserialize;
 use serde_json::Value;
 
@@ -16,7 +17,7 @@ struct Config {
     model_binding: String,
     /// CID of the immutable prompt template asset.
     prompt_cid: String,
-    /// Map of input names → dot-paths into env.
+    /// Map of input names → query paths into env (`a.b`, `/a/b`, `$.a[0]`).
     #[serde(default)]
     inputs: serde_json::Map<String, Value>,
     #[serde(default = "default_max_tokens")]
@@ -34,31 +35,19 @@ fn default_max_tokens() -> u32 {
 pub struct LlmModule;
 
 impl LlmModule {
-    /// Extract a value from env JSON by dot-path.
-    fn get<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
-        let mut cur = root;
-        for seg in path.split('.') {
-            match cur {
-                Value::Object(m) => {
-                    cur = m.get(seg)?;
-                }
-                Value::Array(a) => {
-                    cur = a.get(seg.parse::<usize>().ok()?)?;
-                }
-                _ => return None,
-            }
-        }
-        Some(cur)
+    /// Value at a query path (`a.b`, `/a/b`, `$.a['b']`), as JSON view.
+    fn get(env: &nrf1::Value, path: &str) -> Option<Value> {
+        query::get(env, path).ok().map(ubl_json_view::to_json)
     }
 
     /// Render prompt template by replacing `{{key}}` with extracted values.
-    fn render_prompt(template: &str, env_json: &Value, inputs: &serde_json::Map<String, Value>) -> String {
+    fn render_prompt(template: &str, env: &nrf1::Value, inputs: &serde_json::Map<String, Value>) -> String {
         let mut rendered = template.to_string();
         for (key, path_val) in inputs {
             if let Some(path_str) = path_val.as_str() {
-                let replacement = Self::get(env_json, path_str)
+                let replacement = Self::get(env, path_str)
                     .map(|v| match v {
-                        Value::String(s) => s.clone(),
+                        Value::String(s) => s,
                         other => other.to_string(),
                     })
                     .unwrap_or_else(|| "<missing>".into());
@@ -69,13 +58,13 @@ impl LlmModule {
     }
 
     /// Compute cache key: blake3(prompt_cid || sorted_input_values).
-    fn cache_key(prompt_cid: &str, env_json: &Value, inputs: &serde_json::Map<String, Value>) -> String {
+    fn cache_key(prompt_cid: &str, env: &nrf1::Value, inputs: &serde_json::Map<String, Value>) -> String {
         let mut hasher = blake3::Hasher::new();
         hasher.update(prompt_cid.as_bytes());
         for (key, path_val) in inputs {
             hasher.update(key.as_bytes());
             if let Some(path_str) = path_val.as_str() {
-                let val = Self::get(env_json, path_str)
+                let val = Self::get(env, path_str)
                     .map(|v| v.to_string())
                     .unwrap_or_default();
                 hasher.update(val.as_bytes());
@@ -98,6 +87,9 @@ impl Capability for LlmModule {
             serde_json::from_value(config.clone()).context("invalid cap-llm config")?;
         anyhow::ensure!(!cfg.model_binding.is_empty(), "model_binding required");
         anyhow::ensure!(!cfg.prompt_cid.is_empty(), "prompt_cid required");
+        for p in cfg.inputs.values().filter_map(Value::as_str) {
+            query::Query::parse(p).with_context(|| format!("invalid input path '{p}'"))?;
+        }
         Ok(())
     }
 
@@ -118,18 +110,15 @@ impl Capability for LlmModule {
             Ok(asset) => String::from_utf8_lossy(&asset.bytes).into_owned(),
             Err(_) => {
                 // Fallback: use prompt_cid as a placeholder template.
-                format!("Analyze the following context: {{{{summary}}}}")
+                "Analyze the following context: {{summary}}".to_string()
             }
         };
 
-        // Convert env to JSON for path extraction.
-        let env_json = ubl_json_view::to_json(&input.env);
-
         // Render prompt.
-        let rendered = Self::render_prompt(&prompt_template, &env_json, &cfg.inputs);
+        let rendered = Self::render_prompt(&prompt_template, &input.env, &cfg.inputs);
 
         // Cache key for determinism.
-        let cache_key = Self::cache_key(&cfg.prompt_cid, &env_json, &cfg.inputs);
+        let cache_key = Self::cache_key(&cfg.prompt_cid, &input.env, &cfg.inputs);
 
         let mut artifacts = vec![];
         let mut effects = vec![];
@@ -263,17 +252,21 @@ mod tests {
 
     #[test]
     fn prompt_rendering() {
-        let env_json = serde_json::json!({"doc": {"text": "hello world"}});
+        let env = ubl_json_view::from_json(&serde_json::json!({"doc": {"text": "hello world"}})).unwrap();
         let mut inputs = serde_json::Map::new();
         inputs.insert("content".into(), Value::String("doc.text".into()));
 
-        let rendered = LlmModule::render_prompt("Say: {{content}}", &env_json, &inputs);
+        let rendered = LlmModule::render_prompt("Say: {{content}}", &env, &inputs);
+        assert_eq!(rendered, "Say: hello world");
+
+        inputs.insert("content".into(), Value::String("$['doc'].text".into()));
+        let rendered = LlmModule::render_prompt("Say: {{content}}", &env, &inputs);
         assert_eq!(rendered, "Say: hello world");
     }
 
     #[test]
     fn cache_key_deterministic() {
-        let env = serde_json::json!({"x": 42});
+        let env = ubl_json_view::from_json(&serde_json::json!({"x": 42})).unwrap();
         let mut inputs = serde_json::Map::new();
         inputs.insert("val".into(), Value::String("x".into()));
 
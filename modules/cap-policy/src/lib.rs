@@ -3,6 +3,7 @@
 
 use anyhow::Context;
 use modules_core::{CapInput, CapOutput, Capability, Verdict};
+use nrf1::query::Query;
 use serde::Deserialize;
 use serde_json::Value;
 
@@ -28,48 +29,51 @@ struct Config {
 pub struct PolicyModule;
 
 impl PolicyModule {
-    fn get<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
-        let mut cur = root;
-        for seg in path.split('.') {
-            match cur {
-                Value::Object(m) => {
-                    cur = m.get(seg)?;
-                }
-                Value::Array(a) => {
-                    cur = a.get(seg.parse::<usize>().ok()?)?;
-                }
-                _ => return None,
-            }
-        }
-        Some(cur)
+    /// Every value matched by `path` (a query: `a.b`, `/a/b`, `$.items[*].qty`).
+    /// Bad paths match nothing; `validate_config` rejects them up front.
+    fn select<'a>(root: &'a nrf1::Value, path: &str) -> Vec<&'a nrf1::Value> {
+        Query::parse(path).map(|q| q.select(root)).unwrap_or_default()
     }
 
-    fn as_i64(v: &Value) -> Option<i64> {
+    /// A rule holds on a path when it matches at least once and every match passes.
+    fn all_match(root: &nrf1::Value, path: &str, pred: impl Fn(&nrf1::Value) -> bool) -> bool {
+        let found = Self::select(root, path);
+        !found.is_empty() && found.into_iter().all(pred)
+    }
+
+    fn as_i64(v: &nrf1::Value) -> Option<i64> {
         match v {
-            Value::Number(n) => n.as_i64(),
-            Value::String(s) => s.parse::<i64>().ok(),
+            nrf1::Value::Int(n) => Some(*n),
+            nrf1::Value::String(s) => s.parse::<i64>().ok(),
             _ => None,
         }
     }
 
-    fn rule_ok(j: &Value, r: &Rule) -> bool {
+    fn rule_ok(env: &nrf1::Value, r: &Rule) -> bool {
         match r {
             Rule::Exist { paths } => paths
                 .iter()
-                .all(|p| matches!(Self::get(j, p), Some(v) if !v.is_null())),
-            Rule::Threshold { path, min } => Self::get(j, path)
-                .and_then(Self::as_i64)
-                .map(|v| v >= *min)
-                .unwrap_or(false),
-            Rule::ThresholdRange { path, min, max } => Self::get(j, path)
-                .and_then(Self::as_i64)
-                .map(|v| v >= *min && v <= *max)
-                .unwrap_or(false),
-            Rule::Allowlist { path, values } => match Self::get(j, path) {
-                Some(v) => values.iter().any(|x| x == v),
-                None => false,
-            },
-            Rule::Not { rule } => !Self::rule_ok(j, rule),
+                .all(|p| Self::all_match(env, p, |v| *v != nrf1::Value::Null)),
+            Rule::Threshold { path, min } => {
+                Self::all_match(env, path, |v| Self::as_i64(v).is_some_and(|n| n >= *min))
+            }
+            Rule::ThresholdRange { path, min, max } => Self::all_match(env, path, |v| {
+                Self::as_i64(v).is_some_and(|n| n >= *min && n <= *max)
+            }),
+            Rule::Allowlist { path, values } => {
+                Self::all_match(env, path, |v| values.contains(&ubl_json_view::to_json(v)))
+            }
+            Rule::Not { rule } => !Self::rule_ok(env, rule),
+        }
+    }
+
+    fn rule_paths(r: &Rule) -> Vec<&str> {
+        match r {
+            Rule::Exist { paths } => paths.iter().map(String::as_str).collect(),
+            Rule::Threshold { path, .. }
+            | Rule::ThresholdRange { path, .. }
+            | Rule::Allowlist { path, .. } => vec![path.as_str()],
+            Rule::Not { rule } => Self::rule_paths(rule),
         }
     }
 }
@@ -83,13 +87,15 @@ impl Capability for PolicyModule {
     }
 
     fn validate_config(&self, config: &Value) -> anyhow::Result<()> {
-        let _cfg: Config =
+        let cfg: Config =
             serde_json::from_value(config.clone()).context("invalid cap-policy config")?;
+        for p in cfg.rules.iter().flat_map(Self::rule_paths) {
+            Query::parse(p).with_context(|| format!("invalid path '{p}'"))?;
+        }
         Ok(())
     }
 
     fn execute(&self, input: CapInput) -> anyhow::Result<CapOutput> {
-        let j = ubl_json_view::to_json(&input.env);
         let cfg: Config = serde_json::from_value(input.config.clone())?;
 
         let fail_verdict = match cfg.decision_on_fail.as_deref() {
@@ -99,7 +105,7 @@ impl Capability for PolicyModule {
 
         let mut failed = 0usize;
         for r in &cfg.rules {
-            if !Self::rule_ok(&j, r) {
+            if !Self::rule_ok(&input.env, r) {
                 failed += 1;
             }
         }
@@ -117,3 +123,33 @@ impl Capability for PolicyModule {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(j: Value) -> Rule {
+        serde_json::from_value(j).unwrap()
+    }
+
+    #[test]
+    fn rules_apply_to_every_match() {
+        let env = ubl_json_view::from_json(&serde_json::json!({
+            "items": [{"qty": 2, "sku": "A"}, {"qty": 5, "sku": "B"}],
+            "tier": "gold"
+        }))
+        .unwrap();
+        let ok = |j| PolicyModule::rule_ok(&env, &rule(j));
+        assert!(ok(serde_json::json!({"kind": "THRESHOLD", "path": "$.items[*].qty", "min": 2})));
+        assert!(!ok(serde_json::json!({"kind": "THRESHOLD", "path": "$.items[*].qty", "min": 3})));
+        assert!(ok(serde_json::json!({"kind": "EXIST", "paths": ["/items/1/sku", "tier"]})));
+        assert!(!ok(serde_json::json!({"kind": "EXIST", "paths": ["$.items[?(@.qty > 9)]"]})));
+        assert!(ok(serde_json::json!({"kind": "ALLOWLIST", "path": "tier", "values": ["gold"]})));
+    }
+
+    #[test]
+    fn validate_rejects_bad_paths() {
+        let cfg = serde_json::json!({"rules": [{"kind": "EXIST", "paths": ["$.items["]}]});
+        assert!(PolicyModule.validate_config(&cfg).is_err());
+    }
+}
@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
-use rust_decimal::Decimal;
+use nrf1::NrfDecimal;
 use indexmap::IndexMap;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct PriceReq {
     pub sku: String,
-    pub qty: Option<Decimal>,
+    pub qty: Option<NrfDecimal>,
     /// Region code: BR, BR-SP, US-CA, etc.
     pub region: Option<String>,
     /// For rule matching
@@ -22,14 +22,14 @@ pub struct PriceReq {
 #[derive(Debug, Clone, Serialize)]
 pub struct PriceResp {
     pub sku: String,
-    pub unit_list: Decimal,
-    pub unit_net: Decimal,
-    pub unit_tax: Decimal,
-    pub unit_total: Decimal,
-    pub qty: Decimal,
-    pub total_net: Decimal,
-    pub total_tax: Decimal,
-    pub total: Decimal,
+    pub unit_list: NrfDecimal,
+    pub unit_net: NrfDecimal,
+    pub unit_tax: NrfDecimal,
+    pub unit_total: NrfDecimal,
+    pub qty: NrfDecimal,
+    pub total_net: NrfDecimal,
+    pub total_tax: NrfDecimal,
+    pub total: NrfDecimal,
     pub steps: Vec<Step>,
 }
 
@@ -37,8 +37,8 @@ pub struct PriceResp {
 pub struct Step {
     pub kind: String,
     pub name: String,
-    pub before: Decimal,
-    pub after: Decimal,
+    pub before: NrfDecimal,
+    pub after: NrfDecimal,
     pub meta: IndexMap<String, String>,
 }
 
@@ -50,5 +50,5 @@ pub struct ScenarioReq {
 #[derive(Debug, Clone, Serialize)]
 pub struct ScenarioResp {
     pub items: Vec<PriceResp>,
-    pub grand_total: Decimal,
+    pub grand_total: NrfDecimal,
 }
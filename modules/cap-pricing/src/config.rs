@@ -1,11 +1,11 @@
 use serde::{Deserialize, Serialize};
-use rust_decimal::Decimal;
+use nrf1::{NrfDecimal, RoundingMode};
 use indexmap::IndexMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricingConfig {
     /// Base prices by SKU (list price)
-    pub list: IndexMap<String, Decimal>,
+    pub list: IndexMap<String, NrfDecimal>,
 
     /// Rules: ordered, first match applies (multiple if stackable=true)
     #[serde(default)]
@@ -29,7 +29,7 @@ pub struct Rule {
     pub matcher: String,
     /// Action: "discount_pct" | "discount_abs" | "surcharge_pct" | "surcharge_abs"
     pub action: String,
-    pub value: Decimal,
+    pub value: NrfDecimal,
     /// Stack with subsequent matching rules? (default: false)
     #[serde(default)]
     pub stackable: bool,
@@ -39,10 +39,10 @@ pub struct Rule {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TaxConfig {
-    pub default_pct: Option<Decimal>,
+    pub default_pct: Option<NrfDecimal>,
     /// Overrides by region code (e.g. "BR-SP": 0.19)
     #[serde(default)]
-    pub by_region: IndexMap<String, Decimal>,
+    pub by_region: IndexMap<String, NrfDecimal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,12 +50,12 @@ pub struct Rounding {
     /// Decimal places (typically 2)
     pub scale: u32,
     /// "bankers" | "half_up"
-    pub mode: String,
+    pub mode: RoundingMode,
 }
 
 fn default_round() -> Rounding {
     Rounding {
         scale: 2,
-        mode: "half_up".into(),
+        mode: RoundingMode::HalfUp,
     }
 }
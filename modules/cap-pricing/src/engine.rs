@@ -2,15 +2,14 @@ use crate::api::{PriceReq, PriceResp, Step};
 use crate::config::{PricingConfig, Rounding};
 use anyhow::{anyhow, Result};
 use indexmap::IndexMap;
-use rust_decimal::Decimal;
+use nrf1::NrfDecimal;
 
 pub fn price_one(cfg: &PricingConfig, req: &PriceReq) -> Result<PriceResp> {
-    let qty = req.qty.unwrap_or(Decimal::ONE);
-    let list = cfg
+    let qty = req.qty.unwrap_or(NrfDecimal::ONE);
+    let list = *cfg
         .list
         .get(&req.sku)
-        .ok_or_else(|| anyhow!("unknown sku: {}", req.sku))?
-        .clone();
+        .ok_or_else(|| anyhow!("unknown sku: {}", req.sku))?;
 
     let mut steps = vec![];
     let mut unit = list;
@@ -26,10 +25,10 @@ pub fn price_one(cfg: &PricingConfig, req: &PriceReq) -> Result<PriceResp> {
         }
         let before = unit;
         unit = match r.action.as_str() {
-            "discount_pct" => unit * (Decimal::ONE - r.value / dec(100)),
-            "discount_abs" => unit - r.value,
-            "surcharge_pct" => unit * (Decimal::ONE + r.value / dec(100)),
-            "surcharge_abs" => unit + r.value,
+            "discount_pct" => mul(unit, sub(NrfDecimal::ONE, pct(r.value)?)?)?,
+            "discount_abs" => sub(unit, r.value)?,
+            "surcharge_pct" => mul(unit, add(NrfDecimal::ONE, pct(r.value)?)?)?,
+            "surcharge_abs" => add(unit, r.value)?,
             _ => before,
         };
         let mut meta = IndexMap::new();
@@ -47,8 +46,8 @@ pub fn price_one(cfg: &PricingConfig, req: &PriceReq) -> Result<PriceResp> {
 
     // Tax
     let pct = tax_pct(cfg, req.region.as_deref());
-    let unit_tax = round_by(unit_net * pct, &cfg.rounding);
-    let unit_total = round_by(unit_net + unit_tax, &cfg.rounding);
+    let unit_tax = round_by(mul(unit_net, pct)?, &cfg.rounding);
+    let unit_total = round_by(add(unit_net, unit_tax)?, &cfg.rounding);
 
     {
         let mut m = IndexMap::new();
@@ -57,9 +56,9 @@ pub fn price_one(cfg: &PricingConfig, req: &PriceReq) -> Result<PriceResp> {
     }
 
     // Totals
-    let total_net = round_by(unit_net * qty, &cfg.rounding);
-    let total_tax = round_by(unit_tax * qty, &cfg.rounding);
-    let total = round_by(unit_total * qty, &cfg.rounding);
+    let total_net = round_by(mul(unit_net, qty)?, &cfg.rounding);
+    let total_tax = round_by(mul(unit_tax, qty)?, &cfg.rounding);
+    let total = round_by(mul(unit_total, qty)?, &cfg.rounding);
 
     Ok(PriceResp {
         sku: req.sku.clone(),
@@ -114,8 +113,8 @@ fn target_value<'a>(target: &str, req: &'a PriceReq) -> Option<&'a str> {
 fn step(
     kind: &str,
     name: &str,
-    before: Decimal,
-    after: Decimal,
+    before: NrfDecimal,
+    after: NrfDecimal,
     meta: IndexMap<String, String>,
 ) -> Step {
     Step {
@@ -127,7 +126,7 @@ fn step(
     }
 }
 
-fn tax_pct(cfg: &PricingConfig, region: Option<&str>) -> Decimal {
+fn tax_pct(cfg: &PricingConfig, region: Option<&str>) -> NrfDecimal {
     if let Some(r) = region {
         if let Some(p) = cfg.tax.by_region.get(r) {
             return *p;
@@ -139,20 +138,37 @@ fn tax_pct(cfg: &PricingConfig, region: Option<&str>) -> Decimal {
             }
         }
     }
-    cfg.tax.default_pct.unwrap_or(dec(0))
+    cfg.tax.default_pct.unwrap_or(NrfDecimal::ZERO)
 }
 
-fn round_by(v: Decimal, r: &Rounding) -> Decimal {
-    use rust_decimal::RoundingStrategy;
-    let mode = match r.mode.as_str() {
-        "bankers" => RoundingStrategy::MidpointNearestEven,
-        _ => RoundingStrategy::MidpointAwayFromZero,
-    };
-    v.round_dp_with_strategy(r.scale, mode)
+fn round_by(v: NrfDecimal, r: &Rounding) -> NrfDecimal {
+    v.round(r.scale, r.mode)
+}
+
+/// `v` percent as a fraction; exact (two more fractional digits).
+fn pct(v: NrfDecimal) -> Result<NrfDecimal> {
+    let scale = v.scale().checked_add(2).ok_or_else(|| overflow(v))?;
+    Ok(NrfDecimal::new(v.mantissa(), scale))
+}
+
+// Quantities and rule values come from the request and config, so the
+// arithmetic is checked: overflow is an error, never a panic.
+
+/// Checked sum, also used for scenario and quote totals.
+pub fn add(a: NrfDecimal, b: NrfDecimal) -> Result<NrfDecimal> {
+    a.checked_add(&b).ok_or_else(|| overflow(a))
 }
 
-fn dec(x: i64) -> Decimal {
-    Decimal::from(x)
+fn sub(a: NrfDecimal, b: NrfDecimal) -> Result<NrfDecimal> {
+    a.checked_sub(&b).ok_or_else(|| overflow(a))
+}
+
+fn mul(a: NrfDecimal, b: NrfDecimal) -> Result<NrfDecimal> {
+    a.checked_mul(&b).ok_or_else(|| overflow(a))
+}
+
+fn overflow(v: NrfDecimal) -> anyhow::Error {
+    anyhow!("amount out of range near {v}")
 }
 
 // ---------------------------------------------------------------------------
@@ -203,7 +219,7 @@ tax:
         let cfg = test_config();
         let req = PriceReq {
             sku: "PLAN-BASIC".into(),
-            qty: Some(Decimal::ONE),
+            qty: Some(NrfDecimal::ONE),
             region: Some("US".into()),
             category: None,
             customer_tier: None,
@@ -211,9 +227,9 @@ tax:
             explain: false,
         };
         let r = price_one(&cfg, &req).unwrap();
-        assert_eq!(r.unit_net, Decimal::from_str("19.90").unwrap());
-        assert_eq!(r.unit_tax, Decimal::ZERO);
-        assert_eq!(r.total, Decimal::from_str("19.90").unwrap());
+        assert_eq!(r.unit_net, NrfDecimal::from_str("19.90").unwrap());
+        assert_eq!(r.unit_tax, NrfDecimal::ZERO);
+        assert_eq!(r.total, NrfDecimal::from_str("19.90").unwrap());
     }
 
     #[test]
@@ -221,7 +237,7 @@ tax:
         let cfg = test_config();
         let req = PriceReq {
             sku: "PLAN-PRO".into(),
-            qty: Some(Decimal::ONE),
+            qty: Some(NrfDecimal::ONE),
             region: Some("BR".into()),
             category: None,
             customer_tier: None,
@@ -230,9 +246,32 @@ tax:
         };
         let r = price_one(&cfg, &req).unwrap();
         // 49 - 20% = 39.20; tax 12% of 39.20 = 4.704 → 4.70; total = 43.90
-        assert_eq!(r.unit_net, Decimal::from_str("39.20").unwrap());
-        assert_eq!(r.unit_tax, Decimal::from_str("4.70").unwrap());
-        assert_eq!(r.unit_total, Decimal::from_str("43.90").unwrap());
+        assert_eq!(r.unit_net, NrfDecimal::from_str("39.20").unwrap());
+        assert_eq!(r.unit_tax, NrfDecimal::from_str("4.70").unwrap());
+        assert_eq!(r.unit_total, NrfDecimal::from_str("43.90").unwrap());
+    }
+
+    #[test]
+    fn declared_profile_canonicalises_amounts() {
+        use modules_core::Capability;
+        let cfg = test_config();
+        let req = PriceReq {
+            sku: "PLAN-BASIC".into(),
+            qty: Some(NrfDecimal::ONE),
+            region: Some("US".into()),
+            category: None,
+            customer_tier: None,
+            coupons: None,
+            explain: false,
+        };
+        let r = price_one(&cfg, &req).unwrap();
+        let env = serde_json::json!({ "pricing": serde_json::to_value(&r).unwrap() });
+        let env = ubl_json_view::from_json(&env).unwrap();
+        let profile = crate::PricingModule.rho_profile().unwrap();
+        let out = nrf1::rho::normalize_with_profile(&env, profile).unwrap();
+        let out = ubl_json_view::to_json(&out);
+        assert_eq!(out["pricing"]["total"], "19.9");
+        assert_eq!(out["pricing"]["unit_tax"], "0");
     }
 
     #[test]
@@ -250,13 +289,50 @@ tax:
         assert!(price_one(&cfg, &req).is_err());
     }
 
+    #[test]
+    fn huge_qty_is_an_error_not_a_panic() {
+        let cfg = test_config();
+        let req = PriceReq {
+            sku: "PLAN-PRO".into(),
+            qty: Some(NrfDecimal::new(i128::MAX / 10, 0)),
+            region: None,
+            category: None,
+            customer_tier: None,
+            coupons: None,
+            explain: false,
+        };
+        let err = price_one(&cfg, &req).unwrap_err();
+        assert!(err.to_string().starts_with("amount out of range"));
+    }
+
+    #[test]
+    fn scenario_total_overflow_is_an_error() {
+        let cfg = test_config();
+        // Each line fits; their sum does not.
+        let item = PriceReq {
+            sku: "PLAN-PRO".into(),
+            qty: Some(NrfDecimal::new(i128::MAX / 490 * 6, 0)),
+            region: None,
+            category: None,
+            customer_tier: None,
+            coupons: None,
+            explain: false,
+        };
+        price_one(&cfg, &item).unwrap();
+        let req = crate::api::ScenarioReq {
+            items: vec![item.clone(), item],
+        };
+        let err = crate::price_scenario_with(&cfg, &req).unwrap_err();
+        assert!(err.to_string().starts_with("amount out of range"));
+    }
+
     #[test]
     fn region_fallback() {
         let cfg = test_config();
         // BR-RJ not in by_region, falls back to BR (0.12)
         let req = PriceReq {
             sku: "PLAN-BASIC".into(),
-            qty: Some(Decimal::ONE),
+            qty: Some(NrfDecimal::ONE),
             region: Some("BR-RJ".into()),
             category: None,
             customer_tier: None,
@@ -265,6 +341,6 @@ tax:
         };
         let r = price_one(&cfg, &req).unwrap();
         // 19.90 * 0.12 = 2.388 → 2.39
-        assert_eq!(r.unit_tax, Decimal::from_str("2.39").unwrap());
+        assert_eq!(r.unit_tax, NrfDecimal::from_str("2.39").unwrap());
     }
 }
@@ -10,7 +10,9 @@ pub mod engine;
 
 use anyhow::{Context, Result};
 use modules_core::{CapInput, CapOutput, Capability};
+use nrf1::rho::{Profile, Rule};
 use serde_json::Value as JsonValue;
+use std::sync::LazyLock;
 
 pub use config::PricingConfig;
 
@@ -21,6 +23,27 @@ pub use config::PricingConfig;
 #[derive(Default)]
 pub struct PricingModule;
 
+/// Every amount written under `pricing` is a canonical decimal string.
+static PRICING_PROFILE: LazyLock<Profile> = LazyLock::new(|| {
+    let mut rules: Vec<(&str, Rule)> = [
+        "$.pricing.unit_list",
+        "$.pricing.unit_net",
+        "$.pricing.unit_tax",
+        "$.pricing.unit_total",
+        "$.pricing.qty",
+        "$.pricing.total_net",
+        "$.pricing.total_tax",
+        "$.pricing.total",
+        "$.pricing.steps[*].before",
+        "$.pricing.steps[*].after",
+    ]
+    .into_iter()
+    .map(|p| (p, Rule::Decimal))
+    .collect();
+    rules.push(("$.pricing.sku", Rule::AsciiId));
+    Profile::new(&rules).expect("cap-pricing ρ profile")
+});
+
 impl Capability for PricingModule {
     fn kind(&self) -> &'static str { "cap-pricing" }
     fn api_version(&self) -> &'static str { "1.0" }
@@ -60,11 +83,15 @@ impl Capability for PricingModule {
             effects: vec![],
             metrics: vec![
                 ("pricing.unit_total_cents".into(),
-                 (result.unit_total * rust_decimal::Decimal::from(100))
+                 (result.unit_total * nrf1::NrfDecimal::from(100))
                      .to_string().parse::<i64>().unwrap_or(0)),
             ],
         })
     }
+
+    fn rho_profile(&self) -> Option<&Profile> {
+        Some(&PRICING_PROFILE)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -82,10 +109,10 @@ pub fn price_one_with(cfg: &PricingConfig, req: &api::PriceReq) -> Result<api::P
 /// Price a scenario (multiple items) with an explicit config. Pure.
 pub fn price_scenario_with(cfg: &PricingConfig, req: &api::ScenarioReq) -> Result<api::ScenarioResp> {
     let mut items = vec![];
-    let mut grand = rust_decimal::Decimal::ZERO;
+    let mut grand = nrf1::NrfDecimal::ZERO;
     for it in &req.items {
         let r = engine::price_one(cfg, it)?;
-        grand += r.total;
+        grand = engine::add(grand, r.total)?;
         items.push(r);
     }
     Ok(api::ScenarioResp {
@@ -9,7 +9,6 @@ use anyhow::Context;
 use modules_core::{Artifact, CapInput, CapOutput, Capability, Cid, Effect};
 use serde::Deserialize;
 use serde_json::Value;
-use std::collections::BTreeMap;
 
 #[derive(Debug, Deserialize)]
 struct Relay {
@@ -23,6 +22,7 @@ struct Config {
     #[serde(default)]
     relay: Vec<Relay>,
     #[serde(default = "default_clock_skew")]
+    #[allow(dead_code)]
     clock_skew_sec: i64,
 }
 
@@ -43,17 +43,18 @@ impl TransportModule {
         node: &str,
         ts: i64,
     ) -> Vec<u8> {
-        let mut m = BTreeMap::new();
-        m.insert("domain".into(), nrf1::Value::String("ubl-receipt/1.0".into()));
-        m.insert("kind".into(), nrf1::Value::String(kind.into()));
-        m.insert("node".into(), nrf1::Value::String(node.into()));
-        m.insert("of".into(), nrf1::Value::Bytes(of.to_vec()));
-        m.insert("prev".into(), nrf1::Value::Bytes(prev.to_vec()));
-        m.insert("ts".into(), nrf1::Value::Int(ts));
-        nrf1::encode(&nrf1::Value::Map(m))
+        nrf1::encode(&nrf1::nrf!({
+            "domain": "ubl-receipt/1.0",
+            "kind": kind,
+            "node": node,
+            "of": bytes(of.to_vec()),
+            "prev": bytes(prev.to_vec()),
+            "ts": ts,
+        }))
     }
 
     /// Check expiration: ts_nanos + skew >= now.
+    #[allow(dead_code)]
     fn check_exp(exp_nanos: Option<i64>, now_nanos: i64, skew_sec: i64) -> anyhow::Result<()> {
         if let Some(exp) = exp_nanos {
             let deadline = exp + (skew_sec * 1_000_000_000);
@@ -168,7 +169,7 @@ mod tests {
 
     fn make_input(config: Value) -> CapInput {
         CapInput {
-            env: nrf1::Value::Map(BTreeMap::new()),
+            env: nrf1::nrf!({}),
             config,
             assets: Box::new(NullResolver),
             prev_receipts: vec![],
@@ -54,8 +54,8 @@ def read_varint32(buf: bytes, i: int) -> Tuple[int, int]:
         b = buf[i]; i += 1
         payload = b & 0x7F
         # non-minimal rules
-        if count == 0 and b == 0x80: raise NonMinimalVarint()
         if count > 0 and b == 0x00: raise NonMinimalVarint()
+        if count == 4 and b > 0x0F: raise NonMinimalVarint()
         res |= (payload << shift)
         shift += 7
         if (b & 0x80) == 0:
@@ -88,6 +88,8 @@ pub fn build_router(state: Arc<state::AppState>) -> Router {
         .route("/api/v0/whoami", get(whoami))
         .nest("/v1", routes::receipts::router())
         .nest("/v1", routes::ghosts::router())
+        .nest("/v1", routes::schemas::router())
+        .nest("/v1", routes::capsules::router())
         .with_state(state);
 
     // When compiled with --features modules, mount permit + pipeline routes
@@ -101,7 +103,7 @@ pub fn build_router(state: Arc<state::AppState>) -> Router {
             routes::modules::init_modules_state(&state_dir);
         tracing::info!(state_dir = %state_dir, "modules layer enabled");
         base.merge(routes::modules::modules_router(modules_state, permit_state))
-            .merge(routes::cap_http::cap_http_router())
+            .merge(routes::cap_http::cap_http_router(&state_dir))
     };
 
     let cors = CorsLayer::new()
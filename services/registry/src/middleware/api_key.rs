@@ -55,7 +55,7 @@ impl ApiKeyStore {
     }
 
     pub fn validate(&self, product: &str, key: &str) -> bool {
-        self.keys.get(product).map_or(false, |k| k == key)
+        self.keys.get(product).is_some_and(|k| k == key)
     }
 }
 
@@ -21,7 +21,6 @@ use axum::{
 use modules_core::{AssetResolver, Asset, CapInput, Capability, Cid, ExecutionMeta};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::BTreeMap;
 
 // ---------------------------------------------------------------------------
 // Shared helpers
@@ -36,30 +35,6 @@ fn now_nanos() -> i64 {
     i64::try_from(nanos).unwrap_or(i64::MAX)
 }
 
-fn json_to_nrf(j: &Value) -> anyhow::Result<nrf1::Value> {
-    use nrf1::Value as V;
-    Ok(match j {
-        Value::Null => V::Null,
-        Value::Bool(b) => V::Bool(*b),
-        Value::Number(n) => {
-            if let Some(i) = n.as_i64() {
-                V::Int(i)
-            } else {
-                anyhow::bail!("numbers must fit i64 (no floats)");
-            }
-        }
-        Value::String(s) => V::String(s.clone()),
-        Value::Array(a) => V::Array(a.iter().map(json_to_nrf).collect::<anyhow::Result<Vec<_>>>()?),
-        Value::Object(o) => {
-            let mut m = BTreeMap::new();
-            for (k, v) in o {
-                m.insert(k.clone(), json_to_nrf(v)?);
-            }
-            V::Map(m)
-        }
-    })
-}
-
 fn make_meta(tenant: Option<&str>) -> ExecutionMeta {
     ExecutionMeta {
         run_id: uuid::Uuid::new_v4().to_string(),
@@ -80,12 +55,27 @@ impl AssetResolver for NullResolver {
     }
 }
 
-fn cap_input(env_json: &Value, config: Value, tenant: Option<&str>) -> Result<CapInput, StatusCode> {
-    let env = json_to_nrf(env_json).map_err(|_| StatusCode::BAD_REQUEST)?;
+/// `{STATE_DIR}/assets`, set once by `cap_http_router` (salts, packs, …).
+static ASSETS: std::sync::OnceLock<module_runner::assets::DirResolver> =
+    std::sync::OnceLock::new();
+
+fn assets() -> Box<dyn AssetResolver> {
+    match ASSETS.get() {
+        Some(dir) => dir.box_clone(),
+        None => Box::new(NullResolver),
+    }
+}
+
+fn cap_input(
+    env_json: &Value,
+    config: Value,
+    tenant: Option<&str>,
+) -> Result<CapInput, ubl_error::UblError> {
+    let env = super::modules::json_to_nrf(env_json)?;
     Ok(CapInput {
         env,
         config,
-        assets: Box::new(NullResolver),
+        assets: assets(),
         prev_receipts: vec![],
         meta: make_meta(tenant),
     })
@@ -160,7 +150,7 @@ async fn intake_handler(Json(req): Json<IntakeReq>) -> impl IntoResponse {
 
     let input = match cap_input(&req.env, config, req.tenant.as_deref()) {
         Ok(i) => i,
-        Err(s) => return (s, Json(json!({"error": "invalid env"}))).into_response(),
+        Err(ue) => return (StatusCode::BAD_REQUEST, Json(ue.to_json())).into_response(),
     };
 
     let module = cap_intake::IntakeModule;
@@ -208,7 +198,7 @@ async fn permit_eval_handler(Json(req): Json<PermitEvalReq>) -> impl IntoRespons
 
     let input = match cap_input(&json!({}), config, req.tenant.as_deref()) {
         Ok(i) => i,
-        Err(s) => return (s, Json(json!({"error": "invalid input"}))).into_response(),
+        Err(ue) => return (StatusCode::BAD_REQUEST, Json(ue.to_json())).into_response(),
     };
 
     let module = cap_permit::PermitModule;
@@ -237,7 +227,7 @@ struct PolicyApplyReq {
 async fn policy_apply_handler(Json(req): Json<PolicyApplyReq>) -> impl IntoResponse {
     let input = match cap_input(&req.env, req.config, req.tenant.as_deref()) {
         Ok(i) => i,
-        Err(s) => return (s, Json(json!({"error": "invalid env"}))).into_response(),
+        Err(ue) => return (StatusCode::BAD_REQUEST, Json(ue.to_json())).into_response(),
     };
 
     let module = cap_policy::PolicyModule;
@@ -266,7 +256,7 @@ struct EnrichApplyReq {
 async fn enrich_apply_handler(Json(req): Json<EnrichApplyReq>) -> impl IntoResponse {
     let input = match cap_input(&req.env, req.config, req.tenant.as_deref()) {
         Ok(i) => i,
-        Err(s) => return (s, Json(json!({"error": "invalid env"}))).into_response(),
+        Err(ue) => return (StatusCode::BAD_REQUEST, Json(ue.to_json())).into_response(),
     };
 
     let module = cap_enrich::EnrichModule;
@@ -295,7 +285,7 @@ struct TransportDeriveReq {
 async fn transport_derive_handler(Json(req): Json<TransportDeriveReq>) -> impl IntoResponse {
     let input = match cap_input(&req.env, req.config, req.tenant.as_deref()) {
         Ok(i) => i,
-        Err(s) => return (s, Json(json!({"error": "invalid env"}))).into_response(),
+        Err(ue) => return (StatusCode::BAD_REQUEST, Json(ue.to_json())).into_response(),
     };
 
     let module = cap_transport::TransportModule;
@@ -347,7 +337,7 @@ async fn llm_complete_handler(Json(req): Json<LlmCompleteReq>) -> impl IntoRespo
     // Capability mode: delegate to cap-llm module.
     let input = match cap_input(&req.env, req.config.clone(), req.tenant.as_deref()) {
         Ok(i) => i,
-        Err(s) => return (s, Json(json!({"error": "invalid env"}))).into_response(),
+        Err(ue) => return (StatusCode::BAD_REQUEST, Json(ue.to_json())).into_response(),
     };
 
     let module = cap_llm::LlmModule;
@@ -687,7 +677,10 @@ async fn invoice_reject_handler(Json(req): Json<InvoiceActionReq>) -> impl IntoR
 // Router
 // ---------------------------------------------------------------------------
 
-pub fn cap_http_router() -> Router {
+pub fn cap_http_router(state_dir: &str) -> Router {
+    let _ = ASSETS.set(module_runner::assets::DirResolver::new(format!(
+        "{state_dir}/assets"
+    )));
     // Load pricing config if PRICING_PATH is set
     if let Ok(p) = std::env::var("PRICING_PATH") {
         if let Err(e) = cap_pricing::load_pricing_from(&p) {
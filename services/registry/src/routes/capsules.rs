@@ -0,0 +1,87 @@
+use axum::{http::StatusCode, routing::post, Json, Router};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use ubl_capsule::resolver::{DidKey, FirstMatch, KeyResolver, Keyring};
+
+use crate::state::AppState;
+
+// ---------------------------------------------------------------------------
+// Capsule routes — offline checks over a posted capsule (BASE terrain)
+//
+// POST /v1/capsules/receipts/verify  body = {capsule, keys?, did_docs?};
+//                                    answers with a status for every hop,
+//                                    the longest valid prefix and any forks
+//
+// did:key nodes resolve on their own; other nodes need `keys`
+// ({"kid": "hex_pubkey"}) or a DID document in `did_docs`.
+// ---------------------------------------------------------------------------
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+#[derive(Deserialize)]
+pub struct VerifyChainReq {
+    pub capsule: serde_json::Value,
+    #[serde(default)]
+    pub keys: HashMap<String, String>,
+    #[serde(default)]
+    pub did_docs: Vec<String>,
+}
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new().route("/capsules/receipts/verify", post(verify_receipts))
+}
+
+fn bad_request(code: &str, message: String, hint: &str) -> ApiError {
+    let ue = ubl_error::UblError::new(code, message, hint, 400);
+    (StatusCode::BAD_REQUEST, Json(ue.to_json()))
+}
+
+fn resolver(req: &VerifyChainReq) -> Result<FirstMatch, ApiError> {
+    let mut keys = HashMap::new();
+    for (kid, hex_pk) in &req.keys {
+        let pk = hex::decode(hex_pk)
+            .ok()
+            .and_then(|b| <[u8; 32]>::try_from(b).ok())
+            .and_then(|b| ed25519_dalek::VerifyingKey::from_bytes(&b).ok())
+            .ok_or_else(|| {
+                bad_request(
+                    "Err.Key.Malformed",
+                    format!("key for {kid} is not a 32-byte hex Ed25519 public key"),
+                    "Send keys as {\"kid\": \"<64 hex chars>\"}",
+                )
+            })?;
+        keys.insert(kid.clone(), pk);
+    }
+    let mut chain: Vec<Box<dyn KeyResolver>> = vec![Box::new(DidKey), Box::new(keys)];
+    for doc in &req.did_docs {
+        let ring = Keyring::from_did_document_json(doc).map_err(|e| {
+            bad_request(
+                "Err.Key.Malformed",
+                e.to_string(),
+                "did_docs entries must be DID documents with publicKeyMultibase keys",
+            )
+        })?;
+        chain.push(Box::new(ring));
+    }
+    Ok(FirstMatch(chain))
+}
+
+async fn verify_receipts(
+    Json(req): Json<VerifyChainReq>,
+) -> Result<Json<ubl_capsule::receipt::ChainReport>, ApiError> {
+    let capsule: ubl_capsule::Capsule =
+        serde_json::from_value(req.capsule.clone()).map_err(|e| {
+            bad_request(
+                "Err.Parse.InvalidCapsuleJSON",
+                e.to_string(),
+                "Send the capsule as produced by `ubl cap sign`",
+            )
+        })?;
+    let resolver = resolver(&req)?;
+    Ok(Json(ubl_capsule::receipt::verify_chain_report(
+        &capsule.id,
+        &capsule.receipts,
+        &resolver,
+    )))
+}
@@ -1,5 +1,7 @@
+pub mod capsules;
 pub mod ghosts;
 pub mod receipts;
+pub mod schemas;
 #[cfg(feature = "modules")]
 pub mod modules;
 #[cfg(feature = "modules")]
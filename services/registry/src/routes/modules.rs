@@ -153,13 +153,7 @@ async fn run_pipeline(
 
     let env = match json_to_nrf(&body.env) {
         Ok(v) => v,
-        Err(e) => {
-            let ue = ubl_error::UblError::bad_request(
-                format!("invalid env: {e}"),
-                "The 'env' field must be a JSON object with string/integer/boolean values. Floats are forbidden (NRF type system). Use integers instead.",
-            );
-            return (StatusCode::BAD_REQUEST, Json(ue.to_json())).into_response();
-        }
+        Err(ue) => return (StatusCode::BAD_REQUEST, Json(ue.to_json())).into_response(),
     };
 
     let caps = build_cap_registry();
@@ -169,7 +163,7 @@ async fn run_pipeline(
         .unwrap_or(serde_json::Value::Null);
 
     let tenant = &identity.tenant;
-    let assets = module_runner::assets::MemoryResolver::new();
+    let assets = module_runner::assets::DirResolver::new(format!("{}/assets", state.state_dir));
     let runner = module_runner::runner::Runner::new(
         &caps,
         Box::new(assets),
@@ -556,27 +550,48 @@ fn build_cap_registry() -> module_runner::cap_registry::CapRegistry {
     reg
 }
 
+/// JSON env → NRF. Errors name the offending field (`env.items[3].sku`).
+#[cfg(feature = "modules")]
+pub(crate) fn json_to_nrf(j: &serde_json::Value) -> Result<nrf1::Value, ubl_error::UblError> {
+    let mut path = String::from("env");
+    json_to_nrf_at(j, &mut path).map_err(|msg| {
+        ubl_error::UblError::bad_request(
+            format!("invalid env: {msg} at {path}"),
+            "The 'env' field must be a JSON object with string/integer/boolean values. Floats are forbidden (NRF type system). Use integers instead.",
+        )
+        .with_at(None, path.clone())
+    })
+}
+
+/// On error `path` is left pointing at the offending value.
 #[cfg(feature = "modules")]
-fn json_to_nrf(j: &serde_json::Value) -> anyhow::Result<nrf1::Value> {
+fn json_to_nrf_at(j: &serde_json::Value, path: &mut String) -> Result<nrf1::Value, &'static str> {
     use nrf1::Value as V;
     Ok(match j {
         serde_json::Value::Null => V::Null,
         serde_json::Value::Bool(b) => V::Bool(*b),
-        serde_json::Value::Number(n) => {
-            if let Some(i) = n.as_i64() {
-                V::Int(i)
-            } else {
-                anyhow::bail!("numbers must fit i64 (no floats)");
-            }
-        }
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => V::Int(i),
+            None => return Err("numbers must fit i64 (no floats)"),
+        },
         serde_json::Value::String(s) => V::String(s.clone()),
         serde_json::Value::Array(a) => {
-            V::Array(a.iter().map(json_to_nrf).collect::<anyhow::Result<Vec<_>>>()?)
+            let mut out = Vec::with_capacity(a.len());
+            for (i, it) in a.iter().enumerate() {
+                let len = path.len();
+                nrf_core::push_path_index(path, i);
+                out.push(json_to_nrf_at(it, path)?);
+                path.truncate(len);
+            }
+            V::Array(out)
         }
         serde_json::Value::Object(o) => {
             let mut m = std::collections::BTreeMap::new();
             for (k, v) in o {
-                m.insert(k.clone(), json_to_nrf(v)?);
+                let len = path.len();
+                nrf_core::push_path_key(path, k);
+                m.insert(k.clone(), json_to_nrf_at(v, path)?);
+                path.truncate(len);
             }
             V::Map(m)
         }
@@ -590,7 +605,7 @@ fn json_to_nrf(j: &serde_json::Value) -> anyhow::Result<nrf1::Value> {
 #[cfg(feature = "modules")]
 pub fn init_modules_state(state_dir: &str) -> (Arc<ModulesState>, Arc<PermitState>) {
     // Ensure state directories exist
-    for sub in &["idem", "permit-tickets", "llm-cache", "resume"] {
+    for sub in &["idem", "permit-tickets", "llm-cache", "resume", "assets"] {
         let _ = std::fs::create_dir_all(format!("{state_dir}/{sub}"));
     }
 
@@ -71,12 +71,12 @@ async fn create_receipt(
         }
     }
 
-    let err = ubl_error::UblError {
-        code: "Err.Receipt.NotImplemented".into(),
-        message: "Receipt pipeline not yet implemented".into(),
-        hint: "Use POST /api/v0/pipeline/run for module-based pipelines, or wait for receipt capability modules".into(),
-        status: 501,
-    };
+    let err = ubl_error::UblError::new(
+        "Err.Receipt.NotImplemented",
+        "Receipt pipeline not yet implemented",
+        "Use POST /api/v0/pipeline/run for module-based pipelines, or wait for receipt capability modules",
+        501,
+    );
     Err((
         axum::http::StatusCode::NOT_IMPLEMENTED,
         Json(err.to_json()),
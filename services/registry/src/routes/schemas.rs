@@ -0,0 +1,65 @@
+use axum::{
+    extract::Path,
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use crate::state::AppState;
+
+// ---------------------------------------------------------------------------
+// Schema routes — validate a JSON document as an NRF value (BASE terrain)
+//
+// GET  /v1/schemas                 names of the builtin schemas
+// POST /v1/schemas/:name/validate  body = the document; answers with every
+//                                  violation and its path, not just the first
+// ---------------------------------------------------------------------------
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+pub fn router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/schemas", get(list_schemas))
+        .route("/schemas/:name/validate", post(validate))
+}
+
+fn api_error(ue: ubl_error::UblError) -> ApiError {
+    let status = StatusCode::from_u16(ue.status).unwrap_or(StatusCode::BAD_REQUEST);
+    (status, Json(ue.to_json()))
+}
+
+/// Builtin schemas, compiled on first use. The set is fixed at build time.
+fn builtin(name: &str) -> Result<&'static nrf_schema::Schema, nrf_schema::SchemaError> {
+    type Compiled = HashMap<&'static str, Result<nrf_schema::Schema, nrf_schema::SchemaError>>;
+    static BUILTINS: OnceLock<Compiled> = OnceLock::new();
+    let compiled = BUILTINS.get_or_init(|| {
+        nrf_schema::builtin_names()
+            .map(|n| (n, nrf_schema::Schema::builtin(n)))
+            .collect()
+    });
+    match compiled.get(name) {
+        Some(schema) => schema.as_ref().map_err(Clone::clone),
+        None => Err(nrf_schema::SchemaError::UnknownBuiltin(name.to_string())),
+    }
+}
+
+async fn list_schemas() -> Json<serde_json::Value> {
+    let names: Vec<&str> = nrf_schema::builtin_names().collect();
+    Json(serde_json::json!({ "schemas": names }))
+}
+
+async fn validate(
+    Path(name): Path<String>,
+    Json(doc): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let schema = builtin(&name).map_err(|e| api_error(e.into()))?;
+    let value = ubl_json_view::from_json_at(&doc).map_err(|e| api_error(e.into()))?;
+    let violations = schema.validate(&value);
+    Ok(Json(serde_json::json!({
+        "ok": violations.is_empty(),
+        "schema": name,
+        "violations": violations,
+    })))
+}
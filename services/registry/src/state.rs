@@ -60,14 +60,23 @@ impl AppState {
             std::env::var("BINARY_SHA256").unwrap_or_else(|_| "dev-build-no-hash".into());
         let rt = runtime::SelfAttestation::new(&binary_sha256);
 
-        // Append-only audit ledger — NDJSON files per tenant/product
+        // Append-only audit ledger — NDJSON files (default) or NRF record
+        // logs (LEDGER_FORMAT=nrflog) per tenant/product
         let ledger_dir = std::env::var("LEDGER_DIR").unwrap_or_else(|_| {
             let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
             format!("{home}/.ai-nrf1/ledger")
         });
-        let ledger: Arc<dyn LedgerWriter> = {
-            tracing::info!(ledger_dir = %ledger_dir, "ledger: ndjson (append-only files)");
-            Arc::new(ubl_storage::ndjson::NdjsonLedger::new(&ledger_dir))
+        let ledger_format = std::env::var("LEDGER_FORMAT").unwrap_or_else(|_| "ndjson".into());
+        let ledger: Arc<dyn LedgerWriter> = match ledger_format.as_str() {
+            "ndjson" => {
+                tracing::info!(ledger_dir = %ledger_dir, "ledger: ndjson (append-only files)");
+                Arc::new(ubl_storage::ndjson::NdjsonLedger::new(&ledger_dir))
+            }
+            "nrflog" => {
+                tracing::info!(ledger_dir = %ledger_dir, "ledger: nrflog (canonical NRF records)");
+                Arc::new(ubl_storage::nrflog::NrfLogLedger::new(&ledger_dir))
+            }
+            other => anyhow::bail!("LEDGER_FORMAT must be ndjson or nrflog, got {other:?}"),
         };
 
         Ok(Arc::new(Self {
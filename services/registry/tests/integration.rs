@@ -115,10 +115,97 @@ async fn test_create_receipt_with_auth_returns_501() {
     let body: Value = resp.json().await.unwrap();
     assert_eq!(body["ok"], false);
     assert_eq!(body["error"]["code"], "Err.Receipt.NotImplemented");
-    assert!(body["error"]["hint"].as_str().unwrap().len() > 0);
+    assert!(!body["error"]["hint"].as_str().unwrap().is_empty());
     assert_eq!(body["error"]["status"], 501);
 }
 
+#[tokio::test]
+async fn test_schema_validate_reports_all_violations() {
+    let base = start_server().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/v1/schemas/ghost.v1/validate"))
+        .json(&json!({"v": "ghost.v1", "t": 1, "status": "lost", "nonce": 7}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["ok"], false);
+    let paths: Vec<&str> = body["violations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, vec!["", "nonce", "status"]);
+
+    // Unknown schema → canonical error shape
+    let resp = client
+        .post(format!("{base}/v1/schemas/nope.v1/validate"))
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let body: Value = resp.json().await.unwrap();
+    verify_error_shape(&body, 404);
+}
+
+#[tokio::test]
+async fn test_receipt_chain_report_lists_every_hop() {
+    use ubl_capsule::receipt::add_hop;
+    let base = start_server().await;
+    let client = reqwest::Client::new();
+
+    let relay = ed25519_dalek::SigningKey::generate(&mut rand::thread_rng());
+    let stranger = ed25519_dalek::SigningKey::generate(&mut rand::thread_rng());
+    let id = [0x42; 32];
+    let r0 = add_hop(id, [0u8; 32], "relay", "did:ubl:relay#k1", 1, &relay).unwrap();
+    let r1 = add_hop(id, r0.id, "exec", "did:ubl:relay#k1", 2, &stranger).unwrap();
+    let r2 = add_hop(id, r0.id, "deliver", "did:ubl:other#k1", 3, &relay).unwrap();
+    let capsule = json!({
+        "domain": "ubl-capsule/1.0",
+        "id": hex::encode(id),
+        "hdr": {"src": "did:ubl:a", "nonce": "aa".repeat(16), "ts": 1, "act": "TRANSACT"},
+        "env": {"body": {}},
+        "seal": {"kid": "did:ubl:a#k1", "sig": "00".repeat(64), "scope": "capsule"},
+        "receipts": [r0, r1, r2],
+    });
+    let keys = json!({"did:ubl:relay#k1": hex::encode(relay.verifying_key().as_bytes())});
+
+    let resp = client
+        .post(format!("{base}/v1/capsules/receipts/verify"))
+        .json(&json!({"capsule": capsule, "keys": keys}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["ok"], false);
+    assert_eq!(body["valid_prefix"], 1);
+    let statuses: Vec<&str> = body["hops"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, vec!["ok", "bad_signature", "fork"]);
+    assert_eq!(body["forks"][0]["hops"], json!([1, 2]));
+
+    // Malformed key → canonical error shape
+    let resp = client
+        .post(format!("{base}/v1/capsules/receipts/verify"))
+        .json(&json!({"capsule": capsule, "keys": {"did:ubl:relay#k1": "zz"}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = resp.json().await.unwrap();
+    verify_error_shape(&body, 400);
+}
+
 // ==========================================================================
 // Error shape tests — verify all error responses are structured JSON
 // ==========================================================================
@@ -54,6 +54,7 @@ fn make_receipt() -> receipt::Receipt {
         effects: None,
         body,
         body_cid,
+        body_root: None,
         inputs_cid: None,
         policy: None,
         reasoning_cid: None,
@@ -569,7 +570,7 @@ fn art4_1_receipt_sign_verify_roundtrip() {
     let (sk, vk) = keygen();
     let mut r = make_receipt();
     r.receipt_cid = r.compute_cid();
-    r.sign(&sk);
+    r.sign(&sk).unwrap();
     assert!(
         r.verify(&vk),
         "ARTICLE IV §4.1 VIOLATION: sign → verify roundtrip failed"
@@ -582,7 +583,7 @@ fn art4_1_receipt_wrong_key_rejected() {
     let (_sk2, wrong_vk) = keygen();
     let mut r = make_receipt();
     r.receipt_cid = r.compute_cid();
-    r.sign(&sk);
+    r.sign(&sk).unwrap();
     assert!(
         !r.verify(&wrong_vk),
         "ARTICLE IV §4.1 VIOLATION: signature verified with wrong key. \
@@ -595,7 +596,7 @@ fn art4_1_receipt_sig_tamper_detected() {
     let (sk, vk) = keygen();
     let mut r = make_receipt();
     r.receipt_cid = r.compute_cid();
-    r.sign(&sk);
+    r.sign(&sk).unwrap();
     // Tamper: flip a bit in the signature
     if let Some(ref mut sig) = r.sig {
         sig[0] ^= 0x01;
@@ -836,6 +837,7 @@ fn art4_4_capsule_ghost_requires_links_prev() {
             evidence: None,
             meta: None,
             links: None, // VIOLATION: GHOST requires links.prev
+            role: None,
         },
         seal: ubl_transport::Seal {
             alg: ubl_transport::SigAlg::Ed25519,
@@ -884,6 +886,7 @@ fn art4_4_capsule_allow_requires_evidence() {
             evidence: None, // VIOLATION: ALLOW requires evidence
             meta: None,
             links: None,
+            role: None,
         },
         seal: ubl_transport::Seal {
             alg: ubl_transport::SigAlg::Ed25519,
@@ -1049,7 +1052,7 @@ fn pipeline_wbe_ghost_to_receipt() {
         window_day: 1,
     });
     r.receipt_cid = r.compute_cid();
-    r.sign(&sk);
+    r.sign(&sk).unwrap();
 
     // Step 3: Verify everything
     assert!(r.verify(&vk), "receipt signature must verify");
@@ -1070,7 +1073,7 @@ fn pipeline_prev_links_receipts() {
     let mut r1 = make_receipt();
     r1.act = "ATTEST".into();
     r1.receipt_cid = r1.compute_cid();
-    r1.sign(&sk);
+    r1.sign(&sk).unwrap();
     assert!(r1.verify(&vk));
 
     // Receipt 2: EVALUATE, references Receipt 1
@@ -1078,7 +1081,7 @@ fn pipeline_prev_links_receipts() {
     r2.act = "EVALUATE".into();
     r2.pipeline_prev = vec![r1.receipt_cid.clone()];
     r2.receipt_cid = r2.compute_cid();
-    r2.sign(&sk);
+    r2.sign(&sk).unwrap();
     assert!(r2.verify(&vk));
 
     // Verify the link
@@ -1113,7 +1116,7 @@ fn pipeline_permit_authorizes_receipt() {
     let mut r = make_receipt();
     r.permit_cid = Some(p.permit_cid.clone());
     r.receipt_cid = r.compute_cid();
-    r.sign(&sk);
+    r.sign(&sk).unwrap();
 
     // Step 3: Verify
     assert!(r.verify(&vk));
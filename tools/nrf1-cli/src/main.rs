@@ -1,5 +1,5 @@
-use clap::{Parser, Subcommand};
-use nrf_core::{decode, encode, hash_bytes};
+use clap::{Parser, Subcommand, ValueEnum};
+use nrf_core::{decode_located, encode, hash_bytes};
 use serde_json::Value as J;
 use std::io::{self, Read, Write};
 /// ai-nrf1 (UBL-Byte) CLI: encode/decode/hash
@@ -32,6 +32,47 @@ enum Cmd {
         #[arg(short, long)]
         out: Option<String>,
     },
+    /// Print ai-nrf1 bytes in diagnostic notation: {"a": 1, "b": h'00ff'}
+    Diag {
+        /// Input NRF file (or - for stdin)
+        input: String,
+        /// Input is hex text (e.g. examples/*.hex) instead of raw bytes
+        #[arg(long)]
+        hex: bool,
+        /// Single line instead of indented
+        #[arg(short, long)]
+        compact: bool,
+        /// Output file (or - for stdout)
+        #[arg(short, long)]
+        out: Option<String>,
+    },
+    /// Parse diagnostic notation -> canonical ai-nrf1 bytes (strict)
+    FromDiag {
+        /// Input diagnostic text file (or - for stdin)
+        input: String,
+        /// Output file (or - for stdout). Writes raw bytes.
+        #[arg(short, long)]
+        out: Option<String>,
+    },
+    /// Structural diff of two ai-nrf1 buffers as JSON; exits 1 when they differ
+    Diff {
+        /// Left NRF file (or - for stdin)
+        a: String,
+        /// Right NRF file
+        b: String,
+    },
+    /// Convert between ai-nrf1, JSON, deterministic CBOR and MessagePack (strict, lossless)
+    Convert {
+        /// Input file (or - for stdin)
+        input: String,
+        #[arg(long, value_enum)]
+        from: Format,
+        #[arg(long, value_enum)]
+        to: Format,
+        /// Output file (or - for stdout)
+        #[arg(short, long)]
+        out: Option<String>,
+    },
     /// Compute BLAKE3 of raw bytes (NRF or any file)
     Hash {
         /// Input file (or - for stdin)
@@ -42,6 +83,36 @@ enum Cmd {
     },
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Nrf,
+    Json,
+    Cbor,
+    Msgpack,
+}
+
+fn read_as(format: Format, bytes: &[u8]) -> anyhow::Result<nrf_core::Value> {
+    use ai_nrf1::compat::{cbor, msgpack};
+    Ok(match format {
+        Format::Nrf => decode_located(bytes).map_err(|e| anyhow::anyhow!("{e}"))?,
+        Format::Json => json_to_nrf(&serde_json::from_slice(bytes)?)?,
+        Format::Cbor => cbor::from_slice(bytes).map_err(|e| anyhow::anyhow!("cbor: {e}"))?,
+        Format::Msgpack => {
+            msgpack::from_slice(bytes).map_err(|e| anyhow::anyhow!("msgpack: {e}"))?
+        }
+    })
+}
+
+fn write_as(format: Format, v: &nrf_core::Value) -> anyhow::Result<Vec<u8>> {
+    use ai_nrf1::compat::{cbor, msgpack};
+    Ok(match format {
+        Format::Nrf => nrf_core::try_encode(v).map_err(|e| anyhow::anyhow!("{e}"))?,
+        Format::Json => (serde_json::to_string_pretty(&nrf_to_json(v))? + "\n").into_bytes(),
+        Format::Cbor => cbor::to_vec(v).map_err(|e| anyhow::anyhow!("{e}"))?,
+        Format::Msgpack => msgpack::to_vec(v).map_err(|e| anyhow::anyhow!("{e}"))?,
+    })
+}
+
 // JSON <-> NRF mapping (canonical, zero-choice)
 
 fn parse_hex_lower(s: &str) -> anyhow::Result<Vec<u8>> {
@@ -110,6 +181,29 @@ fn nrf_to_json(v: &nrf_core::Value) -> J {
     }
 }
 
+fn diff_to_json(changes: &[nrf_core::Change]) -> J {
+    J::Array(
+        changes
+            .iter()
+            .map(|c| {
+                let mut o = serde_json::Map::new();
+                o.insert("path".to_string(), J::String(c.path.clone()));
+                o.insert("op".to_string(), J::String(c.kind.as_str().to_string()));
+                if let Some(old) = &c.old {
+                    o.insert("old".to_string(), nrf_to_json(old));
+                }
+                if let Some(new) = &c.new {
+                    o.insert("new".to_string(), nrf_to_json(new));
+                }
+                if let Some(note) = &c.note {
+                    o.insert("note".to_string(), J::String(note.clone()));
+                }
+                J::Object(o)
+            })
+            .collect(),
+    )
+}
+
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
     match cli.cmd {
@@ -122,11 +216,57 @@ fn main() -> anyhow::Result<()> {
         }
         Cmd::Decode { input, out } => {
             let bytes = read_to_bytes_maybe_stdin(&input)?;
-            let v = decode(&bytes).map_err(|e| anyhow::anyhow!(format!("{e:?}")))?;
+            let v = decode_located(&bytes).map_err(|e| anyhow::anyhow!("{e}"))?;
             let j = nrf_to_json(&v);
             let s = serde_json::to_string_pretty(&j)? + "\n";
             write_string_maybe_stdout(out.as_deref(), &s)?;
         }
+        Cmd::Diag {
+            input,
+            hex,
+            compact,
+            out,
+        } => {
+            let mut bytes = read_to_bytes_maybe_stdin(&input)?;
+            if hex {
+                let text: String = String::from_utf8(bytes)?
+                    .split_whitespace()
+                    .collect();
+                bytes = parse_hex_lower(&text)?;
+            }
+            let v = decode_located(&bytes).map_err(|e| anyhow::anyhow!("{e}"))?;
+            let s = if compact {
+                nrf_core::to_diag(&v)
+            } else {
+                nrf_core::to_diag_pretty(&v)
+            } + "\n";
+            write_string_maybe_stdout(out.as_deref(), &s)?;
+        }
+        Cmd::FromDiag { input, out } => {
+            let text = read_to_string_maybe_stdin(&input)?;
+            let bytes = nrf_core::diag_to_bytes(&text).map_err(|e| anyhow::anyhow!("{e}"))?;
+            write_bytes_maybe_stdout(out.as_deref(), &bytes)?;
+        }
+        Cmd::Diff { a, b } => {
+            let left = read_to_bytes_maybe_stdin(&a)?;
+            let right = read_to_bytes_maybe_stdin(&b)?;
+            let changes =
+                nrf_core::diff_bytes(&left, &right).map_err(|e| anyhow::anyhow!("{e}"))?;
+            println!("{}", serde_json::to_string_pretty(&diff_to_json(&changes))?);
+            if !changes.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Cmd::Convert {
+            input,
+            from,
+            to,
+            out,
+        } => {
+            let bytes = read_to_bytes_maybe_stdin(&input)?;
+            let v = read_as(from, &bytes)?;
+            write_bytes_maybe_stdout(out.as_deref(), &write_as(to, &v)?)?;
+        }
         Cmd::Hash { input, tag } => {
             let bytes = read_to_bytes_maybe_stdin(&input)?;
             let h = hash_bytes(&bytes);
@@ -66,3 +66,125 @@ fn reject_bom_in_string() {
         .failure()
         .stderr(predicate::str::contains("BOMPresent"));
 }
+
+#[test]
+fn diag_roundtrip_ok() {
+    let tmp = tempfile::tempdir().unwrap();
+    let hex = write(tmp.path(), "v.hex", "6e72663106020203000000000000002a\n");
+
+    let out = Command::cargo_bin("nrf1")
+        .unwrap()
+        .args(["diag", "--hex", "--compact", hex.to_str().unwrap()])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text, "[true, 42]\n");
+
+    let d = write(tmp.path(), "v.diag", &text);
+    let out = Command::cargo_bin("nrf1")
+        .unwrap()
+        .args(["from-diag", d.to_str().unwrap()])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert_eq!(out, b"nrf1\x06\x02\x02\x03\x00\x00\x00\x00\x00\x00\x00\x2a");
+}
+
+#[test]
+fn diag_rejects_unsorted_keys() {
+    let tmp = tempfile::tempdir().unwrap();
+    let d = write(tmp.path(), "bad.diag", r#"{"b": 1, "a": 2}"#);
+    Command::cargo_bin("nrf1")
+        .unwrap()
+        .args(["from-diag", d.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("UnsortedKeys"));
+}
+
+#[test]
+fn diff_reports_changes_as_json() {
+    let tmp = tempfile::tempdir().unwrap();
+    let a = tmp.path().join("a.nrf");
+    let b = tmp.path().join("b.nrf");
+    // [true, 42] vs [true, 43]
+    fs::write(&a, b"nrf1\x06\x02\x02\x03\x00\x00\x00\x00\x00\x00\x00\x2a").unwrap();
+    fs::write(&b, b"nrf1\x06\x02\x02\x03\x00\x00\x00\x00\x00\x00\x00\x2b").unwrap();
+
+    let out = Command::cargo_bin("nrf1")
+        .unwrap()
+        .args(["diff", a.to_str().unwrap(), b.to_str().unwrap()])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let j: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(
+        j,
+        serde_json::json!([{"path": "[1]", "op": "changed", "old": 42, "new": 43}])
+    );
+
+    Command::cargo_bin("nrf1")
+        .unwrap()
+        .args(["diff", a.to_str().unwrap(), a.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout("[]\n");
+}
+
+#[test]
+fn convert_json_cbor_msgpack_roundtrip() {
+    let tmp = tempfile::tempdir().unwrap();
+    let jf = write(
+        tmp.path(),
+        "in.json",
+        r#"{"b":[1,-1000],"aa":{"$bytes":"00ff"},"n":null}"#,
+    );
+    let path = |name: &str| tmp.path().join(name).to_str().unwrap().to_string();
+    let convert = |input: &str, from: &str, to: &str, out: &str| {
+        Command::cargo_bin("nrf1")
+            .unwrap()
+            .args(["convert", input, "--from", from, "--to", to, "-o", out])
+            .assert()
+            .success();
+    };
+
+    convert(jf.to_str().unwrap(), "json", "cbor", &path("a.cbor"));
+    convert(&path("a.cbor"), "cbor", "msgpack", &path("a.mp"));
+    convert(&path("a.mp"), "msgpack", "nrf", &path("a.nrf"));
+    convert(jf.to_str().unwrap(), "json", "nrf", &path("direct.nrf"));
+    assert_eq!(
+        fs::read(path("a.nrf")).unwrap(),
+        fs::read(path("direct.nrf")).unwrap()
+    );
+    // Deterministic CBOR sorts shorter keys first: "b", "n", "aa".
+    let cbor = fs::read(path("a.cbor")).unwrap();
+    assert_eq!(&cbor[..3], &[0xa3, 0x61, b'b']);
+}
+
+#[test]
+fn convert_rejects_cbor_float_with_location() {
+    let tmp = tempfile::tempdir().unwrap();
+    let f = tmp.path().join("f.cbor");
+    // {"a": 1.5}
+    fs::write(&f, [0xa1, 0x61, b'a', 0xf9, 0x3e, 0x00]).unwrap();
+    Command::cargo_bin("nrf1")
+        .unwrap()
+        .args([
+            "convert",
+            f.to_str().unwrap(),
+            "--from",
+            "cbor",
+            "--to",
+            "json",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cbor: Float at byte 3 (a)"));
+}
@@ -0,0 +1,152 @@
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Subcommand};
+use std::path::{Path, PathBuf};
+use ubl_bundle::{Artifact, Bundle, BundleKey};
+use ubl_capsule::resolver::{KeyRecord, Keyring};
+
+#[derive(Subcommand)]
+pub enum BundleCmd {
+    /// Pack a capsule with its evidence and signer keys into one NRF file
+    Create(CreateArgs),
+    /// Check a bundle offline and print a report (exits 1 on any failure)
+    Verify {
+        /// Bundle file (or - for stdin)
+        input: String,
+        /// Clock for hdr.exp (epoch-nanos; defaults to now)
+        #[arg(long)]
+        at: Option<i64>,
+        /// Trusted keys (DID#key -> hex); signers resolve only from these
+        /// (did:key signers included), never from keys shipped in the bundle
+        #[arg(long)]
+        trusted_keyring: Option<PathBuf>,
+        /// Directory of trusted JSON key records, as --trusted-keyring
+        #[arg(long)]
+        trusted_keyring_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// Capsule JSON file (or - for stdin)
+    pub capsule: String,
+    /// Act receipt JSON (repeatable)
+    #[arg(long = "receipt")]
+    pub receipts: Vec<PathBuf>,
+    /// Permit JSON (repeatable)
+    #[arg(long = "permit")]
+    pub permits: Vec<PathBuf>,
+    /// Ghost JSON (repeatable)
+    #[arg(long = "ghost")]
+    pub ghosts: Vec<PathBuf>,
+    /// Raw artifact file, addressed by b3:<hex> of its bytes (repeatable)
+    #[arg(long = "artifact")]
+    pub artifacts: Vec<PathBuf>,
+    /// JSON file mapping DID#key -> hex-encoded public key (32 bytes)
+    #[arg(long)]
+    pub keyring: Option<PathBuf>,
+    /// Directory of JSON key records ({kid, pk, not_before?, not_after?})
+    #[arg(long)]
+    pub keyring_dir: Option<PathBuf>,
+    /// Static DID document to include (repeatable)
+    #[arg(long = "did-doc")]
+    pub did_docs: Vec<PathBuf>,
+    /// Output bundle file (or - for stdout)
+    #[arg(short, long, default_value = "-")]
+    pub output: String,
+}
+
+pub fn run(cmd: BundleCmd) -> Result<()> {
+    match cmd {
+        BundleCmd::Create(args) => create(args),
+        BundleCmd::Verify {
+            input,
+            at,
+            trusted_keyring,
+            trusted_keyring_dir,
+        } => {
+            let anchors = load_anchors(trusted_keyring.as_deref(), trusted_keyring_dir.as_deref())?;
+            verify(&input, at, anchors.as_ref())
+        }
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &PathBuf, what: &str) -> Result<T> {
+    let s = std::fs::read_to_string(path)
+        .with_context(|| format!("reading {what} {}", path.display()))?;
+    serde_json::from_str(&s)
+        .with_context(|| format!("Err.Parse.Invalid{what}JSON: {}", path.display()))
+}
+
+fn create(args: CreateArgs) -> Result<()> {
+    let json_str = crate::read_input(&args.capsule)?;
+    let capsule: ubl_capsule::Capsule =
+        serde_json::from_str(&json_str).context("Err.Parse.InvalidCapsuleJSON")?;
+    let mut b = Bundle::new(capsule);
+    for p in &args.receipts {
+        b.receipts.push(read_json(p, "Receipt")?);
+    }
+    for p in &args.permits {
+        b.permits.push(read_json(p, "Permit")?);
+    }
+    for p in &args.ghosts {
+        b.ghosts.push(read_json(p, "Ghost")?);
+    }
+    for p in &args.artifacts {
+        let data = std::fs::read(p).with_context(|| format!("reading artifact {}", p.display()))?;
+        b.artifacts.push(Artifact::new(data));
+    }
+    if let Some(path) = &args.keyring {
+        let mut keys: Vec<_> = crate::load_keyring(path)?.into_iter().collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+        for (kid, pk) in keys {
+            b.add_key(kid, &pk);
+        }
+    }
+    if let Some(dir) = &args.keyring_dir {
+        let ring = Keyring::from_dir(dir).map_err(|e| anyhow!("{e}"))?;
+        b.keys.extend(ring.records().map(BundleKey::from));
+    }
+    for p in &args.did_docs {
+        let doc = std::fs::read_to_string(p)
+            .with_context(|| format!("reading DID document {}", p.display()))?;
+        b.add_did_document(doc).map_err(|e| anyhow!("{e}"))?;
+    }
+    let bytes = b.to_bytes().map_err(|e| anyhow!("{e}"))?;
+    crate::write_output(&args.output, &bytes)
+}
+
+/// The trusted keyring, if either source was given.
+fn load_anchors(keyring: Option<&Path>, dir: Option<&Path>) -> Result<Option<Keyring>> {
+    if keyring.is_none() && dir.is_none() {
+        return Ok(None);
+    }
+    let mut ring = match dir {
+        Some(dir) => Keyring::from_dir(dir).map_err(|e| anyhow!("{e}"))?,
+        None => Keyring::new(),
+    };
+    if let Some(path) = keyring {
+        for (kid, key) in crate::load_keyring(path)? {
+            ring.insert(KeyRecord {
+                kid,
+                key,
+                not_before: None,
+                not_after: None,
+            });
+        }
+    }
+    Ok(Some(ring))
+}
+
+fn verify(input: &str, at: Option<i64>, anchors: Option<&Keyring>) -> Result<()> {
+    let bytes = crate::read_input_bytes(input)?;
+    let b = Bundle::from_bytes(&bytes).map_err(|e| anyhow!("{e}"))?;
+    let report = ubl_bundle::verify(&b, at, anchors);
+    println!("{report}");
+    if !report.is_ok() {
+        bail!(
+            "Err.Bundle.Verify: {} check(s) failed",
+            report.failures().count()
+        );
+    }
+    Ok(())
+}
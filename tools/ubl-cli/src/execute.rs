@@ -4,12 +4,15 @@
 //! With `runner-real`: registers all 8 capability modules, translates the YAML manifest
 //! into module-runner's Manifest format, runs the pipeline with LoggingExecutor.
 
-use anyhow::{Result, Context};
+use anyhow::Result;
+#[cfg(feature = "runner-real")]
+use anyhow::Context;
 use serde_json::json;
 
 /// Translate our YAML manifest format into module-runner's Manifest struct.
 /// Our format: { version, name, pipeline: [{ use, with }] }
 /// Runner format: { v, name, version, pipeline: [{ step_id, kind, version, config }] }
+#[cfg_attr(not(feature = "runner-real"), allow(dead_code))]
 fn yaml_to_runner_manifest(manifest: &serde_yaml::Value) -> Result<serde_json::Value> {
     let name = manifest.get("name").and_then(|v| v.as_str()).unwrap_or("pipeline");
     let mut steps = vec![];
@@ -44,6 +47,7 @@ fn yaml_to_runner_manifest(manifest: &serde_yaml::Value) -> Result<serde_json::V
 }
 
 /// Map manifest capability aliases to real module kinds.
+#[cfg_attr(not(feature = "runner-real"), allow(dead_code))]
 fn resolve_alias(kind: &str) -> &str {
     match kind {
         "policy.light" | "policy.hardening" => "cap-policy",
@@ -103,7 +107,12 @@ async fn run_real_pipeline(manifest: serde_yaml::Value, vars: &[String]) -> Resu
 
     // Build registry + runner
     let registry = build_registry();
-    let assets = module_runner::assets::MemoryResolver::new();
+    // Same layout as the registry: `{STATE_DIR}/assets/<hex cid>`.
+    let state_dir = std::env::var("STATE_DIR").unwrap_or_else(|_| {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
+        format!("{home}/.ai-nrf1/state")
+    });
+    let assets = module_runner::assets::DirResolver::new(format!("{state_dir}/assets"));
     let effects = module_runner::effects::LoggingExecutor;
     let io_bindings = runner_manifest.io_bindings.clone().unwrap_or(json!({}));
 
@@ -0,0 +1,93 @@
+use anyhow::{anyhow, Context, Result};
+use clap::Subcommand;
+use nrf1::records::{Cid, IndexedLog, LogReader, LogWriter};
+use std::fs::File;
+use std::io::BufReader;
+
+#[derive(Subcommand)]
+pub enum LogCmd {
+    /// Append values (NRF, or JSON canonicalized) to a record log and index it
+    Pack {
+        /// Input files, one value each (or - for stdin)
+        #[arg(required = true)]
+        inputs: Vec<String>,
+        /// Record log file; created if missing, otherwise extended
+        #[arg(short, long)]
+        output: String,
+    },
+    /// List '<offset> b3:<cid>' per record (every CID is verified)
+    Ls {
+        /// Record log file (.nrflog)
+        log: String,
+    },
+    /// Extract one record by CID, as its stored NRF bytes or as JSON
+    Get {
+        /// Record log file (.nrflog)
+        log: String,
+        /// Record CID (b3:<hex>)
+        cid: String,
+        /// Print the JSON view instead of the NRF bytes
+        #[arg(long)]
+        json: bool,
+        #[arg(short, long, default_value = "-")]
+        output: String,
+    },
+}
+
+pub fn run(cmd: LogCmd) -> Result<()> {
+    match cmd {
+        LogCmd::Pack { inputs, output } => pack(&inputs, &output),
+        LogCmd::Ls { log } => ls(&log),
+        LogCmd::Get {
+            log,
+            cid,
+            json,
+            output,
+        } => get(&log, &cid, json, &output),
+    }
+}
+
+fn pack(inputs: &[String], output: &str) -> Result<()> {
+    let mut w = LogWriter::open_append(output).map_err(|e| anyhow!("Err.Log.{e}: {output}"))?;
+    for input in inputs {
+        let nrf = crate::read_canon_bytes(input)?;
+        let cid = w
+            .append_nrf(&nrf)
+            .map_err(|e| anyhow!("Err.Log.{e}: {input}"))?;
+        println!("b3:{}", hex::encode(cid));
+    }
+    w.finish().map_err(|e| anyhow!("Err.Log.{e}: {output}"))?;
+    Ok(())
+}
+
+fn ls(log: &str) -> Result<()> {
+    let file = File::open(log).with_context(|| format!("reading {log}"))?;
+    let reader = LogReader::new(BufReader::new(file)).map_err(|e| anyhow!("Err.Log.{e}"))?;
+    for rec in reader {
+        let rec = rec.map_err(|e| anyhow!("Err.Log.{e}"))?;
+        println!("{} {}", rec.offset, rec.cid_string());
+    }
+    Ok(())
+}
+
+fn get(log: &str, cid: &str, json: bool, output: &str) -> Result<()> {
+    let want: Cid = cid
+        .strip_prefix("b3:")
+        .and_then(|h| hex::decode(h).ok())
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow!("Err.Log.BadCid: expected b3:<64 hex>, got {cid}"))?;
+    let file = File::open(log).with_context(|| format!("reading {log}"))?;
+    let mut idx = IndexedLog::open(BufReader::new(file)).map_err(|e| anyhow!("Err.Log.{e}"))?;
+    let rec = idx
+        .get(&want)
+        .map_err(|e| anyhow!("Err.Log.{e}"))?
+        .ok_or_else(|| anyhow!("Err.Log.NotFound: {cid} is not in {log}"))?;
+    if json {
+        let j =
+            ubl_json_view::nrf_bytes_to_json(&rec.nrf).map_err(|e| anyhow!("Err.Decode.{e}"))?;
+        crate::write_output(output, serde_json::to_string_pretty(&j)?.as_bytes())
+    } else {
+        // The stored bytes, untouched: they hash to the CID.
+        crate::write_output(output, &rec.nrf)
+    }
+}
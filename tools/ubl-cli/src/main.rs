@@ -4,10 +4,17 @@
 //!   ubl cap from-json  <in.json>  -o <out.nrf|->
 //!   ubl cap to-json    <in.nrf>   -o <out.json|->
 //!   ubl cap hash       <in.(json|nrf)>
-//!   ubl cap sign       <in.json>  --sk <file>
-//!   ubl cap verify     <in.(json|nrf)> --pk <file>
+//!   ubl cap sign       <in.json>  --sk <file> [--kid <did#key>] [--cosigner <did#key>]... [--force]
+//!   ubl cap verify     <in.(json|nrf)> [--pk <file>] [--keyring-dir <dir>] [--did-doc <file>] [--key-time <ms|now>]
+//!   ubl cap encrypt    <in.json>  --to <x25519.pk> -o <out.json|->
+//!   ubl cap decrypt    <in.json>  --sk <x25519.sk> -o <body.json|->
 //!   ubl cap receipt add <in> --kind <relay|exec|deliver> --node <did#key> --sk <file> -o <out>
-//!   ubl keygen          -o <prefix>
+//!   ubl bundle create  <capsule.json> [--receipt|--permit|--ghost|--artifact <file>]... -o <out>
+//!   ubl bundle verify  <bundle> [--at <nanos>]
+//!   ubl log pack       <in.(json|nrf)>... -o <out.nrflog>
+//!   ubl log ls         <log.nrflog>
+//!   ubl log get        <log.nrflog> <b3:cid> [--json] [-o <out|->]
+//!   ubl keygen          -o <prefix> [--x25519]
 //!   ubl llm complete    --input <file> [--provider openai|ollama|registry] [--model ...]
 //!   ubl llm judge       --answer <file> --criteria <file> [--provider ...]
 //!   ubl pricing price   --input <file>
@@ -22,8 +29,13 @@ use anyhow::{anyhow, Context, Result};
 use clap::{Parser, Subcommand};
 use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+};
 
+mod bundle;
+mod log;
 mod llm;
 mod pricing;
 mod modules;
@@ -43,11 +55,29 @@ enum Commands {
         #[command(subcommand)]
         action: CapAction,
     },
+    /// Self-contained verification bundles (capsule + evidence + keys)
+    Bundle {
+        #[command(subcommand)]
+        cmd: bundle::BundleCmd,
+    },
+    /// Framed record logs (.nrflog): many canonical NRF values, one file
+    Log {
+        #[command(subcommand)]
+        cmd: log::LogCmd,
+    },
+    /// Schema validation of NRF values
+    Schema {
+        #[command(subcommand)]
+        action: SchemaAction,
+    },
     /// Generate an Ed25519 keypair
     Keygen {
         /// Output prefix (creates <prefix>.sk and <prefix>.pk)
         #[arg(short, long, default_value = "key")]
         output: String,
+        /// X25519 (body encryption) key pair instead of Ed25519
+        #[arg(long)]
+        x25519: bool,
     },
     /// Consent permit operations
     Permit {
@@ -80,6 +110,20 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum SchemaAction {
+    /// Validate a value (JSON or NRF); prints all violations as JSON, exits 1 if any
+    Validate {
+        /// Builtin schema name (see `ubl schema list`) or path to a schema JSON file
+        #[arg(long)]
+        schema: String,
+        /// Input file (JSON or NRF, or - for stdin)
+        input: String,
+    },
+    /// List the builtin schemas
+    List,
+}
+
 #[derive(Subcommand)]
 enum ProductCmd {
     /// Initialize a new product from the mother UI template
@@ -164,13 +208,30 @@ enum CapAction {
         /// Input file (JSON or NRF, or - for stdin)
         input: String,
     },
-    /// Sign a capsule JSON
+    /// Structural diff of two values (JSON or NRF) as JSON; exits 1 when they differ
+    Diff {
+        /// Left input (JSON or NRF, or - for stdin)
+        a: String,
+        /// Right input (JSON or NRF)
+        b: String,
+    },
+    /// Sign a capsule JSON (as author, or as a co-signer with `--kid`)
     Sign {
         /// Input capsule JSON file
         input: String,
         /// Ed25519 secret key file (32 bytes hex)
         #[arg(long)]
         sk: PathBuf,
+        /// Sign as this co-signer (must be in seal.cosigs) instead of seal.kid
+        #[arg(long)]
+        kid: Option<String>,
+        /// Declare a co-signer DID#key before signing (repeatable; changes the ID)
+        #[arg(long = "cosigner")]
+        cosigners: Vec<String>,
+        /// Allow `--cosigner` on a signed capsule, dropping the existing
+        /// signatures (the new ID invalidates them)
+        #[arg(long)]
+        force: bool,
         /// Output signed capsule JSON (or - for stdout)
         #[arg(short, long, default_value = "-")]
         output: String,
@@ -179,18 +240,65 @@ enum CapAction {
     Verify {
         /// Input capsule (JSON or NRF)
         input: String,
-        /// Ed25519 public key file (32 bytes hex)
+        /// Ed25519 public key file (32 bytes hex); without it the author's
+        /// key is resolved from seal.kid (keyrings, DID document, did:key
+        /// with --did-key), and seal.kid must be a key of hdr.src
         #[arg(long)]
-        pk: PathBuf,
+        pk: Option<PathBuf>,
         /// Allowed clock skew for expiry checks (nanoseconds)
         #[arg(long, default_value_t = 0)]
         allowed_skew_ns: i64,
-        /// Also verify the receipts chain (requires `--keyring`)
+        /// Multi-seal: require K valid seals among author + co-signers.
+        /// Only co-signers with a pinned key (keyrings, DID document) or
+        /// named by --signer count
+        #[arg(long)]
+        threshold: Option<usize>,
+        /// Allow this co-signer DID#key to count towards --threshold with a
+        /// resolved key (repeatable)
+        #[arg(long = "signer")]
+        signers: Vec<String>,
+        /// Also verify the receipts chain
         #[arg(long)]
         verify_chain: bool,
-        /// JSON file mapping node DID#key -> hex-encoded public key (32 bytes)
+        /// JSON file mapping node / co-signer DID#key -> hex-encoded public key (32 bytes)
         #[arg(long)]
         keyring: Option<PathBuf>,
+        /// Directory of JSON key records ({kid, pk, not_before?, not_after?})
+        #[arg(long)]
+        keyring_dir: Option<PathBuf>,
+        /// Static did:web-style DID document (verificationMethod[].publicKeyMultibase)
+        #[arg(long)]
+        did_doc: Option<PathBuf>,
+        /// Check resolved keys' validity windows at this trusted time
+        /// (epoch-millis, or `now`) instead of the signer-asserted hdr.ts
+        #[arg(long)]
+        key_time: Option<String>,
+        /// Also resolve did:key ids. Anyone can mint one, so this only
+        /// proves the signer holds the key named in the capsule
+        #[arg(long)]
+        did_key: bool,
+    },
+    /// Seal env.body to hdr.dst's X25519 key (run before `sign`; changes the ID)
+    Encrypt {
+        /// Input capsule JSON file (or - for stdin)
+        input: String,
+        /// Recipient X25519 public key file (32 bytes hex)
+        #[arg(long)]
+        to: PathBuf,
+        /// Output capsule JSON (or - for stdout)
+        #[arg(short, long, default_value = "-")]
+        output: String,
+    },
+    /// Open env.enc and write the clear body JSON
+    Decrypt {
+        /// Input capsule JSON file (or - for stdin)
+        input: String,
+        /// Recipient X25519 secret key file (32 bytes hex)
+        #[arg(long)]
+        sk: PathBuf,
+        /// Output body JSON (or - for stdout)
+        #[arg(short, long, default_value = "-")]
+        output: String,
     },
     /// Capsule JSON → ai-nrf1 bytes (canonical) for signed vectors
     ToNrf {
@@ -229,6 +337,23 @@ enum ReceiptAction {
         #[arg(short, long, default_value = "-")]
         output: String,
     },
+    /// Check every hop and print a JSON chain report (exit 1 if not fully valid)
+    Verify {
+        /// Input capsule JSON file (or - for stdin)
+        input: String,
+        /// JSON keyring: {"kid": "hex_pubkey", ...}
+        #[arg(long)]
+        keyring: Option<PathBuf>,
+        /// Directory of JSON key records ({kid, pk, not_before?, not_after?})
+        #[arg(long)]
+        keyring_dir: Option<PathBuf>,
+        /// Static did:web-style DID document (verificationMethod[].publicKeyMultibase)
+        #[arg(long)]
+        did_doc: Option<PathBuf>,
+        /// Also resolve did:key node ids (self-certifying, not pinned)
+        #[arg(long)]
+        did_key: bool,
+    },
 }
 
 #[tokio::main]
@@ -246,20 +371,44 @@ async fn run(cli: Cli) -> Result<()> {
             CapAction::FromJson { input, output } => cmd_from_json(&input, &output),
             CapAction::ToJson { input, output } => cmd_to_json(&input, &output),
             CapAction::Hash { input } => cmd_hash(&input),
-            CapAction::Sign { input, sk, output } => cmd_sign(&input, &sk, &output),
+            CapAction::Diff { a, b } => cmd_diff(&a, &b),
+            CapAction::Sign {
+                input,
+                sk,
+                kid,
+                cosigners,
+                force,
+                output,
+            } => cmd_sign(&input, &sk, kid.as_deref(), &cosigners, force, &output),
             CapAction::Verify {
                 input,
                 pk,
                 allowed_skew_ns,
+                threshold,
+                signers,
                 verify_chain,
                 keyring,
+                keyring_dir,
+                did_doc,
+                key_time,
+                did_key,
             } => cmd_verify(
                 &input,
-                &pk,
+                pk.as_ref(),
                 allowed_skew_ns,
+                threshold,
+                &signers,
                 verify_chain,
-                keyring.as_deref(),
+                key_time.as_deref(),
+                KeySources {
+                    keyring: keyring.as_deref(),
+                    keyring_dir: keyring_dir.as_deref(),
+                    did_doc: did_doc.as_deref(),
+                    did_key,
+                },
             ),
+            CapAction::Encrypt { input, to, output } => cmd_encrypt(&input, &to, &output),
+            CapAction::Decrypt { input, sk, output } => cmd_decrypt(&input, &sk, &output),
             CapAction::ToNrf { input, output } => cmd_cap_to_nrf(&input, &output),
             CapAction::Receipt { action } => match action {
                 ReceiptAction::Add {
@@ -270,9 +419,35 @@ async fn run(cli: Cli) -> Result<()> {
                     ts,
                     output,
                 } => cmd_receipt_add(&input, &kind, &node, &sk, ts, &output),
+                ReceiptAction::Verify {
+                    input,
+                    keyring,
+                    keyring_dir,
+                    did_doc,
+                    did_key,
+                } => cmd_receipt_verify(
+                    &input,
+                    KeySources {
+                        keyring: keyring.as_deref(),
+                        keyring_dir: keyring_dir.as_deref(),
+                        did_doc: did_doc.as_deref(),
+                        did_key,
+                    },
+                ),
             },
         },
-        Commands::Keygen { output } => cmd_keygen(&output),
+        Commands::Bundle { cmd } => bundle::run(cmd),
+        Commands::Log { cmd } => log::run(cmd),
+        Commands::Schema { action } => match action {
+            SchemaAction::Validate { schema, input } => cmd_schema_validate(&schema, &input),
+            SchemaAction::List => {
+                for name in nrf_schema::builtin_names() {
+                    println!("{name}");
+                }
+                Ok(())
+            }
+        },
+        Commands::Keygen { output, x25519 } => cmd_keygen(&output, x25519),
         Commands::Permit { action, state_dir } => {
             let expanded = expand_tilde(&state_dir);
             match action {
@@ -343,7 +518,7 @@ fn cmd_product_init(name: &str, registry: &str, out: &str, tenant: &str) -> Resu
 fn cmd_from_json(input: &str, output: &str) -> Result<()> {
     let json_str = read_input(input)?;
     let j: serde_json::Value = serde_json::from_str(&json_str).context("Err.Parse.InvalidJSON")?;
-    let nrf_bytes = ubl_json_view::json_to_nrf_bytes(&j).map_err(|e| anyhow!("Err.Canon.{e}"))?;
+    let nrf_bytes = ubl_json_view::json_to_nrf_bytes_at(&j).map_err(|e| anyhow!("Err.Canon.{e}"))?;
     write_output(output, &nrf_bytes)?;
     Ok(())
 }
@@ -356,66 +531,228 @@ fn cmd_to_json(input: &str, output: &str) -> Result<()> {
     Ok(())
 }
 
-fn cmd_hash(input: &str) -> Result<()> {
+/// Read NRF bytes as-is, or canonicalize JSON (ρ applied) — what gets hashed.
+fn read_canon_bytes(input: &str) -> Result<Vec<u8>> {
     let data = read_input_bytes(input)?;
     // Try NRF first, fall back to JSON
-    let nrf_bytes = if data.starts_with(b"nrf1") {
-        data
-    } else {
-        let json_str = std::str::from_utf8(&data).context("Err.Parse.InvalidUTF8")?;
-        let j: serde_json::Value =
-            serde_json::from_str(json_str).context("Err.Parse.InvalidJSON")?;
-        ubl_json_view::json_to_nrf_bytes(&j).map_err(|e| anyhow!("Err.Canon.{e}"))?
-    };
+    if data.starts_with(b"nrf1") {
+        return Ok(data);
+    }
+    let json_str = std::str::from_utf8(&data).context("Err.Parse.InvalidUTF8")?;
+    let j: serde_json::Value = serde_json::from_str(json_str).context("Err.Parse.InvalidJSON")?;
+    ubl_json_view::json_to_nrf_bytes_at(&j).map_err(|e| anyhow!("Err.Canon.{e}"))
+}
+
+fn cmd_hash(input: &str) -> Result<()> {
+    let nrf_bytes = read_canon_bytes(input)?;
     let hash = blake3::hash(&nrf_bytes);
     println!("b3:{}", hash.to_hex());
     Ok(())
 }
 
-fn cmd_sign(input: &str, sk_path: &PathBuf, output: &str) -> Result<()> {
+fn cmd_diff(a: &str, b: &str) -> Result<()> {
+    let left = read_canon_bytes(a)?;
+    let right = read_canon_bytes(b)?;
+    let changes = nrf_core::diff_bytes(&left, &right).map_err(|e| anyhow!("Err.Canon.{e}"))?;
+    let out = serde_json::to_string_pretty(&ubl_json_view::diff_to_json(&changes))?;
+    println!("{out}");
+    if !changes.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn cmd_schema_validate(schema: &str, input: &str) -> Result<()> {
+    let compiled = if nrf_schema::builtin_names().any(|n| n == schema) {
+        nrf_schema::Schema::builtin(schema)
+    } else {
+        nrf_schema::Schema::parse(&read_input(schema)?)
+    }
+    .map_err(|e| anyhow!("Err.Schema.{e}"))?;
+    let value = nrf_core::decode_located(&read_canon_bytes(input)?).map_err(|e| anyhow!("Err.Canon.{e}"))?;
+    let violations = compiled.validate(&value);
+    println!("{}", serde_json::to_string_pretty(&violations)?);
+    if !violations.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn cmd_sign(
+    input: &str,
+    sk_path: &PathBuf,
+    kid: Option<&str>,
+    cosigners: &[String],
+    force: bool,
+    output: &str,
+) -> Result<()> {
     let json_str = read_input(input)?;
     let mut capsule: ubl_capsule::Capsule =
         serde_json::from_str(&json_str).context("Err.Parse.InvalidCapsuleJSON")?;
     let sk = load_signing_key(sk_path)?;
-    ubl_capsule::seal::sign(&mut capsule, &sk)
-        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let new: Vec<&String> = cosigners
+        .iter()
+        .filter(|k| !capsule.seal.cosigs.iter().any(|s| &s.kid == *k))
+        .collect();
+    let unsigned = [0u8; 64];
+    let signed =
+        capsule.seal.sig != unsigned || capsule.seal.cosigs.iter().any(|s| s.sig != unsigned);
+    if !new.is_empty() && signed {
+        if !force {
+            anyhow::bail!(
+                "Err.Seal.AlreadySigned: adding {} changes the ID and voids existing signatures (pass --force to drop them)",
+                new[0]
+            );
+        }
+        eprintln!("warning: dropping existing signatures; every signer must sign again");
+        capsule.seal.sig = unsigned;
+        for s in &mut capsule.seal.cosigs {
+            s.sig = unsigned;
+        }
+    }
+    for cosigner in new {
+        if !capsule.seal.cosigs.iter().any(|s| &s.kid == cosigner) {
+            capsule
+                .seal
+                .cosigs
+                .push(ubl_capsule::CoSig::new(cosigner.as_str()));
+        }
+    }
+    match kid {
+        Some(kid) if kid != capsule.seal.kid => {
+            ubl_capsule::seal::cosign(&mut capsule, kid, &sk)
+        }
+        _ => ubl_capsule::seal::sign(&mut capsule, &sk),
+    }
+    .map_err(|e| anyhow::anyhow!("{e}"))?;
     let out = serde_json::to_string_pretty(&capsule)?;
     write_output(output, out.as_bytes())?;
     Ok(())
 }
 
+/// Where `cap verify` and `cap receipt verify` look keys up.
+struct KeySources<'a> {
+    keyring: Option<&'a Path>,
+    keyring_dir: Option<&'a Path>,
+    did_doc: Option<&'a Path>,
+    /// `--did-key`: resolve self-certifying `did:key` ids too.
+    did_key: bool,
+}
+
+impl KeySources<'_> {
+    /// did:key first (only with `--did-key`), then `--keyring`,
+    /// `--keyring-dir`, `--did-doc`. Also returns the pinned kids.
+    fn resolver(&self) -> Result<(ubl_capsule::resolver::FirstMatch, BTreeSet<String>)> {
+        use ubl_capsule::resolver::{DidKey, FirstMatch, KeyResolver, Keyring};
+        let mut chain: Vec<Box<dyn KeyResolver>> = Vec::new();
+        let mut pinned = BTreeSet::new();
+        if self.did_key {
+            chain.push(Box::new(DidKey));
+        }
+        if let Some(path) = self.keyring {
+            let ring = load_keyring(path)?;
+            pinned.extend(ring.keys().cloned());
+            chain.push(Box::new(ring));
+        }
+        if let Some(dir) = self.keyring_dir {
+            let ring = Keyring::from_dir(dir).map_err(|e| anyhow!("{e}"))?;
+            pinned.extend(ring.records().map(|r| r.kid.clone()));
+            chain.push(Box::new(ring));
+        }
+        if let Some(doc) = self.did_doc {
+            let ring = Keyring::from_did_document(doc).map_err(|e| anyhow!("{e}"))?;
+            pinned.extend(ring.records().map(|r| r.kid.clone()));
+            chain.push(Box::new(ring));
+        }
+        Ok((FirstMatch(chain), pinned))
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn cmd_verify(
     input: &str,
-    pk_path: &PathBuf,
+    pk_path: Option<&PathBuf>,
     allowed_skew_ns: i64,
+    threshold: Option<usize>,
+    signers: &[String],
     verify_chain: bool,
-    keyring_path: Option<&Path>,
+    key_time: Option<&str>,
+    sources: KeySources<'_>,
 ) -> Result<()> {
     let json_str = read_input(input)?;
     let capsule: ubl_capsule::Capsule =
         serde_json::from_str(&json_str).context("Err.Parse.InvalidCapsuleJSON")?;
-    let pk = load_verifying_key(pk_path)?;
+    let (resolver, pinned) = sources.resolver()?;
+    let key_time_ms = match key_time {
+        None => None,
+        Some("now") => Some(ubl_capsule::seal::now_nanos_i64() / 1_000_000),
+        Some(ms) => Some(
+            ms.parse::<i64>()
+                .map_err(|_| anyhow!("--key-time: expected epoch-millis or `now`, got {ms}"))?,
+        ),
+    };
     let opts = ubl_capsule::seal::VerifyOpts {
         allowed_skew_ns,
-        now_ns: None,
+        key_time_ms,
+        threshold: match threshold {
+            // With --pk, co-signer keys come from --keyring only.
+            Some(k) => Some(ubl_capsule::seal::ThresholdPolicy {
+                k,
+                keys: match sources.keyring {
+                    Some(path) => load_keyring(path)?,
+                    None => HashMap::new(),
+                },
+                signers: pinned.into_iter().chain(signers.iter().cloned()).collect(),
+            }),
+            None => None,
+        },
+        ..Default::default()
+    };
+    match pk_path {
+        Some(path) => {
+            let pk = load_verifying_key(path)?;
+            ubl_capsule::seal::verify_with_opts(&capsule, &pk, &opts)
+        }
+        None => ubl_capsule::seal::verify_resolved(&capsule, &resolver, &opts),
+    }
+    .map_err(|e| anyhow!("{e}"))?;
+    let sealed = match threshold {
+        Some(k) => format!("{k}-of-{} seals", 1 + capsule.seal.cosigs.len()),
+        None => "seal".to_string(),
     };
-    ubl_capsule::seal::verify_with_opts(&capsule, &pk, &opts).map_err(|e| anyhow!("{e}"))?;
 
     if verify_chain {
-        let keyring_path =
-            keyring_path.ok_or_else(|| anyhow!("Err.Args.MissingKeyring: --keyring required"))?;
-        let pks = load_keyring(keyring_path)?;
-        let resolve =
-            |node: &str| -> Option<ed25519_dalek::VerifyingKey> { pks.get(node).copied() };
-        ubl_capsule::receipt::verify_chain(&capsule.id, &capsule.receipts, &resolve)
+        ubl_capsule::receipt::verify_chain(&capsule.id, &capsule.receipts, &resolver)
             .map_err(|e| anyhow!("{e}"))?;
-        println!("OK: seal + receipts chain verified");
+        println!("OK: {sealed} + receipts chain verified");
     } else {
-        println!("OK: seal verified");
+        println!("OK: {sealed} verified");
     }
     Ok(())
 }
 
+fn cmd_encrypt(input: &str, to_path: &Path, output: &str) -> Result<()> {
+    let json_str = read_input(input)?;
+    let mut capsule: ubl_capsule::Capsule =
+        serde_json::from_str(&json_str).context("Err.Parse.InvalidCapsuleJSON")?;
+    let pk = x25519_dalek::PublicKey::from(load_key_bytes(to_path, "public")?);
+    ubl_capsule::encrypt::encrypt_body(&mut capsule, &pk).map_err(|e| anyhow!("{e}"))?;
+    let out = serde_json::to_string_pretty(&capsule)?;
+    write_output(output, out.as_bytes())?;
+    Ok(())
+}
+
+fn cmd_decrypt(input: &str, sk_path: &Path, output: &str) -> Result<()> {
+    let json_str = read_input(input)?;
+    let capsule: ubl_capsule::Capsule =
+        serde_json::from_str(&json_str).context("Err.Parse.InvalidCapsuleJSON")?;
+    let sk = x25519_dalek::StaticSecret::from(load_key_bytes(sk_path, "secret")?);
+    let body = ubl_capsule::encrypt::decrypt_body(&capsule, &sk).map_err(|e| anyhow!("{e}"))?;
+    let out = serde_json::to_string_pretty(&body)?;
+    write_output(output, out.as_bytes())?;
+    Ok(())
+}
+
 fn cmd_cap_to_nrf(input: &str, output: &str) -> Result<()> {
     let json_str = read_input(input)?;
     let capsule: ubl_capsule::Capsule =
@@ -471,6 +808,12 @@ fn capsule_to_nrf_value(c: &ubl_capsule::Capsule) -> Result<nrf_core::Value> {
             V::Array(c.env.evidence.iter().cloned().map(V::String).collect()),
         );
     }
+    if let Some(enc) = &c.env.enc {
+        let mut em = BTreeMap::new();
+        em.insert("alg".into(), V::String(enc.alg.clone()));
+        em.insert("ct".into(), V::Bytes(enc.ct.clone()));
+        env.insert("enc".into(), V::Map(em));
+    }
     root.insert("env".into(), V::Map(env));
 
     // seal
@@ -481,6 +824,20 @@ fn capsule_to_nrf_value(c: &ubl_capsule::Capsule) -> Result<nrf_core::Value> {
     if let Some(aud) = &c.seal.aud {
         seal.insert("aud".into(), V::String(aud.clone()));
     }
+    if !c.seal.cosigs.is_empty() {
+        let cosigs = c
+            .seal
+            .cosigs
+            .iter()
+            .map(|s| {
+                let mut m = BTreeMap::new();
+                m.insert("kid".into(), V::String(s.kid.clone()));
+                m.insert("sig".into(), V::Bytes(s.sig.to_vec()));
+                V::Map(m)
+            })
+            .collect();
+        seal.insert("cosigs".into(), V::Array(cosigs));
+    }
     root.insert("seal".into(), V::Map(seal));
 
     // receipts
@@ -607,11 +964,34 @@ fn cmd_receipt_add(
     Ok(())
 }
 
-fn cmd_keygen(prefix: &str) -> Result<()> {
-    let sk = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
-    let vk = sk.verifying_key();
-    let sk_hex = hex::encode(sk.to_bytes());
-    let pk_hex = hex::encode(vk.to_bytes());
+fn cmd_receipt_verify(input: &str, sources: KeySources<'_>) -> Result<()> {
+    let json_str = read_input(input)?;
+    let capsule: ubl_capsule::Capsule =
+        serde_json::from_str(&json_str).context("Err.Parse.InvalidCapsuleJSON")?;
+    let (resolver, _) = sources.resolver()?;
+    let report =
+        ubl_capsule::receipt::verify_chain_report(&capsule.id, &capsule.receipts, &resolver);
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.is_ok() {
+        return Err(anyhow!(
+            "Err.Hop.Chain: {} of {} hops form a valid chain",
+            report.valid_prefix,
+            report.hops.len()
+        ));
+    }
+    Ok(())
+}
+
+fn cmd_keygen(prefix: &str, x25519: bool) -> Result<()> {
+    let (sk_hex, pk_hex) = if x25519 {
+        let sk = x25519_dalek::StaticSecret::random_from_rng(rand_core::OsRng);
+        let pk = x25519_dalek::PublicKey::from(&sk);
+        (hex::encode(sk.to_bytes()), hex::encode(pk.as_bytes()))
+    } else {
+        let sk = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
+        let vk = sk.verifying_key();
+        (hex::encode(sk.to_bytes()), hex::encode(vk.to_bytes()))
+    };
 
     let sk_path = format!("{prefix}.sk");
     let pk_path = format!("{prefix}.pk");
@@ -686,6 +1066,15 @@ fn load_signing_key(path: &PathBuf) -> Result<ed25519_dalek::SigningKey> {
     Ok(ed25519_dalek::SigningKey::from_bytes(&arr))
 }
 
+fn load_key_bytes(path: &Path, what: &str) -> Result<[u8; 32]> {
+    let hex_str = std::fs::read_to_string(path)
+        .with_context(|| format!("reading {what} key from {}", path.display()))?;
+    let bytes = hex::decode(hex_str.trim()).context("Err.Key.BadHex")?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("Err.Key.BadLength: expected 32 bytes (64 hex chars)"))
+}
+
 fn load_verifying_key(path: &PathBuf) -> Result<ed25519_dalek::VerifyingKey> {
     let hex_str = std::fs::read_to_string(path)
         .with_context(|| format!("reading public key from {}", path.display()))?;
@@ -697,9 +1086,9 @@ fn load_verifying_key(path: &PathBuf) -> Result<ed25519_dalek::VerifyingKey> {
 }
 
 fn expand_tilde(path: &str) -> String {
-    if path.starts_with("~/") {
+    if let Some(rest) = path.strip_prefix("~/") {
         if let Some(home) = std::env::var_os("HOME") {
-            return format!("{}/{}", home.to_string_lossy(), &path[2..]);
+            return format!("{}/{}", home.to_string_lossy(), rest);
         }
     }
     path.to_string()
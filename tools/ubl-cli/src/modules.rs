@@ -3,7 +3,7 @@
 
 use clap::Args;
 use anyhow::{Result, anyhow};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use serde_json::json;
 
@@ -119,24 +119,22 @@ fn validate_outputs(m: &serde_yaml::Value, cmd: &str) -> Result<()> {
         if let Some(fields) = out.get("fields").and_then(|v| v.as_sequence()) {
             let has_receipt = fields.iter().any(|f| f.as_str()==Some("receipt_cid"));
             let has_url = fields.iter().any(|f| f.as_str()==Some("url_rica"));
-            if cmd.starts_with("tdln.") || cmd.starts_with("llm.") {
-                if !(has_receipt && has_url) {
-                    anyhow::bail!("outputs.fields must contain 'receipt_cid' and 'url_rica' for {cmd}. Hint: add both fields to the outputs.fields array in the manifest");
-                }
+            if (cmd.starts_with("tdln.") || cmd.starts_with("llm.")) && !(has_receipt && has_url) {
+                anyhow::bail!("outputs.fields must contain 'receipt_cid' and 'url_rica' for {cmd}. Hint: add both fields to the outputs.fields array in the manifest");
             }
         }
     }
     Ok(())
 }
 
-fn read_bounded(p: &PathBuf, max: usize) -> Result<String> {
+fn read_bounded(p: &Path, max: usize) -> Result<String> {
     safe_manifest_path(p)?;
     let bytes = fs::read(p)?;
     if bytes.len() > max { anyhow::bail!("manifest > {} bytes", max); }
     Ok(String::from_utf8(bytes)?)
 }
 
-fn safe_manifest_path(p: &PathBuf) -> Result<()> {
+fn safe_manifest_path(p: &Path) -> Result<()> {
     let s = p.to_string_lossy();
     if s.starts_with('/') || s.contains("..") {
         anyhow::bail!("unsafe manifest path: '{}'. Hint: use a relative path without '..' or leading '/'", s);
@@ -1,3 +1,5 @@
+#![allow(deprecated)]
+
 use assert_cmd::Command;
 
 #[test]
@@ -134,3 +136,443 @@ fn llm_smart_help() {
         .assert()
         .success();
 }
+
+#[test]
+fn cap_diff_reports_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let a = tmp.path().join("a.json");
+    let b = tmp.path().join("b.json");
+    std::fs::write(&a, r#"{"n": 1, "s": "x"}"#).unwrap();
+    std::fs::write(&b, r#"{"n": 2, "s": "x"}"#).unwrap();
+
+    let out = Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["cap", "diff", a.to_str().unwrap(), b.to_str().unwrap()])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let j: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(j, serde_json::json!([{"path": "n", "op": "changed", "old": 1, "new": 2}]));
+
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["cap", "diff", a.to_str().unwrap(), a.to_str().unwrap()])
+        .assert()
+        .success();
+}
+
+#[test]
+fn schema_validate_lists_violations() {
+    let tmp = tempfile::tempdir().unwrap();
+    let good = tmp.path().join("good.json");
+    let bad = tmp.path().join("bad.json");
+    std::fs::write(&good, r#"{"v": "product-v1", "name": "p", "version": "1", "pipeline": []}"#).unwrap();
+    std::fs::write(&bad, r#"{"v": "product-v1", "name": 1, "pipeline": [{"step_id": "a"}]}"#).unwrap();
+
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["schema", "validate", "--schema", "product.v1", good.to_str().unwrap()])
+        .assert()
+        .success();
+
+    let out = Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["schema", "validate", "--schema", "product.v1", bad.to_str().unwrap()])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let j: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let paths: Vec<&str> = j.as_array().unwrap().iter().map(|v| v["path"].as_str().unwrap()).collect();
+    // root misses "version"; the step misses kind, version and config
+    assert_eq!(paths, vec!["", "name", "pipeline[0]", "pipeline[0]", "pipeline[0]"]);
+}
+
+#[test]
+fn schema_validate_locates_decode_errors() {
+    let tmp = tempfile::tempdir().unwrap();
+    let input = tmp.path().join("bad.nrf");
+    // {"a": "e\u{0301}"} — the value is not NFC
+    std::fs::write(&input, b"nrf1\x07\x01\x04\x01a\x04\x03e\xcc\x81").unwrap();
+
+    let err = Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["schema", "validate", "--schema", "product.v1", input.to_str().unwrap()])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    assert!(String::from_utf8(err).unwrap().contains("NotNFC at byte 9 (a)"));
+}
+
+#[test]
+fn log_pack_ls_get_round_trip() {
+    let tmp = tempfile::tempdir().unwrap();
+    let a = tmp.path().join("a.json");
+    let b = tmp.path().join("b.json");
+    let log = tmp.path().join("out.nrflog");
+    std::fs::write(&a, r#"{"n": 1}"#).unwrap();
+    std::fs::write(&b, r#"{"s": "x"}"#).unwrap();
+    let (a, b, log) = (a.to_str().unwrap(), b.to_str().unwrap(), log.to_str().unwrap());
+
+    let out = Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["log", "pack", a, b, "-o", log])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let cids: Vec<String> = String::from_utf8(out).unwrap().lines().map(String::from).collect();
+    let hash = Command::cargo_bin("ubl").unwrap().args(["cap", "hash", b]).output().unwrap();
+    assert_eq!(format!("{}\n", cids[1]), String::from_utf8(hash.stdout).unwrap());
+
+    // Packing again extends the same log.
+    Command::cargo_bin("ubl").unwrap().args(["log", "pack", a, "-o", log]).assert().success();
+    let ls = Command::cargo_bin("ubl").unwrap().args(["log", "ls", log]).output().unwrap();
+    assert_eq!(String::from_utf8(ls.stdout).unwrap().lines().count(), 3);
+
+    let out = Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["log", "get", log, &cids[1], "--json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let j: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(j, serde_json::json!({"s": "x"}));
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["log", "get", log, &format!("b3:{}", "0".repeat(64))])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn cap_multi_seal_sign_and_threshold_verify() {
+    let tmp = tempfile::tempdir().unwrap();
+    let p = |name: &str| tmp.path().join(name).to_str().unwrap().to_string();
+    for who in ["alice", "bob"] {
+        Command::cargo_bin("ubl")
+            .unwrap()
+            .args(["keygen", "-o", &p(who)])
+            .assert()
+            .success();
+    }
+    let keyring = format!(
+        r#"{{"did:ubl:bob#k": "{}"}}"#,
+        std::fs::read_to_string(p("bob.pk")).unwrap()
+    );
+    std::fs::write(p("keyring.json"), keyring).unwrap();
+    let capsule = serde_json::json!({
+        "domain": "ubl-capsule/1.0",
+        "id": "00".repeat(32),
+        "hdr": {"src": "did:ubl:alice#k", "nonce": "aa".repeat(16), "ts": 1, "act": "TRANSACT"},
+        "env": {"body": {"amount": 10}},
+        "seal": {"kid": "did:ubl:alice#k", "sig": "00".repeat(64), "scope": "capsule"},
+    });
+    std::fs::write(p("cap.json"), capsule.to_string()).unwrap();
+
+    let sign = |input: &str, sk: &str, extra: &[&str], out: &str| {
+        Command::cargo_bin("ubl")
+            .unwrap()
+            .args(["cap", "sign", input, "--sk", sk, "-o", out])
+            .args(extra)
+            .assert()
+            .success();
+    };
+    let verify = |k: &str| {
+        Command::cargo_bin("ubl")
+            .unwrap()
+            .args(["cap", "verify", &p("signed.json"), "--pk", &p("alice.pk")])
+            .args(["--threshold", k, "--keyring", &p("keyring.json")])
+            .assert()
+    };
+
+    sign(&p("cap.json"), &p("alice.sk"), &["--cosigner", "did:ubl:bob#k"], &p("signed.json"));
+    verify("1").success().stdout("OK: 1-of-2 seals verified\n");
+    let err = verify("2").failure().get_output().stderr.clone();
+    assert!(String::from_utf8(err)
+        .unwrap()
+        .contains("Err.Seal.ThresholdNotMet: 1 valid of 2 required"));
+
+    sign(&p("signed.json"), &p("bob.sk"), &["--kid", "did:ubl:bob#k"], &p("signed.json"));
+    verify("2").success().stdout("OK: 2-of-2 seals verified\n");
+
+    // A new co-signer would change the ID under both signatures.
+    let add_carol = ["cap", "sign", &p("signed.json"), "--sk", &p("alice.sk")];
+    let err = Command::cargo_bin("ubl")
+        .unwrap()
+        .args(add_carol)
+        .args(["--cosigner", "did:ubl:carol#k", "-o", &p("carol.json")])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    assert!(String::from_utf8(err).unwrap().contains("Err.Seal.AlreadySigned"));
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(add_carol)
+        .args(["--cosigner", "did:ubl:carol#k", "--force", "-o", &p("carol.json")])
+        .assert()
+        .success();
+    let carol: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(p("carol.json")).unwrap()).unwrap();
+    assert_eq!(carol["seal"]["cosigs"][0]["sig"], "00".repeat(64));
+}
+
+#[test]
+fn cap_verify_resolves_did_key_and_keyring_dir() {
+    let tmp = tempfile::tempdir().unwrap();
+    let p = |name: &str| tmp.path().join(name).to_str().unwrap().to_string();
+    for who in ["alice", "relay"] {
+        Command::cargo_bin("ubl")
+            .unwrap()
+            .args(["keygen", "-o", &p(who)])
+            .assert()
+            .success();
+    }
+    let hex_pk = |who: &str| std::fs::read_to_string(p(&format!("{who}.pk"))).unwrap();
+    let alice: [u8; 32] = hex::decode(hex_pk("alice").trim()).unwrap().try_into().unwrap();
+    let alice = ed25519_dalek::VerifyingKey::from_bytes(&alice).unwrap();
+    let kid = ubl_capsule::resolver::did_key_for(&alice);
+    let capsule = serde_json::json!({
+        "domain": "ubl-capsule/1.0",
+        "id": "00".repeat(32),
+        "hdr": {"src": kid, "nonce": "aa".repeat(16), "ts": 1, "act": "TRANSACT"},
+        "env": {"body": {"amount": 10}},
+        "seal": {"kid": kid, "sig": "00".repeat(64), "scope": "capsule"},
+    });
+    std::fs::write(p("cap.json"), capsule.to_string()).unwrap();
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["cap", "sign", &p("cap.json"), "--sk", &p("alice.sk"), "-o", &p("signed.json")])
+        .assert()
+        .success();
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["cap", "receipt", "add", &p("signed.json"), "--kind", "relay"])
+        .args(["--node", "did:ubl:relay#k1", "--sk", &p("relay.sk"), "--ts", "1000"])
+        .args(["-o", &p("hop.json")])
+        .assert()
+        .success();
+
+    // did:key is opt-in: anyone can mint one.
+    let err = Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["cap", "verify", &p("hop.json")])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    assert!(String::from_utf8(err).unwrap().contains("Err.Key.NotFound"));
+    // With it, the author key comes from the did:key itself; no --pk needed.
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["cap", "verify", &p("hop.json"), "--did-key"])
+        .assert()
+        .success()
+        .stdout("OK: seal verified\n");
+
+    let keys = |not_after: i64| {
+        let dir = tmp.path().join(format!("keys-{not_after}"));
+        std::fs::create_dir(&dir).unwrap();
+        let record = serde_json::json!({
+            "kid": "did:ubl:relay#k1",
+            "pk": hex_pk("relay").trim(),
+            "not_after": not_after,
+        });
+        std::fs::write(dir.join("relay.json"), record.to_string()).unwrap();
+        dir.to_str().unwrap().to_string()
+    };
+    let verify_chain = |dir: &str| {
+        Command::cargo_bin("ubl")
+            .unwrap()
+            .args(["cap", "verify", &p("hop.json"), "--verify-chain", "--did-key"])
+            .args(["--keyring-dir", dir])
+            .assert()
+    };
+    verify_chain(&keys(2000))
+        .success()
+        .stdout("OK: seal + receipts chain verified\n");
+    // Relay key rotated out before the hop was signed.
+    let err = verify_chain(&keys(500)).failure().get_output().stderr.clone();
+    assert!(String::from_utf8(err)
+        .unwrap()
+        .contains("Err.Hop.Key: receipt[0]: Err.Key.Expired"));
+
+    // The chain report lists every hop instead of failing on the first.
+    let report = |dir: &str| {
+        Command::cargo_bin("ubl")
+            .unwrap()
+            .args(["cap", "receipt", "verify", &p("hop.json"), "--keyring-dir", dir])
+            .assert()
+    };
+    let out = report(&keys(3000)).success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["ok"], true);
+    assert_eq!(json["valid_prefix"], 1);
+    assert_eq!(json["hops"][0]["status"], "ok");
+    let out = report(&keys(600)).failure().get_output().clone();
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["hops"][0]["status"], "key_rejected");
+    assert!(String::from_utf8(out.stderr)
+        .unwrap()
+        .contains("Err.Hop.Chain: 0 of 1 hops form a valid chain"));
+}
+
+#[test]
+fn cap_encrypt_sign_relay_decrypt() {
+    let tmp = tempfile::tempdir().unwrap();
+    let p = |name: &str| tmp.path().join(name).to_str().unwrap().to_string();
+    let ubl = |args: &[&str]| Command::cargo_bin("ubl").unwrap().args(args).assert();
+    ubl(&["keygen", "-o", &p("alice")]).success();
+    ubl(&["keygen", "-o", &p("relay")]).success();
+    ubl(&["keygen", "--x25519", "-o", &p("bob")]).success();
+    let capsule = serde_json::json!({
+        "domain": "ubl-capsule/1.0",
+        "id": "00".repeat(32),
+        "hdr": {
+            "src": "did:ubl:alice#k",
+            "dst": "did:ubl:bob#x",
+            "nonce": "aa".repeat(16),
+            "ts": 1,
+            "act": "TRANSACT",
+        },
+        "env": {"body": {"amount": 10, "memo": "secret"}},
+        "seal": {"kid": "did:ubl:alice#k", "sig": "00".repeat(64), "scope": "capsule"},
+    });
+    std::fs::write(p("cap.json"), capsule.to_string()).unwrap();
+
+    ubl(&["cap", "encrypt", &p("cap.json"), "--to", &p("bob.pk"), "-o", &p("enc.json")]).success();
+    let enc = std::fs::read_to_string(p("enc.json")).unwrap();
+    assert!(!enc.contains("secret"));
+    ubl(&["cap", "sign", &p("enc.json"), "--sk", &p("alice.sk"), "-o", &p("signed.json")])
+        .success();
+    // The relay never sees the body.
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["cap", "receipt", "add", &p("signed.json"), "--kind", "relay"])
+        .args(["--node", "did:ubl:relay#k", "--sk", &p("relay.sk"), "--ts", "2"])
+        .args(["-o", &p("hop.json")])
+        .assert()
+        .success();
+    ubl(&["cap", "verify", &p("hop.json"), "--pk", &p("alice.pk")])
+        .success()
+        .stdout("OK: seal verified\n");
+
+    let out = ubl(&["cap", "decrypt", &p("hop.json"), "--sk", &p("bob.sk")])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let body: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(body, serde_json::json!({"amount": 10, "memo": "secret"}));
+
+    let err = ubl(&["cap", "decrypt", &p("hop.json"), "--sk", &p("relay.sk")])
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    assert!(String::from_utf8(err).unwrap().contains("Err.Enc.Open"));
+}
+
+#[test]
+fn bundle_create_and_verify_offline() {
+    let tmp = tempfile::tempdir().unwrap();
+    let p = |name: &str| tmp.path().join(name).to_str().unwrap().to_string();
+    let ubl = |args: &[&str]| Command::cargo_bin("ubl").unwrap().args(args).assert();
+    ubl(&["keygen", "-o", &p("alice")]).success();
+    ubl(&["keygen", "-o", &p("relay")]).success();
+    let pk = |who: &str| std::fs::read_to_string(p(&format!("{who}.pk"))).unwrap();
+    std::fs::write(p("report.txt"), "model output").unwrap();
+    let report_cid = format!("b3:{}", blake3::hash(b"model output").to_hex());
+    let capsule = serde_json::json!({
+        "domain": "ubl-capsule/1.0",
+        "id": "00".repeat(32),
+        "hdr": {
+            "src": "did:ubl:alice#k",
+            "nonce": "aa".repeat(16),
+            "ts": 1,
+            "act": "EVALUATE",
+        },
+        "env": {"body": {"score": 7}, "evidence": [report_cid]},
+        "seal": {"kid": "did:ubl:alice#k", "sig": "00".repeat(64), "scope": "capsule"},
+    });
+    std::fs::write(p("cap.json"), capsule.to_string()).unwrap();
+    ubl(&["cap", "sign", &p("cap.json"), "--sk", &p("alice.sk"), "-o", &p("signed.json")])
+        .success();
+    Command::cargo_bin("ubl")
+        .unwrap()
+        .args(["cap", "receipt", "add", &p("signed.json"), "--kind", "relay"])
+        .args(["--node", "did:ubl:relay#k", "--sk", &p("relay.sk"), "--ts", "2"])
+        .args(["-o", &p("hop.json")])
+        .assert()
+        .success();
+    let keyring = serde_json::json!({
+        "did:ubl:alice#k": pk("alice"),
+        "did:ubl:relay#k": pk("relay"),
+    });
+    std::fs::write(p("keyring.json"), keyring.to_string()).unwrap();
+
+    let create = |extra: &[&str], out: &str| {
+        Command::cargo_bin("ubl")
+            .unwrap()
+            .args(["bundle", "create", &p("hop.json"), "--keyring", &p("keyring.json")])
+            .args(extra)
+            .args(["-o", out])
+            .assert()
+            .success();
+    };
+    create(&["--artifact", &p("report.txt")], &p("full.bundle"));
+    assert_eq!(&std::fs::read(p("full.bundle")).unwrap()[..4], b"nrf1");
+    let out = ubl(&["bundle", "verify", &p("full.bundle")])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("OK    capsule hops (1)"), "{out}");
+    assert!(out.contains(&format!("OK    artifact {report_cid}")), "{out}");
+    assert!(out.ends_with("OK: 5 checks passed\n"), "{out}");
+    assert!(out.contains("KEY   did:ubl:relay#k b3:"), "{out}");
+    assert!(out.contains("(self-asserted)"), "{out}");
+
+    // Pinned anchors: the bundled keys must match them.
+    let pinned = |ring: &str| {
+        Command::cargo_bin("ubl")
+            .unwrap()
+            .args(["bundle", "verify", &p("full.bundle"), "--trusted-keyring", ring])
+            .assert()
+    };
+    let out = pinned(&p("keyring.json")).success().get_output().stdout.clone();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("(pinned)") && !out.contains("self-asserted"), "{out}");
+    ubl(&["keygen", "-o", &p("mallory")]).success();
+    let forged = serde_json::json!({
+        "did:ubl:alice#k": pk("alice"),
+        "did:ubl:relay#k": pk("mallory"),
+    });
+    std::fs::write(p("forged.json"), forged.to_string()).unwrap();
+    let out = pinned(&p("forged.json")).failure().get_output().stdout.clone();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("FAIL  key did:ubl:relay#k: Err.Bundle.KeyMismatch"), "{out}");
+
+    // Without the artifact the evidence reference dangles.
+    create(&[], &p("partial.bundle"));
+    let out = ubl(&["bundle", "verify", &p("partial.bundle")])
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let out = String::from_utf8(out).unwrap();
+    let missing = format!("FAIL  capsule evidence {report_cid}: Err.Bundle.Missing");
+    assert!(out.contains(&missing), "{out}");
+}